@@ -1,7 +1,6 @@
 use std::{
     env,
-    fs::File,
-    io::{BufRead, BufReader, Result},
+    io::{BufRead, Result},
 };
 
 #[cfg(not(feature = "with-fuel"))]
@@ -24,18 +23,18 @@ fn calculate_fuel_requirement(mass: u64) -> u64 {
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "with-fuel") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut fuel_requirement = 0;
     for line in reader.lines() {
         let mass: u64 = line?.parse().unwrap();
         fuel_requirement += calculate_fuel_requirement(mass);
     }
     println!("Total fuel requirement is {}", fuel_requirement);
+    args.report_time();
     Ok(())
 }
 
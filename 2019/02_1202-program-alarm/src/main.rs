@@ -1,93 +1,74 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader, Result},
-};
+use intcode::Program;
+use std::{io::BufRead, sync::mpsc};
 
-fn process_opcode(mut program: Vec<usize>) -> Vec<usize> {
-    fn binary_operator<F>(program: &mut [usize], offset: usize, operator: F)
-    where
-        F: Fn(usize, usize) -> usize,
-    {
-        let ensure_offset = |offset: usize| -> () {
-            assert!(offset < program.len(), "Out of bound reference");
-        };
-        let offset_a = program[offset + 1];
-        ensure_offset(offset_a);
-        let offset_b = program[offset + 2];
-        ensure_offset(offset_b);
-        let a = program[offset_a];
-        let b = program[offset_b];
-        let output_offset = program[offset + 3];
-        ensure_offset(output_offset);
-        program[output_offset] = operator(a, b);
-    }
-    let mut index = 0;
-    while index < program.len() && program[index] != 99 {
-        match program[index] {
-            1 => {
-                binary_operator(&mut program, index, |a, b| a + b);
-                index += 4;
-            }
-            2 => {
-                binary_operator(&mut program, index, |a, b| a * b);
-                index += 4;
-            }
-            _ => {
-                index += 1;
-            }
-        }
-    }
-    program
+/// Value left at address 0 by `opcodes`, once patched with `noun` at address
+/// 1 and `verb` at address 2.
+fn output(opcodes: &[i64], noun: i64, verb: i64) -> Result<i64, intcode::Error> {
+    // The program neither reads inputs nor writes outputs
+    let (_, inputs) = mpsc::sync_channel(0);
+    let (outputs, _) = mpsc::sync_channel(0);
+    let mut program = Program::new(opcodes.to_vec(), inputs, outputs);
+    program.poke(1, noun);
+    program.poke(2, verb);
+    program.run()?;
+    Ok(program
+        .peek(0)
+        .expect("expect the program to keep its first opcode"))
 }
 
-fn init_opcode(mut program: Vec<usize>, first: usize, second: usize) -> Vec<usize> {
-    program[1] = first;
-    program[2] = second;
-    program
+/// Smallest noun and verb, each between 0 and 99, for which `opcodes` leave
+/// `expected` at address 0. Every pair is run, on every core with the
+/// `parallel` feature.
+#[cfg(feature = "noun-verb")]
+fn find_noun_verb(opcodes: &[i64], expected: i64) -> Result<Option<(i64, i64)>, intcode::Error> {
+    let pairs: Vec<(i64, i64)> = (0..=99)
+        .flat_map(|noun| (0..=99).map(move |verb| (noun, verb)))
+        .collect();
+    common::parallel::map_reduce(
+        pairs,
+        || Ok(None),
+        |(noun, verb)| {
+            let found = output(opcodes, noun, verb)? == expected;
+            Ok(Some((noun, verb)).filter(|_| found))
+        },
+        |first, second| {
+            Ok(match (first?, second?) {
+                (Some(first), Some(second)) => Some(first.min(second)),
+                (first, second) => first.or(second),
+            })
+        },
+    )
 }
 
 #[cfg(not(feature = "noun-verb"))]
-fn run(program: Vec<usize>) {
-    let program = init_opcode(program, 12, 2);
-    let program = process_opcode(program);
-    println!("Position [0] contains '{}'", program[0]);
+fn run(opcodes: Vec<i64>) -> Result<(), intcode::Error> {
+    println!("Position [0] contains '{}'", output(&opcodes, 12, 2)?);
+    Ok(())
 }
 
 #[cfg(feature = "noun-verb")]
-fn run(program: Vec<usize>) {
-    for noun in 0..=99 {
-        for verb in 0..=99 {
-            let test_program = init_opcode(program.clone(), noun, verb);
-            let test_program = process_opcode(test_program);
-            if test_program[0] == 19690720 {
-                println!(
-                    "Noun is '{}' and verb is '{}' (100 * noun + verb = {})",
-                    noun,
-                    verb,
-                    100 * noun + verb
-                );
-            }
-        }
+fn run(opcodes: Vec<i64>) -> Result<(), intcode::Error> {
+    if let Some((noun, verb)) = find_noun_verb(&opcodes, 19690720)? {
+        println!(
+            "Noun is '{}' and verb is '{}' (100 * noun + verb = {})",
+            noun,
+            verb,
+            100 * noun + verb
+        );
     }
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+fn main() -> Result<(), intcode::Error> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "noun-verb") { 2 } else { 1 }],
+    );
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut program_str = String::new();
     reader.read_line(&mut program_str)?;
-    let program: Vec<usize> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .map(std::result::Result::unwrap)
-        .collect();
-    run(program);
+    run(intcode::parse(&program_str)?)?;
+    args.report_time();
     Ok(())
 }
 
@@ -95,6 +76,15 @@ fn main() -> Result<()> {
 mod tests {
     use super::*;
 
+    /// Memory of `opcodes` once they ran.
+    fn process_opcode(opcodes: Vec<i64>) -> Vec<i64> {
+        let (_, inputs) = mpsc::sync_channel(0);
+        let (outputs, _) = mpsc::sync_channel(0);
+        let mut program = Program::new(opcodes, inputs, outputs);
+        program.run().unwrap();
+        program.memory().to_vec()
+    }
+
     #[test]
     fn process() {
         assert_eq!(
@@ -112,4 +102,31 @@ mod tests {
             process_opcode(vec![1, 1, 1, 4, 99, 5, 6, 0, 99])
         );
     }
+
+    #[test]
+    fn patched_output() -> Result<(), intcode::Error> {
+        // [0] = [noun] * [verb]
+        let opcodes = [2, 0, 0, 0, 99, 7, 6];
+        assert_eq!(42, output(&opcodes, 5, 6)?);
+        assert_eq!(49, output(&opcodes, 5, 5)?);
+        Ok(())
+    }
+
+    #[cfg(feature = "noun-verb")]
+    #[test]
+    fn smallest_noun_verb() -> Result<(), intcode::Error> {
+        // [0] = [noun] + [verb], reached by several pairs
+        let opcodes = [1, 0, 0, 0, 99, 40, 2];
+        let first = |expected| {
+            (0..=99)
+                .flat_map(|noun| (0..=99).map(move |verb| (noun, verb)))
+                .find(|&(noun, verb)| output(&opcodes, noun, verb).unwrap() == expected)
+        };
+        for &expected in &[80, 42, 139] {
+            assert!(first(expected).is_some());
+            assert_eq!(first(expected), find_noun_verb(&opcodes, expected)?);
+        }
+        assert_eq!(None, find_noun_verb(&opcodes, 1000)?);
+        Ok(())
+    }
 }
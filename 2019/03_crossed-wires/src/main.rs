@@ -1,12 +1,16 @@
 use std::{
-    collections::HashSet,
+    collections::HashMap,
     env,
-    fs::File,
-    io::{self, BufRead, BufReader},
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead},
     str::FromStr,
 };
 use thiserror::Error;
 
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: common::alloc::Counting = common::alloc::Counting;
+
 #[derive(Debug, Error)]
 enum MyError {
     #[error("'{to_parse}' can not be parsed as a number")]
@@ -17,6 +21,18 @@ enum MyError {
     },
     #[error("'{to_parse}' can not be parsed as a Direction")]
     UnknownDirection { to_parse: String },
+    #[error("Invalid directive in the wire\n{underline}")]
+    InvalidDirective {
+        underline: String,
+        #[source]
+        source: Box<MyError>,
+    },
+    #[error("'{to_parse}' is not a metric, expect manhattan, steps or chebyshev")]
+    UnknownMetric { to_parse: String },
+    #[error("The wires never cross")]
+    NoIntersection,
+    #[error("Cannot read the wires")]
+    IOError(#[from] io::Error),
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
@@ -24,7 +40,6 @@ struct Position {
     x: i64,
     y: i64,
 }
-type Positions = Vec<Position>;
 
 impl From<(i64, i64)> for Position {
     fn from((x, y): (i64, i64)) -> Position {
@@ -62,133 +77,209 @@ type Directives = Vec<Directive>;
 impl FromStr for Directive {
     type Err = MyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let direction: Direction = s[0..1].parse()?;
-        let length: i64 = s[1..].parse().map_err(|source| MyError::NotANumber {
+        let split = s.chars().next().map_or(0, char::len_utf8);
+        let direction: Direction = s[..split].parse()?;
+        let length: i64 = s[split..].parse().map_err(|source| MyError::NotANumber {
             source,
-            to_parse: s[1..].to_string(),
+            to_parse: s[split..].to_string(),
         })?;
         Ok(Directive { direction, length })
     }
 }
 
-impl Directive {
-    fn positions_from(&self, init: &Position) -> Positions {
-        let mut positions = Vec::new();
-        let mut last_position = init.clone();
-        for _ in 0..self.length {
-            use Direction::*;
-            match self.direction {
-                Up => {
-                    last_position = Position {
-                        x: last_position.x,
-                        y: last_position.y + 1,
-                    }
-                }
-                Down => {
-                    last_position = Position {
-                        x: last_position.x,
-                        y: last_position.y - 1,
-                    }
-                }
-                Left => {
-                    last_position = Position {
-                        x: last_position.x - 1,
-                        y: last_position.y,
-                    }
-                }
-                Right => {
-                    last_position = Position {
-                        x: last_position.x + 1,
-                        y: last_position.y,
-                    }
-                }
-            }
-            positions.push(last_position.clone());
+/// Straight part of a wire, going `length` units in `direction` from the
+/// position after `start`, reached after `steps` steps along the wire.
+struct Segment {
+    start: Position,
+    direction: Direction,
+    length: i64,
+    steps: i64,
+}
+
+impl Segment {
+    /// Smallest and largest coordinates of the positions of the segment,
+    /// `start` excluded.
+    fn bounds(&self) -> (Position, Position) {
+        use Direction::*;
+        let Position { x, y } = self.start;
+        let (first, last) = match self.direction {
+            Up => ((x, y + 1), (x, y + self.length)),
+            Down => ((x, y - self.length), (x, y - 1)),
+            Left => ((x - self.length, y), (x - 1, y)),
+            Right => ((x + 1, y), (x + self.length, y)),
+        };
+        (Position::from(first), Position::from(last))
+    }
+
+    fn end(&self) -> Position {
+        let (first, last) = self.bounds();
+        match self.direction {
+            Direction::Up | Direction::Right => last,
+            Direction::Down | Direction::Left => first,
         }
-        positions
+    }
+
+    /// Steps along the wire to reach `position` of the segment.
+    fn steps_to(&self, position: &Position) -> i64 {
+        self.steps + (position.x - self.start.x).abs() + (position.y - self.start.y).abs()
     }
 }
 
-fn wire_directives(wire_path: &str) -> Directives {
-    wire_path
-        .trim()
-        .split(",")
-        .map(Directive::from_str)
-        .map(Result::unwrap)
+fn wire_directives(wire_path: &str) -> Result<Directives, MyError> {
+    let line = wire_path.trim();
+    common::token::split(line, ',')
+        .map(|token| {
+            token
+                .text
+                .parse()
+                .map_err(|source| MyError::InvalidDirective {
+                    underline: token.underline(line),
+                    source: Box::new(source),
+                })
+        })
         .collect()
 }
 
-fn wire_positions(wire_directives: Directives) -> Positions {
-    let init = Position::from((0, 0));
-    let mut positions = Vec::new();
-    let mut last_position = None;
-    for directive in wire_directives {
-        let new_positions = directive.positions_from(last_position.unwrap_or_else(|| &init));
-        positions.extend(new_positions);
-        last_position = positions.last();
+fn wire_segments(wire_directives: Directives) -> Vec<Segment> {
+    let mut segments = Vec::with_capacity(wire_directives.len());
+    let mut start = Position::from((0, 0));
+    let mut steps = 0;
+    for Directive { direction, length } in wire_directives {
+        let segment = Segment {
+            start,
+            direction,
+            length,
+            steps,
+        };
+        start = segment.end();
+        steps += length;
+        segments.push(segment);
     }
-    positions
+    segments
 }
 
-#[cfg(not(feature = "shortest"))]
-fn optimized_crossed_wires(wire1_directives: Directives, wire2_directives: Directives) -> i64 {
-    fn manhattan_distance(position: &Position) -> i64 {
-        position.x.abs() + position.y.abs()
+/// Positions where the wires cross, with the fewest steps along each wire to
+/// reach them. Only the bends of the wires are kept, the crossings being
+/// found between each pair of segments.
+fn intersections(
+    wire1_directives: Directives,
+    wire2_directives: Directives,
+) -> HashMap<Position, (i64, i64)> {
+    let wire1 = wire_segments(wire1_directives);
+    let wire2 = wire_segments(wire2_directives);
+    let mut intersections: HashMap<Position, (i64, i64)> = HashMap::new();
+    for segment1 in &wire1 {
+        let (min1, max1) = segment1.bounds();
+        for segment2 in &wire2 {
+            let (min2, max2) = segment2.bounds();
+            // A single position for crossing segments, several for segments
+            // along the same line
+            for x in min1.x.max(min2.x)..=max1.x.min(max2.x) {
+                for y in min1.y.max(min2.y)..=max1.y.min(max2.y) {
+                    let position = Position { x, y };
+                    let steps = (segment1.steps_to(&position), segment2.steps_to(&position));
+                    intersections
+                        .entry(position)
+                        .and_modify(|fewest| {
+                            *fewest = (fewest.0.min(steps.0), fewest.1.min(steps.1))
+                        })
+                        .or_insert(steps);
+                }
+            }
+        }
     }
+    intersections
+}
 
-    let wire1_positions: HashSet<_> = wire_positions(wire1_directives).into_iter().collect();
-    let wire2_positions: HashSet<_> = wire_positions(wire2_directives).into_iter().collect();
-    wire1_positions
-        .intersection(&wire2_positions)
-        .map(manhattan_distance)
-        .min()
-        .unwrap()
-}
-
-#[cfg(feature = "shortest")]
-fn optimized_crossed_wires(wire1_directives: Directives, wire2_directives: Directives) -> i64 {
-    let wire1_positions = wire_positions(wire1_directives);
-    let wire2_positions = wire_positions(wire2_directives);
-    let wire1_positions_set: HashSet<_> = wire1_positions.iter().collect();
-    let wire2_positions_set: HashSet<_> = wire2_positions.iter().collect();
-    let intersections: HashSet<_> = wire1_positions_set
-        .intersection(&wire2_positions_set)
-        .collect();
-    let mut min_steps = std::i64::MAX;
-    for intersection in intersections {
-        let wire1_steps = wire1_positions
-            .iter()
-            .position(|position| position == *intersection)
-            .map(|steps| steps + 1);
-        let wire2_steps = wire2_positions
-            .iter()
-            .position(|position| position == *intersection)
-            .map(|steps| steps + 1);
-        if let (Some(wire1_steps), Some(wire2_steps)) = (wire1_steps, wire2_steps) {
-            let total_steps = (wire1_steps + wire2_steps) as i64;
-            if total_steps < min_steps {
-                min_steps = total_steps;
-            }
+/// How an intersection is ranked, the best one being the smallest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Metric {
+    Manhattan,
+    Steps,
+    Chebyshev,
+}
+
+const METRICS: [Metric; 3] = [Metric::Manhattan, Metric::Steps, Metric::Chebyshev];
+
+impl FromStr for Metric {
+    type Err = MyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "manhattan" => Ok(Metric::Manhattan),
+            "steps" => Ok(Metric::Steps),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            unknown => Err(MyError::UnknownMetric {
+                to_parse: unknown.to_string(),
+            }),
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Metric::Manhattan => write!(f, "Manhattan distance"),
+            Metric::Steps => write!(f, "combined steps"),
+            Metric::Chebyshev => write!(f, "Chebyshev distance"),
         }
     }
-    min_steps
 }
 
-fn main() -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+impl Metric {
+    fn measure(self, position: &Position, (steps1, steps2): (i64, i64)) -> i64 {
+        match self {
+            Metric::Manhattan => position.x.abs() + position.y.abs(),
+            Metric::Steps => steps1 + steps2,
+            Metric::Chebyshev => position.x.abs().max(position.y.abs()),
+        }
     }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+}
+
+/// Smallest measure of an intersection by `metric`, if the wires cross.
+fn optimized_crossed_wires(
+    intersections: &HashMap<Position, (i64, i64)>,
+    metric: Metric,
+) -> Option<i64> {
+    intersections
+        .iter()
+        .map(|(position, &steps)| metric.measure(position, steps))
+        .min()
+}
+
+fn main() -> Result<(), MyError> {
+    #[cfg(feature = "alloc-stats")]
+    let start = std::time::Instant::now();
+    let args = common::cli::Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[1, 2],
+        vec![common::cli::Arg::new("metric")
+            .long("metric")
+            .value_name("METRIC")
+            .help("Only rank the intersections by manhattan, steps or chebyshev")],
+    );
+    let metrics = match args.value("metric") {
+        Some(metric) => vec![metric.parse()?],
+        None => METRICS.to_vec(),
+    };
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut wire1 = String::new();
     reader.read_line(&mut wire1)?;
     let mut wire2 = String::new();
     reader.read_line(&mut wire2)?;
-    let wire1_directives = wire_directives(&wire1);
-    let wire2_directives = wire_directives(&wire2);
-    let distance = optimized_crossed_wires(wire1_directives, wire2_directives);
-    println!("The optimized intersection is {} unit away", distance);
+    let wire1_directives = wire_directives(&wire1)?;
+    let wire2_directives = wire_directives(&wire2)?;
+    let intersections = intersections(wire1_directives, wire2_directives);
+    for metric in metrics {
+        let distance =
+            optimized_crossed_wires(&intersections, metric).ok_or(MyError::NoIntersection)?;
+        println!(
+            "The optimized intersection is {} unit away by {}",
+            distance, metric
+        );
+    }
+    #[cfg(feature = "alloc-stats")]
+    common::alloc::report(start);
+    args.report_time();
     Ok(())
 }
 
@@ -196,31 +287,154 @@ fn main() -> io::Result<()> {
 mod tests {
     use super::*;
 
-    #[cfg(not(feature = "shortest"))]
+    fn optimized(wire1: &str, wire2: &str) -> Vec<Option<i64>> {
+        let intersections = intersections(
+            wire_directives(wire1).unwrap(),
+            wire_directives(wire2).unwrap(),
+        );
+        METRICS
+            .iter()
+            .map(|&metric| optimized_crossed_wires(&intersections, metric))
+            .collect()
+    }
+
     #[test]
     fn cross_wires() {
-        let wire1 = wire_directives("R8,U5,L5,D3");
-        let wire2 = wire_directives("U7,R6,D4,L4");
-        assert_eq!(6, optimized_crossed_wires(wire1, wire2));
-        let wire1 = wire_directives("R75,D30,R83,U83,L12,D49,R71,U7,L72");
-        let wire2 = wire_directives("U62,R66,U55,R34,D71,R55,D58,R83");
-        assert_eq!(159, optimized_crossed_wires(wire1, wire2));
-        let wire1 = wire_directives("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51");
-        let wire2 = wire_directives("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7");
-        assert_eq!(135, optimized_crossed_wires(wire1, wire2));
-    }
-
-    #[cfg(feature = "shortest")]
+        assert_eq!(
+            vec![Some(6), Some(30), Some(3)],
+            optimized("R8,U5,L5,D3", "U7,R6,D4,L4")
+        );
+        assert_eq!(
+            vec![Some(159), Some(610), Some(146)],
+            optimized(
+                "R75,D30,R83,U83,L12,D49,R71,U7,L72",
+                "U62,R66,U55,R34,D71,R55,D58,R83"
+            )
+        );
+        assert_eq!(
+            vec![Some(135), Some(410), Some(107)],
+            optimized(
+                "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
+                "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7"
+            )
+        );
+        assert_eq!(vec![None, None, None], optimized("R8", "L8"));
+    }
+
     #[test]
-    fn cross_wires() {
-        let wire1 = wire_directives("R8,U5,L5,D3");
-        let wire2 = wire_directives("U7,R6,D4,L4");
-        assert_eq!(30, optimized_crossed_wires(wire1, wire2));
-        let wire1 = wire_directives("R75,D30,R83,U83,L12,D49,R71,U7,L72");
-        let wire2 = wire_directives("U62,R66,U55,R34,D71,R55,D58,R83");
-        assert_eq!(610, optimized_crossed_wires(wire1, wire2));
-        let wire1 = wire_directives("R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51");
-        let wire2 = wire_directives("U98,R91,D20,R16,D67,R40,U7,R15,U6,R7");
-        assert_eq!(410, optimized_crossed_wires(wire1, wire2));
+    fn overlapping_wires() {
+        // The second wire goes along the first one from (3, 0) to (7, 0)
+        let found = intersections(
+            wire_directives("R10").unwrap(),
+            wire_directives("U1,R3,D1,R4").unwrap(),
+        );
+        assert_eq!(5, found.len());
+        assert_eq!(Some(&(3, 5)), found.get(&Position::from((3, 0))));
+        assert_eq!(Some(&(7, 9)), found.get(&Position::from((7, 0))));
+        // The first wire goes by (1, 0) twice, and keeps its fewest steps
+        let found = intersections(
+            wire_directives("R2,U1,L1,D2").unwrap(),
+            wire_directives("U1,R1,D1").unwrap(),
+        );
+        assert_eq!(Some(&(1, 3)), found.get(&Position::from((1, 0))));
+        assert_eq!(Some(&(4, 2)), found.get(&Position::from((1, 1))));
+    }
+
+    #[test]
+    fn metric() {
+        assert_eq!(Metric::Steps, "steps".parse().unwrap());
+        assert_eq!(
+            "'euclid' is not a metric, expect manhattan, steps or chebyshev",
+            "euclid".parse::<Metric>().unwrap_err().to_string()
+        );
+        let position = Position::from((-3, 4));
+        assert_eq!(7, Metric::Manhattan.measure(&position, (10, 20)));
+        assert_eq!(30, Metric::Steps.measure(&position, (10, 20)));
+        assert_eq!(4, Metric::Chebyshev.measure(&position, (10, 20)));
+    }
+
+    #[test]
+    fn invalid_directive() {
+        let error = wire_directives("R8,X5,L5\n").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Invalid directive in the wire\nR8,X5,L5\n   ^^"
+        );
+    }
+
+    mod directive_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn wire() -> impl Strategy<Value = String> {
+            prop::collection::vec(("[UDLR]", 0i64..12), 1..12).prop_map(|directives| {
+                directives
+                    .iter()
+                    .map(|(direction, length)| format!("{}{}", direction, length))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+        }
+
+        /// Intersections found by walking every position of both wires.
+        fn position_intersections(wire1: &str, wire2: &str) -> HashMap<Position, (i64, i64)> {
+            fn positions(wire: &str) -> HashMap<Position, i64> {
+                let mut positions = HashMap::new();
+                let (mut x, mut y, mut steps) = (0, 0, 0);
+                for directive in wire.split(',') {
+                    let (dx, dy) = match &directive[..1] {
+                        "U" => (0, 1),
+                        "D" => (0, -1),
+                        "L" => (-1, 0),
+                        _ => (1, 0),
+                    };
+                    for _ in 0..directive[1..].parse::<i64>().unwrap() {
+                        x += dx;
+                        y += dy;
+                        steps += 1;
+                        positions.entry(Position { x, y }).or_insert(steps);
+                    }
+                }
+                positions
+            }
+            let positions1 = positions(wire1);
+            let positions2 = positions(wire2);
+            positions1
+                .into_iter()
+                .filter_map(|(position, steps1)| {
+                    positions2
+                        .get(&position)
+                        .map(|&steps2| (position, (steps1, steps2)))
+                })
+                .collect()
+        }
+
+        proptest! {
+            #[test]
+            fn parse_directive(direction in "[UDLR]", length in 0i64..1000) {
+                let directive: Directive = format!("{}{}", direction, length).parse().unwrap();
+                prop_assert_eq!(length, directive.length);
+                let end = wire_segments(vec![directive])[0].end();
+                prop_assert_eq!(length, end.x.abs() + end.y.abs());
+            }
+
+            #[test]
+            fn same_intersections_as_positions(
+                wire1 in wire(),
+                wire2 in wire(),
+            ) {
+                let expected = position_intersections(&wire1, &wire2);
+                let intersections = intersections(
+                    wire_directives(&wire1).unwrap(),
+                    wire_directives(&wire2).unwrap(),
+                );
+                prop_assert_eq!(expected, intersections);
+            }
+
+            #[test]
+            fn wire_never_panics(line in common::strategy::line()) {
+                let _ = wire_directives(&line);
+            }
+        }
     }
 }
@@ -1,38 +1,46 @@
-use std::{convert::TryFrom, env};
+use common::cli::{value_parser, Arg, ArgAction, Args};
+use std::convert::TryFrom;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 enum MyError {
-    #[error("Needs to integer as arguments")]
-    WrongNumberOfArguments,
-    #[error("Input arguments must be integer")]
-    InvalidArgument(#[from] std::num::ParseIntError),
-    #[error("Number {0} is not between 100000 and 999999")]
-    InvalidBound(usize),
+    #[error("Number {0} is not between 1 and {}", usize::MAX)]
+    InvalidBound(u128),
 }
 
 struct PasswordIterator {
+    start: usize,
     current: usize,
     end: usize,
 }
 
+impl PasswordIterator {
+    /// Number of candidates in the range, valid or not.
+    fn candidates(&self) -> usize {
+        self.end - self.start + 1
+    }
+    /// Number of candidates already checked.
+    fn checked(&self) -> usize {
+        self.current - (self.start - 1)
+    }
+}
+
 impl TryFrom<(usize, usize)> for PasswordIterator {
     type Error = MyError;
     fn try_from((start, end): (usize, usize)) -> Result<Self, Self::Error> {
-        let check_number = |num| num >= 100000 && num <= 999999;
-        if !check_number(start) {
-            return Err(MyError::InvalidBound(start));
-        }
-        if !check_number(end) {
-            return Err(MyError::InvalidBound(end));
+        // `current` starts right before `start`
+        if start == 0 || end == 0 {
+            return Err(MyError::InvalidBound(0));
         }
         if start < end {
             Ok(PasswordIterator {
+                start,
                 current: start - 1,
                 end,
             })
         } else {
             Ok(PasswordIterator {
+                start: end,
                 current: end - 1,
                 end: start,
             })
@@ -98,21 +106,175 @@ impl Iterator for PasswordIterator {
     }
 }
 
-fn valid_passwords(start: usize, end: usize) -> Result<PasswordIterator, MyError> {
-    PasswordIterator::try_from((start, end))
+/// Number of ways to choose `k` among `n`.
+fn binomial(n: u128, k: u128) -> u128 {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |binomial, i| binomial * (n - i) / (i + 1))
+}
+
+/// Number of ways to draw `len` digits, in order, among `digits` digits,
+/// i.e. to put `len` stars into `digits` bars.
+fn non_decreasing(len: u128, digits: u128) -> u128 {
+    if digits == 0 {
+        (len == 0) as u128
+    } else {
+        binomial(len + digits - 1, digits - 1)
+    }
+}
+
+/// Whether a run of `run` times the same digit makes the password valid.
+#[cfg(not(feature = "no-group"))]
+fn is_pair(run: u128) -> bool {
+    run >= 2
+}
+
+/// Whether a run of `run` times the same digit makes the password valid.
+#[cfg(feature = "no-group")]
+fn is_pair(run: u128) -> bool {
+    run == 2
+}
+
+/// Same as `non_decreasing`, without any run making a pair.
+#[cfg(not(feature = "no-group"))]
+fn without_pair(len: u128, digits: u128) -> u128 {
+    // Each digit at most once
+    binomial(digits, len)
+}
+
+/// Same as `non_decreasing`, without any run making a pair.
+#[cfg(feature = "no-group")]
+fn without_pair(len: u128, digits: u128) -> u128 {
+    // Inclusion-exclusion over the digits repeated exactly twice
+    let (mut added, mut removed) = (0, 0);
+    for twice in 0..=digits.min(len / 2) {
+        let count = binomial(digits, twice) * non_decreasing(len - 2 * twice, digits - twice);
+        if twice % 2 == 0 {
+            added += count;
+        } else {
+            removed += count;
+        }
+    }
+    added - removed
+}
+
+/// Number of valid endings of `len` digits, none lower than `digit`, after
+/// a run of `run` times `digit`, `paired` telling whether a previous run
+/// already made a pair.
+fn valid_endings(len: u128, digit: u128, run: u128, paired: bool) -> u128 {
+    let higher = 9 - digit;
+    (0..=len)
+        .map(|more| {
+            let rest = len - more;
+            if paired || is_pair(run + more) {
+                non_decreasing(rest, higher)
+            } else {
+                non_decreasing(rest, higher) - without_pair(rest, higher)
+            }
+        })
+        .sum()
+}
+
+/// Number of valid passwords from 1 to `number`, of any number of digits,
+/// without going through them.
+fn count_up_to(number: u128) -> u128 {
+    if number == 0 {
+        return 0;
+    }
+    let digits: Vec<u128> = number
+        .to_string()
+        .bytes()
+        .map(|digit| (digit - b'0') as u128)
+        .collect();
+    let len = digits.len() as u128;
+    // Passwords with fewer digits
+    let mut count = (1..len)
+        .map(|shorter| valid_endings(shorter, 1, 0, false))
+        .sum();
+    // Passwords starting as `number` then with a lower digit
+    let (mut previous, mut run, mut paired) = (1, 0, false);
+    for (position, &digit) in digits.iter().enumerate() {
+        let rest = len - position as u128 - 1;
+        for lower in previous..digit {
+            count += if lower == previous {
+                valid_endings(rest, lower, run + 1, paired)
+            } else {
+                valid_endings(rest, lower, 1, paired || is_pair(run))
+            };
+        }
+        if digit < previous {
+            return count;
+        }
+        if digit == previous {
+            run += 1;
+        } else {
+            paired = paired || is_pair(run);
+            previous = digit;
+            run = 1;
+        }
+    }
+    // `number` itself
+    if paired || is_pair(run) {
+        count += 1;
+    }
+    count
+}
+
+/// Number of valid passwords between `start` and `end`, counted with the
+/// combinations of digits instead of going through the range.
+fn count_valid_passwords(start: u128, end: u128) -> u128 {
+    let (start, end) = (start.min(end), start.max(end));
+    count_up_to(end) - count_up_to(start.saturating_sub(1))
+}
+
+fn valid_passwords(start: u128, end: u128) -> Result<PasswordIterator, MyError> {
+    let bound = |bound| usize::try_from(bound).map_err(|_| MyError::InvalidBound(bound));
+    PasswordIterator::try_from((bound(start)?, bound(end)?))
+}
+
+/// Arguments `<START> <END>` and `--count`, to be given to
+/// `cli::Args::parse_values`.
+fn arguments() -> Vec<Arg> {
+    let bound = |id, value_name| {
+        Arg::new(id)
+            .value_name(value_name)
+            .required(true)
+            .value_parser(value_parser!(u128))
+    };
+    vec![
+        bound("start", "START").help("First password of the range"),
+        bound("end", "END").help("Last password of the range"),
+        Arg::new("count")
+            .long("count")
+            .action(ArgAction::SetTrue)
+            .help("Count the valid passwords without checking them one after the other"),
+    ]
 }
 
 fn main() -> Result<(), MyError> {
-    let bounds: Vec<usize> = env::args()
-        .skip(1)
-        .take(2)
-        .map(|arg| arg.parse())
-        .collect::<Result<_, _>>()?;
-    if bounds.len() != 2 {
-        return Err(MyError::WrongNumberOfArguments);
-    }
-    let valid_passwords = valid_passwords(bounds[0], bounds[1])?;
-    println!("There is {} valid passwords", valid_passwords.count());
+    let args = Args::parse_values(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "no-group") { 2 } else { 1 }],
+        arguments(),
+    );
+    let start = args.get::<u128>("start").expect("expect a required start");
+    let end = args.get::<u128>("end").expect("expect a required end");
+    let count = if args.flag("count") {
+        count_valid_passwords(start, end)
+    } else {
+        let mut valid_passwords = valid_passwords(start, end)?;
+        let mut progress = common::progress::eta(valid_passwords.candidates() as u64, "Passwords");
+        let mut count = 0;
+        while valid_passwords.next().is_some() {
+            count += 1;
+            progress.set_position(valid_passwords.checked() as u64);
+        }
+        progress.finish_and_clear();
+        count
+    };
+    println!("There is {} valid passwords", count);
+    args.report_time();
     Ok(())
 }
 
@@ -153,4 +315,107 @@ mod tests {
         assert_eq!(1, valid_passwords(122333, 122333)?.count());
         Ok(())
     }
+
+    #[test]
+    fn checked_candidates() -> Result<(), MyError> {
+        let mut passwords = valid_passwords(123450, 123459)?;
+        assert_eq!(10, passwords.candidates());
+        assert_eq!(0, passwords.checked());
+        passwords.next();
+        assert_eq!(6, passwords.checked());
+        while passwords.next().is_some() {}
+        assert_eq!(10, passwords.checked());
+        Ok(())
+    }
+
+    #[test]
+    fn counted_as_iterated() -> Result<(), MyError> {
+        for &(start, end) in &[
+            (100000, 999999),
+            (245182, 790572),
+            (111111, 111111),
+            (122233, 122234),
+            (123444, 123466),
+            (199999, 200000),
+            (555555, 123456),
+        ] {
+            assert_eq!(
+                valid_passwords(start, end)?.count() as u128,
+                count_valid_passwords(start, end),
+                "between {} and {}",
+                start,
+                end
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn options() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_values_from(
+                "secure-container",
+                &[1],
+                arguments(),
+                std::iter::once("secure-container").chain(args.iter().copied()),
+            )
+        };
+        let args = parse(&["245182", "790572", "--count"]).unwrap();
+        assert!(args.flag("count"));
+        assert_eq!(Some(245182), args.get::<u128>("start"));
+        assert_eq!(Some(790572), args.get::<u128>("end"));
+        assert!(parse(&["245182", "--count", "790572"])
+            .unwrap()
+            .flag("count"));
+        assert!(!parse(&["245182", "790572"]).unwrap().flag("count"));
+        assert!(parse(&["245182"]).is_err());
+        assert!(parse(&["245182", "790572", "1"]).is_err());
+        assert!(parse(&["245182", "many"]).is_err());
+    }
+
+    #[test]
+    fn too_large_to_check() {
+        assert!(matches!(
+            valid_passwords(123456, 10u128.pow(30)),
+            Err(MyError::InvalidBound(bound)) if bound == 10u128.pow(30)
+        ));
+    }
+
+    #[test]
+    fn any_number_of_digits() -> Result<(), MyError> {
+        assert_eq!(
+            count_valid_passwords(1, 999_999),
+            valid_passwords(1, 999_999)?.count() as u128
+        );
+        assert!(matches!(
+            valid_passwords(0, 10),
+            Err(MyError::InvalidBound(0))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn warned_of_a_long_search() -> Result<(), MyError> {
+        let mut passwords = valid_passwords(123456, usize::MAX as u128)?;
+        let mut progress = common::progress::eta(passwords.candidates() as u64, "Passwords");
+        while progress.projected().is_none() {
+            passwords.next();
+            progress.set_position(passwords.checked() as u64);
+        }
+        assert!(progress.warning().is_some());
+        Ok(())
+    }
+
+    #[cfg(not(feature = "no-group"))]
+    #[test]
+    fn astronomical_range() {
+        // Every non-decreasing password of each length, but the ones
+        // without any digit twice
+        let expected: u128 = (1..=30)
+            .map(|len| non_decreasing(len, 9) - binomial(9, len))
+            .sum();
+        assert_eq!(expected, count_valid_passwords(1, 10u128.pow(30) - 1));
+        assert_eq!(0, count_valid_passwords(0, 10));
+        assert_eq!(1, count_valid_passwords(11, 12));
+    }
 }
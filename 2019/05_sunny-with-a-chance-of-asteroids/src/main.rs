@@ -1,12 +1,10 @@
-use intcode::{self, Program};
+use intcode::{self, Profile, Program};
 use std::{
     env,
-    fs::File,
-    io::{self, BufRead, BufReader, Write},
+    io::{self, BufRead, Write},
     sync::mpsc,
     thread,
 };
-use thiserror;
 
 #[derive(Debug, thiserror::Error)]
 enum Error {
@@ -15,26 +13,42 @@ enum Error {
     #[error("Failed to convert to an Offset from a String")]
     InvalidStringOffset(#[from] std::num::ParseIntError),
     #[error("An error occured in program execution")]
-    ProgramError(#[from] intcode::Error),
+    FailedProgram(#[from] intcode::Error),
     #[error("Failed to send a message to the program")]
-    SendError(#[from] mpsc::SendError<i64>),
+    FailedSend(#[from] mpsc::SendError<i64>),
     #[error("Diagnostic has an error code of '{0}'")]
     InvalidDiagnostic(i64),
 }
 
-fn run_diagnostics(program: Vec<i64>, system_id: i64) -> Result<Vec<i64>, Error> {
+/// Outputs of the diagnostic program, with the instructions it executed.
+fn run_diagnostics(program: Vec<i64>, system_id: i64) -> Result<(Vec<i64>, Profile), Error> {
     let (sender_to_thread, receiver_from_host) = mpsc::sync_channel(0);
     let (sender_to_host, receiver_from_thread) = mpsc::sync_channel(0);
-    let mut program = Program::new(program, receiver_from_host, sender_to_host);
-    thread::spawn(move || program.run().unwrap());
+    let mut program = Program::new(program, receiver_from_host, sender_to_host).with_profile();
+    let handle = thread::spawn(move || {
+        program
+            .run()
+            .map(|_| program.profile().cloned().unwrap_or_default())
+    });
     let mut diagnostics = Vec::new();
     sender_to_thread.send(system_id)?;
-    loop {
-        match receiver_from_thread.recv() {
-            Ok(diagnostic) => diagnostics.push(diagnostic),
-            Err(_) => return Ok(diagnostics),
-        }
+    // The channel is closed once the program is over
+    while let Ok(diagnostic) = receiver_from_thread.recv() {
+        diagnostics.push(diagnostic);
     }
+    let profile = handle.join().expect("expect the program not to panic")?;
+    Ok((diagnostics, profile))
+}
+
+/// Number of executed instructions of each kind, the kinds never executed
+/// being pointed out as the diagnostic did not test them.
+fn coverage(profile: &Profile) -> String {
+    let mut report = String::from("Executed instructions:\n");
+    for (name, count) in profile.counts() {
+        let missed = if count == 0 { " (never executed)" } else { "" };
+        report += &format!("{:>15} {:>6}{}\n", name, count, missed);
+    }
+    report
 }
 
 fn check_diagnostics(diagnostics: Vec<i64>) -> Result<i64, Error> {
@@ -49,28 +63,23 @@ fn check_diagnostics(diagnostics: Vec<i64>) -> Result<i64, Error> {
 }
 
 fn main() -> Result<(), Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut program_str = String::new();
     reader.read_line(&mut program_str)?;
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .map_err(Error::from)?;
+    // The input may be the standard input, release it for the user input below
+    drop(reader);
+    let program = intcode::parse(&program_str)?;
     let mut buffer = String::new();
     print!("System ID: ");
     io::stdout().flush()?;
     io::stdin().read_line(&mut buffer)?;
     let system_id = buffer.trim().parse()?;
-    let diagnostics = run_diagnostics(program, system_id)?;
+    let (diagnostics, profile) = run_diagnostics(program, system_id)?;
     let diagnostic = check_diagnostics(diagnostics)?;
     println!("Diagnostic is {}", diagnostic);
+    print!("{}", coverage(&profile));
+    args.report_time();
     Ok(())
 }
 
@@ -82,7 +91,7 @@ mod tests {
     fn process_opcodes() -> Result<(), Error> {
         assert_eq!(
             vec![7, 42],
-            run_diagnostics(vec![103, 0, 104, 7, 104, 42, 99], 1)?
+            run_diagnostics(vec![103, 0, 104, 7, 104, 42, 99], 1)?.0
         );
         Ok(())
     }
@@ -91,8 +100,21 @@ mod tests {
     fn process_opcodes_with_tests_and_jumps() -> Result<(), Error> {
         assert_eq!(
             vec![0],
-            run_diagnostics(vec![103, 0, 1101, 9, 10, 3, 1008, 3, 3, 3, 4, 3, 99], 1)?
+            run_diagnostics(vec![103, 0, 1101, 9, 10, 3, 1008, 3, 3, 3, 4, 3, 99], 1)?.0
         );
         Ok(())
     }
+
+    #[test]
+    fn coverage_of_diagnostics() -> Result<(), Error> {
+        let (_, profile) =
+            run_diagnostics(vec![103, 0, 1101, 9, 10, 3, 1008, 3, 3, 3, 4, 3, 99], 1)?;
+        let report = coverage(&profile);
+        assert!(report.starts_with("Executed instructions:\n"));
+        assert!(report.contains("            add      1\n"));
+        assert!(report.contains("         equals      1\n"));
+        assert!(report.contains("       multiply      0 (never executed)\n"));
+        assert_eq!(11, report.lines().count());
+        Ok(())
+    }
 }
@@ -1,8 +1,7 @@
 use std::{
     collections::HashMap,
     env,
-    fs::File,
-    io::{BufRead, BufReader, Result},
+    io::{BufRead, Result},
 };
 
 type OrbitalObjects = HashMap<String, Option<String>>;
@@ -59,12 +58,11 @@ fn transfers_to_santa(orbital_objects: &OrbitalObjects) -> usize {
 }
 
 fn main() -> Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "santa") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     #[cfg(not(feature = "santa"))]
     {
         let orbital_objects = build_orbital_objects(reader.lines().map(Result::unwrap));
@@ -77,6 +75,7 @@ fn main() -> Result<()> {
         let transfers_count = transfers_to_santa(&orbital_objects);
         println!("Count of Transfers to Santa: {}", transfers_count);
     }
+    args.report_time();
     Ok(())
 }
 
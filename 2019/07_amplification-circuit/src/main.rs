@@ -1,12 +1,9 @@
 use intcode::{self, Program};
-use std::{
-    collections::HashSet,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-    sync::mpsc,
-    thread,
-};
+#[cfg(any(test, not(feature = "loopback")))]
+use std::collections::HashMap;
+#[cfg(any(test, feature = "loopback"))]
+use std::thread;
+use std::{collections::HashSet, env, io::BufRead, sync::mpsc};
 
 fn phase_settings_combinations(settings: Vec<i64>) -> HashSet<Vec<i64>> {
     let mut combinations = HashSet::new();
@@ -26,48 +23,58 @@ fn phase_settings_combinations(settings: Vec<i64>) -> HashSet<Vec<i64>> {
     combinations
 }
 
-fn optimize_thrusters(program: Vec<i64>) -> Result<i64, intcode::Error> {
-    let mut max_thrusting = 0;
-    #[cfg(not(feature = "loopback"))]
-    let phase_combinations = phase_settings_combinations(vec![0, 1, 2, 3, 4]);
-    #[cfg(feature = "loopback")]
-    let phase_combinations = phase_settings_combinations(vec![5, 6, 7, 8, 9]);
-    for phase_settings in phase_combinations {
-        let mut threads = Vec::new();
-        let (mut sender, mut receiver) = mpsc::sync_channel(1);
-        let init_sender = sender.clone();
-        for phase_setting in phase_settings {
-            sender.send(phase_setting)?;
-            let channel = mpsc::sync_channel(1);
-            sender = channel.0;
-            let next_receiver = channel.1;
-            let sender_for_thread = sender.clone();
-            let mut program_for_thread = Program::new(program.clone(), receiver, sender_for_thread);
-            let thread = thread::Builder::new()
-                .name(phase_setting.to_string())
-                .spawn(move || {
-                    program_for_thread.run().unwrap();
-                });
-            threads.push(thread);
-            receiver = next_receiver;
+/// Run a single amplifier with its phase setting on the input signal.
+#[cfg(any(test, not(feature = "loopback")))]
+fn amplify(program: &[i64], phase_setting: i64, signal: i64) -> Result<i64, intcode::Error> {
+    let (sender, receiver_from_host) = mpsc::sync_channel(2);
+    let (sender_to_host, receiver) = mpsc::sync_channel(1);
+    sender.send(phase_setting)?;
+    sender.send(signal)?;
+    Program::new(program.to_vec(), receiver_from_host, sender_to_host).run()?;
+    Ok(receiver.recv()?)
+}
+
+/// Amplifiers in series, remembering the output signal of each prefix of
+/// phase settings so that permutations sharing a prefix do not run it again.
+#[cfg(any(test, not(feature = "loopback")))]
+struct SerialChain<'p> {
+    program: &'p [i64],
+    signals: HashMap<Vec<i64>, i64>,
+    runs: usize,
+}
+
+#[cfg(any(test, not(feature = "loopback")))]
+impl<'p> SerialChain<'p> {
+    fn new(program: &'p [i64]) -> Self {
+        SerialChain {
+            program,
+            signals: HashMap::new(),
+            runs: 0,
         }
-        init_sender.send(0)?;
-        #[cfg(not(feature = "loopback"))]
-        let output = receiver.recv()?;
-        #[cfg(feature = "loopback")]
-        let output = {
-            let mut output = receiver.recv()?;
-            loop {
-                if let Err(_) = init_sender.send(output) {
-                    break;
-                };
-                output = match receiver.recv() {
-                    Ok(o) => o,
-                    Err(_) => break,
-                };
-            }
-            output
+    }
+
+    fn output(&mut self, phase_settings: &[i64]) -> Result<i64, intcode::Error> {
+        let (phase_setting, prefix) = match phase_settings.split_last() {
+            Some(split) => split,
+            None => return Ok(0),
         };
+        if let Some(&signal) = self.signals.get(phase_settings) {
+            return Ok(signal);
+        }
+        let input = self.output(prefix)?;
+        let signal = amplify(self.program, *phase_setting, input)?;
+        self.runs += 1;
+        self.signals.insert(phase_settings.to_vec(), signal);
+        Ok(signal)
+    }
+}
+
+#[cfg(not(feature = "loopback"))]
+fn optimize_thrusters(program: Vec<i64>) -> Result<i64, intcode::Error> {
+    let mut chain = SerialChain::new(&program);
+    let mut max_thrusting = 0;
+    for phase_settings in phase_settings_combinations(vec![0, 1, 2, 3, 4]) {
+        let output = chain.output(&phase_settings)?;
         if output > max_thrusting {
             max_thrusting = output;
         }
@@ -75,23 +82,67 @@ fn optimize_thrusters(program: Vec<i64>) -> Result<i64, intcode::Error> {
     Ok(max_thrusting)
 }
 
-fn main() -> Result<(), intcode::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+/// Run the amplifiers in a loop, each one on its own thread, until they halt,
+/// giving the last signal sent to the thrusters.
+#[cfg(any(test, feature = "loopback"))]
+fn loopback_output(program: &[i64], phase_settings: Vec<i64>) -> Result<i64, intcode::Error> {
+    let (mut sender, mut receiver) = mpsc::sync_channel(1);
+    let init_sender = sender.clone();
+    for phase_setting in phase_settings {
+        sender.send(phase_setting)?;
+        let channel = mpsc::sync_channel(1);
+        sender = channel.0;
+        let next_receiver = channel.1;
+        let sender_for_thread = sender.clone();
+        let mut program_for_thread = Program::new(program.to_vec(), receiver, sender_for_thread);
+        thread::Builder::new()
+            .name(phase_setting.to_string())
+            .spawn(move || {
+                program_for_thread.run().unwrap();
+            })
+            .expect("expect the thread of an amplifier to start");
+        receiver = next_receiver;
     }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+    // Only the last amplifier sends to the host, so that no more signal is
+    // received once it halts
+    drop(sender);
+    init_sender.send(0)?;
+    let mut output = receiver.recv()?;
+    loop {
+        if init_sender.send(output).is_err() {
+            break;
+        };
+        output = match receiver.recv() {
+            Ok(o) => o,
+            Err(_) => break,
+        };
+    }
+    Ok(output)
+}
+
+#[cfg(feature = "loopback")]
+fn optimize_thrusters(program: Vec<i64>) -> Result<i64, intcode::Error> {
+    let phase_combinations = phase_settings_combinations(vec![5, 6, 7, 8, 9]);
+    common::parallel::map_reduce(
+        phase_combinations.into_iter().collect(),
+        || Ok(0),
+        |phase_settings| loopback_output(&program, phase_settings),
+        |first, second| Ok(first?.max(second?)),
+    )
+}
+
+fn main() -> Result<(), intcode::Error> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "loopback") { 2 } else { 1 }],
+    );
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut program_str = String::new();
     reader.read_line(&mut program_str)?;
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .map_err(intcode::Error::from)?;
+    let program = intcode::parse(&program_str)?;
     let max_thrusting = optimize_thrusters(program)?;
     println!("Max thrusting is {}", max_thrusting);
+    args.report_time();
     Ok(())
 }
 
@@ -140,6 +191,95 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(not(feature = "loopback"))]
+    #[test]
+    fn serial_chain_runs() -> Result<(), intcode::Error> {
+        let program = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        let mut chain = SerialChain::new(&program);
+        assert_eq!(43210, chain.output(&[4, 3, 2, 1, 0])?);
+        assert_eq!(5, chain.runs);
+        // Only the last amplifier differs
+        assert_eq!(43201, chain.output(&[4, 3, 2, 0, 1])?);
+        assert_eq!(7, chain.runs);
+        let mut chain = SerialChain::new(&program);
+        for phase_settings in phase_settings_combinations(vec![0, 1, 2, 3, 4]) {
+            chain.output(&phase_settings)?;
+        }
+        // Instead of 120 permutations times 5 amplifiers, each distinct prefix
+        // runs once: 5 + 5*4 + 5*4*3 + 5*4*3*2 + 5*4*3*2*1
+        assert_eq!(5 + 20 + 60 + 120 + 120, chain.runs);
+        Ok(())
+    }
+
+    #[test]
+    fn loopback_of_pipelines() -> Result<(), intcode::Error> {
+        // Programs halting after a single output never read the signal fed
+        // back, so the loop gives the same signals as the amplifiers in series
+        let programs = vec![
+            vec![
+                3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+            ],
+            vec![
+                3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4,
+                23, 99, 0, 0,
+            ],
+            vec![
+                3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33,
+                1, 33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+            ],
+        ];
+        for program in &programs {
+            let mut chain = SerialChain::new(program);
+            let mut max_serial = 0;
+            let mut max_loopback = 0;
+            for phase_settings in phase_settings_combinations(vec![0, 1, 2, 3, 4]) {
+                let serial = chain.output(&phase_settings)?;
+                let loopback = loopback_output(program, phase_settings.clone())?;
+                assert_eq!(serial, loopback, "phase settings {:?}", phase_settings);
+                max_serial = max_serial.max(serial);
+                max_loopback = max_loopback.max(loopback);
+            }
+            assert_eq!(max_serial, max_loopback);
+        }
+        Ok(())
+    }
+
+    mod loopback_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// A pipeline amplifier: it reads its phase setting `p` and the signal
+        /// `s`, outputs `a * s + b * p + c` and halts.
+        fn pipeline() -> impl Strategy<Value = Vec<i64>> {
+            (-10i64..10, -10i64..10, -100i64..100).prop_map(|(a, b, c)| {
+                vec![
+                    3, 23, 3, 24, 1002, 24, a, 24, 1002, 23, b, 23, 1, 24, 23, 24, 1001, 24, c, 24,
+                    4, 24, 99, 0, 0,
+                ]
+            })
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(16))]
+            #[test]
+            fn same_max_thrust_as_serial(program in pipeline()) {
+                let mut chain = SerialChain::new(&program);
+                let mut max_serial = i64::MIN;
+                let mut max_loopback = i64::MIN;
+                for phase_settings in phase_settings_combinations(vec![0, 1, 2, 3, 4]) {
+                    let serial = chain.output(&phase_settings).unwrap();
+                    let loopback = loopback_output(&program, phase_settings).unwrap();
+                    prop_assert_eq!(serial, loopback);
+                    max_serial = max_serial.max(serial);
+                    max_loopback = max_loopback.max(loopback);
+                }
+                prop_assert_eq!(max_serial, max_loopback);
+            }
+        }
+    }
+
     #[cfg(feature = "loopback")]
     #[test]
     fn process_opcodes() -> Result<(), intcode::Error> {
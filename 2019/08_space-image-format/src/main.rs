@@ -1,9 +1,13 @@
 #[cfg(not(feature = "decode"))]
-use std::collections::HashMap;
+use common::cli;
+use common::cli::{value_parser, Arg, Args};
+#[cfg(feature = "animation")]
+use std::path::PathBuf;
+#[cfg(not(feature = "decode"))]
+use std::{collections::HashMap, str::FromStr};
 use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    fmt::{self, Display, Formatter},
+    io::BufRead,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -24,6 +28,23 @@ impl From<char> for Color {
     }
 }
 
+/// A color given as an option, a single digit.
+#[cfg(not(feature = "decode"))]
+impl FromStr for Color {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(Color::Black),
+            "1" => Ok(Color::White),
+            "2" => Ok(Color::Transparent),
+            s => Err(format!(
+                "expect a color among '0', '1' and '2', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
 type ImageData = Vec<Color>;
 struct Layer<'a> {
     rows: Vec<&'a [Color]>,
@@ -41,6 +62,90 @@ impl Layer<'_> {
         }
         frequencies
     }
+
+    fn count(&self, color: Color) -> usize {
+        self.frequencies().get(&color).copied().unwrap_or(0)
+    }
+}
+
+/// Colors used to compute the score of an image: the layer with the fewest
+/// cells of color `fewest` is selected, then the score is the product of its
+/// numbers of cells of each color in `product`.
+#[cfg(not(feature = "decode"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Checksum {
+    fewest: Color,
+    product: (Color, Color),
+}
+
+#[cfg(not(feature = "decode"))]
+impl Default for Checksum {
+    // Layer with the fewest '0' digits, then number of '1' times number of '2'
+    fn default() -> Self {
+        Checksum {
+            fewest: Color::Black,
+            product: (Color::White, Color::Transparent),
+        }
+    }
+}
+
+#[cfg(not(feature = "decode"))]
+impl Checksum {
+    /// Arguments `--fewest <DIGIT>` and `--product <DIGIT,DIGIT>`, to be
+    /// given to `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("fewest")
+                .long("fewest")
+                .value_name("DIGIT")
+                .value_parser(value_parser!(Color))
+                .help("Color of the layer to select, the one with the fewest cells of it"),
+            Arg::new("product")
+                .long("product")
+                .value_name("DIGIT,DIGIT")
+                .help("Colors of the selected layer whose numbers of cells are multiplied"),
+        ]
+    }
+
+    /// Checksum read from `args`, any missing option keeping its default
+    /// value.
+    fn from_args(args: &Args) -> Result<Self, cli::Error> {
+        let mut checksum = Checksum::default();
+        if let Some(fewest) = args.get::<Color>("fewest") {
+            checksum.fewest = fewest;
+        }
+        if let Some(product) = args.get::<String>("product") {
+            let mut colors = product.split(',').map(str::parse::<Color>);
+            checksum.product = match (colors.next(), colors.next(), colors.next()) {
+                (Some(Ok(color1)), Some(Ok(color2)), None) => (color1, color2),
+                (Some(Err(error)), _, _) | (_, Some(Err(error)), _) => {
+                    return Err(args.error(error))
+                }
+                _ => {
+                    return Err(args.error(format!(
+                        "option '--product' expects two colors, got '{}'",
+                        product
+                    )))
+                }
+            };
+        }
+        Ok(checksum)
+    }
+
+    fn score(&self, layers: &[Layer]) -> Option<usize> {
+        let layer = layers.iter().min_by_key(|layer| layer.count(self.fewest))?;
+        Some(layer.count(self.product.0) * layer.count(self.product.1))
+    }
+}
+
+#[cfg(feature = "animation")]
+impl Color {
+    fn rgb(_: common::render::Position, color: Option<&Color>) -> viz::Rgb {
+        match color {
+            Some(Color::White) => [255, 255, 255],
+            _ => [0, 0, 0],
+        }
+    }
 }
 
 #[cfg(feature = "decode")]
@@ -54,6 +159,15 @@ impl Image {
         Image { rows: Vec::new() }
     }
 
+    /// The image drawn by stacking `layers`, the first one on top.
+    fn decode(layers: &[Layer]) -> Self {
+        let mut image = Image::new();
+        for layer in layers {
+            image.apply(layer);
+        }
+        image
+    }
+
     fn apply(&mut self, layer: &Layer) {
         for row_index in 0..layer.rows.len() {
             let layer_row = layer.rows[row_index];
@@ -61,10 +175,10 @@ impl Image {
                 self.rows.push(Vec::new());
             }
             let row = self.rows.get_mut(row_index).unwrap();
-            for cell_index in 0..layer_row.len() {
+            for (cell_index, &layer_color) in layer_row.iter().enumerate() {
                 use self::Color::*;
                 let cell = row.get(cell_index);
-                let new_color = match (cell, layer_row[cell_index]) {
+                let new_color = match (cell, layer_color) {
                     (None, color) => color,
                     (Some(Black), _) => Black,
                     (Some(White), _) => White,
@@ -78,6 +192,16 @@ impl Image {
             }
         }
     }
+
+    /// The letters drawn by the white cells, if they can be read.
+    fn password(&self) -> Option<String> {
+        let white_rows: Vec<Vec<bool>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|&color| color == Color::White).collect())
+            .collect();
+        common::ocr::read(&white_rows)
+    }
 }
 
 fn build_layers<'a>(wide: usize, tall: usize, image_data: &'a ImageData) -> Layers<'a> {
@@ -94,48 +218,173 @@ fn build_layers<'a>(wide: usize, tall: usize, image_data: &'a ImageData) -> Laye
     layers
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Error {
+    MissingLayers {
+        layers: usize,
+        count: usize,
+    },
+    #[cfg(feature = "decode")]
+    PartialImages {
+        layers: usize,
+        layers_per_image: usize,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::MissingLayers { layers, count } => write!(
+                f,
+                "the image has {} layers, fewer than the {} to keep",
+                layers, count
+            ),
+            #[cfg(feature = "decode")]
+            Error::PartialImages {
+                layers,
+                layers_per_image,
+            } => write!(
+                f,
+                "the {} layers can't be split into images of {} layers",
+                layers, layers_per_image
+            ),
+        }
+    }
+}
+
+/// The first `count` layers of the image, the ones after being ignored.
+fn first_layers(mut layers: Layers, count: usize) -> Result<Layers, Error> {
+    if count > layers.len() {
+        return Err(Error::MissingLayers {
+            layers: layers.len(),
+            count,
+        });
+    }
+    layers.truncate(count);
+    Ok(layers)
+}
+
+/// Images encoded back-to-back, `layers_per_image` layers each, e.g. the
+/// frames of an animation. The number of images follows from the number of
+/// layers.
+#[cfg(feature = "decode")]
+fn decode_images(layers: &[Layer], layers_per_image: usize) -> Result<Vec<Image>, Error> {
+    if layers_per_image == 0 || !layers.len().is_multiple_of(layers_per_image) {
+        return Err(Error::PartialImages {
+            layers: layers.len(),
+            layers_per_image,
+        });
+    }
+    Ok(layers.chunks(layers_per_image).map(Image::decode).collect())
+}
+
+/// Arguments `--width <PIXELS>` and `--height <PIXELS>` of the layers and
+/// `--layer-count <COUNT>`, next to the ones of the selected part, to be given
+/// to `cli::Args::parse_with`.
+fn arguments() -> Vec<Arg> {
+    let pixels = || value_parser!(u64).range(1..);
+    #[allow(unused_mut)]
+    let mut arguments = vec![
+        Arg::new("width")
+            .long("width")
+            .value_name("PIXELS")
+            .value_parser(pixels())
+            .default_value("25")
+            .help("Width of a layer"),
+        Arg::new("height")
+            .long("height")
+            .value_name("PIXELS")
+            .value_parser(pixels())
+            .default_value("6")
+            .help("Height of a layer"),
+        Arg::new("layer-count")
+            .long("layer-count")
+            .value_name("COUNT")
+            .value_parser(value_parser!(u64).range(1..))
+            .help("Layers of the image to keep, the first ones, all of them by default"),
+    ];
+    #[cfg(not(feature = "decode"))]
+    arguments.extend(Checksum::arguments());
+    #[cfg(feature = "decode")]
+    arguments.push(
+        Arg::new("layers")
+            .long("layers")
+            .value_name("COUNT")
+            .value_parser(value_parser!(u64).range(1..))
+            .help("Layers of each image, all the layers making a single image by default"),
+    );
+    #[cfg(feature = "animation")]
+    arguments.push(
+        Arg::new("gif")
+            .long("gif")
+            .value_name("PATH")
+            .value_parser(value_parser!(PathBuf))
+            .help("Where to write the animation, instead of $AOC_ANIMATION or animation.gif"),
+    );
+    arguments
+}
+
 fn main() -> std::io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        panic!("Four arguments needed, received {:#?}", args);
-    }
-    let wide = args[1].parse().unwrap();
-    let tall = args[2].parse().unwrap();
-    let file = File::open(&args[3])?;
-    let mut reader = BufReader::new(file);
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "decode") { 2 } else { 1 }],
+        arguments(),
+    );
+    let wide = args.get::<u64>("width").unwrap_or(25) as usize;
+    let tall = args.get::<u64>("height").unwrap_or(6) as usize;
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut image_data = String::new();
     reader.read_line(&mut image_data)?;
     let image_data = image_data.trim().chars().map(Color::from).collect();
-    let layers = build_layers(wide, tall, &image_data);
+    let mut layers = build_layers(wide, tall, &image_data);
+    if let Some(count) = args.get::<u64>("layer-count") {
+        layers =
+            first_layers(layers, count as usize).unwrap_or_else(|error| args.error(error).exit());
+    }
     #[cfg(not(feature = "decode"))]
     {
-        let min_layer = layers
-            .into_iter()
-            .min_by_key(|layer| *layer.frequencies().get(&Color::Black).unwrap())
-            .unwrap();
-        let frequencies = min_layer.frequencies();
-        let score = *frequencies.get(&Color::White).unwrap()
-            * *frequencies.get(&Color::Transparent).unwrap();
+        let score = Checksum::from_args(&args)
+            .unwrap_or_else(|error| error.exit())
+            .score(&layers)
+            .expect("expect at least one layer in the image");
         println!("Final score is {}", score);
     }
     #[cfg(feature = "decode")]
     {
-        let mut image = Image::new();
-        for layer in &layers {
-            image.apply(layer);
+        let layers_per_image = args
+            .get::<u64>("layers")
+            .map_or(layers.len(), |count| count as usize);
+        let images = decode_images(&layers, layers_per_image)
+            .unwrap_or_else(|error| args.error(error).exit());
+        for (index, image) in images.iter().enumerate() {
+            if images.len() > 1 {
+                println!("Frame {}", index + 1);
+            }
+            for row in &image.rows {
+                for cell in row {
+                    use self::Color::*;
+                    let c = match cell {
+                        White => '█',
+                        _ => ' ',
+                    };
+                    print!("{}", c);
+                }
+                println!();
+            }
+            if let Some(password) = image.password() {
+                println!("The password is {}", password);
+            }
         }
-        for row in image.rows {
-            for cell in row {
-                use self::Color::*;
-                let c = match cell {
-                    White => '█',
-                    _ => ' ',
-                };
-                print!("{}", c);
+        #[cfg(feature = "animation")]
+        {
+            let mut animation = viz::Animation::default().delay(50);
+            for image in &images {
+                animation.capture(&image.rows, Color::rgb);
             }
-            println!();
+            animation.save(args.get::<PathBuf>("gif").unwrap_or_else(viz::path))?;
         }
     }
+    args.report_time();
     Ok(())
 }
 
@@ -143,6 +392,29 @@ fn main() -> std::io::Result<()> {
 mod tests {
     use self::Color::*;
     use super::*;
+    use common::cli;
+
+    fn parse(args: &[&str]) -> Result<Args, cli::Error> {
+        Args::try_parse_from(
+            "space-image-format",
+            &[1],
+            arguments(),
+            std::iter::once("space-image-format").chain(args.iter().copied()),
+        )
+    }
+
+    #[test]
+    fn dimensions() {
+        let args = parse(&["image.txt"]).unwrap();
+        assert_eq!(Some(25), args.get::<u64>("width"));
+        assert_eq!(Some(6), args.get::<u64>("height"));
+        assert_eq!(Some("image.txt"), args.input());
+        let args = parse(&["--width", "3", "--height", "2"]).unwrap();
+        assert_eq!(Some(3), args.get::<u64>("width"));
+        assert_eq!(Some(2), args.get::<u64>("height"));
+        assert!(parse(&["--width", "0"]).is_err());
+        assert!(parse(&["25", "6", "image.txt"]).is_err());
+    }
 
     #[test]
     fn get_layers() {
@@ -172,16 +444,96 @@ mod tests {
         assert_eq!([Black, White, Transparent], layer.rows[1]);
     }
 
+    #[test]
+    fn layer_count() {
+        let data = vec![Black; 8];
+        let layers = first_layers(build_layers(2, 1, &data), 3).unwrap();
+        assert_eq!(3, layers.len());
+        let error = first_layers(build_layers(2, 1, &data), 5).err().unwrap();
+        assert_eq!(
+            Error::MissingLayers {
+                layers: 4,
+                count: 5
+            },
+            error
+        );
+        assert_eq!(
+            "the image has 4 layers, fewer than the 5 to keep",
+            error.to_string()
+        );
+        assert_eq!(
+            Some(2),
+            parse(&["--layer-count", "2"])
+                .unwrap()
+                .get::<u64>("layer-count")
+        );
+        assert!(parse(&["--layer-count", "0"]).is_err());
+    }
+
     #[cfg(not(feature = "decode"))]
     #[test]
     fn frequencies() {
-        let data = vec![Black, Black, White, Black];
+        let data = [Black, Black, White, Black];
         let layer = Layer {
             rows: vec![&data[0..2], &data[2..4]],
         };
         let frequencies = layer.frequencies();
         assert_eq!(3, *frequencies.get(&Black).unwrap());
         assert_eq!(1, *frequencies.get(&White).unwrap());
+        assert_eq!(0, layer.count(Transparent));
+    }
+
+    #[cfg(not(feature = "decode"))]
+    #[test]
+    fn checksum_options() {
+        let checksum = |args: &[&str]| Checksum::from_args(&parse(args)?);
+        assert_eq!(Checksum::default(), checksum(&[]).unwrap());
+        assert_eq!(
+            Checksum {
+                fewest: Transparent,
+                product: (Black, Black)
+            },
+            checksum(&["--product", "0,0", "--fewest", "2"]).unwrap()
+        );
+        assert!(checksum(&["--fewest", "3"]).is_err());
+        assert!(checksum(&["--product", "0"]).is_err());
+        assert!(checksum(&["--product", "0,1,2"]).is_err());
+        assert!(checksum(&["--product", "0,x"]).is_err());
+    }
+
+    #[cfg(not(feature = "decode"))]
+    #[test]
+    fn checksum() {
+        let data = vec![
+            Black,
+            White,
+            Transparent,
+            Transparent,
+            White,
+            White,
+            Black,
+            Black,
+        ];
+        let layers = build_layers(2, 2, &data);
+        assert_eq!(Some(2), Checksum::default().score(&layers));
+        let checksum = Checksum {
+            fewest: Transparent,
+            product: (Black, White),
+        };
+        assert_eq!(Some(4), checksum.score(&layers));
+        assert_eq!(None, Checksum::default().score(&[]));
+    }
+
+    #[cfg(not(feature = "decode"))]
+    #[test]
+    fn checksum_of_stored_input() {
+        let data = include_str!("../../../inputs/2019/08.txt")
+            .trim()
+            .chars()
+            .map(Color::from)
+            .collect();
+        let layers = build_layers(25, 6, &data);
+        assert_eq!(Some(2975), Checksum::default().score(&layers));
     }
 
     #[cfg(feature = "decode")]
@@ -224,4 +576,61 @@ mod tests {
         image.apply(&layer4);
         assert_eq!(Black, image.rows[0][0]);
     }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn password_of_stored_input() {
+        let data = include_str!("../../../inputs/2019/08.txt")
+            .trim()
+            .chars()
+            .map(Color::from)
+            .collect();
+        let image = Image::decode(&build_layers(25, 6, &data));
+        assert_eq!(Some("EHRUE".to_string()), image.password());
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn frames() {
+        // Two frames of two layers, the second frame being the first inverted
+        let data = vec![
+            Transparent,
+            White,
+            Black,
+            Transparent,
+            White,
+            Transparent,
+            Transparent,
+            Black,
+        ];
+        let layers = build_layers(2, 1, &data);
+        let images = decode_images(&layers, 2).unwrap();
+        assert_eq!(2, images.len());
+        assert_eq!(vec![vec![Black, White]], images[0].rows);
+        assert_eq!(vec![vec![White, Black]], images[1].rows);
+        assert_eq!(1, decode_images(&layers, 4).unwrap().len());
+        assert_eq!(
+            Some(2),
+            parse(&["--layers", "2"]).unwrap().get::<u64>("layers")
+        );
+        assert!(parse(&["--layers", "0"]).is_err());
+    }
+
+    #[cfg(feature = "decode")]
+    #[test]
+    fn frames_of_partial_images() {
+        let data = vec![Black; 8];
+        let layers = build_layers(2, 1, &data);
+        assert_eq!(
+            Some(Error::PartialImages {
+                layers: 4,
+                layers_per_image: 3
+            }),
+            decode_images(&layers, 3).err()
+        );
+        assert_eq!(
+            "the 4 layers can't be split into images of 3 layers",
+            decode_images(&layers, 3).err().unwrap().to_string()
+        );
+    }
 }
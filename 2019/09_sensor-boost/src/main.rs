@@ -1,36 +1,43 @@
 use intcode::{self, Program};
 use std::{
     env,
-    fs::File,
-    io::{self, BufRead, BufReader, Write},
+    io::{self, BufRead, Write},
     sync::mpsc,
     thread,
 };
 
-fn boost_keycode(opcodes: Vec<i64>, user_mode: i64) -> Result<i64, intcode::Error> {
-    let (sender_to_thread, receiver_from_host) = mpsc::sync_channel(0);
+/// Run the program with `inputs` until it halts, and give every output.
+fn boost(opcodes: Vec<i64>, inputs: &[i64]) -> Result<Vec<i64>, intcode::Error> {
+    let (sender_to_thread, receiver_from_host) = mpsc::sync_channel(inputs.len());
+    for &input in inputs {
+        sender_to_thread.send(input)?;
+    }
+    // A program asking for more inputs fails instead of waiting forever
+    drop(sender_to_thread);
     let (sender_to_host, receiver_from_thread) = mpsc::sync_channel(0);
     let mut program = Program::new(opcodes, receiver_from_host, sender_to_host);
-    thread::spawn(move || program.run().unwrap());
-    sender_to_thread.send(user_mode)?;
-    receiver_from_thread.recv().map_err(intcode::Error::from)
+    let thread = thread::spawn(move || program.run());
+    let outputs = receiver_from_thread.iter().collect();
+    thread.join().expect("expect the program not to panic")?;
+    Ok(outputs)
+}
+
+/// The keycode is the last output, after the opcodes which malfunction, if
+/// any, in test mode. A program halting without any output has no keycode.
+fn boost_keycode(opcodes: Vec<i64>, user_mode: i64) -> Result<i64, intcode::Error> {
+    boost(opcodes, &[user_mode])?
+        .pop()
+        .ok_or(intcode::Error::OutputsExhausted)
 }
 
 fn main() -> Result<(), intcode::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut program_str = String::new();
     reader.read_line(&mut program_str)?;
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .map_err(intcode::Error::from)?;
+    // The input may be the standard input, release it for the user input below
+    drop(reader);
+    let program = intcode::parse(&program_str)?;
     let mut buffer = String::new();
     print!("Input mode: ");
     io::stdout().flush()?;
@@ -38,5 +45,60 @@ fn main() -> Result<(), intcode::Error> {
     let user_mode = buffer.trim().parse()?;
     let boost_keycode = boost_keycode(program, user_mode)?;
     println!("Boost Keycode is {}", boost_keycode);
+    args.report_time();
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, inputs: &[i64]) -> Vec<i64> {
+        boost(intcode::parse(program).unwrap(), inputs).unwrap()
+    }
+
+    #[test]
+    fn quine() {
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        assert_eq!(intcode::parse(program).unwrap(), run(program, &[]));
+    }
+
+    #[test]
+    fn large_numbers() {
+        let outputs = run("1102,34915192,34915192,7,4,7,99,0", &[]);
+        assert_eq!(vec![1_219_070_632_396_864], outputs);
+        assert_eq!(16, outputs[0].to_string().len());
+        let outputs = run("104,1125899906842624,99", &[]);
+        assert_eq!(vec![1_125_899_906_842_624], outputs);
+    }
+
+    #[test]
+    fn relative_base() {
+        // base = 100, input in [base], output [base]
+        assert_eq!(vec![42], run("109,100,203,0,204,0,99", &[42]));
+        // base = 100, input in [base], base = 50, output [base + 50]
+        assert_eq!(vec![42], run("109,100,203,0,109,-50,204,50,99", &[42]));
+        // base = 20, [base + 1] = 3 + 4, output [base + 1]
+        assert_eq!(vec![7], run("109,20,21101,3,4,1,204,1,99", &[]));
+        // base = 4, output [base - 4], the first opcode
+        assert_eq!(vec![109], run("109,4,204,-4,99", &[]));
+        // base = 5, base += [base - 1] = 204, output [base - 209]
+        assert_eq!(vec![109], run("109,5,209,-1,204,-209,99", &[]));
+        // memory past the program reads as 0
+        assert_eq!(vec![0], run("109,1000,204,0,99", &[]));
+    }
+
+    #[test]
+    fn boost_keycode_is_the_last_output() {
+        assert_eq!(
+            2,
+            boost_keycode(intcode::parse("104,1,104,2,99").unwrap(), 1).unwrap()
+        );
+        assert!(matches!(
+            boost_keycode(intcode::parse("99").unwrap(), 1),
+            Err(intcode::Error::OutputsExhausted)
+        ));
+        // Asking for a second input fails instead of waiting for it
+        assert!(boost(intcode::parse("3,0,3,0,99").unwrap(), &[1]).is_err());
+    }
+}
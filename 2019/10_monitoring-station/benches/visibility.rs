@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use monitoring_station::{legacy, visible_asteroids, Asteroid, Map};
+
+/// The example maps of the puzzle, by the number of asteroids seen from
+/// their best monitoring station.
+const EXAMPLES: [(&str, &str); 5] = [
+    ("8", ".#..#\n.....\n#####\n....#\n...##"),
+    (
+        "33",
+        "......#.#.\n#..#.#....\n..#######.\n.#.#.###..\n.#..#.....\n\
+         ..#....#.#\n#..#....#.\n.##.#..###\n##...#..#.\n.#....####",
+    ),
+    (
+        "35",
+        "#.#...#.#.\n.###....#.\n.#....#...\n##.#.#.#.#\n....#.#.#.\n\
+         .##..###.#\n..#...##..\n..##....##\n......#...\n.####.###.",
+    ),
+    (
+        "41",
+        ".#..#..###\n####.###.#\n....###.#.\n..###.##.#\n##.##.#.#.\n\
+         ....###..#\n..#.#..#.#\n#..#.#.###\n.##...##.#\n.....#.#..",
+    ),
+    (
+        "210",
+        ".#..##.###...#######\n##.############..##.\n.#.######.########.#\n\
+         .###.#######.####.#.\n#####.##.#.##.###.##\n..#####..#.#########\n\
+         ####################\n#.####....###.#.#.##\n##.#################\n\
+         #####.##.###..####..\n..######..##.#######\n####.##.####...##..#\n\
+         .#####..#.######.###\n##...#.##########...\n#.##########.#######\n\
+         .####.#.###.###.#.##\n....##.##.###..#####\n.#.#.###########.###\n\
+         #.#.#.#####.####.###\n###.##.####.##.#..##",
+    ),
+];
+
+/// A field of `size` by `size` where two cells out of three hold an asteroid.
+fn dense_field(size: usize) -> String {
+    (0..size)
+        .map(|y| {
+            (0..size)
+                .map(|x| if (7 * x + 13 * y) % 3 == 0 { '.' } else { '#' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn visibility(c: &mut Criterion) {
+    let mut group = c.benchmark_group("visible_asteroids");
+    group.sample_size(10);
+    let maps = EXAMPLES
+        .iter()
+        .map(|(name, map)| (name.to_string(), map.to_string()))
+        .chain(std::iter::once((
+            "dense 100x100".to_string(),
+            dense_field(100),
+        )));
+    for (name, text) in maps {
+        // From the middle of the map, asteroid or not
+        let origin = Asteroid {
+            x: text.lines().next().map(str::len).unwrap_or(0) / 2,
+            y: text.lines().count() / 2,
+        };
+        let map = Map::from(text);
+        group.bench_with_input(BenchmarkId::new("legacy", &name), &origin, |b, origin| {
+            b.iter(|| legacy::visible_asteroids(origin, &map))
+        });
+        group.bench_with_input(BenchmarkId::new("gcd", &name), &origin, |b, origin| {
+            b.iter(|| visible_asteroids(origin, &map))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, visibility);
+criterion_main!(benches);
@@ -0,0 +1,776 @@
+use common::genvec::Rng;
+use std::{
+    cmp::{Ord, Ordering, PartialOrd},
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt::{Display, Error, Formatter},
+    iter::{FromIterator, IntoIterator},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Asteroid {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl From<(usize, usize)> for Asteroid {
+    fn from((x, y): (usize, usize)) -> Self {
+        Asteroid { x, y }
+    }
+}
+
+impl From<Asteroid> for (usize, usize) {
+    fn from(asteroid: Asteroid) -> Self {
+        (asteroid.x, asteroid.y)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct LaserRay<'a> {
+    from: &'a Asteroid,
+    to: &'a Asteroid,
+}
+
+impl LaserRay<'_> {
+    fn vector(&self) -> (i64, i64) {
+        (
+            self.to.x as i64 - self.from.x as i64,
+            self.to.y as i64 - self.from.y as i64,
+        )
+    }
+}
+
+impl<'a> From<(&'a Asteroid, &'a Asteroid)> for LaserRay<'a> {
+    fn from((from, to): (&'a Asteroid, &'a Asteroid)) -> Self {
+        LaserRay { from, to }
+    }
+}
+
+impl PartialOrd for LaserRay<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LaserRay<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let self_v = self.vector();
+        let other_v = other.vector();
+        // Clockwise from straight up, the right half-plane (including
+        // straight up) comes before the left half-plane (including straight
+        // down).
+        let half_plane = |v: (i64, i64)| {
+            if v.0 > 0 || (v.0 == 0 && v.1 < 0) {
+                0
+            } else {
+                1
+            }
+        };
+        match half_plane(self_v).cmp(&half_plane(other_v)) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+        let dydx = -self_v.1 * other_v.0;
+        let dydx2 = -other_v.1 * self_v.0;
+        if dydx > dydx2 {
+            Ordering::Less
+        } else if dydx < dydx2 {
+            Ordering::Greater
+        } else {
+            let d = self_v.0 * self_v.0 + self_v.1 * self_v.1;
+            let d2 = other_v.0 * other_v.0 + other_v.1 * other_v.1;
+            if d < d2 {
+                Ordering::Less
+            } else if d > d2 {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Map {
+    asteroids: HashSet<Asteroid>,
+    width: usize,
+    height: usize,
+    pub monitoring_station: Option<Asteroid>,
+}
+
+impl From<String> for Map {
+    fn from(string_map: String) -> Self {
+        let mut map = Map::default();
+        for (y, line) in string_map.lines().enumerate() {
+            map.height = y + 1;
+            for (x, cell) in line.chars().enumerate() {
+                map.width = x + 1;
+                match cell {
+                    '#' => {
+                        map.asteroids.insert(Asteroid { x, y });
+                    }
+                    _ => continue,
+                }
+            }
+        }
+        map
+    }
+}
+
+impl FromIterator<Asteroid> for Map {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = Asteroid>,
+    {
+        let asteroids: HashSet<Asteroid> = iter.into_iter().collect();
+        let mut width = 0;
+        let mut height = 0;
+        for asteroid in &asteroids {
+            if asteroid.x > width {
+                width = asteroid.x;
+            }
+            if asteroid.y > height {
+                height = asteroid.y;
+            }
+        }
+        Map {
+            asteroids,
+            width,
+            height,
+            monitoring_station: None,
+        }
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let mut asteroids = Vec::from_iter(self.asteroids.clone());
+        asteroids.sort_by_key(|asteroid| asteroid.x);
+        asteroids.sort_by_key(|asteroid| asteroid.y);
+        let mut asteroid_index = 0;
+        write!(f, "+")?;
+        for _ in 0..self.width {
+            write!(f, "-")?;
+        }
+        writeln!(f, "+")?;
+        for y in 0..self.height {
+            write!(f, "|")?;
+            for x in 0..self.width {
+                let c = asteroids
+                    .get(asteroid_index)
+                    .and_then(|asteroid| {
+                        if asteroid.x == x && asteroid.y == y {
+                            asteroid_index += 1;
+                            if self
+                                .monitoring_station
+                                .as_ref()
+                                .map(|ms| ms.x == asteroid.x && ms.y == asteroid.y)
+                                .unwrap_or(false)
+                            {
+                                Some('●')
+                            } else {
+                                Some('○')
+                            }
+                        } else {
+                            None
+                        }
+                    })
+                    .unwrap_or(' ');
+                write!(f, "{}", c)?;
+            }
+            writeln!(f, "|")?;
+        }
+        write!(f, "+")?;
+        for _ in 0..self.width {
+            write!(f, "-")?;
+        }
+        writeln!(f, "+")?;
+        Ok(())
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Asteroids seen from `origin`: the nearest asteroid in each direction, the
+/// direction to an asteroid being its vector from `origin` divided by the gcd
+/// of its coordinates.
+pub fn visible_asteroids<'a>(origin: &Asteroid, map: &'a Map) -> HashSet<&'a Asteroid> {
+    let mut nearest: HashMap<(i64, i64), (i64, &Asteroid)> = HashMap::new();
+    for asteroid in &map.asteroids {
+        let vector = (
+            asteroid.x as i64 - origin.x as i64,
+            asteroid.y as i64 - origin.y as i64,
+        );
+        // The gcd is also the distance to `origin` along the direction
+        let distance = gcd(vector.0.abs(), vector.1.abs());
+        if distance == 0 {
+            continue;
+        }
+        let direction = (vector.0 / distance, vector.1 / distance);
+        let (nearest_distance, nearest_asteroid) =
+            nearest.entry(direction).or_insert((distance, asteroid));
+        if distance < *nearest_distance {
+            *nearest_distance = distance;
+            *nearest_asteroid = asteroid;
+        }
+    }
+    nearest
+        .into_values()
+        .map(|(_, asteroid)| asteroid)
+        .collect()
+}
+
+/// A map of `size` by `size` where about two cells out of five hold an
+/// asteroid, like the puzzle inputs, to run the day on much larger maps.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    common::genvec::grid(rng, size, size, 40, '#', '.')
+}
+
+/// Visibility checking every asteroid against every other one, kept to
+/// compare with `visible_asteroids` in the tests and the benchmarks.
+#[cfg(any(test, feature = "legacy"))]
+pub mod legacy {
+    use super::{Asteroid, Map};
+    use std::collections::HashSet;
+
+    // Based on https://stackoverflow.com/a/11908158/7447059
+    fn is_asteroid_between(
+        (origin, destination): (&Asteroid, &Asteroid),
+        asteroid: &Asteroid,
+    ) -> bool {
+        if origin == destination || asteroid == origin || asteroid == destination {
+            return false;
+        }
+        type Vector = (i64, i64);
+        let vector =
+            |a: &Asteroid, b: &Asteroid| (b.x as i64 - a.x as i64, b.y as i64 - a.y as i64);
+        let cross_product = |v1: Vector, v2: Vector| v1.0 * v2.1 - v1.1 * v2.0;
+        let from_origin = vector(origin, asteroid);
+        let origin_to_destination = vector(origin, destination);
+        let is_colinear = cross_product(from_origin, origin_to_destination) == 0;
+        if !is_colinear {
+            return false;
+        }
+        if origin_to_destination.0.abs() >= origin_to_destination.1.abs() {
+            if origin_to_destination.0 > 0 {
+                origin.x <= asteroid.x && asteroid.x <= destination.x
+            } else {
+                destination.x <= asteroid.x && asteroid.x <= origin.x
+            }
+        } else {
+            if origin_to_destination.1 > 0 {
+                origin.y <= asteroid.y && asteroid.y <= destination.y
+            } else {
+                destination.y <= asteroid.y && asteroid.y <= origin.y
+            }
+        }
+    }
+
+    pub fn visible_asteroids<'a>(origin: &Asteroid, map: &'a Map) -> HashSet<&'a Asteroid> {
+        let mut obstructed_asteroids = HashSet::new();
+        for asteroid_to_check in &map.asteroids {
+            for asteroid in &map.asteroids {
+                let is_obstructed = is_asteroid_between((origin, asteroid_to_check), asteroid);
+                if is_obstructed {
+                    obstructed_asteroids.insert(asteroid_to_check);
+                }
+            }
+        }
+        let visible_asteroids = map
+            .asteroids
+            .iter()
+            .filter(|&asteroid| asteroid != origin)
+            .filter(|&asteroid| !obstructed_asteroids.contains(asteroid))
+            .collect::<HashSet<_>>();
+        visible_asteroids
+    }
+}
+
+impl Map {
+    pub fn find_monitoring_station(&mut self) {
+        // In reading order, so that among the asteroids seeing the most other
+        // ones, the first one is the same whatever the order of the set
+        let mut asteroids: Vec<&Asteroid> = self.asteroids.iter().collect();
+        asteroids.sort_unstable_by_key(|asteroid| (asteroid.y, asteroid.x));
+        let station = common::parallel::max_by_key(asteroids, |asteroid| {
+            visible_asteroids(asteroid, self).len()
+        });
+        // An asteroid seeing no other one is no better than no station
+        self.monitoring_station = station
+            .filter(|station| !visible_asteroids(station, self).is_empty())
+            .cloned();
+    }
+
+    pub fn destroy_asteroids(&self) -> Vec<&Asteroid> {
+        self.destroy_asteroids_by_round()
+            .into_iter()
+            .map(|(_, asteroid)| asteroid)
+            .collect()
+    }
+
+    /// Destroyed asteroids in order, each one with the rotation of the laser
+    /// (starting at 1) which destroyed it.
+    fn destroy_asteroids_by_round(&self) -> Vec<(usize, &Asteroid)> {
+        let mut destroyed_asteroids = Vec::new();
+        let mut next_round = HashSet::new();
+        if let Some(monitoring_station) = &self.monitoring_station {
+            let laser_rays: BTreeSet<LaserRay> = self
+                .asteroids
+                .iter()
+                .filter(|asteroid| asteroid != &monitoring_station)
+                .map(|asteroid| LaserRay::from((monitoring_station, asteroid)))
+                .collect();
+            let mut last_ray = None;
+            for laser_ray in &laser_rays {
+                let vec = laser_ray.vector();
+                let last_vec = last_ray.map(LaserRay::vector);
+                let dydx = last_vec.map(|v| -vec.1 * v.0);
+                let last_dydx = last_vec.map(|v| -v.1 * vec.0);
+                let dot_product = last_vec.map(|v| v.0 * vec.0 + v.1 * vec.1);
+                match (dydx, last_dydx, dot_product) {
+                    (Some(dd), Some(last_dd), Some(dot)) if dd == last_dd && dot > 0 => {
+                        next_round.insert(laser_ray.to.clone());
+                    }
+                    _ => {
+                        destroyed_asteroids.push((1, laser_ray.to));
+                    }
+                }
+                last_ray = Some(laser_ray);
+            }
+        }
+        if !next_round.is_empty() {
+            let map = Map {
+                asteroids: next_round,
+                width: self.width,
+                height: self.height,
+                monitoring_station: self.monitoring_station.clone(),
+            };
+            let next_round_destroyed = map.destroy_asteroids_by_round();
+            for (round, next_round_asteroid) in next_round_destroyed {
+                for asteroid in &self.asteroids {
+                    if next_round_asteroid == asteroid {
+                        destroyed_asteroids.push((round + 1, asteroid));
+                    }
+                }
+            }
+        }
+        destroyed_asteroids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn calculate_visible_asteroids() {
+        let map = vec![(0, 0), (1, 1), (2, 2), (2, 1)]
+            .into_iter()
+            .map(Asteroid::from)
+            .collect();
+        assert_eq!(2, visible_asteroids(&Asteroid { x: 0, y: 0 }, &map).len());
+
+        assert_eq!((1i64 - 1).signum(), ((-1i64) - (-1)).signum());
+    }
+
+    #[test]
+    fn calculate_best_asteroid() {
+        let mut map: Map = vec![(0, 0), (0, 1), (1, 1), (2, 2), (2, 1)]
+            .into_iter()
+            .map(Asteroid::from)
+            .collect();
+        map.find_monitoring_station();
+        assert_eq!((1, 1), map.monitoring_station.unwrap().into());
+        let mut map = Map::from(
+            r#".#..#
+.....
+#####
+....#
+...##"#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        assert_eq!((3, 4), map.monitoring_station.unwrap().into());
+
+        let mut map = Map::from(
+            r#"......#.#.
+#..#.#....
+..#######.
+.#.#.###..
+.#..#.....
+..#....#.#
+#..#....#.
+.##.#..###
+##...#..#.
+.#....####"#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        assert_eq!((5, 8), map.monitoring_station.unwrap().into());
+
+        let mut map = Map::from(
+            r#"#.#...#.#.
+.###....#.
+.#....#...
+##.#.#.#.#
+....#.#.#.
+.##..###.#
+..#...##..
+..##....##
+......#...
+.####.###."#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        assert_eq!((1, 2), map.monitoring_station.unwrap().into());
+
+        let mut map = Map::from(
+            r#".#..#..###
+####.###.#
+....###.#.
+..###.##.#
+##.##.#.#.
+....###..#
+..#.#..#.#
+#..#.#.###
+.##...##.#
+.....#.#.."#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        assert_eq!((6, 3), map.monitoring_station.unwrap().into());
+
+        let mut map = Map::from(
+            r#"#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##"#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        assert_eq!((11, 13), map.monitoring_station.unwrap().into());
+    }
+
+    #[test]
+    fn same_visible_asteroids_as_legacy() {
+        let map = Map::from(
+            r#".#..#..###
+####.###.#
+....###.#.
+..###.##.#
+##.##.#.#.
+....###..#
+..#.#..#.#
+#..#.#.###
+.##...##.#
+.....#.#.."#
+                .to_string(),
+        );
+        for origin in &map.asteroids {
+            assert_eq!(
+                legacy::visible_asteroids(origin, &map),
+                visible_asteroids(origin, &map)
+            );
+        }
+    }
+
+    mod laser_ray_ordering {
+        use super::*;
+        use pretty_assertions::assert_eq;
+
+        #[test]
+        fn equal() {
+            let monitoring_station = Asteroid::from((0, 0));
+            let asteroid = Asteroid::from((1, 1));
+            assert_eq!(
+                Ordering::Equal,
+                LaserRay::from((&monitoring_station, &asteroid))
+                    .cmp(&LaserRay::from((&monitoring_station, &asteroid)))
+            );
+        }
+
+        #[test]
+        fn order() {
+            let monitoring_station = Asteroid::from((2, 2));
+            let asteroids: Vec<Asteroid> = vec![
+                (2, 1),
+                (3, 0),
+                (3, 1),
+                (4, 1),
+                (3, 2),
+                (4, 3),
+                (3, 3),
+                (3, 4),
+                (2, 3),
+                (1, 4),
+                (1, 3),
+                (0, 3),
+                (1, 2),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+            ]
+            .into_iter()
+            .map(Asteroid::from)
+            .collect();
+            let laser_rays: Vec<LaserRay> = asteroids
+                .iter()
+                .map(|asteroid| LaserRay::from((&monitoring_station, asteroid)))
+                .collect();
+            for i in 0..(laser_rays.len() - 1) {
+                let lr1 = &laser_rays[i];
+                let lr2 = &laser_rays[i + 1];
+                assert_eq!(Ordering::Less, lr1.cmp(lr2));
+                assert_eq!(Ordering::Greater, lr2.cmp(lr1));
+            }
+        }
+
+        #[test]
+        fn limit() {
+            let monitoring_station = Asteroid::from((2, 2));
+            let asteroid1 = Asteroid::from((2, 1));
+            let asteroid2 = Asteroid::from((1, 0));
+            assert_eq!(
+                Ordering::Less,
+                LaserRay::from((&monitoring_station, &asteroid1))
+                    .cmp(&LaserRay::from((&monitoring_station, &asteroid2)))
+            );
+        }
+
+        #[test]
+        fn vertical() {
+            let monitoring_station = Asteroid::from((0, 2));
+            let up = Asteroid::from((0, 0));
+            let down = Asteroid::from((0, 3));
+            let up_ray = LaserRay::from((&monitoring_station, &up));
+            let down_ray = LaserRay::from((&monitoring_station, &down));
+            assert_eq!(Ordering::Less, up_ray.cmp(&down_ray));
+            assert_eq!(Ordering::Greater, down_ray.cmp(&up_ray));
+        }
+
+        #[test]
+        fn distance() {
+            let monitoring_station = Asteroid::from((0, 0));
+            let asteroid1 = Asteroid::from((1, 1));
+            let asteroid2 = Asteroid::from((2, 2));
+            assert_eq!(
+                Ordering::Less,
+                LaserRay::from((&monitoring_station, &asteroid1))
+                    .cmp(&LaserRay::from((&monitoring_station, &asteroid2)))
+            );
+        }
+    }
+
+    #[test]
+    fn generated_map() {
+        let text = generate(&mut Rng::new(common::genvec::SEED), 40);
+        assert_eq!(text, generate(&mut Rng::new(common::genvec::SEED), 40));
+        let mut map = Map::from(text);
+        assert_eq!((40, 40), (map.width, map.height));
+        map.find_monitoring_station();
+        let station = map.monitoring_station.clone().unwrap();
+        assert_eq!(
+            visible_asteroids(&station, &map).len(),
+            legacy::visible_asteroids(&station, &map).len()
+        );
+    }
+
+    #[test]
+    fn same_answers_whatever_the_order() {
+        // Station and destroyed asteroids, from a new set of asteroids each time
+        let answers = |text: &str| {
+            let mut map = Map::from(text.to_string());
+            map.find_monitoring_station();
+            let destroyed: Vec<Asteroid> = map.destroy_asteroids().into_iter().cloned().collect();
+            (map.monitoring_station, destroyed)
+        };
+        // Every asteroid sees the 3 other ones, the first one is the station
+        let diamond = ".#.\n#.#\n.#.";
+        assert_eq!(Some(Asteroid { x: 1, y: 0 }), answers(diamond).0);
+        let generated = generate(&mut Rng::new(common::genvec::SEED), 20);
+        for text in &[diamond, generated.as_str()] {
+            let first = answers(text);
+            for _ in 0..10 {
+                assert_eq!(first, answers(text));
+            }
+        }
+    }
+
+    #[test]
+    fn laser_destroy() {
+        let mut map = Map::from(
+            r#".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....#...###..
+..#.#.....#....##"#
+                .to_string(),
+        );
+        map.find_monitoring_station();
+        let destroyed_asteroids = map.destroy_asteroids();
+        let expected: Vec<Asteroid> = vec![
+            (8, 1),
+            (9, 0),
+            (9, 1),
+            (10, 0),
+            (9, 2),
+            (11, 1),
+            (12, 1),
+            (11, 2),
+            (15, 1),
+            (12, 2),
+            (13, 2),
+            (14, 2),
+            (15, 2),
+            (12, 3),
+            (16, 4),
+            (15, 4),
+            (10, 4),
+            (4, 4),
+            (2, 4),
+            (2, 3),
+            (0, 2),
+            (1, 2),
+            (0, 1),
+            (1, 1),
+            (5, 2),
+            (1, 0),
+            (5, 1),
+            (6, 1),
+            (6, 0),
+            (7, 0),
+            (8, 0),
+            (10, 1),
+            (14, 0),
+            (16, 1),
+            (13, 3),
+            (14, 3),
+        ]
+        .into_iter()
+        .map(Asteroid::from)
+        .collect();
+        let expected_ref: Vec<&Asteroid> = expected.iter().collect();
+        assert_eq!(expected_ref, destroyed_asteroids);
+    }
+
+    #[test]
+    fn laser_destroy_opposite_asteroids_in_first_round() {
+        let mut map: Map = vec![(1, 2), (3, 2), (4, 2)]
+            .into_iter()
+            .map(Asteroid::from)
+            .collect();
+        map.monitoring_station = Some(Asteroid::from((3, 2)));
+        let destroyed_asteroids: Vec<(usize, (usize, usize))> = map
+            .destroy_asteroids_by_round()
+            .into_iter()
+            .map(|(round, asteroid)| (round, asteroid.clone().into()))
+            .collect();
+        assert_eq!(vec![(1, (4, 2)), (1, (1, 2))], destroyed_asteroids);
+    }
+
+    mod destruction_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn asteroids(rows: Vec<Vec<bool>>) -> Vec<Asteroid> {
+            let mut asteroids = Vec::new();
+            for (y, row) in rows.into_iter().enumerate() {
+                for (x, cell) in row.into_iter().enumerate() {
+                    if cell {
+                        asteroids.push(Asteroid { x, y });
+                    }
+                }
+            }
+            asteroids
+        }
+
+        fn field() -> impl Strategy<Value = (Vec<Asteroid>, usize)> {
+            common::strategy::grid(1..12, 1..12)
+                .prop_map(|(rows, _)| asteroids(rows))
+                .prop_filter("no asteroid in the field", |asteroids| {
+                    !asteroids.is_empty()
+                })
+                .prop_flat_map(|asteroids| {
+                    let count = asteroids.len();
+                    (Just(asteroids), 0..count)
+                })
+        }
+
+        fn is_same_direction(v1: (i64, i64), v2: (i64, i64)) -> bool {
+            v1.0 * v2.1 == v1.1 * v2.0 && v1.0 * v2.0 + v1.1 * v2.1 > 0
+        }
+
+        proptest! {
+            #[test]
+            fn parse_map((rows, text) in common::strategy::grid(1..12, 1..12)) {
+                let height = rows.len();
+                let width = rows[0].len();
+                let expected: HashSet<Asteroid> = asteroids(rows).into_iter().collect();
+                let map = Map::from(text);
+                prop_assert_eq!(expected, map.asteroids);
+                prop_assert_eq!(width, map.width);
+                prop_assert_eq!(height, map.height);
+            }
+
+            #[test]
+            fn same_visible_asteroids_as_legacy((asteroids, station_index) in field()) {
+                let map: Map = asteroids.iter().cloned().collect();
+                let origin = &asteroids[station_index];
+                prop_assert_eq!(
+                    legacy::visible_asteroids(origin, &map),
+                    visible_asteroids(origin, &map)
+                );
+            }
+
+            #[test]
+            fn destroy_every_asteroid_exactly_once((asteroids, station_index) in field()) {
+                let monitoring_station = asteroids[station_index].clone();
+                let mut map: Map = asteroids.iter().cloned().collect();
+                map.monitoring_station = Some(monitoring_station.clone());
+                let destroyed_asteroids = map.destroy_asteroids_by_round();
+
+                prop_assert_eq!(asteroids.len() - 1, destroyed_asteroids.len());
+                let unique_destroyed: HashSet<&Asteroid> =
+                    destroyed_asteroids.iter().map(|(_, asteroid)| *asteroid).collect();
+                prop_assert_eq!(destroyed_asteroids.len(), unique_destroyed.len());
+                prop_assert!(!unique_destroyed.contains(&monitoring_station));
+
+                for rounds in destroyed_asteroids.windows(2) {
+                    prop_assert!(rounds[0].0 <= rounds[1].0);
+                }
+                for (round, asteroid) in &destroyed_asteroids {
+                    let ray = LaserRay::from((&monitoring_station, *asteroid));
+                    let hiding_asteroids = destroyed_asteroids
+                        .iter()
+                        .map(|(_, other)| LaserRay::from((&monitoring_station, *other)))
+                        .filter(|other| is_same_direction(ray.vector(), other.vector()))
+                        .filter(|other| other < &ray)
+                        .count();
+                    prop_assert_eq!(hiding_asteroids + 1, *round);
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,34 @@
-use intcode::{self, Program};
+use common::render::Render;
+use intcode::{self, protocol::Outputs, Program};
 use std::{
     collections::HashMap,
     env,
     fmt::{self, Display, Formatter},
     fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufWriter, Write},
     ops::Add,
+    str::FromStr,
     sync::mpsc,
     thread,
+    time::Duration,
 };
 
-#[derive(Debug, Clone)]
+/// Environment variable naming the file the events of the robot are recorded to.
+const RECORD: &str = "AOC_RECORD";
+/// Environment variable naming recorded events, replayed instead of running the robot.
+const REPLAY: &str = "AOC_REPLAY";
+
+#[derive(Debug, thiserror::Error)]
+enum ReplayError {
+    #[error("Failed to read or write the events")]
+    FailedIo(#[from] io::Error),
+    #[error("Failed to run the painting robot")]
+    FailedProgram(#[from] intcode::Error),
+    #[error("Cannot convert '{0}' into an event (e.g. '0,-1 white left')")]
+    InvalidEvent(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Color {
     Black,
     White,
@@ -26,9 +44,9 @@ impl From<i64> for Color {
     }
 }
 
-impl Into<i64> for &mut Color {
-    fn into(self) -> i64 {
-        match self {
+impl From<&mut Color> for i64 {
+    fn from(val: &mut Color) -> Self {
+        match val {
             Color::Black => 0,
             Color::White => 1,
         }
@@ -45,7 +63,18 @@ impl Display for Color {
     }
 }
 
-#[derive(Debug)]
+impl FromStr for Color {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "black" => Ok(Color::Black),
+            "white" => Ok(Color::White),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Turn {
     Left,
     Right,
@@ -61,6 +90,17 @@ impl From<i64> for Turn {
     }
 }
 
+impl FromStr for Turn {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "left" => Ok(Turn::Left),
+            "right" => Ok(Turn::Right),
+            _ => Err(()),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum Direction {
     Up,
@@ -100,7 +140,93 @@ impl Direction {
 type Position = (i64, i64);
 type Tiles = HashMap<Position, Color>;
 
-fn painting_robot(opcodes: Vec<i64>) -> Result<Tiles, intcode::Error> {
+/// The robot painted the panel at `position` with `color`, then turned.
+///
+/// Written as one line, e.g. `0,-1 white left`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Event {
+    position: Position,
+    color: Color,
+    turn: Turn,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let color = match self.color {
+            Color::Black => "black",
+            Color::White => "white",
+        };
+        let turn = match self.turn {
+            Turn::Left => "left",
+            Turn::Right => "right",
+        };
+        write!(
+            f,
+            "{},{} {} {}",
+            self.position.0, self.position.1, color, turn
+        )
+    }
+}
+
+impl FromStr for Event {
+    type Err = ReplayError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReplayError::InvalidEvent(s.to_string());
+        let splits: Vec<_> = s.split_whitespace().collect();
+        if splits.len() != 3 {
+            return Err(invalid());
+        }
+        let (x, y) = splits[0].split_once(',').ok_or_else(invalid)?;
+        let position = (
+            x.parse().map_err(|_| invalid())?,
+            y.parse().map_err(|_| invalid())?,
+        );
+        let color = splits[1].parse().map_err(|_| invalid())?;
+        let turn = splits[2].parse().map_err(|_| invalid())?;
+        Ok(Event {
+            position,
+            color,
+            turn,
+        })
+    }
+}
+
+fn write_events<W: Write>(events: &[Event], mut writer: W) -> Result<(), ReplayError> {
+    for event in events {
+        writeln!(writer, "{}", event)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_events<R: BufRead>(reader: R) -> Result<Vec<Event>, ReplayError> {
+    reader
+        .lines()
+        .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(|line| line?.parse())
+        .collect()
+}
+
+/// Paint the tiles again from the recorded events, calling `on_event` with
+/// the tiles after each event.
+fn replay<F: FnMut(&Tiles)>(events: &[Event], mut on_event: F) -> Tiles {
+    let mut tiles = Tiles::new();
+    #[cfg(feature = "start-white")]
+    tiles.insert((0, 0), Color::White);
+    let mut position = (0, 0);
+    let mut direction = Direction::Up;
+    for event in events {
+        tiles.insert(event.position, event.color.clone());
+        on_event(&tiles);
+        direction = direction + event.turn;
+        position = direction.move_forward(event.position);
+    }
+    // The robot still stands on a last panel, which it looked at
+    tiles.entry(position).or_insert(Color::Black);
+    tiles
+}
+
+fn painting_robot(opcodes: Vec<i64>) -> Result<Vec<Event>, intcode::Error> {
     let (sender_to_thread, receiver_from_host) = mpsc::sync_channel(0);
     let (sender_to_host, receiver_from_thread) = mpsc::sync_channel(0);
     let mut program = Program::new(opcodes, receiver_from_host, sender_to_host);
@@ -110,53 +236,93 @@ fn painting_robot(opcodes: Vec<i64>) -> Result<Tiles, intcode::Error> {
     tiles.insert((0, 0), Color::White);
     let mut position = (0, 0);
     let mut direction = Direction::Up;
+    let mut events = Vec::new();
     loop {
-        let color = tiles.entry(position.clone()).or_insert(Color::Black);
-        if let Err(_) = sender_to_thread.send(color.into()) {
+        let color = tiles.entry(position).or_insert(Color::Black);
+        if sender_to_thread.send(color.into()).is_err() {
             break;
         }
-        *color = Color::from(receiver_from_thread.recv()?);
-        direction = direction + Turn::from(receiver_from_thread.recv()?);
+        let (painted, turn) = receiver_from_thread.recv_pair()?;
+        *color = Color::from(painted);
+        let turn = Turn::from(turn);
+        events.push(Event {
+            position,
+            color: color.clone(),
+            turn,
+        });
+        direction = direction + turn;
         position = direction.move_forward(position);
     }
-    Ok(tiles)
+    Ok(events)
 }
 
 fn print_tiles(tiles: &Tiles) {
-    if tiles.is_empty() {
-        return;
-    }
-    let min_x = tiles.keys().map(|tile| tile.0).min().unwrap();
-    let max_x = tiles.keys().map(|tile| tile.0).max().unwrap();
-    let min_y = tiles.keys().map(|tile| tile.1).min().unwrap();
-    let max_y = tiles.keys().map(|tile| tile.1).max().unwrap();
-    for y in (min_y..=max_y).rev() {
-        for x in min_x..=max_x {
-            let color = tiles.get(&(x, y)).cloned().unwrap_or(Color::Black);
-            print!("{}", color);
+    let render = Render::new(tiles, |_, color: Option<&Color>| {
+        color.cloned().unwrap_or(Color::Black)
+    });
+    print!("{}", render.y_up());
+}
+
+/// The letters painted in white, if they can be read.
+#[cfg(feature = "start-white")]
+fn registration_identifier(tiles: &Tiles) -> Option<String> {
+    let xs = tiles.keys().map(|&(x, _)| x);
+    let ys = tiles.keys().map(|&(_, y)| y);
+    let (min_x, max_x) = (xs.clone().min()?, xs.max()?);
+    let (min_y, max_y) = (ys.clone().min()?, ys.max()?);
+    // The `y` axis goes up, so the top row is the last one
+    let white_rows: Vec<Vec<bool>> = (min_y..=max_y)
+        .rev()
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| tiles.get(&(x, y)) == Some(&Color::White))
+                .collect()
+        })
+        .collect();
+    common::ocr::read(&white_rows)
+}
+
+fn main() -> Result<(), ReplayError> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "start-white") { 2 } else { 1 }],
+    );
+    let replaying = env::var_os(REPLAY);
+    let events = match &replaying {
+        Some(path) => read_events(common::input::open(path)?)?,
+        None => {
+            let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
+            let mut program_str = String::new();
+            reader.read_line(&mut program_str)?;
+            let program = intcode::parse(&program_str)?;
+            painting_robot(program)?
+        }
+    };
+    if let Some(path) = env::var_os(RECORD) {
+        write_events(&events, BufWriter::new(File::create(path)?))?;
+    }
+    // A replay is animated, each step being drawn over the previous one
+    let animated = replaying.is_some() && common::render::is_terminal();
+    if animated {
+        print!("{}", common::render::CLEAR);
+    }
+    let tiles = replay(&events, |tiles| {
+        if animated {
+            print!("{}", common::render::HOME);
+            print_tiles(tiles);
+            thread::sleep(Duration::from_millis(10));
         }
-        println!();
-    }
-}
-
-fn main() -> Result<(), intcode::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
-    let mut program_str = String::new();
-    reader.read_line(&mut program_str)?;
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .map_err(intcode::Error::from)?;
-    let tiles = painting_robot(program)?;
+    });
+    if animated {
+        print!("{}", common::render::HOME);
+    }
     print_tiles(&tiles);
     println!("Number of painted tiles is {}", tiles.len());
+    #[cfg(feature = "start-white")]
+    if let Some(identifier) = registration_identifier(&tiles) {
+        println!("The registration identifier is {}", identifier);
+    }
+    args.report_time();
     Ok(())
 }
 
@@ -164,13 +330,62 @@ fn main() -> Result<(), intcode::Error> {
 mod tests {
     use super::*;
 
-    #[test]
-    fn process_opcodes() -> Result<(), intcode::Error> {
-        let tiles = painting_robot(vec![
+    fn events() -> Result<Vec<Event>, intcode::Error> {
+        painting_robot(vec![
             103, 0, 104, 1, 104, 0, 103, 0, 104, 0, 104, 0, 103, 0, 104, 1, 104, 0, 103, 0, 104, 1,
             104, 0, 103, 1, 104, 0, 104, 1, 103, 0, 104, 1, 104, 0, 103, 0, 104, 1, 104, 0, 99,
-        ])?;
+        ])
+    }
+
+    #[test]
+    fn process_opcodes() -> Result<(), intcode::Error> {
+        let tiles = replay(&events()?, |_| {});
         assert_eq!(7, tiles.len());
         Ok(())
     }
+
+    #[test]
+    fn record_events() -> Result<(), intcode::Error> {
+        let events = events()?;
+        assert_eq!(7, events.len());
+        assert_eq!(
+            Event {
+                position: (0, 0),
+                color: Color::White,
+                turn: Turn::Left,
+            },
+            events[0]
+        );
+        assert_eq!("-1,0 black left", events[1].to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn events_round_trip() -> Result<(), ReplayError> {
+        let events = events()?;
+        let mut recorded = Vec::new();
+        write_events(&events, &mut recorded)?;
+        assert_eq!(events, read_events(recorded.as_slice())?);
+        Ok(())
+    }
+
+    #[cfg(feature = "start-white")]
+    #[test]
+    fn registration_identifier_of_stored_input() -> Result<(), intcode::Error> {
+        let program = intcode::parse(include_str!("../../../inputs/2019/11.txt"))?;
+        let tiles = replay(&painting_robot(program)?, |_| {});
+        assert_eq!(
+            Some("APFKRKBR".to_string()),
+            registration_identifier(&tiles)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_event() {
+        assert!("0,0 white".parse::<Event>().is_err());
+        assert!("0;0 white left".parse::<Event>().is_err());
+        assert!("0,0 red left".parse::<Event>().is_err());
+        assert!("0,0 white up".parse::<Event>().is_err());
+    }
 }
@@ -1,22 +1,86 @@
+use common::{
+    cli::{value_parser, Arg, Args},
+    progress::ProgressBar,
+};
 use regex::Regex;
 use std::{
-    collections::HashMap,
+    collections::BTreeMap,
     convert::TryFrom,
-    env,
-    fs::File,
-    io::{self, BufRead, BufReader},
+    fmt::{self, Display, Formatter},
+    fs,
+    io::{self, BufRead},
     num::ParseIntError,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Instant,
 };
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+enum MyError {
+    #[error("Cannot read the file")]
+    IOError(#[from] io::Error),
+    #[error("Cannot convert into a number")]
+    ParseNumber(#[from] ParseIntError),
+    #[error("Cannot convert '{0}' into a line of checkpoint")]
+    InvalidCheckpoint(String),
+    #[error("The checkpoint {0:?} was saved for another system")]
+    MismatchedCheckpoint(PathBuf),
+}
+
+/// Where the cycle search is saved, and how often.
+#[derive(Debug, PartialEq, Eq)]
+struct Checkpointing {
+    path: PathBuf,
+    every: u64,
+}
+
+/// Options given on the command line.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    /// Save of the cycle search, with `--checkpoint <file>` and
+    /// `--every <steps>`.
+    checkpointing: Option<Checkpointing>,
+}
+
+impl Options {
+    /// Arguments `--checkpoint <FILE>` and `--every <STEPS>`, to be given to
+    /// `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("checkpoint")
+                .long("checkpoint")
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Save the cycle search in FILE, and resume it from there"),
+            Arg::new("every")
+                .long("every")
+                .value_name("STEPS")
+                .value_parser(value_parser!(u64).range(1..))
+                .default_value("10000000")
+                .help("Steps between two saves of the cycle search"),
+        ]
+    }
+
+    /// Options read from `args`.
+    fn from_args(args: &Args) -> Self {
+        let every = args.get("every").expect("expect a default number of steps");
+        let checkpointing = args
+            .get("checkpoint")
+            .map(|path| Checkpointing { path, every });
+        Options { checkpointing }
+    }
+}
 
 fn prime_factors(mut number: u64) -> Vec<u64> {
     let mut primes = vec![2];
     let mut factors = Vec::new();
     loop {
         for prime in &primes {
-            while number % prime == 0 {
+            while number.is_multiple_of(*prime) {
                 factors.push(*prime);
-                number = number / prime;
+                number /= prime;
             }
         }
         if number == 1 {
@@ -38,6 +102,14 @@ fn prime_factors(mut number: u64) -> Vec<u64> {
     factors
 }
 
+/// Steps for the whole system to come back to a state, from the cycle of each
+/// axis.
+fn next_cycle(cycles: &[u64]) -> u64 {
+    cycles
+        .iter()
+        .fold(1, |next_cycle, &cycle| ppcm(next_cycle, cycle))
+}
+
 fn ppcm(num1: u64, num2: u64) -> u64 {
     let factors1 = prime_factors(num1);
     let factors2 = prime_factors(num2);
@@ -46,14 +118,14 @@ fn ppcm(num1: u64, num2: u64) -> u64 {
     let mut ppcm = 1;
     while index1 < factors1.len() || index2 < factors2.len() {
         if index2 == factors2.len() {
-            for i in index1..factors1.len() {
-                ppcm *= factors1[i];
+            for factor in &factors1[index1..] {
+                ppcm *= factor;
             }
             break;
         }
         if index1 == factors1.len() {
-            for i in index2..factors2.len() {
-                ppcm *= factors2[i];
+            for factor in &factors2[index2..] {
+                ppcm *= factor;
             }
             break;
         }
@@ -105,22 +177,13 @@ impl TryFrom<&str> for Moon {
 }
 
 impl Moon {
-    fn gravity(&mut self, moon: &Self) {
-        if self.position.0 < moon.position.0 {
-            self.velocity.0 += 1;
-        } else if self.position.0 > moon.position.0 {
-            self.velocity.0 -= 1;
-        }
-        if self.position.1 < moon.position.1 {
-            self.velocity.1 += 1;
-        } else if self.position.1 > moon.position.1 {
-            self.velocity.1 -= 1;
-        }
-        if self.position.2 < moon.position.2 {
-            self.velocity.2 += 1;
-        } else if self.position.2 > moon.position.2 {
-            self.velocity.2 -= 1;
-        }
+    /// Change of velocity of this moon pulled by `moon`.
+    fn gravity(&self, moon: &Self) -> Velocity {
+        (
+            (moon.position.0 - self.position.0).signum(),
+            (moon.position.1 - self.position.1).signum(),
+            (moon.position.2 - self.position.2).signum(),
+        )
     }
 
     fn step(&mut self) {
@@ -144,21 +207,30 @@ impl Moon {
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 struct System {
-    moons: HashMap<String, Moon>,
+    moons: BTreeMap<String, Moon>,
 }
 
 impl System {
+    /// Change the velocity of every moon with the gravity of every other
+    /// one, all computed from the positions before the step, then move every
+    /// moon.
     fn step(&mut self) {
-        let mut system = self.clone();
-        for moon in self.keys() {
-            for to in self.keys() {
-                system.get_mut(moon).unwrap().gravity(self.get(to).unwrap());
-            }
-        }
-        for moon in system.values_mut() {
+        let moons: Vec<&Moon> = self.values().collect();
+        let changes: Vec<Velocity> = moons
+            .iter()
+            .map(|moon| {
+                moons.iter().fold((0, 0, 0), |change, other| {
+                    let pull = moon.gravity(other);
+                    (change.0 + pull.0, change.1 + pull.1, change.2 + pull.2)
+                })
+            })
+            .collect();
+        for (moon, change) in self.values_mut().zip(changes) {
+            moon.velocity.0 += change.0;
+            moon.velocity.1 += change.1;
+            moon.velocity.2 += change.2;
             moon.step();
         }
-        *self = system;
     }
 
     fn steps(&mut self, count: usize) {
@@ -171,54 +243,221 @@ impl System {
         self.values().map(|moon| moon.energy()).sum()
     }
 
-    fn next_cycle(&mut self) -> u64 {
-        fn cycle(mut state: Vec<(i64, i64)>) -> u64 {
-            let init = state.clone();
-            let mut steps = 0;
-            loop {
-                let mut new_state = Vec::new();
-                for (position, mut velocity) in &state {
-                    for (p, _) in &state {
-                        if position < p {
-                            velocity += 1;
-                        } else if position > p {
-                            velocity -= 1;
+    /// State of each axis: the position and the velocity of every moon along
+    /// it.
+    fn axes(&self) -> Vec<Axis> {
+        let axis = |coordinate: fn(&(i64, i64, i64)) -> i64| -> Axis {
+            self.values()
+                .map(|moon| (coordinate(&moon.position), coordinate(&moon.velocity)))
+                .collect()
+        };
+        vec![axis(|c| c.0), axis(|c| c.1), axis(|c| c.2)]
+    }
+
+    /// Steps for each axis to come back to its current state. The search is
+    /// resumed from the checkpoint of `checkpointing`, if any, and saved in it.
+    fn axis_cycles(
+        &self,
+        progress: &ProgressBar,
+        checkpointing: Option<&Checkpointing>,
+    ) -> Result<Vec<u64>, MyError> {
+        let init = self.axes();
+        let loaded = match checkpointing {
+            Some(checkpointing) => Checkpoint::load(&checkpointing.path, &init)?,
+            None => None,
+        };
+        let mut checkpoint = loaded.unwrap_or_else(|| Checkpoint::new(init.clone()));
+        while checkpoint.cycles.len() < init.len() {
+            let axis = &init[checkpoint.cycles.len()];
+            let cycle = cycle(
+                axis,
+                checkpoint.steps,
+                checkpoint.state.clone(),
+                |steps, state| {
+                    progress.inc(1);
+                    match checkpointing {
+                        Some(checkpointing) if steps % checkpointing.every == 0 => {
+                            checkpoint.steps = steps;
+                            checkpoint.state = state.clone();
+                            checkpoint.save(&checkpointing.path)
                         }
+                        _ => Ok(()),
                     }
-                    new_state.push((position + velocity, velocity));
+                },
+            )?;
+            checkpoint.complete(cycle);
+            if let Some(checkpointing) = checkpointing {
+                checkpoint.save(&checkpointing.path)?;
+            }
+        }
+        Ok(checkpoint.cycles)
+    }
+}
+
+/// Position and velocity of every moon along one axis.
+type Axis = Vec<(i64, i64)>;
+
+/// State of an axis after one step.
+fn step_axis(state: &Axis) -> Axis {
+    state
+        .iter()
+        .map(|&(position, mut velocity)| {
+            for &(p, _) in state {
+                if position < p {
+                    velocity += 1;
+                } else if position > p {
+                    velocity -= 1;
                 }
-                state = new_state;
-                steps += 1;
-                if state == init {
-                    break;
+            }
+            (position + velocity, velocity)
+        })
+        .collect()
+}
+
+/// Steps for an axis to come back to `init`, starting from `state` reached
+/// after `steps` steps; `on_step` is called after each step.
+fn cycle<E>(
+    init: &Axis,
+    mut steps: u64,
+    mut state: Axis,
+    mut on_step: impl FnMut(u64, &Axis) -> Result<(), E>,
+) -> Result<u64, E> {
+    // The motion is reversible, so the first state to repeat is the initial
+    // one. When all the velocities are null at first, the motion is even
+    // symmetric around any step where they are all null again: the initial
+    // state comes back at twice this step, if not at this step already.
+    let symmetric = init.iter().all(|&(_, velocity)| velocity == 0);
+    loop {
+        state = step_axis(&state);
+        steps += 1;
+        on_step(steps, &state)?;
+        if state == *init {
+            return Ok(steps);
+        }
+        if symmetric && state.iter().all(|&(_, velocity)| velocity == 0) {
+            return Ok(2 * steps);
+        }
+    }
+}
+
+/// Progress of the cycle search, saved every few steps so that an interrupted
+/// search resumes where it stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Checkpoint {
+    /// Initial state of every axis, to recognize the system of the checkpoint.
+    init: Vec<Axis>,
+    /// Cycles of the axes already searched, in order.
+    cycles: Vec<u64>,
+    /// Steps simulated so far on the next axis to search, and its state.
+    steps: u64,
+    state: Axis,
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        fn write_axis(f: &mut Formatter, key: &str, axis: &Axis) -> Result<(), fmt::Error> {
+            write!(f, "{}", key)?;
+            for (position, velocity) in axis {
+                write!(f, " {},{}", position, velocity)?;
+            }
+            writeln!(f)
+        }
+        for axis in &self.init {
+            write_axis(f, "init", axis)?;
+        }
+        write!(f, "cycles")?;
+        for cycle in &self.cycles {
+            write!(f, " {}", cycle)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "steps {}", self.steps)?;
+        write_axis(f, "state", &self.state)
+    }
+}
+
+impl FromStr for Checkpoint {
+    type Err = MyError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn axis(values: &str) -> Result<Axis, MyError> {
+            values
+                .split_whitespace()
+                .map(|moon| {
+                    let (position, velocity) = moon
+                        .split_once(',')
+                        .ok_or_else(|| MyError::InvalidCheckpoint(moon.to_string()))?;
+                    Ok((position.parse()?, velocity.parse()?))
+                })
+                .collect()
+        }
+        let mut checkpoint = Checkpoint::new(Vec::new());
+        for line in s.lines() {
+            let (key, values) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "init" => checkpoint.init.push(axis(values)?),
+                "cycles" => {
+                    checkpoint.cycles = values
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?
                 }
+                "steps" => checkpoint.steps = values.parse()?,
+                "state" => checkpoint.state = axis(values)?,
+                _ => return Err(MyError::InvalidCheckpoint(line.to_string())),
             }
-            steps
         }
-        let init_x: Vec<_> = self
-            .moons
-            .values()
-            .map(|moon| (moon.position.0, moon.velocity.0))
-            .collect();
-        let init_y: Vec<_> = self
-            .moons
-            .values()
-            .map(|moon| (moon.position.1, moon.velocity.1))
-            .collect();
-        let init_z: Vec<_> = self
-            .moons
-            .values()
-            .map(|moon| (moon.position.2, moon.velocity.2))
-            .collect();
-        let cycle_x = cycle(init_x);
-        let cycle_y = cycle(init_y);
-        let cycle_z = cycle(init_z);
-        ppcm(ppcm(cycle_x, cycle_y), cycle_z)
+        Ok(checkpoint)
+    }
+}
+
+impl Checkpoint {
+    /// Checkpoint of a search which didn't start yet.
+    fn new(init: Vec<Axis>) -> Self {
+        let state = init.first().cloned().unwrap_or_default();
+        Checkpoint {
+            init,
+            cycles: Vec::new(),
+            steps: 0,
+            state,
+        }
+    }
+
+    /// Checkpoint saved in `path` for the system starting at `init`, if any.
+    fn load(path: &Path, init: &[Axis]) -> Result<Option<Self>, MyError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let checkpoint: Checkpoint = text.parse()?;
+        if checkpoint.init != init {
+            return Err(MyError::MismatchedCheckpoint(path.to_path_buf()));
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Write the checkpoint in `path`, the previous one being replaced only
+    /// once the new one is fully written.
+    fn save(&self, path: &Path) -> Result<(), MyError> {
+        let partial = path.with_extension("partial");
+        fs::write(&partial, self.to_string())?;
+        fs::rename(&partial, path)?;
+        Ok(())
+    }
+
+    /// Record the cycle of the axis being searched, and start the next one.
+    fn complete(&mut self, cycle: u64) {
+        self.cycles.push(cycle);
+        self.steps = 0;
+        self.state = self
+            .init
+            .get(self.cycles.len())
+            .cloned()
+            .unwrap_or_default();
     }
 }
 
 impl Deref for System {
-    type Target = HashMap<String, Moon>;
+    type Target = BTreeMap<String, Moon>;
 
     fn deref(&self) -> &Self::Target {
         &self.moons
@@ -231,28 +470,37 @@ impl DerefMut for System {
     }
 }
 
-fn main() -> Result<(), io::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+fn main() -> Result<(), MyError> {
+    let args = Args::parse_with(env!("CARGO_PKG_NAME"), &[1, 2], Options::arguments());
+    let options = Options::from_args(&args);
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
 
     let mut system = System::default();
-    for moon_name in vec!["io", "europa", "ganymede", "callisto"] {
+    for moon_name in ["io", "europa", "ganymede", "callisto"] {
         let mut input = String::new();
         reader.read_line(&mut input)?;
-        let io = Moon::try_from(input.trim()).unwrap();
+        let io = Moon::try_from(input.trim())?;
         system.insert(moon_name.to_string(), io);
     }
 
+    let progress = common::progress::spinner("Cycle search steps");
+    let start = Instant::now();
+    let cycles = system.axis_cycles(&progress, options.checkpointing.as_ref())?;
+    let elapsed = start.elapsed();
+    eprintln!(
+        "Simulated {} steps in {:.2?} ({:.0} steps per second)",
+        progress.position(),
+        elapsed,
+        progress.position() as f64 / elapsed.as_secs_f64()
+    );
+    progress.finish_and_clear();
     println!(
         "The next cycle of the system is in {} steps",
-        system.clone().next_cycle()
+        next_cycle(&cycles)
     );
     system.steps(1000);
     println!("The total energy after 1000 steps is {}", system.energy());
+    args.report_time();
     Ok(())
 }
 
@@ -526,7 +774,10 @@ mod tests {
         system.insert("ganymede".to_string(), Moon::from((4, -8, 8)));
         system.insert("callisto".to_string(), Moon::from((3, 5, -1)));
 
-        assert_eq!(2772, system.next_cycle());
+        assert_eq!(
+            2772,
+            super::next_cycle(&system.axis_cycles(&ProgressBar::hidden(), None).unwrap())
+        );
     }
 
     #[test]
@@ -537,6 +788,169 @@ mod tests {
         system.insert("ganymede".to_string(), Moon::from((2, -7, 3)));
         system.insert("callisto".to_string(), Moon::from((9, -8, -3)));
 
-        assert_eq!(4686774924, system.next_cycle());
+        assert_eq!(
+            4686774924,
+            super::next_cycle(&system.axis_cycles(&ProgressBar::hidden(), None).unwrap())
+        );
+    }
+
+    fn example() -> System {
+        let mut system = System::default();
+        system.insert("io".to_string(), Moon::from((-1, 0, 2)));
+        system.insert("europa".to_string(), Moon::from((2, -10, -7)));
+        system.insert("ganymede".to_string(), Moon::from((4, -8, 8)));
+        system.insert("callisto".to_string(), Moon::from((3, 5, -1)));
+        system
+    }
+
+    #[test]
+    fn steps_independent_of_order() {
+        // The moons of the example, inserted and named in the reverse order
+        let mut reversed = System::default();
+        for (name, position) in &[
+            ("d", (3, 5, -1)),
+            ("c", (4, -8, 8)),
+            ("b", (2, -10, -7)),
+            ("a", (-1, 0, 2)),
+        ] {
+            reversed.insert(name.to_string(), Moon::from(*position));
+        }
+        let mut system = example();
+        for _ in 0..100 {
+            system.step();
+            reversed.step();
+            let mut moons: Vec<&Moon> = system.values().collect();
+            let mut reversed_moons: Vec<&Moon> = reversed.values().collect();
+            moons.sort_by_key(|moon| moon.position);
+            reversed_moons.sort_by_key(|moon| moon.position);
+            assert_eq!(moons, reversed_moons);
+        }
+        assert_eq!(system.energy(), reversed.energy());
+    }
+
+    fn axis_cycle(axis: &Axis) -> u64 {
+        cycle(axis, 0, axis.clone(), |_, _| Ok::<(), MyError>(())).unwrap()
+    }
+
+    #[test]
+    fn axis_cycles() {
+        let cycles: Vec<u64> = example().axes().iter().map(axis_cycle).collect();
+        assert_eq!(vec![18, 28, 44], cycles);
+    }
+
+    #[test]
+    fn axis_cycles_with_velocities() {
+        // Without null velocities, the cycle is searched without the symmetry
+        let mut system = example();
+        system.steps(5);
+        let cycles: Vec<u64> = system.axes().iter().map(axis_cycle).collect();
+        assert_eq!(vec![18, 28, 44], cycles);
+    }
+
+    #[test]
+    fn axis_cycles_start_at_init() {
+        // The search only compares with the initial state, which must then be
+        // in the cycle: check it against the generic cycle detection
+        let mut system = example();
+        system.steps(5);
+        for axis in example().axes().iter().chain(&system.axes()) {
+            let cycle = common::cycle::brent(axis.clone(), step_axis);
+            assert_eq!(0, cycle.start);
+            assert_eq!(axis_cycle(axis), cycle.length);
+            assert_eq!(cycle, common::cycle::floyd(axis.clone(), step_axis));
+        }
+    }
+
+    #[test]
+    fn checkpoint_round_trip() {
+        let mut checkpoint = Checkpoint::new(example().axes());
+        checkpoint.complete(18);
+        checkpoint.steps = 7;
+        checkpoint.state = vec![(1, -2), (3, 4), (-5, 0), (6, 1)];
+        let text = checkpoint.to_string();
+        assert!(text.contains("cycles 18\nsteps 7\nstate 1,-2 3,4 -5,0 6,1\n"));
+        assert_eq!(checkpoint, text.parse().unwrap());
+        assert!(matches!(
+            "cycles 18\nspeed 3".parse::<Checkpoint>(),
+            Err(MyError::InvalidCheckpoint(_))
+        ));
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let path = std::env::temp_dir().join(format!("n-body-problem-{}.txt", std::process::id()));
+        let checkpointing = Checkpointing {
+            path: path.clone(),
+            every: 5,
+        };
+        let system = example();
+        // Interrupted after 10 steps on the second axis
+        let mut checkpoint = Checkpoint::new(system.axes());
+        checkpoint.complete(18);
+        let mut moved = system.clone();
+        moved.steps(10);
+        checkpoint.steps = 10;
+        checkpoint.state = moved.axes()[1].clone();
+        checkpoint.save(&path).unwrap();
+
+        let progress = ProgressBar::hidden();
+        let cycles = system.axis_cycles(&progress, Some(&checkpointing));
+        let saved = Checkpoint::load(&path, &system.axes());
+        let other = System::default().axis_cycles(&progress, Some(&checkpointing));
+        fs::remove_file(&path).unwrap();
+        assert_eq!(vec![18, 28, 44], cycles.unwrap());
+        // Only the steps after the checkpoint are simulated
+        assert_eq!((14 - 10) + 22, progress.position());
+        assert_eq!(vec![18, 28, 44], saved.unwrap().unwrap().cycles);
+        assert!(matches!(other, Err(MyError::MismatchedCheckpoint(_))));
+    }
+
+    mod options {
+        use super::*;
+        use common::cli::{Error, ErrorKind};
+
+        fn parse(args: &[&str]) -> Result<Options, Error> {
+            let args = Args::try_parse_from(
+                "day",
+                &[1, 2],
+                Options::arguments(),
+                std::iter::once("day").chain(args.iter().copied()),
+            )?;
+            Ok(Options::from_args(&args))
+        }
+
+        #[test]
+        fn no_checkpoint() {
+            let options = parse(&["input.txt"]).unwrap();
+            assert_eq!(None, options.checkpointing);
+        }
+
+        #[test]
+        fn checkpoint_every() {
+            let options = parse(&["--every", "1000", "--checkpoint", "cycle.txt"]).unwrap();
+            assert_eq!(
+                Some(Checkpointing {
+                    path: PathBuf::from("cycle.txt"),
+                    every: 1000
+                }),
+                options.checkpointing
+            );
+            let defaulted = parse(&["--checkpoint", "cycle.txt"]).unwrap();
+            assert_eq!(
+                Some(10_000_000),
+                defaulted
+                    .checkpointing
+                    .map(|checkpointing| checkpointing.every)
+            );
+        }
+
+        #[test]
+        fn invalid_options() {
+            let error = parse(&["--every", "0"]).unwrap_err();
+            assert_eq!(ErrorKind::ValueValidation, error.kind());
+            assert!(parse(&["--checkpoint"]).is_err());
+            assert!(parse(&["--resume"]).is_err());
+            assert!(parse(&["a.txt", "b.txt"]).is_err());
+        }
     }
 }
@@ -1,10 +1,10 @@
-use intcode::{self, Program};
+use common::render::{Color, Painted, Render};
+use intcode::{self, protocol::Outputs, Program};
 use std::{
     collections::HashMap,
     env,
     fmt::{self, Display, Formatter},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
     ops::{Deref, DerefMut},
     sync::mpsc,
     thread,
@@ -46,12 +46,28 @@ impl Display for Tile {
     }
 }
 
+#[cfg(any(feature = "animation", feature = "image"))]
+impl Tile {
+    fn color(_: Position, tile: Option<&Tile>) -> viz::Rgb {
+        use self::Tile::*;
+        match tile.cloned().unwrap_or(Empty) {
+            Empty => [0, 0, 0],
+            Wall => [128, 128, 128],
+            Block => [64, 96, 224],
+            Paddle => [255, 255, 255],
+            Ball => [224, 32, 32],
+        }
+    }
+}
+
 type Position = (i64, i64);
 type Tiles = HashMap<Position, Tile>;
 #[derive(Debug, Default)]
 struct Game {
     tiles: Tiles,
     score: i64,
+    #[cfg(feature = "animation")]
+    animation: viz::Animation,
 }
 impl Deref for Game {
     type Target = Tiles;
@@ -70,22 +86,26 @@ impl Display for Game {
         if self.tiles.is_empty() {
             return Ok(());
         }
-        let min_x = self.tiles.keys().map(|tile| tile.0).min().unwrap();
-        let max_x = self.tiles.keys().map(|tile| tile.0).max().unwrap();
-        let min_y = self.tiles.keys().map(|tile| tile.1).min().unwrap();
-        let max_y = self.tiles.keys().map(|tile| tile.1).max().unwrap();
-        write!(f, "Score - {}\n", self.score)?;
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let tile = self.tiles.get(&(x, y)).cloned().unwrap_or(Tile::Empty);
-                write!(f, "{}", tile)?;
+        writeln!(f, "Score - {}", self.score)?;
+        let render = Render::new(&self.tiles, |_, tile: Option<&Tile>| {
+            match tile.cloned().unwrap_or(Tile::Empty) {
+                Tile::Ball => Color::Red.paint(Tile::Ball),
+                tile => Painted::plain(tile),
             }
-            write!(f, "\n")?;
-        }
-        Ok(())
+        });
+        write!(f, "{}", render)
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+enum GameError {
+    #[error("An error occured in program execution")]
+    FailedProgram(#[from] intcode::Error),
+    #[cfg(feature = "viz")]
+    #[error("Failed to draw the game")]
+    FailedDrawing(#[from] viz::Error),
+}
+
 fn arcade_cabinet(opcodes: Vec<i64>) -> Result<Game, intcode::Error> {
     #[cfg(not(feature = "free-game"))]
     let (_, receiver_from_host) = mpsc::sync_channel(0);
@@ -95,20 +115,30 @@ fn arcade_cabinet(opcodes: Vec<i64>) -> Result<Game, intcode::Error> {
     let mut program = Program::new(opcodes, receiver_from_host, sender_to_host);
     thread::spawn(move || program.run().unwrap());
     let mut game = Game::default();
+    #[cfg(feature = "free-game")]
+    if common::render::is_terminal() {
+        print!("{}", common::render::CLEAR);
+    }
+    // Without the 'free-game' feature, the screen is only displayed once
+    #[cfg_attr(not(feature = "free-game"), allow(clippy::never_loop))]
     loop {
-        while let (Ok(x), Ok(y)) = (
-            receiver_from_thread.recv_timeout(Duration::from_millis(1000 / 50)),
-            receiver_from_thread.recv_timeout(Duration::from_millis(1000 / 50)),
-        ) {
+        for triple in receiver_from_thread.triples_timeout(Duration::from_millis(1000 / 50)) {
+            let (x, y, value) = triple?;
             let position = (x, y);
             if position == (-1, 0) {
-                game.score = receiver_from_thread.recv()?;
+                game.score = value;
             } else {
-                let tile = Tile::from(receiver_from_thread.recv()?);
-                game.insert(position, tile);
+                game.insert(position, Tile::from(value));
             }
         }
+        // Draw each frame of the game over the previous one
+        #[cfg(feature = "free-game")]
+        if common::render::is_terminal() {
+            print!("{}", common::render::HOME);
+        }
         println!("{}", game);
+        #[cfg(feature = "animation")]
+        game.animation.capture(&game.tiles, Tile::color);
         #[cfg(not(feature = "free-game"))]
         break;
         #[cfg(feature = "free-game")]
@@ -139,23 +169,31 @@ fn arcade_cabinet(opcodes: Vec<i64>) -> Result<Game, intcode::Error> {
     Ok(game)
 }
 
-fn main() -> Result<(), intcode::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+#[cfg(feature = "image")]
+impl Game {
+    /// Write the board as an image, to the path of `viz::image_path`.
+    fn save_image(&self) -> Result<(), viz::Error> {
+        if let Some(image) = viz::Image::capture(&self.tiles, Tile::color) {
+            image.save(viz::image_path())?;
+        }
+        Ok(())
     }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+}
+
+fn main() -> Result<(), GameError> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "free-game") { 2 } else { 1 }],
+    );
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .map_err(intcode::Error::from)?;
     let mut program_str = String::new();
-    reader.read_line(&mut program_str)?;
+    reader
+        .read_line(&mut program_str)
+        .map_err(intcode::Error::from)?;
     #[cfg(feature = "free-game")]
     let program_str = program_str.replacen("1,", "2,", 1);
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
-        .map_err(intcode::Error::from)?;
+    let program = intcode::parse(&program_str)?;
     #[cfg(not(feature = "free-game"))]
     {
         let game = arcade_cabinet(program)?;
@@ -165,9 +203,24 @@ fn main() -> Result<(), intcode::Error> {
             .filter(|&tile| *tile == Tile::Block)
             .count();
         println!("Number of block tiles is {}", block_tiles_count);
+        #[cfg(feature = "animation")]
+        game.animation.save(viz::path())?;
+        #[cfg(feature = "image")]
+        game.save_image()?;
     }
     #[cfg(feature = "free-game")]
-    arcade_cabinet(program)?;
+    {
+        #[cfg_attr(
+            not(any(feature = "animation", feature = "image")),
+            allow(unused_variables)
+        )]
+        let game = arcade_cabinet(program)?;
+        #[cfg(feature = "animation")]
+        game.animation.save(viz::path())?;
+        #[cfg(feature = "image")]
+        game.save_image()?;
+    }
+    args.report_time();
     Ok(())
 }
 
@@ -189,4 +242,33 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn display_game() {
+        let mut game = Game {
+            score: 42,
+            ..Game::default()
+        };
+        for x in 0..7 {
+            game.insert((x, 0), Tile::Wall);
+        }
+        for y in 1..5 {
+            game.insert((0, y), Tile::Wall);
+            game.insert((6, y), Tile::Wall);
+        }
+        for x in 2..5 {
+            game.insert((x, 1), Tile::Block);
+        }
+        game.insert((4, 3), Tile::Ball);
+        game.insert((3, 4), Tile::Paddle);
+        common::assert_display_snapshot!(game, "game.txt");
+        // The next frame only moves the ball
+        game.insert((4, 3), Tile::Empty);
+        game.insert((5, 2), Tile::Ball);
+        common::assert_grid_eq!(
+            "Score - 42\n███████\n█ ░░░ █\n█    ●█\n█     █\n█  ▂  █\n",
+            game
+        );
+        assert_eq!("", Game::default().to_string());
+    }
 }
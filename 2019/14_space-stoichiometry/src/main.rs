@@ -1,29 +1,96 @@
 use std::{
     collections::{hash_map::Entry, HashMap},
-    env,
     fmt::{self, Display, Formatter},
-    fs::File,
-    io::{self, BufRead, BufReader},
-    num::ParseIntError,
+    io::{self, BufRead},
     str::FromStr,
 };
 
+use common::{
+    cli::{value_parser, Arg, Args},
+    genvec::{Request, Rng},
+    parse,
+    token::{self, Token},
+};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 enum MyError {
-    #[error("Cannot convert into a quantity")]
-    ParseQuantity(#[from] ParseIntError),
-    #[error("Cannot convert {0} into a Chemical")]
+    #[error("Cannot convert into a Chemical, {0}")]
     ParseChemical(String),
     #[error("Not enough input chemicals for a reaction")]
     NotEnoughInputChemical(String),
-    #[error("Cannot convert {0} into a Reaction")]
+    #[error("Cannot convert into a Reaction, {0}")]
     ParseReaction(String),
+    #[error("Cannot convert a chemical of the reaction\n{underline}")]
+    InvalidChemical {
+        underline: String,
+        #[source]
+        source: Box<MyError>,
+    },
     #[error("Cannot open the file")]
     IOError(#[from] io::Error),
 }
 
+/// Production questions asked on the command line.
+#[derive(Debug, PartialEq, Eq)]
+struct Question {
+    /// Quantity of FUEL to produce, with `--fuel <quantity>`.
+    fuel: usize,
+    /// Quantity of ORE available, with `--ore-budget <quantity>`.
+    ore_budget: usize,
+    /// Reactions to generate instead of reading them, with
+    /// `--generate <size>` and `--seed <seed>`.
+    generate: Option<Request>,
+}
+
+impl Default for Question {
+    fn default() -> Self {
+        Question {
+            fuel: 1,
+            ore_budget: 1000000000000,
+            generate: None,
+        }
+    }
+}
+
+impl Question {
+    /// Arguments `--fuel <QUANTITY>` and `--ore-budget <QUANTITY>`, next to
+    /// the ones of `genvec::Request`, to be given to `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        let quantity = || value_parser!(u64).range(1..);
+        let mut arguments = vec![
+            Arg::new("fuel")
+                .long("fuel")
+                .value_name("QUANTITY")
+                .value_parser(quantity())
+                .allow_negative_numbers(true)
+                .help("Quantity of FUEL to produce, 1 by default"),
+            Arg::new("ore-budget")
+                .long("ore-budget")
+                .value_name("QUANTITY")
+                .value_parser(quantity())
+                .allow_negative_numbers(true)
+                .help("Quantity of ORE available, 1000000000000 by default"),
+        ];
+        arguments.extend(Request::arguments());
+        arguments
+    }
+
+    /// Question read from `args`, any missing option keeping its default
+    /// value.
+    fn from_args(args: &Args) -> Self {
+        let mut question = Question::default();
+        if let Some(fuel) = args.get::<u64>("fuel") {
+            question.fuel = fuel as usize;
+        }
+        if let Some(ore_budget) = args.get::<u64>("ore-budget") {
+            question.ore_budget = ore_budget as usize;
+        }
+        question.generate = Request::from_args(args);
+        question
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Chemical {
     name: String,
@@ -39,16 +106,19 @@ impl Display for Chemical {
 impl FromStr for Chemical {
     type Err = MyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splits: Vec<_> = s.split(" ").collect();
-        if splits.len() != 2 {
-            Err(MyError::ParseChemical(s.to_string()))
-        } else {
-            let chemical = Chemical {
-                name: splits[1].to_string(),
-                quantity: splits[0].parse()?,
-            };
-            Ok(chemical)
+        let (quantity, name) =
+            parse::quantity_of(s).map_err(|error| MyError::ParseChemical(error.to_string()))?;
+        if name.contains(char::is_whitespace) {
+            return Err(MyError::ParseChemical(format!(
+                "expect a single word, found '{}'",
+                name
+            )));
         }
+        let chemical = Chemical {
+            name: name.to_string(),
+            quantity,
+        };
+        Ok(chemical)
     }
 }
 
@@ -58,25 +128,36 @@ struct Reaction {
     output: Chemical,
 }
 
+impl Display for Reaction {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let inputs: Vec<String> = self.inputs.iter().map(Chemical::to_string).collect();
+        write!(f, "{} => {}", inputs.join(", "), self.output)
+    }
+}
+
 impl FromStr for Reaction {
     type Err = MyError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let splits: Vec<_> = s.split("=>").collect();
-        if splits.len() != 2 {
-            Err(MyError::ParseReaction(s.to_string()))
-        } else {
-            let inputs: Vec<Chemical> = splits[0]
-                .split(",")
-                .map(str::trim)
-                .map(str::parse)
-                .collect::<Result<_, _>>()?;
-            if inputs.len() < 1 {
-                return Err(MyError::NotEnoughInputChemical(s.to_string()));
-            }
-            let output: Chemical = splits[1].trim().parse()?;
-            let reaction = Reaction { inputs, output };
-            Ok(reaction)
+        let (inputs, output) =
+            parse::key_value(s, "=>").map_err(|error| MyError::ParseReaction(error.to_string()))?;
+        let chemical = |token: Token| -> Result<Chemical, MyError> {
+            token
+                .text
+                .parse()
+                .map_err(|source| MyError::InvalidChemical {
+                    underline: token.underline(s),
+                    source: Box::new(source),
+                })
+        };
+        let inputs: Vec<Chemical> = token::split(inputs, ',')
+            .map(|input| chemical(token::within(s, input.text)))
+            .collect::<Result<_, _>>()?;
+        if inputs.is_empty() {
+            return Err(MyError::NotEnoughInputChemical(s.to_string()));
         }
+        let output = chemical(token::within(s, output))?;
+        let reaction = Reaction { inputs, output };
+        Ok(reaction)
     }
 }
 
@@ -101,7 +182,7 @@ impl Reactions {
     fn produce_from(&mut self, into: &Chemical, from: &str) -> Chemical {
         let mut quantity = 0;
         let reaction = self.reactions.get(&into.name).unwrap().clone();
-        let count = if into.quantity % reaction.output.quantity == 0 {
+        let count = if into.quantity.is_multiple_of(reaction.output.quantity) {
             into.quantity / reaction.output.quantity
         } else {
             1 + into.quantity / reaction.output.quantity
@@ -139,79 +220,79 @@ impl Reactions {
     }
 
     fn produce_with(&mut self, into: &str, from: &Chemical) -> Chemical {
-        let mut quantity = 1;
-        let mut has_capped = false;
-        let mut increment = 1;
-        while increment != 0 {
+        let quantity = common::search::max_satisfying(|quantity| {
             let chemical = Chemical {
                 name: into.to_string(),
-                quantity,
+                quantity: quantity as usize,
             };
             self.waste.clear();
-            let p = self.produce_from(&chemical, &from.name);
-            if !has_capped {
-                if p.quantity <= from.quantity {
-                    increment *= 2;
-                    quantity = increment;
-                } else {
-                    has_capped = true;
-                }
-            } else {
-                increment /= 2;
-                if p.quantity <= from.quantity {
-                    quantity += increment;
-                } else {
-                    quantity -= increment;
-                }
-            }
-        }
-        let chemical = Chemical {
+            self.produce_from(&chemical, &from.name).quantity <= from.quantity
+        })
+        .expect("expect nothing to be produced from nothing");
+        Chemical {
             name: into.to_string(),
-            quantity,
-        };
-        self.waste.clear();
-        let p = self.produce_from(&chemical, &from.name);
-        if p.quantity <= from.quantity {
-            Chemical {
-                name: into.to_string(),
-                quantity,
-            }
-        } else {
-            Chemical {
-                name: into.to_string(),
-                quantity: quantity - 1,
-            }
+            quantity: quantity as usize,
         }
     }
 }
 
+/// Reactions of `size` chemicals besides ORE and FUEL, each one produced
+/// from the one before, and sometimes from ORE too, so that FUEL is at the
+/// end of a chain of every chemical. A reaction never needs more of the
+/// chemical before than it produces, else the ORE needed would double every
+/// few reactions.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    // 0 is ORE, then the chemicals in the order they are produced
+    let name = |chemical: usize| match chemical {
+        0 => "ORE".to_string(),
+        chemical if chemical == size + 1 => "FUEL".to_string(),
+        chemical => format!("C{}", chemical),
+    };
+    (1..=size + 1)
+        .map(|chemical| {
+            let output = rng.range(1..10);
+            let mut inputs = vec![format!(
+                "{} {}",
+                rng.range(1..output + 1),
+                name(chemical - 1)
+            )];
+            if chemical > 1 && rng.chance(30) {
+                inputs.insert(0, format!("{} ORE", rng.range(1..20)));
+            }
+            format!("{} => {} {}", inputs.join(", "), output, name(chemical))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 fn main() -> Result<(), MyError> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+    let args = Args::parse_with(env!("CARGO_PKG_NAME"), &[1, 2], Question::arguments());
+    let question = Question::from_args(&args);
+    if let Some(request) = question.generate {
+        println!("{}", generate(&mut request.rng(), request.size));
+        return Ok(());
     }
-    let file = File::open(&args[1])?;
-    let reader = BufReader::new(file);
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
     let mut reactions = reader
         .lines()
-        .filter(Result::is_ok)
-        .map(Result::unwrap)
+        .map_while(Result::ok)
         .map(|s| s.trim().parse())
         .collect::<Result<Vec<Reaction>, MyError>>()
         .map(Reactions::from)?;
     let into = Chemical {
         name: "FUEL".to_string(),
-        quantity: 1,
+        quantity: question.fuel,
     };
     let from = reactions.produce_from(&into, "ORE");
     println!("To produce {}, you need {}", into, from);
     reactions.waste.clear();
     let from = Chemical {
         name: "ORE".to_string(),
-        quantity: 1000000000000,
+        quantity: question.ore_budget,
     };
     let into = reactions.produce_with("FUEL", &from);
     println!("With {}, you can produce {}", from, into);
+    args.report_time();
     Ok(())
 }
 
@@ -262,6 +343,106 @@ mod tests {
         fn incorrect_reaction() {
             Reaction::from_str("1 ORE, 1 MUSHROOM > 1 FUEL").unwrap();
         }
+
+        #[test]
+        fn underline_incorrect_chemical() {
+            let error = Reaction::from_str("7 A, 1 => 1 C").unwrap_err();
+            assert_eq!(
+                "Cannot convert a chemical of the reaction\n7 A, 1 => 1 C\n     ^",
+                error.to_string()
+            );
+        }
+    }
+
+    mod parse_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn chemical_round_trip((quantity, name, text) in common::strategy::quantity()) {
+                let chemical = Chemical::from_str(&text).unwrap();
+                prop_assert_eq!(quantity, chemical.quantity);
+                prop_assert_eq!(&name, &chemical.name);
+                prop_assert_eq!(text, chemical.to_string());
+            }
+
+            #[test]
+            fn reaction_never_panics(line in common::strategy::line()) {
+                let _ = Reaction::from_str(&line);
+            }
+        }
+    }
+
+    mod solver_properties {
+        use super::*;
+        use proptest::{collection, prelude::*, sample::Index};
+
+        /// Lines of reactions without any cycle: the chemical of each line is
+        /// produced from ORE and the chemicals of the lines before, the last
+        /// one being FUEL.
+        fn reactions() -> impl Strategy<Value = Vec<String>> {
+            let inputs = collection::vec((any::<Index>(), 1..10usize), 1..4);
+            collection::vec((inputs, 1..10usize), 1..8).prop_map(|reactions| {
+                let count = reactions.len();
+                // 0 is ORE, then the chemicals in the order they are produced
+                let name = |chemical: usize| match chemical {
+                    0 => "ORE".to_string(),
+                    chemical if chemical == count => "FUEL".to_string(),
+                    chemical => format!("C{}", chemical),
+                };
+                reactions
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (inputs, quantity))| {
+                        let inputs: Vec<String> = inputs
+                            .iter()
+                            .map(|(input, quantity)| {
+                                format!("{} {}", quantity, name(input.index(index + 1)))
+                            })
+                            .collect();
+                        format!("{} => {} {}", inputs.join(", "), quantity, name(index + 1))
+                    })
+                    .collect()
+            })
+        }
+
+        fn ore_needed(reactions: &mut Reactions, fuel: usize) -> usize {
+            let into = Chemical {
+                name: "FUEL".to_string(),
+                quantity: fuel,
+            };
+            reactions.waste.clear();
+            reactions.produce_from(&into, "ORE").quantity
+        }
+
+        proptest! {
+            #[test]
+            fn reaction_round_trip(lines in reactions()) {
+                for line in &lines {
+                    prop_assert_eq!(line, &Reaction::from_str(line).unwrap().to_string());
+                }
+            }
+
+            #[test]
+            fn more_fuel_needs_more_ore(lines in reactions(), fuel in 1..50usize) {
+                let mut reactions = lines
+                    .iter()
+                    .map(|line| line.parse())
+                    .collect::<Result<Vec<Reaction>, MyError>>()
+                    .map(Reactions::from)
+                    .unwrap();
+                let ore = ore_needed(&mut reactions, fuel);
+                prop_assert!(ore > 0);
+                prop_assert!(ore <= ore_needed(&mut reactions, fuel + 1));
+                let budget = Chemical {
+                    name: "ORE".to_string(),
+                    quantity: ore,
+                };
+                reactions.waste.clear();
+                prop_assert!(reactions.produce_with("FUEL", &budget).quantity >= fuel);
+            }
+        }
     }
 
     mod produce_from {
@@ -280,7 +461,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let into = Chemical {
                 name: "FUEL".to_string(),
@@ -305,7 +486,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let into = Chemical {
                 name: "FUEL".to_string(),
@@ -332,7 +513,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let into = Chemical {
                 name: "FUEL".to_string(),
@@ -362,7 +543,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let into = Chemical {
                 name: "FUEL".to_string(),
@@ -397,7 +578,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let into = Chemical {
                 name: "FUEL".to_string(),
@@ -428,7 +609,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let from = Chemical {
                 name: "ORE".to_string(),
@@ -458,7 +639,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let from = Chemical {
                 name: "ORE".to_string(),
@@ -493,7 +674,7 @@ mod tests {
             .into_iter()
             .map(str::parse)
             .collect::<Result<Vec<Reaction>, MyError>>()
-            .map(|reaction| Reactions::from(reaction))
+            .map(Reactions::from)
             .unwrap();
             let from = Chemical {
                 name: "ORE".to_string(),
@@ -504,4 +685,81 @@ mod tests {
             assert_eq!(460664, into.quantity);
         }
     }
+
+    #[test]
+    fn generated_reactions() {
+        let text = generate(&mut Rng::new(common::genvec::SEED), 100);
+        assert_eq!(101, text.lines().count());
+        let mut reactions = text
+            .lines()
+            .map(str::parse)
+            .collect::<Result<Vec<Reaction>, MyError>>()
+            .map(Reactions::from)
+            .unwrap();
+        let into = Chemical {
+            name: "FUEL".to_string(),
+            quantity: 1,
+        };
+        // Every chemical is needed, as FUEL needs the last one which needs
+        // the one before and so on
+        let ore = reactions.produce_from(&into, "ORE").quantity;
+        assert!(ore > 0);
+        let budget = Chemical {
+            name: "ORE".to_string(),
+            quantity: ore,
+        };
+        reactions.waste.clear();
+        assert!(reactions.produce_with("FUEL", &budget).quantity >= 1);
+    }
+
+    mod questions {
+        use super::*;
+        use common::cli::ErrorKind;
+
+        fn parse(args: &[&str]) -> Result<Question, common::cli::Error> {
+            let args = Args::try_parse_from(
+                "day",
+                &[1, 2],
+                Question::arguments(),
+                std::iter::once("day").chain(args.iter().copied()),
+            )?;
+            Ok(Question::from_args(&args))
+        }
+
+        #[test]
+        fn default_question() {
+            let question = parse(&[]).unwrap();
+            assert_eq!(Question::default(), question);
+        }
+
+        #[test]
+        fn fuel_and_ore_budget() {
+            let question = parse(&["--ore-budget", "1000", "input.txt", "--fuel", "3"]).unwrap();
+            assert_eq!(3, question.fuel);
+            assert_eq!(1000, question.ore_budget);
+        }
+
+        #[test]
+        fn invalid_quantities() {
+            for quantity in &["0", "-5", "lots"] {
+                let error = parse(&["--ore-budget", quantity]).unwrap_err();
+                assert_eq!(ErrorKind::ValueValidation, error.kind());
+            }
+            assert!(parse(&["--fuel"]).is_err());
+        }
+
+        #[test]
+        fn generate_and_seed() {
+            let question = parse(&["--generate", "100", "--seed", "7"]).unwrap();
+            assert_eq!(Some(Request { size: 100, seed: 7 }), question.generate);
+            let error = parse(&["--generate", "100", "--seed", "-1"]).unwrap_err();
+            assert_eq!(ErrorKind::ValueValidation, error.kind());
+        }
+
+        #[test]
+        fn unknown_option() {
+            assert!(parse(&["--oxygen", "1"]).is_err());
+            assert!(parse(&["a.txt", "b.txt"]).is_err());
+        }
+    }
 }
@@ -1,10 +1,15 @@
+use common::{
+    bitgrid::BitGrid,
+    genvec::{Request, Rng},
+    render::{Color, Painted, Render},
+};
 use intcode::{self, Program};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     env,
     fmt::{self, Display, Formatter},
-    fs::File,
-    io::{BufRead, BufReader},
+    io::BufRead,
+    mem,
     ops::{Deref, DerefMut},
     sync::mpsc,
     thread,
@@ -12,6 +17,21 @@ use std::{
 
 type Position = (i64, i64);
 
+#[derive(Debug, thiserror::Error)]
+enum ExplorationError {
+    #[error("The droid reported {new:?} at {position:?} but the map has {old:?}")]
+    InconsistentMap {
+        position: Position,
+        old: Tile,
+        new: Tile,
+    },
+    #[error("An error occured in program execution")]
+    FailedProgram(#[from] intcode::Error),
+    #[cfg(feature = "viz")]
+    #[error("Failed to draw the maze")]
+    FailedDrawing(#[from] viz::Error),
+}
+
 #[derive(Debug, Clone)]
 enum Tile {
     Empty(usize),
@@ -42,6 +62,19 @@ impl Display for Tile {
     }
 }
 
+#[cfg(any(feature = "animation", feature = "image"))]
+impl Tile {
+    fn color(tile: Option<&Tile>) -> viz::Rgb {
+        use self::Tile::*;
+        match tile {
+            None => [0, 0, 0],
+            Some(Empty(_)) => [224, 224, 224],
+            Some(Wall) => [96, 96, 96],
+            Some(Oxygen(_)) => [32, 192, 224],
+        }
+    }
+}
+
 #[derive(Debug)]
 enum Command {
     North,
@@ -50,10 +83,10 @@ enum Command {
     East,
 }
 
-impl Into<i64> for Command {
-    fn into(self) -> i64 {
+impl From<Command> for i64 {
+    fn from(val: Command) -> Self {
         use self::Command::*;
-        match self {
+        match val {
             North => 1,
             South => 2,
             West => 3,
@@ -79,6 +112,8 @@ type Tiles = HashMap<Position, Tile>;
 struct Map {
     tiles: Tiles,
     position: Position,
+    #[cfg(feature = "animation")]
+    animation: viz::Animation,
 }
 impl Deref for Map {
     type Target = Tiles;
@@ -94,44 +129,45 @@ impl DerefMut for Map {
 
 impl Display for Map {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        if self.tiles.is_empty() {
-            return Ok(());
-        }
-        let min_x = self.keys().map(|position| position.0).min().unwrap();
-        let max_x = self.keys().map(|position| position.0).max().unwrap();
-        let min_y = self.keys().map(|position| position.1).min().unwrap();
-        let max_y = self.keys().map(|position| position.1).max().unwrap();
-        for y in (min_y..=max_y).rev() {
-            for x in min_x..=max_x {
-                if self.position.0 == x && self.position.1 == y {
-                    write!(f, " x ")?;
-                } else {
-                    let tile = self.tiles.get(&(x, y)).cloned().unwrap_or(Tile::Empty(999));
-                    write!(f, "{}", tile)?;
-                }
+        let render = Render::new(&self.tiles, |position, tile: Option<&Tile>| {
+            match tile.cloned().unwrap_or(Tile::Empty(999)) {
+                _ if position == self.position => Color::Red.paint(" x ".to_string()),
+                tile @ Tile::Oxygen(_) => Color::Cyan.paint(tile.to_string()),
+                tile => Painted::plain(tile.to_string()),
             }
-            write!(f, "\n")?;
-        }
-        Ok(())
+        });
+        write!(f, "{}", render.y_up())
     }
 }
 
 impl Map {
+    /// Check that the tile reported by the droid at `position` is of the same
+    /// kind than the one already in the map, if any.
+    fn check(&self, position: Position, new: &Tile) -> Result<(), ExplorationError> {
+        match self.get(&position) {
+            Some(old) if mem::discriminant(old) != mem::discriminant(new) => {
+                Err(ExplorationError::InconsistentMap {
+                    position,
+                    old: old.clone(),
+                    new: new.clone(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn time_to_oxygenize(&mut self) -> usize {
         let mut minutes = 0;
         let mut oxygen_positions: Vec<Position> = self
             .iter()
-            .filter(|(_, tile)| match tile {
-                Tile::Oxygen(_) => true,
-                _ => false,
-            })
-            .map(|(position, _)| position.clone())
+            .filter(|(_, tile)| matches!(tile, Tile::Oxygen(_)))
+            .map(|(position, _)| *position)
             .collect();
         loop {
             let mut new_oxygen_positions = Vec::new();
             for oxygen_position in oxygen_positions.drain(..) {
                 let neighbour_positions: Vec<Position> =
-                    vec![Command::North, Command::South, Command::West, Command::East]
+                    [Command::North, Command::South, Command::West, Command::East]
                         .iter()
                         .map(|command| command.next(&oxygen_position))
                         .collect();
@@ -153,27 +189,46 @@ impl Map {
     }
 }
 
-fn find_oxygen(opcodes: Vec<i64>) -> Result<Map, intcode::Error> {
+/// Explore the maze with the droid, drawing the map after each move when
+/// `animated`.
+fn find_oxygen(opcodes: Vec<i64>, animated: bool) -> Result<Map, ExplorationError> {
     let (sender_to_thread, receiver_from_host) = mpsc::sync_channel(0);
     let (sender_to_host, receiver_from_thread) = mpsc::sync_channel(0);
     let mut program = Program::new(opcodes, receiver_from_host, sender_to_host);
-    thread::spawn(move || program.run().unwrap());
-    let mut map = Map::default();
-    map.position = (0, 0);
+    // The droid is left waiting for a command once the maze is explored, its
+    // errors being those of the exploration
+    thread::spawn(move || program.run());
+    let mut map = Map {
+        position: (0, 0),
+        #[cfg(feature = "animation")]
+        animation: viz::Animation::default().y_up(),
+        ..Default::default()
+    };
     let mut current_distance = 0;
     map.insert((0, 0), Tile::Empty(current_distance));
     'outer: loop {
-        println!("{}", map);
-        for command in vec![Command::North, Command::South, Command::West, Command::East] {
-            std::thread::sleep(std::time::Duration::from_millis(10));
+        if animated {
+            println!("{}", map);
+        }
+        #[cfg(feature = "animation")]
+        {
+            let droid = map.position;
+            map.animation.capture(&map.tiles, |position, tile| {
+                if position == droid {
+                    [224, 32, 32]
+                } else {
+                    Tile::color(tile)
+                }
+            });
+        }
+        for command in [Command::North, Command::South, Command::West, Command::East] {
+            if animated {
+                std::thread::sleep(std::time::Duration::from_millis(10));
+            }
             let next_position = command.next(&map.position);
             if let Some(tile) = map.get(&next_position) {
                 if let Tile::Empty(distance) = tile {
-                    let diff = if current_distance > *distance {
-                        current_distance - *distance
-                    } else {
-                        *distance - current_distance
-                    };
+                    let diff = current_distance.abs_diff(*distance);
                     if diff < 2 {
                         continue;
                     }
@@ -181,12 +236,15 @@ fn find_oxygen(opcodes: Vec<i64>) -> Result<Map, intcode::Error> {
                     continue;
                 }
             }
-            sender_to_thread.send(command.into())?;
-            let new_tile = Tile::from(receiver_from_thread.recv()?);
+            sender_to_thread
+                .send(command.into())
+                .map_err(intcode::Error::from)?;
+            let new_tile = Tile::from(receiver_from_thread.recv().map_err(intcode::Error::from)?);
+            map.check(next_position, &new_tile)?;
             match new_tile {
                 Tile::Empty(_) => {
                     current_distance += 1;
-                    map.insert(next_position.clone(), Tile::Empty(current_distance));
+                    map.insert(next_position, Tile::Empty(current_distance));
                     map.position = next_position;
                     continue 'outer;
                 }
@@ -196,24 +254,30 @@ fn find_oxygen(opcodes: Vec<i64>) -> Result<Map, intcode::Error> {
                 }
                 Tile::Oxygen(_) => {
                     current_distance += 1;
-                    map.insert(next_position.clone(), Tile::Oxygen(current_distance));
+                    map.insert(next_position, Tile::Oxygen(current_distance));
                     map.position = next_position;
                     continue 'outer;
                 }
             }
         }
-        // If all around is explored, move backward
-        for command in vec![Command::East, Command::West, Command::South, Command::North] {
+        // If all around the start is explored, so is the whole maze
+        if map.position == (0, 0) {
+            return Ok(map);
+        }
+        // If all around is explored, move backward, possibly through the
+        // oxygen system
+        for command in [Command::East, Command::West, Command::South, Command::North] {
             let next_position = command.next(&map.position);
-            if let Some(Tile::Empty(distance)) = map.get(&next_position) {
+            if let Some(Tile::Empty(distance) | Tile::Oxygen(distance)) = map.get(&next_position) {
                 if *distance <= current_distance {
-                    sender_to_thread.send(command.into())?;
-                    receiver_from_thread.recv()?;
+                    sender_to_thread
+                        .send(command.into())
+                        .map_err(intcode::Error::from)?;
+                    let tile =
+                        Tile::from(receiver_from_thread.recv().map_err(intcode::Error::from)?);
+                    map.check(next_position, &tile)?;
                     current_distance = *distance;
                     map.position = next_position;
-                    if map.position == (0, 0) {
-                        return Ok(map);
-                    }
                     break;
                 }
             }
@@ -221,32 +285,172 @@ fn find_oxygen(opcodes: Vec<i64>) -> Result<Map, intcode::Error> {
     }
 }
 
-fn main() -> Result<(), intcode::Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+/// A maze without any loop, as the one of the puzzle, with the tiles
+/// reported by the droid: 0 for a wall, 1 for an empty tile and 2 for the
+/// oxygen system.
+#[derive(Debug)]
+struct Maze {
+    tiles: Vec<i64>,
+    width: usize,
+    start: usize,
+    oxygen: usize,
+}
+
+impl Maze {
+    /// Maze of `size` by `size` cells, carved from the first cell by a random
+    /// walk going back when stuck, with the droid and the oxygen system on
+    /// two random cells.
+    fn generate(rng: &mut Rng, size: usize) -> Self {
+        let size = size.max(2);
+        let width = 2 * size + 1;
+        let cell = |(x, y): (usize, usize)| (2 * y + 1) * width + 2 * x + 1;
+        let mut tiles = vec![0; width * width];
+        let mut visited = BitGrid::new(size, size);
+        let mut stack = vec![(0, 0)];
+        visited.insert(0, 0);
+        tiles[cell((0, 0))] = 1;
+        while let Some(&(x, y)) = stack.last() {
+            let neighbours: Vec<(usize, usize)> = [(0, 1), (0, -1), (-1, 0), (1, 0)]
+                .iter()
+                .map(|(dx, dy)| (x as i64 + dx, y as i64 + dy))
+                .filter(|&(x, y)| 0 <= x && x < size as i64 && 0 <= y && y < size as i64)
+                .map(|(x, y)| (x as usize, y as usize))
+                .filter(|&(x, y)| !visited.get(x, y))
+                .collect();
+            if neighbours.is_empty() {
+                stack.pop();
+                continue;
+            }
+            let next = *rng.pick(&neighbours);
+            visited.insert(next.0, next.1);
+            tiles[cell(next)] = 1;
+            // The wall between both cells, in the same row or column
+            tiles[(cell((x, y)) + cell(next)) / 2] = 1;
+            stack.push(next);
+        }
+        let random_cell = |rng: &mut Rng| {
+            cell((
+                rng.range(0..size as u64) as usize,
+                rng.range(0..size as u64) as usize,
+            ))
+        };
+        let start = random_cell(rng);
+        let mut oxygen = random_cell(rng);
+        while oxygen == start {
+            oxygen = random_cell(rng);
+        }
+        tiles[oxygen] = 2;
+        Maze {
+            tiles,
+            width,
+            start,
+            oxygen,
+        }
+    }
+
+    /// Intcode program of the droid in this maze: each command moves the
+    /// droid by the offset of its direction in the tiles, unless there is a
+    /// wall, and the tile found there is the output.
+    fn program(&self) -> Vec<i64> {
+        // Addresses of the variables, after the code
+        const COMMAND: i64 = 30;
+        const POSITION: i64 = 31;
+        const NEXT: i64 = 32;
+        const TILE: i64 = 33;
+        const OFFSETS: i64 = 34;
+        const TILES: i64 = 39;
+        let width = self.width as i64;
+        let mut program = Vec::new();
+        // 0: input the command
+        program.extend([3, COMMAND]);
+        // 2: the address of the offset of the command goes in the first
+        // parameter at 7
+        program.extend([101, OFFSETS, COMMAND, 7]);
+        // 6: next position = offset + position
+        program.extend([1, 0, POSITION, NEXT]);
+        // 10: the address of the next tile goes in the first parameter at 15
+        program.extend([101, TILES, NEXT, 15]);
+        // 14: tile = next tile + 0, then output it
+        program.extend([1001, 0, 0, TILE, 4, TILE]);
+        // 20: back to 0 if it is a wall
+        program.extend([1006, TILE, 0]);
+        // 23: move to the next position, then back to 0
+        program.extend([1001, NEXT, 0, POSITION, 1105, 1, 0]);
+        // North, south, west and east, the command 0 being unused
+        program.extend([0, self.start as i64, 0, 0, 0, width, -width, -1, 1]);
+        program.extend(&self.tiles);
+        program
+    }
+
+    /// Number of moves from each empty tile to `from`.
+    fn distances(&self, from: usize) -> HashMap<usize, usize> {
+        let mut distances = HashMap::new();
+        distances.insert(from, 0);
+        let mut queue = VecDeque::from(vec![from]);
+        while let Some(tile) = queue.pop_front() {
+            let distance = distances[&tile];
+            for next in [tile + self.width, tile - self.width, tile - 1, tile + 1] {
+                if self.tiles[next] != 0 && !distances.contains_key(&next) {
+                    distances.insert(next, distance + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Fewest moves from the droid to the oxygen system.
+    fn distance_to_oxygen(&self) -> usize {
+        self.distances(self.start)[&self.oxygen]
+    }
+
+    /// Minutes for the oxygen to reach the tile the farthest from the
+    /// oxygen system.
+    fn time_to_oxygenize(&self) -> usize {
+        self.distances(self.oxygen)
+            .values()
+            .copied()
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+fn main() -> Result<(), ExplorationError> {
+    let args = common::cli::Args::parse_with(env!("CARGO_PKG_NAME"), &[1, 2], Request::arguments());
+    if let Some(request) = Request::from_args(&args) {
+        let maze = Maze::generate(&mut request.rng(), request.size);
+        let program: Vec<String> = maze.program().iter().map(i64::to_string).collect();
+        println!("{}", program.join(","));
+        eprintln!(
+            "Part 1: {}, part 2: {}",
+            maze.distance_to_oxygen(),
+            maze.time_to_oxygenize()
+        );
+        return Ok(());
     }
-    let file = File::open(&args[1])?;
-    let mut reader = BufReader::new(file);
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .map_err(intcode::Error::from)?;
     let mut program_str = String::new();
-    reader.read_line(&mut program_str)?;
-    let program: Vec<i64> = program_str
-        .trim()
-        .split(",")
-        .map(str::parse)
-        .collect::<Result<_, _>>()
+    reader
+        .read_line(&mut program_str)
         .map_err(intcode::Error::from)?;
-    let mut map = find_oxygen(program)?;
-    if let Some(Tile::Oxygen(distance)) = map.values().find(|tile| match tile {
-        Tile::Oxygen(_) => true,
-        _ => false,
-    }) {
+    let program = intcode::parse(&program_str)?;
+    let mut map = find_oxygen(program, common::render::is_terminal())?;
+    if let Some(Tile::Oxygen(distance)) = map.values().find(|tile| matches!(tile, Tile::Oxygen(_)))
+    {
         let distance = *distance;
         println!("{}", map);
         let time = map.time_to_oxygenize();
         println!("The distance to oxygen system is {}", distance);
         println!("It took {} minutes to reoxygenize", time);
     }
+    #[cfg(feature = "animation")]
+    map.animation.save(viz::path())?;
+    #[cfg(feature = "image")]
+    if let Some(image) = viz::Image::capture(&map.tiles, |_, tile| Tile::color(tile)) {
+        image.y_up().save(viz::image_path())?;
+    }
+    args.report_time();
     Ok(())
 }
 
@@ -298,4 +502,96 @@ mod tests {
         map.insert((3, 0), Tile::Wall);
         assert_eq!(4, map.time_to_oxygenize());
     }
+
+    #[test]
+    fn display_map() {
+        let mut map = Map::default();
+        map.insert((0, 1), Tile::Wall);
+        map.insert((1, 1), Tile::Wall);
+        map.insert((-1, 0), Tile::Wall);
+        map.insert((0, 0), Tile::Empty(0));
+        map.insert((1, 0), Tile::Empty(1));
+        map.insert((2, 0), Tile::Wall);
+        map.insert((-1, -1), Tile::Oxygen(2));
+        map.insert((0, -1), Tile::Empty(1));
+        map.insert((1, -1), Tile::Wall);
+        map.insert((-1, -2), Tile::Wall);
+        map.insert((0, -2), Tile::Wall);
+        map.position = (1, 0);
+        common::assert_display_snapshot!(map, "map.txt");
+    }
+
+    #[test]
+    fn inconsistent_map() {
+        let mut map = Map::default();
+        map.insert((0, 0), Tile::Empty(0));
+        map.insert((0, 1), Tile::Wall);
+        map.insert((1, 0), Tile::Oxygen(1));
+        assert!(map.check((0, 0), &Tile::Empty(2)).is_ok());
+        assert!(map.check((0, 1), &Tile::Wall).is_ok());
+        assert!(map.check((1, 0), &Tile::Oxygen(999)).is_ok());
+        assert!(map.check((5, 5), &Tile::Wall).is_ok());
+        let error = map.check((0, 0), &Tile::Wall).unwrap_err();
+        assert!(matches!(
+            error,
+            ExplorationError::InconsistentMap {
+                position: (0, 0),
+                old: Tile::Empty(0),
+                new: Tile::Wall,
+            }
+        ));
+        assert_eq!(
+            "The droid reported Wall at (0, 0) but the map has Empty(0)",
+            error.to_string()
+        );
+        assert!(map.check((0, 1), &Tile::Empty(999)).is_err());
+        assert!(map.check((1, 0), &Tile::Empty(999)).is_err());
+    }
+
+    #[test]
+    fn generated_mazes() -> Result<(), ExplorationError> {
+        let mut rng = Rng::new(common::genvec::SEED);
+        for size in [2, 3, 8, 15] {
+            let maze = Maze::generate(&mut rng, size);
+            let mut map = find_oxygen(maze.program(), false)?;
+            let distance = map.values().find_map(|tile| match tile {
+                Tile::Oxygen(distance) => Some(*distance),
+                _ => None,
+            });
+            assert_eq!(Some(maze.distance_to_oxygen()), distance);
+            assert_eq!(maze.time_to_oxygenize(), map.time_to_oxygenize());
+            // Every empty tile and every wall around them is explored
+            let open = maze.tiles.iter().filter(|&&tile| tile != 0).count();
+            let explored = map
+                .values()
+                .filter(|tile| !matches!(tile, Tile::Wall))
+                .count();
+            assert_eq!(open, explored);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn droid_program() {
+        // A corridor of 3 cells, from the droid on the west to the oxygen
+        // system on the east
+        let maze = Maze {
+            tiles: vec![0, 0, 0, 0, 0, 0, 1, 1, 2, 0, 0, 0, 0, 0, 0],
+            width: 5,
+            start: 6,
+            oxygen: 8,
+        };
+        let (sender, inputs) = mpsc::sync_channel(8);
+        let (outputs, receiver) = mpsc::sync_channel(8);
+        // North into a wall, east twice, then east into a wall
+        for command in [1, 4, 4, 4] {
+            sender.send(command).unwrap();
+        }
+        drop(sender);
+        let mut program = Program::new(maze.program(), inputs, outputs);
+        assert!(program.run().is_err());
+        assert_eq!(vec![0, 1, 2, 0], receiver.try_iter().collect::<Vec<_>>());
+        assert_eq!(2, maze.distance_to_oxygen());
+        assert_eq!(2, maze.time_to_oxygenize());
+    }
 }
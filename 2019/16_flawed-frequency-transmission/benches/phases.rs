@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use flawed_frequency_transmission::{fft, message, suffix_phases, PHASES, REPEAT};
+
+/// A signal of `length` digits, as long as the inputs for 650, whose first
+/// digits give an offset in the second half of the real signal.
+fn signal(length: usize) -> Vec<u8> {
+    let offset = length * REPEAT * 3 / 4;
+    let mut signal: Vec<u8> = (0..length).map(|i| ((7 * i + 3) % 10) as u8).collect();
+    for (i, digit) in format!("{:07}", offset).bytes().enumerate() {
+        signal[i] = digit - b'0';
+    }
+    signal
+}
+
+fn phases(c: &mut Criterion) {
+    let mut group = c.benchmark_group("phases");
+    group.sample_size(10);
+    for &length in &[100, 650] {
+        let signal = signal(length);
+        group.bench_with_input(BenchmarkId::new("naive", length), &signal, |b, signal| {
+            b.iter(|| fft(signal, PHASES))
+        });
+        // The same digits as the second half of the naive phases
+        group.bench_with_input(BenchmarkId::new("suffix", length), &signal, |b, signal| {
+            b.iter(|| {
+                let mut tail = signal[signal.len() / 2..].to_vec();
+                suffix_phases(&mut tail, PHASES);
+                tail
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("message", length), &signal, |b, signal| {
+            b.iter(|| message(signal, REPEAT, PHASES).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, phases);
+criterion_main!(benches);
@@ -0,0 +1,187 @@
+use std::io;
+
+/// Phases applied to the signal before reading its digits.
+pub const PHASES: usize = 100;
+/// Times the signal is repeated to make the real signal.
+pub const REPEAT: usize = 10_000;
+/// Digits of the signal giving the offset of the message in the real signal.
+pub const OFFSET_DIGITS: usize = 7;
+/// Digits read from the output of the last phase.
+pub const MESSAGE_DIGITS: usize = 8;
+
+/// Base pattern of a phase, each value being repeated as many times as the
+/// position of the output digit, from 1.
+const BASE_PATTERN: [i32; 4] = [0, 1, 0, -1];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to read the signal")]
+    FailedInput(#[from] io::Error),
+    #[error("'{0}' is not a digit of the signal")]
+    NotADigit(char),
+    #[error("The signal has {0} digits, fewer than the digits of its offset")]
+    SignalTooShort(usize),
+    #[error("The offset {offset} is in the first half of the {length} digits of the real signal")]
+    OffsetInFirstHalf { offset: usize, length: usize },
+    #[error("The offset {offset} leaves no message in the {length} digits of the real signal")]
+    OffsetPastTheEnd { offset: usize, length: usize },
+}
+
+/// Digits of a line, surrounding whitespaces aside.
+pub fn parse(line: &str) -> Result<Vec<u8>, Error> {
+    line.trim()
+        .chars()
+        .map(|c| {
+            c.to_digit(10)
+                .map(|digit| digit as u8)
+                .ok_or(Error::NotADigit(c))
+        })
+        .collect()
+}
+
+/// Digits written one after the other, e.g. `01029498`.
+pub fn digits(signal: &[u8]) -> String {
+    signal.iter().map(|digit| digit.to_string()).collect()
+}
+
+/// Output of one phase: each digit is the sum of the input digits, each
+/// multiplied by the value of the pattern of this digit at its position,
+/// keeping only the ones digit.
+pub fn phase(signal: &[u8]) -> Vec<u8> {
+    (0..signal.len())
+        .map(|output| {
+            let sum: i32 = signal
+                .iter()
+                .enumerate()
+                .map(|(input, &digit)| {
+                    // The pattern is shifted left by one value
+                    let value = BASE_PATTERN[(input + 1) / (output + 1) % BASE_PATTERN.len()];
+                    value * i32::from(digit)
+                })
+                .sum();
+            (sum.abs() % 10) as u8
+        })
+        .collect()
+}
+
+/// Output of `phases` phases, each one computed from every digit of the
+/// previous one.
+pub fn fft(signal: &[u8], phases: usize) -> Vec<u8> {
+    (0..phases).fold(signal.to_vec(), |signal, _| phase(&signal))
+}
+
+/// Apply `phases` phases to the digits of a signal from some offset to its
+/// end, the offset being in its second half. There, the pattern of a digit
+/// is 0 before it and 1 from it to the end, so that each output digit is the
+/// sum of the input digits from its position, computed from the last one.
+pub fn suffix_phases(tail: &mut [u8], phases: usize) {
+    for _ in 0..phases {
+        let mut sum = 0;
+        for digit in tail.iter_mut().rev() {
+            // Both are digits, their sum is less than 20
+            sum += *digit;
+            if sum >= 10 {
+                sum -= 10;
+            }
+            *digit = sum;
+        }
+    }
+}
+
+/// Message of the real signal, `signal` repeated `repeat` times: the first
+/// digits of the output of `phases` phases, from the offset given by the
+/// first digits of `signal`. Only the digits from the offset are computed,
+/// which needs the offset to be in the second half of the real signal.
+pub fn message(signal: &[u8], repeat: usize, phases: usize) -> Result<Vec<u8>, Error> {
+    if signal.len() < OFFSET_DIGITS {
+        return Err(Error::SignalTooShort(signal.len()));
+    }
+    let offset = signal[..OFFSET_DIGITS]
+        .iter()
+        .fold(0, |offset, &digit| 10 * offset + usize::from(digit));
+    let length = signal.len() * repeat;
+    if offset < length / 2 {
+        return Err(Error::OffsetInFirstHalf { offset, length });
+    }
+    if offset + MESSAGE_DIGITS > length {
+        return Err(Error::OffsetPastTheEnd { offset, length });
+    }
+    let mut tail: Vec<u8> = (offset..length)
+        .map(|position| signal[position % signal.len()])
+        .collect();
+    suffix_phases(&mut tail, phases);
+    tail.truncate(MESSAGE_DIGITS);
+    Ok(tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_of_a_small_signal() {
+        let signal = parse("12345678").unwrap();
+        let outputs = ["48226158", "34040438", "03415518", "01029498"];
+        for (phases, output) in (1..).zip(&outputs) {
+            assert_eq!(*output, digits(&fft(&signal, phases)));
+        }
+    }
+
+    #[test]
+    fn first_digits() {
+        for (signal, expected) in &[
+            ("80871224585914546619083218645595", "24176176"),
+            ("19617804207202209144916044189917", "73745418"),
+            ("69317163492948606335995924319873", "52432133"),
+        ] {
+            let output = fft(&parse(signal).unwrap(), PHASES);
+            assert_eq!(*expected, digits(&output[..MESSAGE_DIGITS]));
+        }
+    }
+
+    #[test]
+    fn suffix_sums_are_the_second_half() {
+        let signal = parse("69317163492948606335995924319873").unwrap();
+        for phases in 0..10 {
+            let mut tail = signal[signal.len() / 2..].to_vec();
+            suffix_phases(&mut tail, phases);
+            let output = fft(&signal, phases);
+            assert_eq!(&output[signal.len() / 2..], &tail[..], "{} phases", phases);
+        }
+    }
+
+    #[test]
+    fn messages() {
+        for (signal, expected) in &[
+            ("03036732577212944063491565474664", "84462026"),
+            ("02935109699940807407585447034323", "78725270"),
+            ("03081770884921959731165446850517", "53553731"),
+        ] {
+            let message = message(&parse(signal).unwrap(), REPEAT, PHASES).unwrap();
+            assert_eq!(*expected, digits(&message));
+        }
+    }
+
+    #[test]
+    fn invalid_signals() {
+        assert!(matches!(parse("123x5"), Err(Error::NotADigit('x'))));
+        assert!(matches!(
+            message(&parse("123").unwrap(), REPEAT, PHASES),
+            Err(Error::SignalTooShort(3))
+        ));
+        assert!(matches!(
+            message(&parse("0000003").unwrap(), 2, PHASES),
+            Err(Error::OffsetInFirstHalf {
+                offset: 3,
+                length: 14
+            })
+        ));
+        assert!(matches!(
+            message(&parse("0000010").unwrap(), 2, PHASES),
+            Err(Error::OffsetPastTheEnd {
+                offset: 10,
+                length: 14
+            })
+        ));
+    }
+}
@@ -0,0 +1,34 @@
+use flawed_frequency_transmission::{digits, parse, Error, PHASES};
+#[cfg(not(feature = "real-signal"))]
+use flawed_frequency_transmission::{fft, MESSAGE_DIGITS};
+#[cfg(feature = "real-signal")]
+use flawed_frequency_transmission::{message, REPEAT};
+use std::io::BufRead;
+
+#[cfg(not(feature = "real-signal"))]
+fn run(signal: &[u8]) -> Result<(), Error> {
+    let output = fft(signal, PHASES);
+    let first = &output[..output.len().min(MESSAGE_DIGITS)];
+    println!("First digits of the output are {}", digits(first));
+    Ok(())
+}
+
+#[cfg(feature = "real-signal")]
+fn run(signal: &[u8]) -> Result<(), Error> {
+    let message = message(signal, REPEAT, PHASES)?;
+    println!("Message of the real signal is {}", digits(&message));
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "real-signal") { 2 } else { 1 }],
+    );
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    run(&parse(&line)?)?;
+    args.report_time();
+    Ok(())
+}
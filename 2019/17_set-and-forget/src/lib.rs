@@ -0,0 +1,318 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+};
+
+/// Functions the main routine may call.
+pub const FUNCTIONS: usize = 3;
+/// Characters of each routine, the commas included.
+pub const MAX_LENGTH: usize = 20;
+
+pub type Position = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    fn turn(self, turn: Turn) -> Self {
+        use Direction::*;
+        match (self, turn) {
+            (Up, Turn::Left) | (Down, Turn::Right) => Left,
+            (Up, Turn::Right) | (Down, Turn::Left) => Right,
+            (Left, Turn::Left) | (Right, Turn::Right) => Down,
+            (Left, Turn::Right) | (Right, Turn::Left) => Up,
+        }
+    }
+
+    fn step(self, (x, y): Position) -> Position {
+        match self {
+            Direction::Up => (x, y - 1),
+            Direction::Right => (x + 1, y),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x - 1, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+}
+
+/// A turn of the robot, then the number of steps forward, e.g. `R,8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub turn: Turn,
+    pub steps: usize,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let turn = match self.turn {
+            Turn::Left => 'L',
+            Turn::Right => 'R',
+        };
+        write!(f, "{},{}", turn, self.steps)
+    }
+}
+
+/// Items separated by commas, as a routine is given to the robot.
+pub fn comma_separated<T: Display>(items: &[T]) -> String {
+    let items: Vec<String> = items.iter().map(ToString::to_string).collect();
+    items.join(",")
+}
+
+#[derive(Debug)]
+pub struct Scaffold {
+    cells: HashSet<Position>,
+    /// Position and direction of the robot, unless it is tumbling through
+    /// space (`X`).
+    pub robot: Option<(Position, Direction)>,
+}
+
+impl From<&str> for Scaffold {
+    fn from(view: &str) -> Self {
+        let mut cells = HashSet::new();
+        let mut robot = None;
+        for (y, line) in (0..).zip(view.lines()) {
+            for (x, cell) in (0..).zip(line.chars()) {
+                let direction = match cell {
+                    '#' => None,
+                    '^' => Some(Direction::Up),
+                    '>' => Some(Direction::Right),
+                    'v' => Some(Direction::Down),
+                    '<' => Some(Direction::Left),
+                    _ => continue,
+                };
+                cells.insert((x, y));
+                robot = direction.map(|direction| ((x, y), direction)).or(robot);
+            }
+        }
+        Scaffold { cells, robot }
+    }
+}
+
+impl Scaffold {
+    /// Cells of the scaffold with scaffold on their 4 sides.
+    pub fn intersections(&self) -> Vec<Position> {
+        let directions = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+        let mut intersections: Vec<Position> = self
+            .cells
+            .iter()
+            .copied()
+            .filter(|&cell| {
+                directions
+                    .iter()
+                    .all(|direction| self.cells.contains(&direction.step(cell)))
+            })
+            .collect();
+        intersections.sort_unstable();
+        intersections
+    }
+
+    /// Sum of the alignment parameters of the intersections, each the
+    /// product of its coordinates.
+    pub fn alignment(&self) -> i64 {
+        self.intersections().iter().map(|(x, y)| x * y).sum()
+    }
+
+    /// Moves of the robot to the end of the scaffold, going straight through
+    /// the intersections, and turning only where the scaffold turns. The
+    /// robot is expected to turn first, as it starts at an end of the
+    /// scaffold, facing away from it.
+    pub fn path(&self) -> Option<Vec<Move>> {
+        let (mut position, mut direction) = self.robot?;
+        let mut moves = Vec::new();
+        loop {
+            let turn = [Turn::Left, Turn::Right]
+                .iter()
+                .copied()
+                .find(|&turn| self.cells.contains(&direction.turn(turn).step(position)));
+            let turn = match turn {
+                Some(turn) => turn,
+                None => return Some(moves),
+            };
+            direction = direction.turn(turn);
+            let mut steps = 0;
+            while self.cells.contains(&direction.step(position)) {
+                position = direction.step(position);
+                steps += 1;
+            }
+            moves.push(Move { turn, steps });
+        }
+    }
+}
+
+/// Main routine calling the functions by their index, `A` being the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Routine {
+    pub main: Vec<usize>,
+    pub functions: Vec<Vec<Move>>,
+}
+
+impl Routine {
+    /// Lines given to the robot: the main routine, then each function. A
+    /// function not needed is the same as the first one, never called, or
+    /// empty if there is no function at all.
+    pub fn lines(&self) -> Vec<String> {
+        let names: Vec<char> = self
+            .main
+            .iter()
+            .map(|&index| char::from(b'A' + index as u8))
+            .collect();
+        let mut lines = vec![comma_separated(&names)];
+        for index in 0..FUNCTIONS {
+            let function = self.functions.get(index).or_else(|| self.functions.first());
+            lines.push(function.map_or_else(String::new, |function| comma_separated(function)));
+        }
+        lines
+    }
+}
+
+/// Split `path` into a main routine calling at most [`FUNCTIONS`] functions,
+/// each routine written in at most [`MAX_LENGTH`] characters. An empty path
+/// can't be split, as the main routine calls at least one function.
+pub fn compress(path: &[Move]) -> Option<Routine> {
+    if path.is_empty() {
+        return None;
+    }
+    search(path, &mut Vec::new(), &mut Vec::new())
+}
+
+/// Rest of the search of [`compress`], the start of the path being already
+/// called by `main` from `functions`.
+fn search<'p>(
+    path: &'p [Move],
+    main: &mut Vec<usize>,
+    functions: &mut Vec<&'p [Move]>,
+) -> Option<Routine> {
+    if path.is_empty() {
+        return Some(Routine {
+            main: main.clone(),
+            functions: functions.iter().map(|function| function.to_vec()).collect(),
+        });
+    }
+    // Each call takes a letter and a comma
+    if 2 * (main.len() + 1) - 1 > MAX_LENGTH {
+        return None;
+    }
+    for index in 0..functions.len() {
+        if path.starts_with(functions[index]) {
+            main.push(index);
+            let routine = search(&path[functions[index].len()..], main, functions);
+            main.pop();
+            if routine.is_some() {
+                return routine;
+            }
+        }
+    }
+    if functions.len() < FUNCTIONS {
+        for length in (1..=path.len()).rev() {
+            if comma_separated(&path[..length]).len() > MAX_LENGTH {
+                continue;
+            }
+            main.push(functions.len());
+            functions.push(&path[..length]);
+            let routine = search(&path[length..], main, functions);
+            functions.pop();
+            main.pop();
+            if routine.is_some() {
+                return routine;
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERSECTIONS: &str = "\
+..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..";
+
+    const LOOPS: &str = "\
+#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......";
+
+    #[test]
+    fn alignment_parameters() {
+        let scaffold = Scaffold::from(INTERSECTIONS);
+        assert_eq!(
+            vec![(2, 2), (2, 4), (6, 4), (10, 4)],
+            scaffold.intersections()
+        );
+        assert_eq!(76, scaffold.alignment());
+    }
+
+    #[test]
+    fn path_through_intersections() {
+        let path = Scaffold::from(LOOPS).path().unwrap();
+        assert_eq!(
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2",
+            comma_separated(&path)
+        );
+        assert_eq!(None, Scaffold::from("..X##").path());
+    }
+
+    #[test]
+    fn compressed_path() {
+        let path = Scaffold::from(LOOPS).path().unwrap();
+        let routine = compress(&path).unwrap();
+        let called: Vec<Move> = routine
+            .main
+            .iter()
+            .flat_map(|&index| routine.functions[index].clone())
+            .collect();
+        assert_eq!(path, called);
+        let lines = routine.lines();
+        assert_eq!(1 + FUNCTIONS, lines.len());
+        assert!(lines.iter().all(|line| line.len() <= MAX_LENGTH));
+        // Too many different moves for 3 functions
+        let steps = |steps| Move {
+            turn: Turn::Left,
+            steps,
+        };
+        assert_eq!(None, compress(&(1..=40).map(steps).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn empty_path() {
+        assert_eq!(None, compress(&[]));
+        let routine = Routine {
+            main: Vec::new(),
+            functions: Vec::new(),
+        };
+        assert_eq!(vec![String::new(); 1 + FUNCTIONS], routine.lines());
+    }
+}
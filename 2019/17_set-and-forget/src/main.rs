@@ -0,0 +1,120 @@
+use intcode::{ascii, Program};
+#[cfg(feature = "wake-up")]
+use set_and_forget::compress;
+use set_and_forget::Scaffold;
+use std::{
+    io::{self, BufRead},
+    sync::mpsc,
+    thread,
+};
+
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("Failed to read the program")]
+    FailedInput(#[from] io::Error),
+    #[error("An error occured in program execution")]
+    FailedProgram(#[from] intcode::Error),
+    #[cfg(feature = "wake-up")]
+    #[error("The camera shows no robot on the scaffold")]
+    NoRobot,
+    #[cfg(feature = "wake-up")]
+    #[error("No main routine calling 3 functions follows the path of the robot")]
+    NoRoutine,
+    #[cfg(feature = "wake-up")]
+    #[error("The robot reported no dust, only:\n{0}")]
+    NoDust(String),
+}
+
+/// What the program printed once given the `lines` up front. An `awake`
+/// robot moves along the scaffold, instead of only showing it.
+fn run(opcodes: Vec<i64>, awake: bool, lines: &[String]) -> Result<ascii::Screen, Error> {
+    let inputs = lines.iter().map(|line| line.len() + 1).sum();
+    let (sender, receiver) = mpsc::sync_channel(inputs);
+    for line in lines {
+        ascii::send_line(&sender, line)?;
+    }
+    // No more inputs, a program asking for one fails instead of waiting
+    drop(sender);
+    let (outputs, received) = mpsc::sync_channel(0);
+    let mut program = Program::new(opcodes, receiver, outputs);
+    // An empty program is left empty, to fail as such
+    if awake && !program.memory().is_empty() {
+        program.poke(0, 2);
+    }
+    let handle = thread::spawn(move || program.run());
+    let screen = ascii::screen(received.iter());
+    handle
+        .join()
+        .expect("expect the program thread to not panic")?;
+    Ok(screen)
+}
+
+#[cfg(not(feature = "wake-up"))]
+fn answer(opcodes: Vec<i64>) -> Result<(), Error> {
+    let camera = run(opcodes, false, &[])?;
+    let scaffold = Scaffold::from(camera.text.as_str());
+    println!(
+        "Sum of the alignment parameters is {}",
+        scaffold.alignment()
+    );
+    Ok(())
+}
+
+#[cfg(feature = "wake-up")]
+fn answer(opcodes: Vec<i64>) -> Result<(), Error> {
+    let camera = run(opcodes.clone(), false, &[])?;
+    let scaffold = Scaffold::from(camera.text.as_str());
+    let path = scaffold.path().ok_or(Error::NoRobot)?;
+    let routine = compress(&path).ok_or(Error::NoRoutine)?;
+    let mut lines = routine.lines();
+    // No continuous video feed
+    lines.push(String::from("n"));
+    let screen = run(opcodes, true, &lines)?;
+    let dust = screen.value.ok_or(Error::NoDust(screen.text))?;
+    println!("The robot collected {} dust", dust);
+    Ok(())
+}
+
+fn main() -> Result<(), Error> {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "wake-up") { 2 } else { 1 }],
+    );
+    let mut reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))?;
+    let mut program_str = String::new();
+    reader.read_line(&mut program_str)?;
+    answer(intcode::parse(&program_str)?)?;
+    args.report_time();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn camera_view() {
+        // Output each character of the view, then halt
+        let view = "..#..\n..^..\n";
+        let mut opcodes: Vec<i64> = ascii::line(view.trim_end())
+            .flat_map(|output| vec![104, output])
+            .collect();
+        opcodes.push(99);
+        let screen = run(opcodes, false, &[]).unwrap();
+        assert_eq!(view, screen.text);
+        assert_eq!(None, screen.value);
+        let scaffold = Scaffold::from(screen.text.as_str());
+        assert_eq!(
+            Some(((2, 1), set_and_forget::Direction::Up)),
+            scaffold.robot
+        );
+    }
+
+    #[test]
+    fn empty_program() {
+        assert!(matches!(
+            run(Vec::new(), true, &[]),
+            Err(Error::FailedProgram(intcode::Error::ProgramEmpty))
+        ));
+    }
+}
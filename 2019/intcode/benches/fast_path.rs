@@ -0,0 +1,73 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use intcode::Program;
+use std::sync::mpsc;
+
+/// One run of the program of a day, once patched.
+struct Run {
+    day: &'static str,
+    program: &'static str,
+    patches: &'static [(usize, i64)],
+    inputs: &'static [i64],
+}
+
+/// The programs of the days without relative mode.
+const RUNS: [Run; 3] = [
+    Run {
+        day: "02",
+        program: include_str!("../../../inputs/2019/02.txt"),
+        patches: &[(1, 12), (2, 2)],
+        inputs: &[],
+    },
+    Run {
+        day: "05",
+        program: include_str!("../../../inputs/2019/05.txt"),
+        patches: &[],
+        inputs: &[5],
+    },
+    // The last amplifier of the first phase setting
+    Run {
+        day: "07",
+        program: include_str!("../../../inputs/2019/07.txt"),
+        patches: &[],
+        inputs: &[4, 0],
+    },
+];
+
+impl Run {
+    fn outputs(&self, opcodes: &[i64], fast_path: bool) -> Vec<i64> {
+        let (sender, receiver) = mpsc::sync_channel(self.inputs.len());
+        for &input in self.inputs {
+            sender.send(input).unwrap();
+        }
+        let (outputs, received) = mpsc::sync_channel(16);
+        let mut program = Program::new(opcodes.to_vec(), receiver, outputs);
+        if !fast_path {
+            program = program.without_fast_path();
+        }
+        for &(address, value) in self.patches {
+            program.poke(address, value);
+        }
+        program.run().unwrap();
+        received.try_iter().collect()
+    }
+}
+
+fn fast_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+    group.sample_size(10);
+    for run in &RUNS {
+        let opcodes = intcode::parse(run.program).unwrap();
+        group.bench_with_input(
+            BenchmarkId::new("general", run.day),
+            &opcodes,
+            |b, opcodes| b.iter(|| run.outputs(opcodes, false)),
+        );
+        group.bench_with_input(BenchmarkId::new("fast", run.day), &opcodes, |b, opcodes| {
+            b.iter(|| run.outputs(opcodes, true))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fast_path);
+criterion_main!(benches);
@@ -0,0 +1,8 @@
+#![no_main]
+use intcode::Instruction;
+use libfuzzer_sys::fuzz_target;
+use std::convert::TryFrom;
+
+fuzz_target!(|opcode: i64| {
+    let _ = Instruction::try_from(opcode);
+});
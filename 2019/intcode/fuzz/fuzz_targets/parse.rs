@@ -0,0 +1,6 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|program: &str| {
+    let _ = intcode::parse(program);
+});
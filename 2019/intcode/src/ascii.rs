@@ -0,0 +1,66 @@
+//! Programs talking in ASCII, as the days whose program prints a map or its
+//! prompts as text, and reads its commands as lines of text.
+
+use crate::Error;
+use std::{convert::TryFrom, iter, sync::mpsc::SyncSender};
+
+/// Inputs spelling `line`, followed by a newline.
+pub fn line(line: &str) -> impl Iterator<Item = i64> + '_ {
+    line.bytes().chain(iter::once(b'\n')).map(i64::from)
+}
+
+/// Send `line` to a program, followed by a newline.
+pub fn send_line(sender: &SyncSender<i64>, line: &str) -> Result<(), Error> {
+    for input in self::line(line) {
+        sender.send(input)?;
+    }
+    Ok(())
+}
+
+/// What an ASCII program printed: its text, and the last output out of the
+/// ASCII range, e.g. an answer too large to be a character.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Screen {
+    pub text: String,
+    pub value: Option<i64>,
+}
+
+/// Split the outputs of a program between its text and its last value out of
+/// the ASCII range.
+pub fn screen<I>(outputs: I) -> Screen
+where
+    I: IntoIterator<Item = i64>,
+{
+    let mut screen = Screen::default();
+    for output in outputs {
+        match u8::try_from(output) {
+            Ok(byte) if byte.is_ascii() => screen.text.push(char::from(byte)),
+            _ => screen.value = Some(output),
+        }
+    }
+    screen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn lines_of_inputs() {
+        assert_eq!(vec![65, 44, 66, 10], line("A,B").collect::<Vec<_>>());
+        assert_eq!(vec![10], line("").collect::<Vec<_>>());
+        let (sender, receiver) = mpsc::sync_channel(8);
+        send_line(&sender, "n").unwrap();
+        assert_eq!(vec![110, 10], receiver.try_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn text_and_value() {
+        let screen = screen(line("#.^").chain(vec![1_234_567, 10]));
+        assert_eq!("#.^\n\n", screen.text);
+        assert_eq!(Some(1_234_567), screen.value);
+        assert_eq!(None, super::screen(line("Main:")).value);
+        assert_eq!(Some(-1), super::screen(vec![-1]).value);
+    }
+}
@@ -0,0 +1,61 @@
+use common::cli::{value_parser, Arg, Args};
+use intcode::{self, Profile, Program};
+use std::io::BufRead;
+
+/// Print how many times each kind of instruction ran, then the kinds which
+/// never did.
+fn print_profile(profile: &Profile) {
+    let executed: Vec<String> = profile
+        .counts()
+        .filter(|(_, count)| *count > 0)
+        .map(|(name, count)| format!("{} {}", name, count))
+        .collect();
+    println!("Executed: {}", executed.join(", "));
+    let missed: Vec<&str> = profile.missed().collect();
+    if missed.is_empty() {
+        println!("Never executed: none");
+    } else {
+        println!("Never executed: {}", missed.join(", "));
+    }
+}
+
+fn main() -> Result<(), intcode::Error> {
+    let args = Args::parse_values(
+        "intcode-demo",
+        &[],
+        vec![
+            Arg::new("program")
+                .value_name("PROGRAM")
+                .required(true)
+                .help("Path of the program, `-` for the standard input"),
+            Arg::new("inputs")
+                .value_name("INPUT")
+                .num_args(0..)
+                .allow_negative_numbers(true)
+                .value_parser(value_parser!(i64))
+                .help("Inputs fed to the program, one after the other"),
+        ],
+    );
+    let program = args.value("program").expect("expect a required program");
+    let mut reader = common::input::open(program)?;
+    let mut program_str = String::new();
+    reader.read_line(&mut program_str)?;
+    let opcodes = intcode::parse(&program_str)?;
+    let inputs: Vec<i64> = args.get_all("inputs");
+    println!("Program of {} opcodes", opcodes.len());
+    println!("Inputs: {:?}", inputs);
+    let stats = intcode::stats::stats(&opcodes);
+    let (outputs, profile) =
+        intcode::run_with(opcodes, &inputs, Program::with_profile, |program| {
+            program.profile().cloned()
+        })?;
+    for output in outputs {
+        println!("Output: {}", output);
+    }
+    print!("{}", stats);
+    if let Some(profile) = profile {
+        print_profile(&profile);
+    }
+    args.report_time();
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use common::cli::{Arg, Args};
+use intcode::diff;
+use std::{io::BufRead, num::ParseIntError};
+
+fn parse_inputs(inputs: &str) -> Result<Vec<i64>, ParseIntError> {
+    inputs
+        .split(',')
+        .filter(|input| !input.is_empty())
+        .map(|input| input.trim().parse())
+        .collect()
+}
+
+/// A comma separated list of inputs, possibly empty.
+fn inputs(id: &'static str) -> Arg {
+    Arg::new(id)
+        .value_name("INPUTS")
+        .required(true)
+        .allow_negative_numbers(true)
+        .value_parser(parse_inputs)
+}
+
+fn main() -> Result<(), intcode::Error> {
+    let args = Args::parse_values(
+        "intcode-diff",
+        &[],
+        vec![
+            Arg::new("program")
+                .value_name("PROGRAM")
+                .required(true)
+                .help("Path of the program, `-` for the standard input"),
+            inputs("first").help("Comma separated inputs of the first run"),
+            inputs("second").help("Comma separated inputs of the second run"),
+        ],
+    );
+    let program = args.value("program").expect("expect a required program");
+    let mut reader = common::input::open(program)?;
+    let mut program_str = String::new();
+    reader.read_line(&mut program_str)?;
+    let opcodes = intcode::parse(&program_str)?;
+    let inputs = |id| args.get::<Vec<i64>>(id).expect("expect required inputs");
+    let first = diff::run(opcodes.clone(), &inputs("first"))?;
+    let second = diff::run(opcodes, &inputs("second"))?;
+    println!("Outputs: {:?} / {:?}", first.outputs, second.outputs);
+    match diff::first_divergence(&first, &second) {
+        Some(step) => println!("Memory diverges from instruction {}", step),
+        None => println!("Memory never diverges"),
+    }
+    for divergence in diff::diverging_cells(&first, &second) {
+        let step = divergence
+            .step
+            .map(|step| format!(" (from instruction {})", step))
+            .unwrap_or_default();
+        println!(
+            "Cell {}: {} / {}{}",
+            divergence.address, divergence.first, divergence.second, step
+        );
+    }
+    args.report_time();
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use common::cli::{Arg, Args};
+use std::io::BufRead;
+
+fn main() -> Result<(), intcode::Error> {
+    let args = Args::parse_values(
+        "intcode-stats",
+        &[],
+        vec![Arg::new("program")
+            .value_name("PROGRAM")
+            .required(true)
+            .help("Path of the program, `-` for the standard input")],
+    );
+    let program = args.value("program").expect("expect a required program");
+    let mut reader = common::input::open(program)?;
+    let mut program_str = String::new();
+    reader.read_line(&mut program_str)?;
+    let opcodes = intcode::parse(&program_str)?;
+    print!("{}", intcode::stats::stats(&opcodes));
+    args.report_time();
+    Ok(())
+}
@@ -0,0 +1,136 @@
+//! Compare the memory of a program run twice with different inputs, to find
+//! which cells depend on the inputs and from which instruction.
+
+use crate::{Error, MemoryWrite, Program};
+
+/// What a program left behind after running to completion.
+#[derive(Debug, Clone)]
+pub struct Run {
+    pub memory: Vec<i64>,
+    pub writes: Vec<MemoryWrite>,
+    pub outputs: Vec<i64>,
+}
+
+/// Run the program to completion, feeding it all the inputs, and record its
+/// writes.
+pub fn run(opcodes: Vec<i64>, inputs: &[i64]) -> Result<Run, Error> {
+    let (outputs, (memory, writes)) =
+        crate::run_with(opcodes, inputs, Program::with_write_log, |program| {
+            let memory = program.memory().to_vec();
+            let writes = program.writes().unwrap_or_default().to_vec();
+            (memory, writes)
+        })?;
+    Ok(Run {
+        memory,
+        writes,
+        outputs,
+    })
+}
+
+/// A cell of memory ending with different values in two runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub address: usize,
+    pub first: i64,
+    pub second: i64,
+    /// Instruction from which the writes to this cell differ, if any.
+    pub step: Option<usize>,
+}
+
+/// Step of the first write differing between two sequences of writes.
+fn first_difference<'w>(
+    mut first: impl Iterator<Item = &'w MemoryWrite>,
+    mut second: impl Iterator<Item = &'w MemoryWrite>,
+) -> Option<usize> {
+    loop {
+        match (first.next(), second.next()) {
+            (Some(write1), Some(write2)) if write1 == write2 => continue,
+            (Some(write1), Some(write2)) => return Some(write1.step.min(write2.step)),
+            (Some(write), None) | (None, Some(write)) => return Some(write.step),
+            (None, None) => return None,
+        }
+    }
+}
+
+/// Instruction from which the two runs stop writing the same values at the
+/// same addresses.
+pub fn first_divergence(first: &Run, second: &Run) -> Option<usize> {
+    first_difference(first.writes.iter(), second.writes.iter())
+}
+
+/// Cells ending with different values, a cell missing from one memory being 0.
+pub fn diverging_cells(first: &Run, second: &Run) -> Vec<Divergence> {
+    let len = first.memory.len().max(second.memory.len());
+    (0..len)
+        .filter_map(|address| {
+            let value1 = first.memory.get(address).copied().unwrap_or(0);
+            let value2 = second.memory.get(address).copied().unwrap_or(0);
+            if value1 == value2 {
+                return None;
+            }
+            let step = first_difference(
+                first.writes.iter().filter(|write| write.address == address),
+                second
+                    .writes
+                    .iter()
+                    .filter(|write| write.address == address),
+            );
+            Some(Divergence {
+                address,
+                first: value1,
+                second: value2,
+                step,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Is the input equal to 8, position mode
+    const EQUAL_TO_EIGHT: [i64; 11] = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+    #[test]
+    fn same_inputs() {
+        let first = run(EQUAL_TO_EIGHT.to_vec(), &[8]).unwrap();
+        let second = run(EQUAL_TO_EIGHT.to_vec(), &[8]).unwrap();
+        assert_eq!(first_divergence(&first, &second), None);
+        assert!(diverging_cells(&first, &second).is_empty());
+    }
+
+    #[test]
+    fn different_inputs() {
+        let first = run(EQUAL_TO_EIGHT.to_vec(), &[8]).unwrap();
+        let second = run(EQUAL_TO_EIGHT.to_vec(), &[7]).unwrap();
+        assert_eq!(first.outputs, vec![1]);
+        assert_eq!(second.outputs, vec![0]);
+        // The input is written by the first instruction
+        assert_eq!(first_divergence(&first, &second), Some(1));
+        assert_eq!(
+            diverging_cells(&first, &second),
+            vec![Divergence {
+                address: 9,
+                first: 1,
+                second: 0,
+                step: Some(1),
+            }]
+        );
+    }
+
+    #[test]
+    fn diverging_after_inputs() {
+        // Add the input to itself, then halt
+        let opcodes = vec![3, 7, 1, 7, 7, 8, 99, 0, 0];
+        let first = run(opcodes.clone(), &[2]).unwrap();
+        let second = run(opcodes, &[3]).unwrap();
+        let cells = diverging_cells(&first, &second);
+        assert_eq!(cells.len(), 2);
+        assert_eq!(
+            (cells[1].address, cells[1].first, cells[1].second),
+            (8, 4, 6)
+        );
+        assert_eq!(cells[1].step, Some(2));
+    }
+}
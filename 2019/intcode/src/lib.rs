@@ -1,11 +1,20 @@
+pub mod ascii;
+pub mod diff;
+pub mod protocol;
+pub mod stats;
+
 use std::{
     cmp,
-    convert::{From, TryFrom, TryInto},
+    collections::BTreeSet,
+    convert::{From, TryFrom},
     iter::FromIterator,
-    ops::{Deref, DerefMut},
-    sync::mpsc::{Receiver, SyncSender},
+    ops::{Deref, DerefMut, Range},
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
 };
-use thiserror;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -15,6 +24,24 @@ pub enum Error {
     InvalidIntOffset(#[from] std::num::TryFromIntError),
     #[error("Failed to convert to an Offset from a String")]
     InvalidStringOffset(#[from] std::num::ParseIntError),
+    #[error("Failed to convert an opcode of the program\n{underline}")]
+    InvalidOpcode {
+        underline: String,
+        #[source]
+        source: std::num::ParseIntError,
+    },
+    #[error("The instruction at {instruction_pointer} refers to the negative address {computed}")]
+    NegativeAddress {
+        instruction_pointer: usize,
+        computed: i64,
+    },
+    #[error("The instruction at {instruction_pointer} moves the relative base to {computed}")]
+    NegativeBase {
+        instruction_pointer: usize,
+        computed: i64,
+    },
+    #[error("The instruction at {instruction_pointer} offsets an address or the relative base past the largest integer")]
+    OffsetOverflow { instruction_pointer: usize },
     #[error("Failed to convert '{0}' to a Mode")]
     InvalidMode(String),
     #[error("Failed to convert '{0}' to an Instruction")]
@@ -25,6 +52,12 @@ pub enum Error {
     MissingInput(#[from] std::sync::mpsc::RecvError),
     #[error("Failed to send the output")]
     MissingOutput(#[from] std::sync::mpsc::SendError<i64>),
+    #[error("The program stopped after {received} of the {expected} outputs of a group")]
+    IncompleteOutput { received: usize, expected: usize },
+    #[error("An output was expected but the program stopped before sending one")]
+    OutputsExhausted,
+    #[error("The shared memory is unavailable because another program panicked")]
+    PoisonedSharedMemory,
 }
 
 #[derive(Debug)]
@@ -54,8 +87,9 @@ impl DerefMut for Offset {
     }
 }
 
+/// Instruction decoded from an opcode, with the modes of its parameters.
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     Add(Mode, Mode, Mode),
     Multiply(Mode, Mode, Mode),
     Input(Mode),
@@ -127,8 +161,55 @@ impl TryFrom<i64> for Instruction {
     }
 }
 
+/// Names of the kinds of instructions, in the order of their opcodes.
+pub const INSTRUCTIONS: [&str; 10] = [
+    "add",
+    "multiply",
+    "input",
+    "output",
+    "jump-if-true",
+    "jump-if-false",
+    "less-than",
+    "equals",
+    "adjust-base",
+    "halt",
+];
+
+impl Instruction {
+    /// Index of the kind of the instruction in [`INSTRUCTIONS`].
+    pub fn kind(&self) -> usize {
+        use self::Instruction::*;
+        match self {
+            Add(..) => 0,
+            Multiply(..) => 1,
+            Input(..) => 2,
+            Output(..) => 3,
+            JumpIf(..) => 4,
+            JumpIfNot(..) => 5,
+            LessThan(..) => 6,
+            Equals(..) => 7,
+            ModifyBase(..) => 8,
+            Halt => 9,
+        }
+    }
+
+    /// Modes of the parameters following the opcode, in order.
+    pub fn modes(&self) -> Vec<&Mode> {
+        use self::Instruction::*;
+        match self {
+            Add(mode1, mode2, mode3)
+            | Multiply(mode1, mode2, mode3)
+            | LessThan(mode1, mode2, mode3)
+            | Equals(mode1, mode2, mode3) => vec![mode1, mode2, mode3],
+            JumpIf(mode1, mode2) | JumpIfNot(mode1, mode2) => vec![mode1, mode2],
+            Input(mode) | Output(mode) | ModifyBase(mode) => vec![mode],
+            Halt => Vec::new(),
+        }
+    }
+}
+
 #[derive(Debug)]
-enum Mode {
+pub enum Mode {
     Immediate,
     Position,
     Relative,
@@ -145,28 +226,241 @@ impl TryFrom<&str> for Mode {
     }
 }
 
+/// A region of memory which can be mapped into several programs.
+///
+/// Each program works on its own copy of the region. Writes are published to
+/// the other programs only at synchronization points, which are the input and
+/// output instructions: the local writes are published before the instruction
+/// and the region is reloaded after it. A value sent through a channel is
+/// therefore always preceded by the writes its sender did before sending it.
+#[derive(Debug, Clone)]
+pub struct SharedMemory {
+    range: Range<usize>,
+    cells: Arc<Mutex<Vec<i64>>>,
+}
+
+impl SharedMemory {
+    pub fn new(range: Range<usize>) -> Self {
+        let cells = vec![0; range.len()];
+        SharedMemory {
+            range,
+            cells: Arc::new(Mutex::new(cells)),
+        }
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Copy of the region as last published by the programs.
+    pub fn snapshot(&self) -> Result<Vec<i64>, Error> {
+        self.cells
+            .lock()
+            .map(|cells| cells.clone())
+            .map_err(|_| Error::PoisonedSharedMemory)
+    }
+}
+
+/// Opcodes of the text of a program, separated by commas.
+pub fn parse(program: &str) -> Result<Vec<i64>, Error> {
+    let line = program.trim();
+    common::token::split(line, ',')
+        .map(|token| {
+            token.text.parse().map_err(|source| Error::InvalidOpcode {
+                underline: token.underline(line),
+                source,
+            })
+        })
+        .collect()
+}
+
+/// A value written in the memory of a program by its `step`-th executed
+/// instruction, the first one being 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub step: usize,
+    pub address: usize,
+    pub value: i64,
+}
+
+/// Number of executed instructions of each kind, see [`Program::with_profile`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    counts: [usize; INSTRUCTIONS.len()],
+}
+
+impl Profile {
+    /// Number of executed instructions of the kind `name`, one of
+    /// [`INSTRUCTIONS`].
+    pub fn count(&self, name: &str) -> Option<usize> {
+        INSTRUCTIONS
+            .iter()
+            .position(|instruction| *instruction == name)
+            .map(|kind| self.counts[kind])
+    }
+
+    /// Every kind of instruction with its number of executions, in the order
+    /// of their opcodes, the ones never executed included.
+    pub fn counts(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        INSTRUCTIONS
+            .iter()
+            .copied()
+            .zip(self.counts.iter().copied())
+    }
+
+    /// Kinds of instructions never executed.
+    pub fn missed(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.counts()
+            .filter(|(_, count)| *count == 0)
+            .map(|(name, _)| name)
+    }
+}
+
+/// Whether the program, decoded as [`stats::stats`] does, never adjusts its
+/// relative base nor has a parameter in relative mode.
+fn without_relative(opcodes: &[i64]) -> bool {
+    let stats = stats::stats(opcodes);
+    stats.relative == 0 && stats.used().all(|(name, _)| name != "adjust-base")
+}
+
 pub struct Program {
     opcodes: Vec<i64>,
     inputs: Receiver<i64>,
     outputs: SyncSender<i64>,
     base: usize,
+    instruction_pointer: usize,
+    shared_memory: Option<SharedMemory>,
+    dirty: BTreeSet<usize>,
+    step: usize,
+    writes: Option<Vec<MemoryWrite>>,
+    profile: Option<Profile>,
+    /// Whether to run the instructions with [`Program::run_without_base`]
+    /// until one needs the general loop.
+    fast_path: bool,
 }
 
 impl Program {
     pub fn new(opcodes: Vec<i64>, inputs: Receiver<i64>, outputs: SyncSender<i64>) -> Self {
         Program {
-            opcodes,
             inputs,
             outputs,
             base: 0,
+            instruction_pointer: 0,
+            shared_memory: None,
+            dirty: BTreeSet::new(),
+            step: 0,
+            writes: None,
+            profile: None,
+            fast_path: without_relative(&opcodes),
+            opcodes,
         }
     }
 
+    /// Run every instruction with the general loop, even for a program
+    /// without relative mode, e.g. to compare both loops.
+    pub fn without_fast_path(mut self) -> Self {
+        self.fast_path = false;
+        self
+    }
+
+    /// Record every write in the memory of the program, see [`Program::writes`].
+    pub fn with_write_log(mut self) -> Self {
+        self.writes = Some(Vec::new());
+        self
+    }
+
+    /// Writes done so far, in order, if the program records them.
+    pub fn writes(&self) -> Option<&[MemoryWrite]> {
+        self.writes.as_deref()
+    }
+
+    /// Count the executed instructions of each kind, see [`Program::profile`].
+    pub fn with_profile(mut self) -> Self {
+        self.profile = Some(Profile::default());
+        self
+    }
+
+    /// Instructions executed so far, if the program counts them.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Memory of the program, which may have grown beyond the initial opcodes.
+    pub fn memory(&self) -> &[i64] {
+        &self.opcodes
+    }
+
+    /// Value at `address` in the memory of the program, `None` past its end.
+    pub fn peek(&self, address: usize) -> Option<i64> {
+        self.opcodes.get(address).copied()
+    }
+
+    /// Write `value` at `address`, e.g. to patch a program before running it,
+    /// the memory growing if needed. It is not one of the writes of the
+    /// program, so it is not recorded with [`Program::with_write_log`].
+    pub fn poke(&mut self, address: usize, value: i64) {
+        if address >= self.opcodes.len() {
+            self.opcodes.resize(address + 1, 0);
+        }
+        self.opcodes[address] = value;
+    }
+
+    /// Map a shared region into the memory of the program. The content of the
+    /// region replaces whatever the program had at these addresses.
+    pub fn with_shared_memory(mut self, shared_memory: SharedMemory) -> Self {
+        if self.opcodes.len() < shared_memory.range.end {
+            self.opcodes.resize(shared_memory.range.end, 0);
+        }
+        self.shared_memory = Some(shared_memory);
+        self
+    }
+
+    fn write(&mut self, offset: Offset, value: i64) {
+        if let Some(shared_memory) = &self.shared_memory {
+            if shared_memory.range.contains(&offset.0) {
+                self.dirty.insert(offset.0);
+            }
+        }
+        if let Some(writes) = &mut self.writes {
+            writes.push(MemoryWrite {
+                step: self.step,
+                address: offset.0,
+                value,
+            });
+        }
+        self.opcodes[offset.0] = value;
+    }
+
+    fn publish_shared_memory(&mut self) -> Result<(), Error> {
+        if let Some(shared_memory) = &self.shared_memory {
+            let mut cells = shared_memory
+                .cells
+                .lock()
+                .map_err(|_| Error::PoisonedSharedMemory)?;
+            for address in &self.dirty {
+                cells[address - shared_memory.range.start] = self.opcodes[*address];
+            }
+            self.dirty.clear();
+        }
+        Ok(())
+    }
+
+    fn reload_shared_memory(&mut self) -> Result<(), Error> {
+        if let Some(shared_memory) = &self.shared_memory {
+            let cells = shared_memory
+                .cells
+                .lock()
+                .map_err(|_| Error::PoisonedSharedMemory)?;
+            self.opcodes[shared_memory.range.clone()].copy_from_slice(&cells);
+        }
+        Ok(())
+    }
+
     fn offset_from_mode(&mut self, index: usize, mode: Mode) -> Result<Offset, Error> {
         let offset = match mode {
-            Mode::Position => Offset::try_from(self.opcodes[index]),
+            Mode::Position => self.address(self.opcodes[index]),
             Mode::Immediate => Ok(Offset::from(index)),
-            Mode::Relative => Offset::try_from(self.opcodes[index] + self.base as i64),
+            Mode::Relative => self.address(self.offset_by_base(self.opcodes[index])?),
         };
         if let Ok(offset) = &offset {
             if offset.0 >= self.opcodes.len() {
@@ -178,15 +472,52 @@ impl Program {
         offset
     }
 
+    /// Address `computed` by the current instruction, which is an error if
+    /// negative rather than a memory to grow.
+    /// `value` offset by the relative base.
+    fn offset_by_base(&self, value: i64) -> Result<i64, Error> {
+        value
+            .checked_add(self.base as i64)
+            .ok_or(Error::OffsetOverflow {
+                instruction_pointer: self.instruction_pointer,
+            })
+    }
+
+    fn address(&self, computed: i64) -> Result<Offset, Error> {
+        if computed < 0 {
+            return Err(Error::NegativeAddress {
+                instruction_pointer: self.instruction_pointer,
+                computed,
+            });
+        }
+        Offset::try_from(computed)
+    }
+
     pub fn run(&mut self) -> Result<(), Error> {
         if self.opcodes.is_empty() {
             return Err(Error::ProgramEmpty);
         }
+        self.reload_shared_memory()?;
         let mut index = 0;
+        if self.fast_path
+            && self.writes.is_none()
+            && self.profile.is_none()
+            && self.shared_memory.is_none()
+        {
+            index = match self.run_without_base(index)? {
+                Some(index) => index,
+                None => return Ok(()),
+            };
+        }
         while index < self.opcodes.len() {
             use self::Instruction::*;
             let instruction = Instruction::try_from(self.opcodes[index])?;
+            self.instruction_pointer = index;
             index += 1;
+            self.step += 1;
+            if let Some(profile) = &mut self.profile {
+                profile.counts[instruction.kind()] += 1;
+            }
             match instruction {
                 Add(op1_mode, op2_mode, result_mode) => {
                     let op1_offset = self.offset_from_mode(index, op1_mode)?;
@@ -195,8 +526,8 @@ impl Program {
                     index += 1;
                     let result_offset = self.offset_from_mode(index, result_mode)?;
                     index += 1;
-                    self.opcodes[result_offset.0] =
-                        self.opcodes[op1_offset.0] + self.opcodes[op2_offset.0];
+                    let result = self.opcodes[op1_offset.0] + self.opcodes[op2_offset.0];
+                    self.write(result_offset, result);
                 }
                 Multiply(op1_mode, op2_mode, result_mode) => {
                     let op1_offset = self.offset_from_mode(index, op1_mode)?;
@@ -205,26 +536,30 @@ impl Program {
                     index += 1;
                     let result_offset = self.offset_from_mode(index, result_mode)?;
                     index += 1;
-                    self.opcodes[result_offset.0] =
-                        self.opcodes[op1_offset.0] * self.opcodes[op2_offset.0];
+                    let result = self.opcodes[op1_offset.0] * self.opcodes[op2_offset.0];
+                    self.write(result_offset, result);
                 }
                 Input(input_mode) => {
                     let input_offset = self.offset_from_mode(index, input_mode)?;
                     index += 1;
+                    self.publish_shared_memory()?;
                     let input = self.inputs.recv()?;
-                    self.opcodes[input_offset.0] = input;
+                    self.reload_shared_memory()?;
+                    self.write(input_offset, input);
                 }
                 Output(output_mode) => {
                     let output_offset = self.offset_from_mode(index, output_mode)?;
                     index += 1;
+                    self.publish_shared_memory()?;
                     self.outputs.send(self.opcodes[output_offset.0])?;
+                    self.reload_shared_memory()?;
                 }
                 JumpIf(condition_mode, pointer_mode) => {
                     let condition_offset = self.offset_from_mode(index, condition_mode)?;
                     index += 1;
                     if self.opcodes[condition_offset.0] != 0 {
                         let pointer_offset = self.offset_from_mode(index, pointer_mode)?;
-                        index = self.opcodes[pointer_offset.0].try_into()?;
+                        index = self.address(self.opcodes[pointer_offset.0])?.0;
                     } else {
                         index += 1;
                     }
@@ -234,7 +569,7 @@ impl Program {
                     index += 1;
                     if self.opcodes[condition_offset.0] == 0 {
                         let pointer_offset = self.offset_from_mode(index, pointer_mode)?;
-                        index = self.opcodes[pointer_offset.0].try_into()?;
+                        index = self.address(self.opcodes[pointer_offset.0])?.0;
                     } else {
                         index += 1;
                     }
@@ -246,12 +581,12 @@ impl Program {
                     index += 1;
                     let result_offset = self.offset_from_mode(index, result_mode)?;
                     index += 1;
-                    self.opcodes[result_offset.0] =
-                        if self.opcodes[op1_offset.0] < self.opcodes[op2_offset.0] {
-                            1
-                        } else {
-                            0
-                        };
+                    let result = if self.opcodes[op1_offset.0] < self.opcodes[op2_offset.0] {
+                        1
+                    } else {
+                        0
+                    };
+                    self.write(result_offset, result);
                 }
                 Equals(op1_mode, op2_mode, result_mode) => {
                     let op1_offset = self.offset_from_mode(index, op1_mode)?;
@@ -260,18 +595,21 @@ impl Program {
                     index += 1;
                     let result_offset = self.offset_from_mode(index, result_mode)?;
                     index += 1;
-                    self.opcodes[result_offset.0] =
-                        if self.opcodes[op1_offset.0] == self.opcodes[op2_offset.0] {
-                            1
-                        } else {
-                            0
-                        };
+                    let result = if self.opcodes[op1_offset.0] == self.opcodes[op2_offset.0] {
+                        1
+                    } else {
+                        0
+                    };
+                    self.write(result_offset, result);
                 }
                 ModifyBase(base_mode) => {
                     let base_offset = self.offset_from_mode(index, base_mode)?;
                     index += 1;
-                    let new_base = self.opcodes[base_offset.0] + self.base as i64;
-                    self.base = new_base.try_into()?;
+                    let new_base = self.offset_by_base(self.opcodes[base_offset.0])?;
+                    self.base = usize::try_from(new_base).map_err(|_| Error::NegativeBase {
+                        instruction_pointer: self.instruction_pointer,
+                        computed: new_base,
+                    })?;
                 }
                 Halt => {
                     break;
@@ -280,4 +618,423 @@ impl Program {
         }
         Ok(())
     }
+
+    /// Address of the `parameter`-th parameter, from 1, of the instruction at
+    /// `index`, if it is in position or immediate mode and within the memory.
+    fn address_without_base(&self, index: usize, parameter: usize) -> Option<usize> {
+        let address = index + parameter;
+        let address = match self.opcodes[index] / 10i64.pow(parameter as u32 + 1) % 10 {
+            0 => usize::try_from(*self.opcodes.get(address)?).ok()?,
+            1 => address,
+            _ => return None,
+        };
+        Some(address).filter(|&address| address < self.opcodes.len())
+    }
+
+    /// Run the instructions from `index`, decoding each opcode with integer
+    /// arithmetic, without any relative base nor memory to grow. Give the
+    /// address of the first instruction which needs the general loop, not
+    /// run yet, or `None` once the program is over.
+    fn run_without_base(&mut self, mut index: usize) -> Result<Option<usize>, Error> {
+        while let Some(&opcode) = self.opcodes.get(index) {
+            let kind = opcode % 100;
+            let parameters = match kind {
+                1 | 2 | 7 | 8 => 3,
+                3 | 4 => 1,
+                5 | 6 => 2,
+                99 => 0,
+                // Adjusting the base, or an opcode the general loop refuses,
+                // negative ones included
+                _ => return Ok(Some(index)),
+            };
+            let mut addresses = [0; 3];
+            for parameter in 1..=parameters {
+                match self.address_without_base(index, parameter) {
+                    Some(address) => addresses[parameter - 1] = address,
+                    None => return Ok(Some(index)),
+                }
+            }
+            let [first, second, third] = addresses;
+            let jump = match kind {
+                5 => self.opcodes[first] != 0,
+                6 => self.opcodes[first] == 0,
+                _ => false,
+            };
+            let next = if jump {
+                match usize::try_from(self.opcodes[second]) {
+                    Ok(target) => target,
+                    // The general loop stops with the negative address
+                    Err(_) => return Ok(Some(index)),
+                }
+            } else {
+                index + 1 + parameters
+            };
+            self.instruction_pointer = index;
+            self.step += 1;
+            match kind {
+                1 => self.opcodes[third] = self.opcodes[first] + self.opcodes[second],
+                2 => self.opcodes[third] = self.opcodes[first] * self.opcodes[second],
+                3 => self.opcodes[first] = self.inputs.recv()?,
+                4 => self.outputs.send(self.opcodes[first])?,
+                7 => self.opcodes[third] = (self.opcodes[first] < self.opcodes[second]) as i64,
+                8 => self.opcodes[third] = (self.opcodes[first] == self.opcodes[second]) as i64,
+                99 => return Ok(None),
+                _ => {}
+            }
+            index = next;
+        }
+        Ok(None)
+    }
+}
+
+/// Run the program to completion, feeding it all the `inputs`, and collect
+/// everything it outputs, along with what `finish` reads from the program once
+/// over. `setup` prepares the program before it runs, e.g. with
+/// [`Program::with_profile`].
+pub fn run_with<S, F, T>(
+    opcodes: Vec<i64>,
+    inputs: &[i64],
+    setup: S,
+    finish: F,
+) -> Result<(Vec<i64>, T), Error>
+where
+    S: FnOnce(Program) -> Program,
+    F: FnOnce(&Program) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender_to_thread, receiver_from_host) = mpsc::channel();
+    let (sender_to_host, receiver_from_thread) = mpsc::sync_channel(0);
+    for input in inputs {
+        sender_to_thread
+            .send(*input)
+            .expect("expect the program to be waiting for inputs");
+    }
+    // No more inputs, a program asking for one fails instead of waiting
+    drop(sender_to_thread);
+    let mut program = setup(Program::new(opcodes, receiver_from_host, sender_to_host));
+    // The program, and its sender of outputs, is dropped at the end of the
+    // thread so that the outputs below end
+    let handle = thread::spawn(move || program.run().map(|_| finish(&program)));
+    let outputs = receiver_from_thread.iter().collect();
+    let finished = handle
+        .join()
+        .expect("expect the program thread to not panic")?;
+    Ok((outputs, finished))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::mpsc, thread};
+
+    fn run_with_shared_memory(
+        opcodes: Vec<i64>,
+        shared_memory: &SharedMemory,
+        inputs: Vec<i64>,
+    ) -> Result<Vec<i64>, Error> {
+        let (sender, receiver) = mpsc::sync_channel(inputs.len());
+        for input in inputs {
+            sender.send(input)?;
+        }
+        let (output_sender, output_receiver) = mpsc::sync_channel(16);
+        Program::new(opcodes, receiver, output_sender)
+            .with_shared_memory(shared_memory.clone())
+            .run()?;
+        Ok(output_receiver.try_iter().collect())
+    }
+
+    #[test]
+    fn parse_program() {
+        assert_eq!(parse("1,0, 0,99\n").unwrap(), vec![1, 0, 0, 99]);
+    }
+
+    #[test]
+    fn parse_invalid_opcode() {
+        let error = parse("1,0,x,99").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to convert an opcode of the program\n1,0,x,99\n    ^"
+        );
+    }
+
+    #[test]
+    fn shared_write_is_visible_after_synchronization() -> Result<(), Error> {
+        let shared_memory = SharedMemory::new(100..102);
+        // [100] = 40 + 2, then output [100]
+        let outputs =
+            run_with_shared_memory(vec![1101, 40, 2, 100, 4, 100, 99], &shared_memory, vec![])?;
+        assert_eq!(vec![42], outputs);
+        assert_eq!(vec![42, 0], shared_memory.snapshot()?);
+        // read an input into [101], then output [100]
+        let outputs = run_with_shared_memory(vec![3, 101, 4, 100, 99], &shared_memory, vec![7])?;
+        assert_eq!(vec![42], outputs);
+        assert_eq!(vec![42, 7], shared_memory.snapshot()?);
+        Ok(())
+    }
+
+    #[test]
+    fn shared_write_stays_local_without_synchronization() -> Result<(), Error> {
+        let shared_memory = SharedMemory::new(100..101);
+        // [100] = 40 + 2, then halt without any input or output
+        let outputs = run_with_shared_memory(vec![1101, 40, 2, 100, 99], &shared_memory, vec![])?;
+        assert!(outputs.is_empty());
+        assert_eq!(vec![0], shared_memory.snapshot()?);
+        Ok(())
+    }
+
+    #[test]
+    fn private_write_is_not_shared() -> Result<(), Error> {
+        let shared_memory = SharedMemory::new(100..101);
+        // [7] = 40 + 2, then output [7]
+        let outputs =
+            run_with_shared_memory(vec![1101, 40, 2, 7, 4, 7, 99, 0], &shared_memory, vec![])?;
+        assert_eq!(vec![42], outputs);
+        assert_eq!(vec![0], shared_memory.snapshot()?);
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_programs_communicate_through_shared_memory() -> Result<(), Error> {
+        let shared_memory = SharedMemory::new(100..101);
+        let (writer_sender, writer_receiver) = mpsc::sync_channel(0);
+        let (reader_sender, reader_receiver) = mpsc::sync_channel(0);
+        let (host_sender, host_receiver) = mpsc::sync_channel(0);
+        // [100] = 40 + 2, then signal the reader
+        let mut writer = Program::new(
+            vec![1101, 40, 2, 100, 104, 1, 99],
+            writer_receiver,
+            reader_sender,
+        )
+        .with_shared_memory(shared_memory.clone());
+        // wait for the writer, then output [100]
+        let mut reader = Program::new(vec![3, 50, 4, 100, 99], reader_receiver, host_sender)
+            .with_shared_memory(shared_memory.clone());
+        let writer = thread::spawn(move || writer.run());
+        let reader = thread::spawn(move || reader.run());
+        assert_eq!(42, host_receiver.recv()?);
+        writer.join().unwrap()?;
+        reader.join().unwrap()?;
+        drop(writer_sender);
+        Ok(())
+    }
+
+    #[test]
+    fn run_with_inputs() {
+        let run = |opcodes, inputs: &[i64]| run_with(opcodes, inputs, |program| program, |_| ());
+        assert_eq!(vec![42], run(vec![3, 0, 4, 0, 99], &[42]).unwrap().0);
+        // Compare the input to 8
+        let opcodes = vec![
+            3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0,
+            0, 1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4,
+            20, 1105, 1, 46, 98, 99,
+        ];
+        assert_eq!(vec![999], run(opcodes.clone(), &[7]).unwrap().0);
+        assert_eq!(vec![1000], run(opcodes.clone(), &[8]).unwrap().0);
+        assert_eq!(vec![1001], run(opcodes, &[9]).unwrap().0);
+        // A missing input fails instead of waiting
+        assert!(run(vec![3, 0, 3, 0, 99], &[1]).is_err());
+        let quine = vec![
+            109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+        ];
+        assert_eq!(quine, run(quine.clone(), &[]).unwrap().0);
+    }
+
+    #[test]
+    fn run_with_profile() {
+        let (outputs, profile) = run_with(
+            vec![3, 0, 4, 0, 99],
+            &[42],
+            Program::with_profile,
+            |program| program.profile().cloned(),
+        )
+        .unwrap();
+        assert_eq!(vec![42], outputs);
+        let profile = profile.unwrap();
+        assert_eq!(Some(1), profile.count("input"));
+        assert_eq!(Some(1), profile.count("output"));
+    }
+
+    #[test]
+    fn profile() -> Result<(), Error> {
+        let (_, receiver) = mpsc::sync_channel(0);
+        let (sender, outputs) = mpsc::sync_channel(10);
+        // Count down from 3, outputting each value
+        let mut program = Program::new(
+            vec![4, 11, 1001, 11, -1, 11, 1005, 11, 0, 99, 0, 3],
+            receiver,
+            sender,
+        )
+        .with_profile();
+        program.run()?;
+        assert_eq!(vec![3, 2, 1], outputs.try_iter().collect::<Vec<_>>());
+        let profile = program.profile().unwrap();
+        assert_eq!(Some(3), profile.count("output"));
+        assert_eq!(Some(3), profile.count("add"));
+        assert_eq!(Some(3), profile.count("jump-if-true"));
+        assert_eq!(Some(1), profile.count("halt"));
+        assert_eq!(None, profile.count("divide"));
+        assert_eq!(
+            vec![
+                "multiply",
+                "input",
+                "jump-if-false",
+                "less-than",
+                "equals",
+                "adjust-base"
+            ],
+            profile.missed().collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn peek_and_poke() -> Result<(), Error> {
+        let (_, receiver) = mpsc::sync_channel(0);
+        let (sender, _outputs) = mpsc::sync_channel(0);
+        let mut program = Program::new(vec![1, 0, 0, 0, 99], receiver, sender).with_write_log();
+        assert_eq!(Some(1), program.peek(0));
+        assert_eq!(None, program.peek(5));
+        // Add [4] to itself into [0]
+        program.poke(1, 4);
+        program.poke(2, 4);
+        program.poke(7, 12);
+        assert_eq!(&[1, 4, 4, 0, 99, 0, 0, 12], program.memory());
+        program.run()?;
+        assert_eq!(Some(198), program.peek(0));
+        assert_eq!(1, program.writes().unwrap().len());
+        Ok(())
+    }
+
+    fn run(opcodes: Vec<i64>) -> Result<(), Error> {
+        let (_, receiver) = mpsc::sync_channel(0);
+        let (sender, _outputs) = mpsc::sync_channel(10);
+        Program::new(opcodes, receiver, sender).run()
+    }
+
+    #[test]
+    fn negative_addresses() {
+        let negative = |opcodes| match run(opcodes) {
+            Err(Error::NegativeAddress {
+                instruction_pointer,
+                computed,
+            }) => Some((instruction_pointer, computed)),
+            _ => None,
+        };
+        // [0] = [-1] + [0]
+        assert_eq!(Some((0, -1)), negative(vec![1, -1, 0, 0, 99]));
+        // base = 5, then output [base - 10]
+        assert_eq!(Some((2, -5)), negative(vec![109, 5, 204, -10, 99]));
+        // output [0], then write [0] + [0] in [-3]
+        assert_eq!(Some((2, -3)), negative(vec![4, 0, 1, 0, 0, -3, 99]));
+        // jump to -7
+        assert_eq!(Some((0, -7)), negative(vec![1105, 1, -7, 99]));
+        let error = run(vec![4, 0, 1, 0, 0, -3, 99]).unwrap_err();
+        assert_eq!(
+            "The instruction at 2 refers to the negative address -3",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn negative_base() {
+        let negative = |opcodes| match run(opcodes) {
+            Err(Error::NegativeBase {
+                instruction_pointer,
+                computed,
+            }) => Some((instruction_pointer, computed)),
+            _ => None,
+        };
+        // base = -5
+        assert_eq!(Some((0, -5)), negative(vec![109, -5, 99]));
+        // base = 3, then base = 3 + [0]
+        assert_eq!(
+            Some((2, -106)),
+            negative(vec![109, 3, 9, 7, 99, 0, 0, -109])
+        );
+        let error = run(vec![109, -5, 99]).unwrap_err();
+        assert_eq!(
+            "The instruction at 0 moves the relative base to -5",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn offset_overflow() {
+        let overflow = |opcodes| match run(opcodes) {
+            Err(Error::OffsetOverflow {
+                instruction_pointer,
+            }) => Some(instruction_pointer),
+            _ => None,
+        };
+        // base = i64::MAX, then base = i64::MAX + 1
+        assert_eq!(
+            Some(2),
+            overflow(vec![109, 9223372036854775807, 109, 1, 99])
+        );
+        // base = 1, then output [i64::MAX + 1]
+        assert_eq!(
+            Some(2),
+            overflow(vec![109, 1, 204, 9223372036854775807, 99])
+        );
+    }
+
+    /// Memory and outputs of `opcodes` given `inputs`, run with or without
+    /// the fast path.
+    fn run_both(opcodes: &[i64], inputs: &[i64]) -> [(Vec<i64>, Vec<i64>); 2] {
+        let run = |fast_path: bool| {
+            let (sender, receiver) = mpsc::sync_channel(inputs.len());
+            for &input in inputs {
+                sender.send(input).unwrap();
+            }
+            let (outputs, received) = mpsc::sync_channel(16);
+            let mut program = Program::new(opcodes.to_vec(), receiver, outputs);
+            if !fast_path {
+                program = program.without_fast_path();
+            }
+            program.run().unwrap();
+            (program.memory().to_vec(), received.try_iter().collect())
+        };
+        [run(true), run(false)]
+    }
+
+    #[test]
+    fn fast_path_same_as_general_loop() {
+        assert!(without_relative(&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]));
+        assert!(!without_relative(&[109, 1, 99]));
+        assert!(!without_relative(&[204, 0, 99]));
+        // Output whether the input is 8, with jumps in position mode
+        let equal = [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+        for &input in &[0, 8] {
+            let [fast, general] = run_both(&equal, &[input]);
+            assert_eq!(general, fast);
+        }
+        // [4] = 209, turning the next instruction into `base += [1]`, then
+        // output [base - 199]
+        let modified = [1101, 200, 9, 4, 1, 1, 204, -199, 99];
+        assert!(without_relative(&modified));
+        let [fast, general] = run_both(&modified, &[]);
+        assert_eq!(vec![200], fast.1);
+        assert_eq!(general, fast);
+        // Write past the end of the program, which grows its memory
+        let [fast, general] = run_both(&[1101, 1, 1, 10, 4, 10, 99], &[]);
+        assert_eq!((vec![2], 11), (fast.1.clone(), fast.0.len()));
+        assert_eq!(general, fast);
+    }
+
+    mod parse_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn parse_round_trip((opcodes, text) in common::strategy::integers(1..50)) {
+                prop_assert_eq!(opcodes, parse(&text).unwrap());
+            }
+
+            #[test]
+            fn parse_never_panics(line in common::strategy::line()) {
+                let _ = parse(&line);
+            }
+        }
+    }
 }
@@ -0,0 +1,146 @@
+//! Outputs of a program read in groups, as the days whose program outputs
+//! each tile or each move as several values in a row.
+
+use crate::Error;
+use std::{sync::mpsc::Receiver, time::Duration};
+
+/// Outputs of a program, received from the other end of its output channel.
+pub trait Outputs {
+    /// Next two outputs, e.g. the color and the turn of a painting robot.
+    fn recv_pair(&self) -> Result<(i64, i64), Error>;
+    /// Next three outputs, e.g. the `x`, `y` and tile of a screen.
+    fn recv_triple(&self) -> Result<(i64, i64, i64), Error>;
+    /// Triples of outputs, until the program stops.
+    fn triples(&self) -> Triples<'_>;
+    /// Triples of outputs, until the program stops or the next triple does
+    /// not start within `timeout`. A triple once started is always read
+    /// whole, so that the next one starts with its first value.
+    fn triples_timeout(&self, timeout: Duration) -> Triples<'_>;
+}
+
+/// Iterator over the triples of outputs, see [`Outputs::triples`].
+pub struct Triples<'r> {
+    receiver: &'r Receiver<i64>,
+    timeout: Option<Duration>,
+}
+
+/// First of a group of outputs, none being received once the program stopped.
+fn recv_first(receiver: &Receiver<i64>) -> Result<i64, Error> {
+    receiver.recv().map_err(|_| Error::OutputsExhausted)
+}
+
+/// Rest of a group of `expected` outputs, after the `received` ones.
+fn recv_rest(receiver: &Receiver<i64>, received: usize, expected: usize) -> Result<i64, Error> {
+    receiver
+        .recv()
+        .map_err(|_| Error::IncompleteOutput { received, expected })
+}
+
+impl Outputs for Receiver<i64> {
+    fn recv_pair(&self) -> Result<(i64, i64), Error> {
+        let first = recv_first(self)?;
+        Ok((first, recv_rest(self, 1, 2)?))
+    }
+
+    fn recv_triple(&self) -> Result<(i64, i64, i64), Error> {
+        let first = recv_first(self)?;
+        Ok((first, recv_rest(self, 1, 3)?, recv_rest(self, 2, 3)?))
+    }
+
+    fn triples(&self) -> Triples<'_> {
+        Triples {
+            receiver: self,
+            timeout: None,
+        }
+    }
+
+    fn triples_timeout(&self, timeout: Duration) -> Triples<'_> {
+        Triples {
+            receiver: self,
+            timeout: Some(timeout),
+        }
+    }
+}
+
+impl Iterator for Triples<'_> {
+    type Item = Result<(i64, i64, i64), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = match self.timeout {
+            Some(timeout) => self.receiver.recv_timeout(timeout).ok()?,
+            None => self.receiver.recv().ok()?,
+        };
+        let rest = recv_rest(self.receiver, 1, 3)
+            .and_then(|second| Ok((first, second, recv_rest(self.receiver, 2, 3)?)));
+        Some(rest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::mpsc, thread};
+
+    /// Receiver of `outputs`, the sender being dropped once they are sent.
+    fn outputs(outputs: &[i64]) -> Receiver<i64> {
+        let (sender, receiver) = mpsc::channel();
+        for &output in outputs {
+            sender.send(output).unwrap();
+        }
+        receiver
+    }
+
+    #[test]
+    fn pairs_and_triples() {
+        let receiver = outputs(&[1, 0, 3, 4, 5]);
+        assert_eq!((1, 0), receiver.recv_pair().unwrap());
+        assert_eq!((3, 4, 5), receiver.recv_triple().unwrap());
+        assert!(matches!(receiver.recv_pair(), Err(Error::OutputsExhausted)));
+    }
+
+    #[test]
+    fn incomplete_group() {
+        let receiver = outputs(&[1, 2]);
+        assert!(matches!(
+            receiver.recv_triple(),
+            Err(Error::IncompleteOutput {
+                received: 2,
+                expected: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn triples_until_the_end() {
+        let receiver = outputs(&[1, 2, 3, -1, 0, 12]);
+        let triples: Vec<_> = receiver.triples().map(Result::unwrap).collect();
+        assert_eq!(vec![(1, 2, 3), (-1, 0, 12)], triples);
+        let receiver = outputs(&[1, 2, 3, 4]);
+        let mut triples = receiver.triples();
+        assert_eq!(Some((1, 2, 3)), triples.next().transpose().unwrap());
+        assert!(matches!(
+            triples.next(),
+            Some(Err(Error::IncompleteOutput { received: 1, .. }))
+        ));
+    }
+
+    #[test]
+    fn triple_started_before_the_timeout() {
+        let (sender, receiver) = mpsc::sync_channel(0);
+        let program = thread::spawn(move || {
+            for output in [1, 2] {
+                sender.send(output).unwrap();
+            }
+            // The rest of the triple comes after the timeout
+            thread::sleep(Duration::from_millis(50));
+            sender.send(3).unwrap();
+            // Kept waiting for an input, the program outputs nothing more
+            thread::sleep(Duration::from_millis(50));
+        });
+        let triples: Vec<_> = receiver
+            .triples_timeout(Duration::from_millis(10))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(vec![(1, 2, 3)], triples);
+        program.join().unwrap();
+    }
+}
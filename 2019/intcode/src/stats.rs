@@ -0,0 +1,183 @@
+//! Statistics of a program read without running it, decoding its opcodes one
+//! after the other from the first one.
+
+use crate::{Instruction, Mode, INSTRUCTIONS};
+use std::{
+    cmp::Reverse,
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+/// Number of literals kept by [`Stats::literals`].
+pub const LITERALS: usize = 5;
+/// Shortest run of opcodes kept by [`Stats::data`], shorter ones being
+/// mostly variables amid the code.
+pub const DATA: usize = 4;
+
+/// What a program looks like, see [`stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub length: usize,
+    /// Number of decoded instructions of each kind, in the order of
+    /// [`INSTRUCTIONS`].
+    pub instructions: [usize; INSTRUCTIONS.len()],
+    pub position: usize,
+    pub immediate: usize,
+    pub relative: usize,
+    /// Largest parameters in immediate mode, by absolute value, with their
+    /// address.
+    pub literals: Vec<(usize, i64)>,
+    /// Runs of at least [`DATA`] opcodes which never decode as an
+    /// instruction.
+    pub data: Vec<Range<usize>>,
+}
+
+/// Decode the program from its first opcode, each instruction followed by
+/// its parameters. An opcode which is not an instruction, or whose parameters
+/// would go past the end of the program, is taken as data, and the decoding
+/// goes on from the next one. Code and data being mixed, some data may still
+/// happen to decode as instructions.
+pub fn stats(opcodes: &[i64]) -> Stats {
+    let mut stats = Stats {
+        length: opcodes.len(),
+        ..Stats::default()
+    };
+    let mut literals = Vec::new();
+    let mut address = 0;
+    while address < opcodes.len() {
+        let instruction = Instruction::try_from(opcodes[address])
+            .ok()
+            .filter(|instruction| address + instruction.modes().len() < opcodes.len());
+        let instruction = match instruction {
+            Some(instruction) => instruction,
+            None => {
+                match stats.data.last_mut() {
+                    Some(data) if data.end == address => data.end += 1,
+                    _ => stats.data.push(address..address + 1),
+                }
+                address += 1;
+                continue;
+            }
+        };
+        stats.instructions[instruction.kind()] += 1;
+        for (parameter, mode) in (address + 1..).zip(instruction.modes()) {
+            match mode {
+                Mode::Position => stats.position += 1,
+                Mode::Immediate => {
+                    stats.immediate += 1;
+                    literals.push((parameter, opcodes[parameter]));
+                }
+                Mode::Relative => stats.relative += 1,
+            }
+        }
+        address += 1 + instruction.modes().len();
+    }
+    literals.sort_by_key(|&(address, value)| (Reverse(value.unsigned_abs()), address));
+    literals.truncate(LITERALS);
+    stats.literals = literals;
+    stats.data.retain(|data| data.len() >= DATA);
+    stats
+}
+
+impl Stats {
+    /// Kinds of instructions decoded at least once, with their number.
+    pub fn used(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        INSTRUCTIONS
+            .iter()
+            .copied()
+            .zip(self.instructions.iter().copied())
+            .filter(|(_, count)| *count > 0)
+    }
+}
+
+fn list(items: &[String]) -> String {
+    if items.is_empty() {
+        String::from("none")
+    } else {
+        items.join(", ")
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "Length: {} opcodes", self.length)?;
+        let used: Vec<String> = self
+            .used()
+            .map(|(name, count)| format!("{} {}", name, count))
+            .collect();
+        writeln!(
+            f,
+            "Instructions: {} kinds of {} ({})",
+            used.len(),
+            INSTRUCTIONS.len(),
+            used.join(", ")
+        )?;
+        let parameters = self.position + self.immediate + self.relative;
+        let percent = |count: usize| count as f64 * 100.0 / parameters.max(1) as f64;
+        writeln!(
+            f,
+            "Parameters: {} position ({:.1}%), {} immediate ({:.1}%), {} relative ({:.1}%)",
+            self.position,
+            percent(self.position),
+            self.immediate,
+            percent(self.immediate),
+            self.relative,
+            percent(self.relative)
+        )?;
+        let literals: Vec<String> = self
+            .literals
+            .iter()
+            .map(|(address, value)| format!("{} at {}", value, address))
+            .collect();
+        writeln!(f, "Largest literals: {}", list(&literals))?;
+        let data: Vec<String> = self
+            .data
+            .iter()
+            .map(|data| format!("{}..{}", data.start, data.end))
+            .collect();
+        writeln!(f, "Data: {}", list(&data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instructions_and_parameters() {
+        // [9] = 40 + 3000, output [9], halt, then 4 opcodes of data
+        let stats = stats(&[1101, 40, 3000, 9, 4, 9, 99, 0, 12345, 0, 0]);
+        assert_eq!(11, stats.length);
+        assert_eq!(
+            vec![("add", 1), ("output", 1), ("halt", 1)],
+            stats.used().collect::<Vec<_>>()
+        );
+        assert_eq!((2, 2, 0), (stats.position, stats.immediate, stats.relative));
+        assert_eq!(vec![(2, 3000), (1, 40)], stats.literals);
+        assert_eq!(vec![7..11], stats.data);
+    }
+
+    #[test]
+    fn truncated_instruction() {
+        // A relative output, two zeros, then an add missing its last parameter
+        let stats = stats(&[204, -1, 0, 0, 1, 0, 0]);
+        assert_eq!(vec![("output", 1)], stats.used().collect::<Vec<_>>());
+        assert_eq!(1, stats.relative);
+        assert_eq!(vec![2..7], stats.data);
+    }
+
+    #[test]
+    fn display() {
+        let stats = stats(&[104, -7, 99, 5]);
+        assert_eq!(
+            "Length: 4 opcodes
+Instructions: 2 kinds of 10 (output 1, halt 1)
+Parameters: 0 position (0.0%), 1 immediate (100.0%), 0 relative (0.0%)
+Largest literals: -7 at 1
+Data: none
+",
+            stats.to_string()
+        );
+    }
+}
@@ -1,8 +1,4 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{env, io::BufRead};
 
 #[cfg(not(feature = "three"))]
 fn fix_expense_report(entries: &[u32]) -> (u32, u32) {
@@ -18,18 +14,15 @@ fn fix_expense_report(entries: &[u32]) -> (u32, u32) {
 
 #[cfg(feature = "three")]
 fn fix_expense_report(entries: &[u32]) -> (u32, u32, u32) {
-    for i in 0..entries.len() {
-        let expense1 = entries[i];
-        for j in 0..entries.len() {
+    for (i, &expense1) in entries.iter().enumerate() {
+        for (j, &expense2) in entries.iter().enumerate() {
             if j == i {
                 continue;
             }
-            let expense2 = entries[j];
-            for k in 0..entries.len() {
+            for (k, &expense3) in entries.iter().enumerate() {
                 if k == i || k == j {
                     continue;
                 }
-                let expense3 = entries[k];
                 if expense1 + expense2 + expense3 == 2020 {
                     return (expense1, expense2, expense3);
                 }
@@ -40,12 +33,12 @@ fn fix_expense_report(entries: &[u32]) -> (u32, u32, u32) {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "three") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
     let entries: Vec<u32> = reader
         .lines()
         .map(|line| {
@@ -64,12 +57,36 @@ fn main() {
         let (expense1, expense2, expense3) = fix_expense_report(&entries);
         println!("Total is {}", expense1 * expense2 * expense3);
     }
+    args.report_time();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn solve(input: &str) -> Vec<(u8, u32)> {
+        let entries: Vec<u32> = input
+            .lines()
+            .map(|line| {
+                line.parse()
+                    .expect("expect string to be parseable as a u32")
+            })
+            .collect();
+        #[cfg(not(feature = "three"))]
+        let (part, total) = {
+            let (expense1, expense2) = fix_expense_report(&entries);
+            (1, expense1 * expense2)
+        };
+        #[cfg(feature = "three")]
+        let (part, total) = {
+            let (expense1, expense2, expense3) = fix_expense_report(&entries);
+            (2, expense1 * expense2 * expense3)
+        };
+        vec![(part, total)]
+    }
+
+    common::test_examples!(solve);
+
     #[test]
     #[cfg(not(feature = "three"))]
     fn expense_report() {
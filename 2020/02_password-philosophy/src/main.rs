@@ -1,8 +1,4 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{env, io::BufRead};
 
 struct Bounds {
     min: usize,
@@ -28,9 +24,38 @@ impl std::str::FromStr for Bounds {
     }
 }
 
+/// Characters constrained by a policy, either a single one (e.g. 'a') or a
+/// class of several ones (e.g. '[abc]').
+struct Constraint(Vec<char>);
+
+impl Constraint {
+    fn matches(&self, c: char) -> bool {
+        self.0.contains(&c)
+    }
+}
+
+impl std::str::FromStr for Constraint {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(class) = s.strip_prefix('[') {
+            let class = class
+                .strip_suffix(']')
+                .ok_or_else(|| "expect the class of characters to end with ']'".to_string())?;
+            if class.is_empty() {
+                return Err("expect at least one character in the class".to_string());
+            }
+            Ok(Constraint(class.chars().collect()))
+        } else if s.len() != 1 {
+            Err("constraint should be one char long".to_string())
+        } else {
+            Ok(Constraint(s.chars().collect()))
+        }
+    }
+}
+
 struct Policy {
     bounds: Bounds,
-    constraint: char,
+    constraint: Constraint,
 }
 
 impl std::str::FromStr for Policy {
@@ -42,16 +67,10 @@ impl std::str::FromStr for Policy {
             .ok_or_else(|| "expect at least a range (e.g. '1-3')".to_string())?
             .parse()
             .map_err(|e| format!("expect the bounds to be in the format '1-3': {}", e))?;
-        let c = split
+        let constraint: Constraint = split
             .next()
-            .ok_or_else(|| "expect at least a character as the constraint".to_string())?;
-        let constraint = if c.len() != 1 {
-            return Err("constraint should be one char long".to_string());
-        } else {
-            c.chars()
-                .next()
-                .expect("expect at least one char for constraint")
-        };
+            .ok_or_else(|| "expect at least a character as the constraint".to_string())?
+            .parse()?;
         let policy = Policy { bounds, constraint };
         Ok(policy)
     }
@@ -83,14 +102,24 @@ impl std::str::FromStr for Entry {
 impl Entry {
     #[cfg(not(feature = "positional"))]
     fn check(&self) -> bool {
-        let count = self.password.matches(self.policy.constraint).count();
+        let count = self
+            .password
+            .chars()
+            .filter(|&c| self.policy.constraint.matches(c))
+            .count();
         count >= self.policy.bounds.min && count <= self.policy.bounds.max
     }
     #[cfg(feature = "positional")]
     fn check(&self) -> bool {
         let chars: Vec<char> = self.password.chars().collect();
-        let is_first = chars[self.policy.bounds.min - 1] == self.policy.constraint;
-        let is_second = chars[self.policy.bounds.max - 1] == self.policy.constraint;
+        let is_first = self
+            .policy
+            .constraint
+            .matches(chars[self.policy.bounds.min - 1]);
+        let is_second = self
+            .policy
+            .constraint
+            .matches(chars[self.policy.bounds.max - 1]);
         // XOR
         (is_first || is_second) && !(is_first && is_second)
     }
@@ -108,12 +137,12 @@ fn filter_valid_entries(lines: impl Iterator<Item = String>) -> impl Iterator<It
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "positional") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
     let valid_entries = filter_valid_entries(
         reader
             .lines()
@@ -121,6 +150,7 @@ fn main() {
     );
     let count = valid_entries.count();
     println!("Total of valid entries is {}", count);
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -144,4 +174,56 @@ mod tests {
             filter_valid_entries(entries.into_iter().map(std::borrow::ToOwned::to_owned));
         assert_eq!(1, valid_entries.count());
     }
+
+    #[test]
+    #[cfg(not(feature = "positional"))]
+    fn character_class() {
+        let entries = vec!["1-3 [ab]: abcde", "2-3 [xyz]: xyzzy", "1-1 [c]: cdefg"];
+        let valid_entries =
+            filter_valid_entries(entries.into_iter().map(std::borrow::ToOwned::to_owned));
+        assert_eq!(2, valid_entries.count());
+    }
+
+    #[test]
+    #[cfg(feature = "positional")]
+    fn character_class() {
+        let entries = vec!["1-3 [ab]: abcde", "1-2 [xy]: xyzzy", "1-3 [de]: cdefg"];
+        let valid_entries =
+            filter_valid_entries(entries.into_iter().map(std::borrow::ToOwned::to_owned));
+        assert_eq!(2, valid_entries.count());
+    }
+
+    #[test]
+    fn unclosed_character_class() {
+        assert!("[ab".parse::<Constraint>().is_err());
+        assert!("[]".parse::<Constraint>().is_err());
+    }
+
+    mod entry_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn parse_entry(
+                min in 0usize..100,
+                max in 0usize..100,
+                letter in "[a-z]",
+                password in "[a-z]{1,20}"
+            ) {
+                let entry: Entry = format!("{}-{} {}: {}", min, max, letter, password)
+                    .parse()
+                    .unwrap();
+                prop_assert_eq!(min, entry.policy.bounds.min);
+                prop_assert_eq!(max, entry.policy.bounds.max);
+                prop_assert_eq!(letter.chars().collect::<Vec<_>>(), entry.policy.constraint.0);
+                prop_assert_eq!(password, entry.password);
+            }
+
+            #[test]
+            fn entry_never_panics(line in common::strategy::line()) {
+                let _ = line.parse::<Entry>();
+            }
+        }
+    }
 }
@@ -1,8 +1,4 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{env, io::BufRead};
 
 type IsTree = bool;
 struct Map {
@@ -63,7 +59,7 @@ impl std::iter::Iterator for MapSlider<'_> {
         let item = self.map.map[self.position.1][self.position.0 % self.map.width];
         self.position.0 += self.slope.0;
         self.position.1 += self.slope.1;
-        return Some(item);
+        Some(item)
     }
 }
 
@@ -74,19 +70,19 @@ impl MapSlider<'_> {
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "positional") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
     let map = Map::from_lines(
         reader
             .lines()
             .map(|line| line.expect("expect each line to be parseable as a String")),
     );
     let mut product = 1;
-    for slope in vec![(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
+    for slope in [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)] {
         let count = map.slide(slope).count_trees();
         println!(
             "Total of encountered trees with slope {:?} is {}",
@@ -95,6 +91,7 @@ fn main() {
         product *= count;
     }
     println!("Product of all encountered trees is {}", product);
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use passeport_processing::Property;
+
+fuzz_target!(|property: &str| {
+    let _ = property.parse::<Property>();
+});
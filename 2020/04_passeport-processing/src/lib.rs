@@ -0,0 +1,80 @@
+use common::parse;
+
+pub type Year = usize;
+pub type Color = String;
+#[derive(Debug, Default)]
+pub enum Unit {
+    Centimeter,
+    Inch,
+    #[default]
+    None,
+}
+
+impl std::str::FromStr for Unit {
+    type Err = std::convert::Infallible;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unit = match s {
+            "cm" => Unit::Centimeter,
+            "in" => Unit::Inch,
+            _ => Unit::None,
+        };
+        Ok(unit)
+    }
+}
+
+#[derive(Debug)]
+pub enum Property {
+    Id(String),
+    CountryId(String),
+    BirthYear(Year),
+    IssueYear(Year),
+    ExpirationYear(Year),
+    Height(usize, Unit),
+    HairColor(Color),
+    EyeColor(Color),
+}
+
+impl std::str::FromStr for Property {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let year = |year| parse::integer(year).map_err(|error| error.to_string());
+        let property = match parse::key_value(s, ":").map_err(|error| error.to_string())? {
+            ("pid", pid) => Property::Id(pid.to_owned()),
+            ("cid", cid) => Property::CountryId(cid.to_owned()),
+            ("byr", byr) => Property::BirthYear(year(byr)?),
+            ("iyr", iyr) => Property::IssueYear(year(iyr)?),
+            ("eyr", eyr) => Property::ExpirationYear(year(eyr)?),
+            ("hgt", hgt) => {
+                let height = hgt.trim_end_matches(char::is_alphabetic);
+                let unit: Unit = hgt[height.len()..].parse().unwrap_or(Unit::None);
+                let height = parse::integer(height).map_err(|error| error.to_string())?;
+                Property::Height(height, unit)
+            }
+            ("hcl", hcl) => Property::HairColor(hcl.to_owned()),
+            ("ecl", ecl) => Property::EyeColor(ecl.to_owned()),
+            (p, v) => return Err(format!("unknown property '{}' with value '{}'", p, v)),
+        };
+        Ok(property)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_properties() {
+        assert!(matches!(
+            "hgt:183cm".parse(),
+            Ok(Property::Height(183, Unit::Centimeter))
+        ));
+        assert!(matches!(
+            "hgt:170".parse(),
+            Ok(Property::Height(170, Unit::None))
+        ));
+        assert!(matches!("byr:1937".parse(), Ok(Property::BirthYear(1937))));
+        assert!("byr:nineteen".parse::<Property>().is_err());
+        assert!("foo:bar".parse::<Property>().is_err());
+        assert!("byr".parse::<Property>().is_err());
+    }
+}
@@ -1,147 +1,6 @@
-use std::{
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
-
-type Year = usize;
-type Color = String;
-#[derive(Debug)]
-enum Unit {
-    Centimeter,
-    Inch,
-    None,
-}
-impl std::default::Default for Unit {
-    fn default() -> Self {
-        Unit::None
-    }
-}
-
-impl std::str::FromStr for Unit {
-    type Err = std::convert::Infallible;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let unit = match s {
-            "cm" => Unit::Centimeter,
-            "in" => Unit::Inch,
-            _ => Unit::None,
-        };
-        Ok(unit)
-    }
-}
-
-#[derive(Debug)]
-enum Property {
-    Id(String),
-    CountryId(String),
-    BirthYear(Year),
-    IssueYear(Year),
-    ExpirationYear(Year),
-    Height(usize, Unit),
-    HairColor(Color),
-    EyeColor(Color),
-    EndPasseport,
-}
-
-impl std::str::FromStr for Property {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() {
-            return Ok(Property::EndPasseport);
-        }
-        let mut split = s.split(':');
-        let property = match (split.next(), split.next()) {
-            (Some("pid"), Some(pid)) => Property::Id(pid.to_owned()),
-            (Some("cid"), Some(cid)) => Property::CountryId(cid.to_owned()),
-            (Some("byr"), Some(byr)) => Property::BirthYear(
-                byr.parse()
-                    .expect(format!("expect '{}' to be parseable as a usize", byr).as_str()),
-            ),
-            (Some("iyr"), Some(iyr)) => Property::IssueYear(
-                iyr.parse()
-                    .expect(format!("expect '{}' to be parseable as a usize", iyr).as_str()),
-            ),
-            (Some("eyr"), Some(eyr)) => Property::ExpirationYear(
-                eyr.parse()
-                    .expect(format!("expect '{}' to be parseable as a usize", eyr).as_str()),
-            ),
-            (Some("hgt"), Some(hgt)) => {
-                let unit: Unit = hgt[hgt.len() - 2..].parse().unwrap_or(Unit::None);
-                let last_index = if let Unit::None = unit {
-                    hgt.len()
-                } else {
-                    hgt.len() - 2
-                };
-                let height: usize = hgt[..last_index]
-                    .parse()
-                    .expect("expect height to be parseable as a usize");
-                Property::Height(height, unit)
-            }
-            (Some("hcl"), Some(hcl)) => Property::HairColor(hcl.to_owned()),
-            (Some("ecl"), Some(ecl)) => Property::EyeColor(ecl.to_owned()),
-            (Some(p), Some(v)) => panic!("unknown property '{}' with value '{}'", p, v),
-            (Some(p), None) => panic!("unknown property '{}' with no value", p),
-            (None, Some(v)) => panic!("value '{}' has no property name", v),
-            (None, None) => unreachable!(),
-        };
-        Ok(property)
-    }
-}
-
-struct Properties<I>
-where
-    I: Iterator<Item = String>,
-{
-    stream: I,
-    split: Option<Vec<String>>,
-}
-
-impl<I> Properties<I>
-where
-    I: Iterator<Item = String>,
-{
-    fn new(stream: I) -> Self {
-        Self {
-            stream,
-            split: None,
-        }
-    }
-}
-
-impl<I> Iterator for Properties<I>
-where
-    I: Iterator<Item = String>,
-{
-    type Item = Property;
-    fn next(&mut self) -> Option<Self::Item> {
-        let property_str =
-            if let Some(Some(property_str)) = self.split.as_mut().map(|split| split.pop()) {
-                // Still have some element in the splitted line
-                property_str
-            } else {
-                // No more element, need to get a new splitted line from stream
-                if let Some(line) = self.stream.next() {
-                    let mut split_whitespace = line
-                        .split_whitespace()
-                        .rev()
-                        .map(std::borrow::ToOwned::to_owned)
-                        .collect::<Vec<String>>();
-                    if let Some(property_str) = split_whitespace.pop() {
-                        self.split = Some(split_whitespace);
-                        property_str
-                    } else {
-                        // If the new splitted line is empty, then it marks the end
-                        // of a passeport
-                        return Some(Property::EndPasseport);
-                    }
-                } else {
-                    // No more line in the stream, end of the iteration
-                    return None;
-                }
-            };
-        property_str.parse().ok()
-    }
-}
+use common::parse;
+use passeport_processing::{Property, Unit};
+use std::{env, io::BufRead};
 
 #[derive(Default)]
 struct PasseportBuilder {
@@ -217,6 +76,8 @@ impl PasseportBuilder {
 #[derive(Debug, Default)]
 struct Passeport {
     id: String,
+    // The country ID is never checked
+    #[allow(dead_code)]
     country_id: Option<String>,
     birth_year: usize,
     issue_year: usize,
@@ -231,42 +92,18 @@ impl Passeport {
         self.id.len() == 9
     }
     fn check_birth_year(&self) -> bool {
-        if self.birth_year >= 1920 && self.birth_year <= 2002 {
-            true
-        } else {
-            false
-        }
+        self.birth_year >= 1920 && self.birth_year <= 2002
     }
     fn check_issue_year(&self) -> bool {
-        if self.issue_year >= 2010 && self.issue_year <= 2020 {
-            true
-        } else {
-            false
-        }
+        self.issue_year >= 2010 && self.issue_year <= 2020
     }
     fn check_expiration_year(&self) -> bool {
-        if self.expiration_year >= 2020 && self.expiration_year <= 2030 {
-            true
-        } else {
-            false
-        }
+        self.expiration_year >= 2020 && self.expiration_year <= 2030
     }
     fn check_height(&self) -> bool {
         match self.height.1 {
-            Unit::Centimeter => {
-                if self.height.0 >= 150 && self.height.0 <= 193 {
-                    true
-                } else {
-                    false
-                }
-            }
-            Unit::Inch => {
-                if self.height.0 >= 59 && self.height.0 <= 76 {
-                    true
-                } else {
-                    false
-                }
-            }
+            Unit::Centimeter => self.height.0 >= 150 && self.height.0 <= 193,
+            Unit::Inch => self.height.0 >= 59 && self.height.0 <= 76,
             Unit::None => false,
         }
     }
@@ -287,10 +124,10 @@ impl Passeport {
         true
     }
     fn check_eye_color(&self) -> bool {
-        match self.eye_color.as_str() {
-            "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-            _ => false,
-        }
+        matches!(
+            self.eye_color.as_str(),
+            "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth"
+        )
     }
     fn check(&self) -> bool {
         self.check_id()
@@ -305,65 +142,64 @@ impl Passeport {
 
 struct Passeports<I>
 where
-    I: Iterator<Item = Property>,
+    I: Iterator<Item = String>,
 {
-    properties: I,
+    blocks: parse::Blocks<I>,
 }
 
 impl<I> Passeports<I>
 where
-    I: Iterator<Item = Property>,
+    I: Iterator<Item = String>,
 {
-    fn new(properties: I) -> Self {
-        Self { properties }
+    fn new(lines: I) -> Self {
+        Self {
+            blocks: parse::blocks(lines),
+        }
     }
 }
 
 impl<I> std::iter::Iterator for Passeports<I>
 where
-    I: Iterator<Item = Property>,
+    I: Iterator<Item = String>,
 {
     type Item = Passeport;
     fn next(&mut self) -> Option<Self::Item> {
-        let mut builder = PasseportBuilder::default();
-        for property in &mut self.properties {
-            builder = match property {
-                Property::Id(id) => builder.id(id),
-                Property::CountryId(country_id) => builder.country_id(country_id),
-                Property::BirthYear(birth_year) => builder.birth_year(birth_year),
-                Property::IssueYear(issue_year) => builder.issue_year(issue_year),
-                Property::ExpirationYear(expiration_year) => {
-                    builder.expiration_year(expiration_year)
-                }
-                Property::Height(height, unit) => builder.height(height, unit),
-                Property::HairColor(hair_color) => builder.hair_color(hair_color),
-                Property::EyeColor(eye_color) => builder.eye_color(eye_color),
-                Property::EndPasseport => {
-                    if let Ok(passeport) = builder.build() {
-                        return Some(passeport);
-                    } else {
-                        PasseportBuilder::default()
+        for block in &mut self.blocks {
+            let mut builder = PasseportBuilder::default();
+            for property in block.iter().flat_map(|line| line.split_whitespace()) {
+                let property = property
+                    .parse()
+                    .unwrap_or_else(|error| panic!("expect a property: {}", error));
+                builder = match property {
+                    Property::Id(id) => builder.id(id),
+                    Property::CountryId(country_id) => builder.country_id(country_id),
+                    Property::BirthYear(birth_year) => builder.birth_year(birth_year),
+                    Property::IssueYear(issue_year) => builder.issue_year(issue_year),
+                    Property::ExpirationYear(expiration_year) => {
+                        builder.expiration_year(expiration_year)
                     }
-                }
-            };
+                    Property::Height(height, unit) => builder.height(height, unit),
+                    Property::HairColor(hair_color) => builder.hair_color(hair_color),
+                    Property::EyeColor(eye_color) => builder.eye_color(eye_color),
+                };
+            }
+            if let Ok(passeport) = builder.build() {
+                return Some(passeport);
+            }
         }
-        builder.build().ok()
+        None
     }
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
-    let properties = Properties::new(
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let passeports = Passeports::new(
         reader
             .lines()
             .map(|line| line.expect("expect line to be parseable as a String")),
     );
-    let passeports = Passeports::new(properties);
     let (complete, valid) = passeports.fold((0, 0), |(mut complete, mut valid), passeport| {
         complete += 1;
         if passeport.check() {
@@ -373,6 +209,7 @@ fn main() {
     });
     println!("Number of complete passeports is {}", complete);
     println!("Number of valid passeports is {}", valid);
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -394,9 +231,8 @@ hgt:179cm
 
 hcl:#cfa07d eyr:2025 pid:166559648
 iyr:2011 ecl:brn hgt:59in"#;
-        let properties =
-            Properties::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
-        let passeports = Passeports::new(properties);
+        let passeports =
+            Passeports::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
         assert_eq!(2, passeports.count());
     }
 
@@ -415,9 +251,8 @@ ecl:brn hgt:182cm pid:021572410 eyr:2020 byr:1992 cid:277
 hgt:59cm ecl:zzz
 eyr:2038 hcl:74454a iyr:2023
 pid:3556412378 byr:2007"#;
-        let properties =
-            Properties::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
-        let passeports = Passeports::new(properties);
+        let passeports =
+            Passeports::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
         assert_eq!(0, passeports.filter(Passeport::check).count());
     }
 
@@ -435,15 +270,14 @@ pid:545766238 ecl:hzl
 eyr:2022
 
 iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
-        let properties =
-            Properties::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
-        let passeports = Passeports::new(properties);
+        let passeports =
+            Passeports::new(passeports.split('\n').map(std::borrow::ToOwned::to_owned));
         assert_eq!(4, passeports.filter(Passeport::check).count());
     }
 
     #[test]
     fn check_id() {
-        for (id, expected) in vec![
+        for (id, expected) in [
             ("000000001", true),
             ("123456789", true),
             ("0123456789", false),
@@ -458,7 +292,7 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
 
     #[test]
     fn check_birth_year() {
-        for (birth_year, expected) in vec![(2002, true), (2003, false)] {
+        for (birth_year, expected) in [(2002, true), (2003, false)] {
             let passeport = Passeport {
                 birth_year,
                 ..Default::default()
@@ -470,7 +304,7 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
     #[test]
     fn check_height() {
         use Unit::*;
-        for (height, expected) in vec![
+        for (height, expected) in [
             ((60, Inch), true),
             ((190, Centimeter), true),
             ((190, Inch), false),
@@ -486,8 +320,7 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
 
     #[test]
     fn check_hair_color() {
-        for (hair_color, expected) in vec![("#123abc", true), ("#123abz", false), ("123abc", false)]
-        {
+        for (hair_color, expected) in [("#123abc", true), ("#123abz", false), ("123abc", false)] {
             let passeport = Passeport {
                 hair_color: String::from(hair_color),
                 ..Default::default()
@@ -498,7 +331,7 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719"#;
 
     #[test]
     fn check_eye_color() {
-        for (eye_color, expected) in vec![("brn", true), ("wat", false)] {
+        for (eye_color, expected) in [("brn", true), ("wat", false)] {
             let passeport = Passeport {
                 eye_color: String::from(eye_color),
                 ..Default::default()
@@ -1,8 +1,7 @@
 use std::{
-    convert::Infallible,
     env,
-    fs::File,
-    io::{BufRead, BufReader},
+    io::{self, BufRead},
+    process,
 };
 
 #[derive(Debug)]
@@ -11,11 +10,11 @@ enum RowMove {
     Back,
 }
 impl RowMove {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Result<Self, String> {
         match c {
-            'F' => RowMove::Front,
-            'B' => RowMove::Back,
-            _ => panic!("expect 'F' or 'B' for row move"),
+            'F' => Ok(RowMove::Front),
+            'B' => Ok(RowMove::Back),
+            c => Err(format!("expect 'F' or 'B' for row move but got '{}'", c)),
         }
     }
 }
@@ -26,11 +25,11 @@ enum ColumnMove {
     Left,
 }
 impl ColumnMove {
-    fn from_char(c: char) -> Self {
+    fn from_char(c: char) -> Result<Self, String> {
         match c {
-            'R' => ColumnMove::Right,
-            'L' => ColumnMove::Left,
-            _ => panic!("expect 'R' or 'L' for column move"),
+            'R' => Ok(ColumnMove::Right),
+            'L' => Ok(ColumnMove::Left),
+            c => Err(format!("expect 'R' or 'L' for column move but got '{}'", c)),
         }
     }
 }
@@ -48,17 +47,20 @@ impl BoardingPass {
 }
 
 impl std::str::FromStr for BoardingPass {
-    type Err = Infallible;
+    type Err = String;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 10 {
-            panic!("expect boarding pass code to be 10 characters long");
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != 10 {
+            return Err(format!(
+                "expect boarding pass code to be 10 characters long but got '{}'",
+                s
+            ));
         }
         let mut row_min = 0;
         let mut row_max = 127;
         let mut window = 64;
-        let chars: Vec<char> = s.chars().collect();
         for c in &chars[0..7] {
-            let row_move = RowMove::from_char(*c);
+            let row_move = RowMove::from_char(*c)?;
             use RowMove::*;
             match row_move {
                 Front => row_max -= window,
@@ -72,7 +74,7 @@ impl std::str::FromStr for BoardingPass {
         let mut column_max = 7;
         let mut window = 4;
         for c in &chars[7..10] {
-            let column_move = ColumnMove::from_char(*c);
+            let column_move = ColumnMove::from_char(*c)?;
             use ColumnMove::*;
             match column_move {
                 Left => column_max -= window,
@@ -89,14 +91,14 @@ impl std::str::FromStr for BoardingPass {
 
 struct BoardingPasses<I>
 where
-    I: Iterator<Item = String>,
+    I: Iterator<Item = io::Result<String>>,
 {
     stream: I,
 }
 
 impl<I> BoardingPasses<I>
 where
-    I: Iterator<Item = String>,
+    I: Iterator<Item = io::Result<String>>,
 {
     fn new(stream: I) -> Self {
         Self { stream }
@@ -105,45 +107,83 @@ where
 
 impl<I> Iterator for BoardingPasses<I>
 where
-    I: Iterator<Item = String>,
+    I: Iterator<Item = io::Result<String>>,
 {
-    type Item = BoardingPass;
+    type Item = Result<BoardingPass, String>;
     fn next(&mut self) -> Option<Self::Item> {
-        self.stream
-            .next()
-            .map(|code| code.parse())
-            .transpose()
-            .expect("expect String to be parseable as BoardingPass")
+        self.stream.next().map(|code| {
+            code.map_err(|error| format!("expect a line of text ({})", error))
+                .and_then(|code| code.parse())
+        })
     }
 }
 
-fn find_seat(mut ids: Vec<usize>) -> usize {
-    ids.sort();
-    for (id, next_id) in ids.iter().take(ids.len() - 1).zip(ids.iter().skip(1)) {
-        if next_id - id != 1 {
-            return id + 1;
+/// IDs of the boarding passes of every input, in the order of the inputs,
+/// with the errors of each input prefixed by its path and the line.
+fn read_ids<I, R>(inputs: I) -> (Vec<usize>, Vec<String>)
+where
+    I: IntoIterator<Item = (String, R)>,
+    R: BufRead,
+{
+    let mut ids = Vec::new();
+    let mut errors = Vec::new();
+    for (path, reader) in inputs {
+        for (index, boarding_pass) in BoardingPasses::new(reader.lines()).enumerate() {
+            match boarding_pass {
+                Ok(boarding_pass) => ids.push(boarding_pass.id()),
+                Err(error) => errors.push(format!("{}:{}: {}", path, index + 1, error)),
+            }
         }
     }
-    panic!("failed to find a seat")
+    (ids, errors)
+}
+
+/// The seat missing between two taken ones, a seat found in several inputs
+/// being taken only once, if any.
+fn find_seat(mut ids: Vec<usize>) -> Option<usize> {
+    ids.sort_unstable();
+    ids.dedup();
+    ids.windows(2)
+        .find(|pair| pair[1] - pair[0] != 1)
+        .map(|pair| pair[0] + 1)
+}
+
+/// Print every error on the standard error and exit with a failure.
+fn exit_with(errors: &[String]) -> ! {
+    for error in errors {
+        eprintln!("{}", error);
+    }
+    process::exit(1)
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+    let args = common::cli::Args::parse_inputs(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let mut errors = Vec::new();
+    let inputs = args.inputs().into_iter().filter_map(|input| {
+        let path = common::input::path(input, env!("CARGO_MANIFEST_DIR"));
+        match common::input::load(input, env!("CARGO_MANIFEST_DIR")) {
+            Ok(reader) => Some((path.display().to_string(), reader)),
+            Err(error) => {
+                errors.push(format!("{}: {}", path.display(), error));
+                None
+            }
+        }
+    });
+    let (ids, pass_errors) = read_ids(inputs);
+    errors.extend(pass_errors);
+    if !errors.is_empty() {
+        exit_with(&errors);
+    }
+    let greater_id = match ids.iter().max() {
+        Some(id) => id,
+        None => exit_with(&[String::from("no boarding pass in the inputs")]),
+    };
+    println!("Greater boarding pass ID is {}", greater_id);
+    match find_seat(ids) {
+        Some(seat) => println!("Your ID seat is {}", seat),
+        None => exit_with(&[String::from("no seat missing between two boarding passes")]),
     }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
-    let boarding_passes = BoardingPasses::new(
-        reader
-            .lines()
-            .map(|line| line.expect("expect line to be parseable as a String")),
-    );
-    let ids = boarding_passes
-        .map(|boarding_pass: BoardingPass| boarding_pass.id())
-        .collect::<Vec<_>>();
-    println!("Greater boarding pass ID is {}", ids.iter().max().unwrap());
-    println!("Your ID seat is {}", find_seat(ids));
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -161,23 +201,54 @@ mod tests {
         let boarding_passes = r#"BFFFBBFRRR
 FFFBBBFRRR
 BBFFBBFRLL"#;
-        let mut boarding_passes = BoardingPasses::new(
-            boarding_passes
-                .split('\n')
-                .map(std::borrow::ToOwned::to_owned),
-        );
-        let boarding_pass = boarding_passes.next().unwrap();
+        let mut boarding_passes =
+            BoardingPasses::new(boarding_passes.split('\n').map(|code| Ok(code.to_owned())));
+        let boarding_pass = boarding_passes.next().unwrap().unwrap();
         assert_eq!(567, boarding_pass.id());
-        let boarding_pass = boarding_passes.next().unwrap();
+        let boarding_pass = boarding_passes.next().unwrap().unwrap();
         assert_eq!(119, boarding_pass.id());
-        let boarding_pass = boarding_passes.next().unwrap();
+        let boarding_pass = boarding_passes.next().unwrap().unwrap();
         assert_eq!(820, boarding_pass.id());
         assert!(boarding_passes.next().is_none());
     }
 
+    #[test]
+    fn invalid_boarding_pass() {
+        assert_eq!(
+            Err(String::from("expect 'F' or 'B' for row move but got 'X'")),
+            "FBXBBFFRLR".parse::<BoardingPass>().map(|pass| pass.id())
+        );
+        assert!("FBFBBFFRL".parse::<BoardingPass>().is_err());
+        assert!("FBFBBFFRLF".parse::<BoardingPass>().is_err());
+    }
+
     #[test]
     fn find_seat() {
         let ids = vec![8, 4, 5, 7];
-        assert_eq!(6, super::find_seat(ids));
+        assert_eq!(Some(6), super::find_seat(ids));
+        let ids = vec![8, 4, 5, 7, 5];
+        assert_eq!(Some(6), super::find_seat(ids));
+        assert_eq!(None, super::find_seat(vec![4, 5, 6]));
+        assert_eq!(None, super::find_seat(vec![4]));
+        assert_eq!(None, super::find_seat(Vec::new()));
+    }
+
+    #[test]
+    fn several_inputs() {
+        let inputs = vec![
+            ("a.txt".to_string(), "BFFFBBFRRR\nFFFBBBFRRR\n".as_bytes()),
+            ("-".to_string(), "FFFBBBFRRX\nBBFFBBFRLL\nBBFF\n".as_bytes()),
+            ("b.txt".to_string(), b"BBFFB\xffFRLL\n".as_slice()),
+        ];
+        let (ids, errors) = read_ids(inputs);
+        assert_eq!(vec![567, 119, 820], ids);
+        assert_eq!(
+            vec![
+                "-:1: expect 'R' or 'L' for column move but got 'X'",
+                "-:3: expect boarding pass code to be 10 characters long but got 'BBFF'",
+                "b.txt:1: expect a line of text (stream did not contain valid UTF-8)"
+            ],
+            errors
+        );
     }
 }
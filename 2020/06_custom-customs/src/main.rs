@@ -1,9 +1,4 @@
-use std::{
-    collections::BTreeSet,
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{collections::BTreeSet, env, io::BufRead};
 
 type Answers = BTreeSet<char>;
 type PersonAnswers = Answers;
@@ -89,12 +84,12 @@ where
 }
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "everyone") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
     let persons_answers = PersonsAnswers::new(
         reader
             .lines()
@@ -103,12 +98,21 @@ fn main() {
     let groups_answers = persons_answers.groups_answers();
     let count = groups_answers.sum_answers();
     println!("Total groups answers is {}", count);
+    args.report_time();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn solve(input: &str) -> Vec<(u8, usize)> {
+        let persons_answers = PersonsAnswers::new(input.lines().map(str::to_owned));
+        let part = if cfg!(feature = "everyone") { 2 } else { 1 };
+        vec![(part, persons_answers.groups_answers().sum_answers())]
+    }
+
+    common::test_examples!(solve);
+
     #[test]
     fn boarding_passes() {
         let boarding_passes = r#"abc
@@ -1,11 +1,13 @@
+use common::{
+    cli::{self, Arg, ArgAction, Args},
+    memo::Memo,
+};
 use std::{
-    collections::{HashMap, HashSet},
-    env,
-    fs::File,
-    io::{BufRead, BufReader},
+    collections::{BTreeMap, HashMap, HashSet},
+    io::BufRead,
 };
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 struct Bag {
     tint: String,
     color: String,
@@ -20,7 +22,48 @@ impl std::fmt::Display for Bag {
 #[derive(Debug)]
 struct Rule {
     bag: Bag,
-    contains: HashMap<Bag, usize>,
+    /// Bags inside, in order, so that they are always checked in the same
+    /// order, e.g. for the bag reported in a refused rule.
+    contains: BTreeMap<Bag, usize>,
+}
+
+fn parse_bag(bag: &str) -> Result<Bag, String> {
+    let mut bag_properties = bag.split_whitespace();
+    let tint = bag_properties
+        .next()
+        .ok_or_else(|| format!("expect a tint in '{}'", bag))?
+        .to_owned();
+    let color = bag_properties
+        .next()
+        .ok_or_else(|| format!("expect a color in '{}'", bag))?
+        .to_owned();
+    Ok(Bag { tint, color })
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let mut split = rule.split(" bags contain ");
+        let bag = parse_bag(split.next().unwrap_or_default())?;
+        let contained_list = split
+            .next()
+            .ok_or_else(|| format!("expect a list of contained bags in '{}'", rule))?
+            .split(", ");
+        let mut contains = BTreeMap::default();
+        for contained in contained_list {
+            if contained.starts_with("no ") {
+                break;
+            }
+            let (quantity, contained) = contained
+                .split_once(' ')
+                .ok_or_else(|| format!("expect a quantity and a bag in '{}'", contained))?;
+            let quantity: usize = quantity
+                .parse()
+                .map_err(|_| format!("expect the quantity '{}' to be a number", quantity))?;
+            contains.insert(parse_bag(contained)?, quantity);
+        }
+        Ok(Rule { bag, contains })
+    }
 }
 
 struct Rules<I>
@@ -30,7 +73,6 @@ where
     stream: I,
 }
 
-type RulesMap = HashMap<Bag, HashMap<Bag, usize>>;
 impl<I> Rules<I>
 where
     I: Iterator<Item = String>,
@@ -38,112 +80,274 @@ where
     fn new(stream: I) -> Self {
         Self { stream }
     }
+}
 
-    fn as_map(self) -> RulesMap {
-        self.map(|rule| (rule.bag, rule.contains)).collect()
+impl<I> Iterator for Rules<I>
+where
+    I: Iterator<Item = String>,
+{
+    type Item = Rule;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.stream
+            .next()
+            .map(|rule| rule.parse().unwrap_or_else(|error| panic!("{}", error)))
     }
 }
 
-fn contains_bag(rules_map: &RulesMap, bag: &Bag) -> HashSet<Bag> {
-    let bag_contains: HashMap<Bag, Vec<Bag>> = rules_map
-        .into_iter()
-        .map(|(bag, contains)| (bag.clone(), contains.keys().cloned().collect()))
-        .collect();
-    fn find_wrappers(bag_contains: &HashMap<Bag, Vec<Bag>>, bag: &Bag) -> HashSet<Bag> {
-        let mut set = HashSet::new();
-        for (b, c) in bag_contains {
-            if c.contains(&bag) {
-                set.insert(b.clone());
-                set.extend(find_wrappers(bag_contains, b));
+/// The rules added so far, with the answers already computed for each bag.
+///
+/// Adding a rule only forgets the answers it changes: the bags around the bag
+/// of the rule for the number of bags inside, and the bags inside the bags
+/// gained or lost by the rule for the bags around.
+#[derive(Debug, Default)]
+struct BagGraph {
+    contains: HashMap<Bag, BTreeMap<Bag, usize>>,
+    contained_in: HashMap<Bag, HashSet<Bag>>,
+    wrappers: HashMap<Bag, HashSet<Bag>>,
+    inside: Memo<Bag, usize>,
+}
+
+impl BagGraph {
+    fn new(rules: impl Iterator<Item = Rule>) -> Result<Self, String> {
+        let mut graph = Self::default();
+        for rule in rules {
+            graph.insert(rule)?;
+        }
+        Ok(graph)
+    }
+
+    fn add_rule(&mut self, rule: &str) -> Result<(), String> {
+        self.insert(rule.parse()?)
+    }
+
+    /// Add a rule, replacing the previous rule of the same bag if any. A rule
+    /// making a bag contain itself is refused.
+    fn insert(&mut self, rule: Rule) -> Result<(), String> {
+        if let Some(inner) = rule
+            .contains
+            .keys()
+            .find(|inner| **inner == rule.bag || self.descendants(inner).contains(&rule.bag))
+        {
+            return Err(format!(
+                "{} would contain itself through {}",
+                rule.bag, inner
+            ));
+        }
+        let old = self.contains.remove(&rule.bag).unwrap_or_default();
+        let gained = rule.contains.keys().filter(|bag| !old.contains_key(bag));
+        let lost = old.keys().filter(|bag| !rule.contains.contains_key(bag));
+        for bag in gained.chain(lost) {
+            self.wrappers.remove(bag);
+            for inner in self.descendants(bag) {
+                self.wrappers.remove(&inner);
             }
         }
-        set
+        if old != rule.contains {
+            self.inside.forget(&rule.bag);
+            for outer in self.ancestors(&rule.bag) {
+                self.inside.forget(&outer);
+            }
+        }
+        for inner in old.keys() {
+            if let Some(outers) = self.contained_in.get_mut(inner) {
+                outers.remove(&rule.bag);
+            }
+        }
+        for inner in rule.contains.keys() {
+            self.contained_in
+                .entry(inner.clone())
+                .or_default()
+                .insert(rule.bag.clone());
+        }
+        self.contains.insert(rule.bag, rule.contains);
+        Ok(())
     }
-    find_wrappers(&bag_contains, bag)
-}
 
-fn bags_in(rules_map: &RulesMap, bag: &Bag) -> Vec<Bag> {
-    let mut bags = Vec::new();
-    rules_map
-        .get(bag)
-        .expect("expect this bag to have a rule")
-        .iter()
-        .for_each(|(b, quantity)| {
-            for _ in 0..*quantity {
-                bags.push(b.clone());
-                bags.extend(bags_in(rules_map, b));
+    /// Whether `bag` appears in any of the rules, as the bag of the rule or
+    /// as a bag inside.
+    fn knows(&self, bag: &Bag) -> bool {
+        self.contains.contains_key(bag) || self.contained_in.contains_key(bag)
+    }
+
+    fn descendants(&self, bag: &Bag) -> HashSet<Bag> {
+        reachable(bag, |bag| {
+            self.contains.get(bag).into_iter().flat_map(BTreeMap::keys)
+        })
+    }
+
+    fn ancestors(&self, bag: &Bag) -> HashSet<Bag> {
+        reachable(bag, |bag| self.contained_in.get(bag).into_iter().flatten())
+    }
+
+    /// Ancestors of each of `bags`, in the same order, searched on every core
+    /// with the `parallel` feature.
+    fn ancestors_many(&self, bags: &[Bag]) -> Vec<HashSet<Bag>> {
+        let bags: Vec<(usize, &Bag)> = bags.iter().enumerate().collect();
+        let mut ancestors = common::parallel::map_reduce(
+            bags,
+            Vec::new,
+            |(index, bag)| vec![(index, self.ancestors(bag))],
+            |mut first, second| {
+                first.extend(second);
+                first
+            },
+        );
+        ancestors.sort_unstable_by_key(|&(index, _)| index);
+        ancestors
+            .into_iter()
+            .map(|(_, ancestors)| ancestors)
+            .collect()
+    }
+
+    /// Bags eventually containing each of `bags`, the ones not known yet
+    /// being searched all at once.
+    fn wrappers_many(&mut self, bags: &[Bag]) -> Vec<&HashSet<Bag>> {
+        let mut unknown: Vec<Bag> = Vec::new();
+        for bag in bags {
+            if !self.wrappers.contains_key(bag) && !unknown.contains(bag) {
+                unknown.push(bag.clone());
             }
-        });
-    bags
+        }
+        let wrappers = self.ancestors_many(&unknown);
+        self.wrappers.extend(unknown.into_iter().zip(wrappers));
+        let known = &self.wrappers;
+        bags.iter().map(|bag| &known[bag]).collect()
+    }
+
+    /// Number of bags inside `bag`, a bag without a rule yet being empty.
+    fn inside(&mut self, bag: &Bag) -> usize {
+        count_inside(&mut self.inside, bag.clone(), &self.contains)
+    }
 }
 
-impl<I> Iterator for Rules<I>
+/// Bags reached from `bag` by following `next` again and again, each of them
+/// visited once.
+fn reachable<'g, N, I>(bag: &'g Bag, next: N) -> HashSet<Bag>
 where
-    I: Iterator<Item = String>,
+    N: Fn(&'g Bag) -> I,
+    I: Iterator<Item = &'g Bag>,
 {
-    type Item = Rule;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.stream.next().map(|rule| {
-            let mut split = rule.split(" bags contain ");
-            let mut bag_properties = split
-                .next()
-                .expect("expect at least a bag definition")
-                .split_whitespace();
-            let tint = bag_properties.next().expect("expect a tint").to_owned();
-            let color = bag_properties.next().expect("expect a color").to_owned();
-            let contained_list = split
-                .next()
-                .expect("expect a list of contained bags")
-                .split(", ");
-            let mut contains = HashMap::default();
-            for contained in contained_list {
-                if contained.starts_with("no ") {
-                    break;
-                }
-                let mut bag_properties = contained.split_whitespace();
-                let quantity: usize = bag_properties
-                    .next()
-                    .expect("expect to have a quantity for the contained bag")
-                    .parse()
-                    .expect("expect the quantity to be parsearble as a usize");
-                let tint = bag_properties.next().expect("expect a tint").to_owned();
-                let color = bag_properties.next().expect("expect a color").to_owned();
-                contains.insert(Bag { color, tint }, quantity);
-            }
-            Rule {
-                bag: Bag { tint, color },
-                contains,
+    let mut visited = HashSet::new();
+    let mut stack = vec![bag];
+    while let Some(bag) = stack.pop() {
+        for bag in next(bag) {
+            if visited.insert(bag.clone()) {
+                stack.push(bag);
             }
-        })
+        }
     }
+    visited
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
+common::memoized! {
+    fn count_inside(memo, bag: Bag, contains: &HashMap<Bag, BTreeMap<Bag, usize>>) -> usize {
+        contains
+            .get(&bag)
+            .into_iter()
+            .flatten()
+            .map(|(inner, quantity)| {
+                quantity * (1 + count_inside(memo, inner.clone(), contains))
+            })
+            .sum()
     }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let reader = BufReader::new(file);
+}
+
+/// Number of bags containing each of `bags`, and number of bags inside it.
+fn answers(graph: &mut BagGraph, bags: &[Bag]) -> Vec<(usize, usize)> {
+    let wrappers: Vec<usize> = graph
+        .wrappers_many(bags)
+        .into_iter()
+        .map(HashSet::len)
+        .collect();
+    wrappers
+        .into_iter()
+        .zip(bags)
+        .map(|(wrappers, bag)| (wrappers, graph.inside(bag)))
+        .collect()
+}
+
+/// Arguments `--explore` and `--bag <BAG>`, to be given to
+/// `cli::Args::parse_with`.
+fn arguments() -> Vec<Arg> {
+    vec![
+        Arg::new("explore")
+            .long("explore")
+            .action(ArgAction::SetTrue)
+            .help("Read more rules from the standard input, answering again after each one"),
+        Arg::new("bag")
+            .long("bag")
+            .value_name("BAG")
+            .action(ArgAction::Append)
+            .help("Bag to answer for instead of the shiny gold bag, as many times as needed"),
+    ]
+}
+
+/// Bags given with `--bag`, each of them known to `graph`, or the shiny gold
+/// bag when none is given.
+fn bags_from_args(args: &Args, graph: &BagGraph) -> Result<Vec<Bag>, cli::Error> {
+    let mut bags = Vec::new();
+    for bag in args.values("bag") {
+        let bag = parse_bag(bag).map_err(|error| args.error(error))?;
+        if !graph.knows(&bag) {
+            return Err(args.error(format!("no rule mentions the {}", bag)));
+        }
+        bags.push(bag);
+    }
+    if bags.is_empty() {
+        bags.push(Bag {
+            tint: "shiny".to_string(),
+            color: "gold".to_string(),
+        });
+    }
+    Ok(bags)
+}
+
+fn main() {
+    let args = Args::parse_with(env!("CARGO_PKG_NAME"), &[1, 2], arguments());
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
     let rules = Rules::new(
         reader
             .lines()
             .map(|line| line.expect("expect line to be parseable as a String")),
     );
-    let bag = Bag {
-        tint: "shiny".to_string(),
-        color: "gold".to_string(),
-    };
-    let rules_map = rules.as_map();
-    let count = contains_bag(&rules_map, &bag).len();
-    println!("There is {} different bags containing a {}", count, bag);
-    let count = bags_in(&rules_map, &bag).len();
-    println!("There is {} bags in {}", count, bag);
+    let mut graph = BagGraph::new(rules).unwrap_or_else(|error| panic!("{}", error));
+    let bags = bags_from_args(&args, &graph).unwrap_or_else(|error| error.exit());
+    let mut answers = answers(&mut graph, &bags);
+    for (bag, (wrappers, inside)) in bags.iter().zip(&answers) {
+        println!("There is {} different bags containing a {}", wrappers, bag);
+        println!("There is {} bags in {}", inside, bag);
+    }
+    args.report_time();
+    if !args.flag("explore") {
+        return;
+    }
+    for rule in std::io::stdin().lock().lines() {
+        let rule = rule.expect("expect line to be parseable as a String");
+        if let Err(error) = graph.add_rule(&rule) {
+            eprintln!("{}", error);
+            continue;
+        }
+        let previous_answers = std::mem::replace(&mut answers, self::answers(&mut graph, &bags));
+        for ((bag, (wrappers, inside)), (previous_wrappers, previous_inside)) in
+            bags.iter().zip(&answers).zip(previous_answers)
+        {
+            println!(
+                "There is {} ({:+}) different bags containing a {}, and {} ({:+}) bags in it",
+                wrappers,
+                *wrappers as i64 - previous_wrappers as i64,
+                bag,
+                inside,
+                *inside as i64 - previous_inside as i64,
+            );
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::cli::ErrorKind;
 
     #[test]
     fn bags() {
@@ -161,9 +365,9 @@ dotted black bags contain no other bags."#;
             tint: "shiny".to_string(),
             color: "gold".to_string(),
         };
-        let rules_map = rules.as_map();
-        assert_eq!(4, contains_bag(&rules_map, &bag).len());
-        assert_eq!(32, bags_in(&rules_map, &bag).len());
+        let mut graph = BagGraph::new(rules).unwrap();
+        assert_eq!(4, wrappers(&mut graph, &bag));
+        assert_eq!(32, graph.inside(&bag));
     }
 
     #[test]
@@ -180,7 +384,177 @@ dark violet bags contain no other bags."#;
             tint: "shiny".to_string(),
             color: "gold".to_string(),
         };
-        let rules_map = rules.as_map();
-        assert_eq!(126, bags_in(&rules_map, &bag).len());
+        let mut graph = BagGraph::new(rules).unwrap();
+        assert_eq!(126, graph.inside(&bag));
+    }
+
+    fn bag(bag: &str) -> Bag {
+        parse_bag(bag).unwrap()
+    }
+
+    /// Number of bags eventually containing `bag`.
+    fn wrappers(graph: &mut BagGraph, bag: &Bag) -> usize {
+        graph.wrappers_many(std::slice::from_ref(bag))[0].len()
+    }
+
+    #[test]
+    fn add_rule_forgets_changed_answers() {
+        let mut graph = BagGraph::default();
+        graph
+            .add_rule("bright white bags contain 1 shiny gold bag.")
+            .unwrap();
+        graph
+            .add_rule("shiny gold bags contain 2 dark olive bags.")
+            .unwrap();
+        graph
+            .add_rule("dark olive bags contain no other bags.")
+            .unwrap();
+        let shiny_gold = bag("shiny gold");
+        let dark_olive = bag("dark olive");
+        assert_eq!(1, wrappers(&mut graph, &shiny_gold));
+        assert_eq!(2, graph.inside(&shiny_gold));
+        assert_eq!(2, wrappers(&mut graph, &dark_olive));
+        assert_eq!(3, graph.inside(&bag("bright white")));
+
+        // Bags around shiny gold are unchanged by a rule below it
+        graph
+            .add_rule("dark olive bags contain 3 faded blue bags.")
+            .unwrap();
+        assert!(graph.wrappers.contains_key(&shiny_gold));
+        assert!(graph.wrappers.contains_key(&dark_olive));
+        assert!(!graph.inside.contains_key(&shiny_gold));
+        assert!(!graph.inside.contains_key(&bag("bright white")));
+        assert_eq!(8, graph.inside(&shiny_gold));
+        assert_eq!(3, wrappers(&mut graph, &bag("faded blue")));
+
+        // Bags inside shiny gold are unchanged by a rule above it
+        graph
+            .add_rule("muted yellow bags contain 2 shiny gold bags.")
+            .unwrap();
+        assert!(graph.inside.contains_key(&shiny_gold));
+        assert!(!graph.wrappers.contains_key(&shiny_gold));
+        assert!(!graph.wrappers.contains_key(&bag("faded blue")));
+        assert_eq!(2, wrappers(&mut graph, &shiny_gold));
+        assert_eq!(4, wrappers(&mut graph, &bag("faded blue")));
+    }
+
+    #[test]
+    fn add_rule_replaces_rule() {
+        let mut graph = BagGraph::default();
+        graph
+            .add_rule("shiny gold bags contain 2 dark olive bags.")
+            .unwrap();
+        assert_eq!(2, graph.inside(&bag("shiny gold")));
+        graph
+            .add_rule("shiny gold bags contain 1 faded blue bag.")
+            .unwrap();
+        assert_eq!(1, graph.inside(&bag("shiny gold")));
+        assert_eq!(0, wrappers(&mut graph, &bag("dark olive")));
+        assert_eq!(1, wrappers(&mut graph, &bag("faded blue")));
+    }
+
+    #[test]
+    fn add_rule_refuses_cycle() {
+        let mut graph = BagGraph::default();
+        graph
+            .add_rule("shiny gold bags contain 2 dark olive bags.")
+            .unwrap();
+        assert!(graph
+            .add_rule("dark olive bags contain 1 shiny gold bag.")
+            .is_err());
+        assert!(graph
+            .add_rule("dark olive bags contain 1 dark olive bag.")
+            .is_err());
+        assert!(graph.add_rule("dark olive bags contain 1 bag.").is_err());
+        assert_eq!(2, graph.inside(&bag("shiny gold")));
+    }
+
+    #[test]
+    fn ancestors_of_many_bags() {
+        let mut graph = BagGraph::default();
+        for rule in &[
+            "light red bags contain 1 bright white bag, 2 muted yellow bags.",
+            "bright white bags contain 1 shiny gold bag.",
+            "muted yellow bags contain 2 shiny gold bags, 9 faded blue bags.",
+            "shiny gold bags contain 1 dark olive bag.",
+        ] {
+            graph.add_rule(rule).unwrap();
+        }
+        let bags = vec![
+            bag("shiny gold"),
+            bag("faded blue"),
+            bag("light red"),
+            bag("shiny gold"),
+        ];
+        let ancestors = graph.ancestors_many(&bags);
+        assert_eq!(4, ancestors.len());
+        for (bag, ancestors) in bags.iter().zip(&ancestors) {
+            assert_eq!(&graph.ancestors(bag), ancestors);
+        }
+        assert_eq!(3, ancestors[0].len());
+        assert_eq!(2, ancestors[1].len());
+        assert!(ancestors[2].is_empty());
+
+        wrappers(&mut graph, &bag("faded blue"));
+        let wrappers: Vec<usize> = graph
+            .wrappers_many(&bags)
+            .into_iter()
+            .map(HashSet::len)
+            .collect();
+        assert_eq!(vec![3, 2, 0, 3], wrappers);
+        assert!(graph.wrappers.contains_key(&bag("light red")));
+    }
+
+    #[test]
+    fn bags_options() {
+        let mut graph = BagGraph::default();
+        graph
+            .add_rule("shiny gold bags contain 1 dark olive bag.")
+            .unwrap();
+        let bags = |args: &[&str]| {
+            let args = Args::try_parse_from(
+                "handy-haversacks",
+                &[1, 2],
+                arguments(),
+                std::iter::once("handy-haversacks").chain(args.iter().copied()),
+            )?;
+            bags_from_args(&args, &graph)
+        };
+        assert_eq!(vec![bag("shiny gold")], bags(&[]).unwrap());
+        assert_eq!(
+            vec![bag("dark olive"), bag("shiny gold")],
+            bags(&["--bag", "dark olive", "--bag", "shiny gold"]).unwrap()
+        );
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            bags(&["--bag", "dark red"]).unwrap_err().kind()
+        );
+        assert!(bags(&["--bag", "shiny"]).is_err());
+        assert!(bags(&["--bag"]).is_err());
+    }
+
+    #[test]
+    fn same_refusal_whatever_the_order() {
+        let refusal = || {
+            let mut graph = BagGraph::default();
+            for outer in &["vibrant plum", "faded blue", "dark olive"] {
+                graph
+                    .add_rule(&format!("{} bags contain 1 shiny gold bag.", outer))
+                    .unwrap();
+            }
+            graph
+                .add_rule(
+                    "shiny gold bags contain 1 vibrant plum bag, 2 faded blue bags, 3 dark olive bags.",
+                )
+                .unwrap_err()
+        };
+        let first = refusal();
+        assert_eq!(
+            "shiny gold bag would contain itself through dark olive bag",
+            first
+        );
+        for _ in 0..10 {
+            assert_eq!(first, refusal());
+        }
     }
 }
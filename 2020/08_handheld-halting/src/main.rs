@@ -1,15 +1,13 @@
 mod program;
 
 use program::Program;
-use std::{env, fs::File};
+use std::env;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        panic!("Only one argument is accepted, received {:#?}", args);
-    }
-    let file = File::open(&args[1]).expect(format!("expect file '{}' to exist", args[1]).as_str());
-    let mut program = Program::from(file);
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let input = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let mut program = Program::from(input);
     let state = program.execute();
     println!("State of the accumulator after loop {}", state.accumulator);
     program.fix();
@@ -18,6 +16,7 @@ fn main() {
         "State of the accumulator with normal exit {}",
         state.accumulator
     );
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -13,7 +13,7 @@ impl std::str::FromStr for Instruction {
         let code = split.next().expect("expect at least an code instruction");
         let value = split
             .next()
-            .expect(&format!("expect a parameter after the code '{}'", code))
+            .unwrap_or_else(|| panic!("expect a parameter after the code '{}'", code))
             .parse()
             .expect("expect parameter to be parseable as an 'isize'");
         let instruction = match code {
@@ -74,15 +74,9 @@ impl Program {
                         state.index += usize::try_from(*value)
                             .expect("expect 'offset: isize' to be convertible to 'usize'");
                     } else {
-                        state.index -= usize::try_from(
-                            value.checked_neg().expect(
-                                format!(
-                                    "expect jump offset to not be the minimum '{}'",
-                                    isize::MIN
-                                )
-                                .as_str(),
-                            ),
-                        )
+                        state.index -= usize::try_from(value.checked_neg().unwrap_or_else(|| {
+                            panic!("expect jump offset to not be the minimum '{}'", isize::MIN)
+                        }))
                         .expect("expect '-offset: isize' to be convertible to 'usize'");
                     }
                 }
@@ -135,10 +129,10 @@ impl std::iter::FromIterator<String> for Program {
     }
 }
 
-impl std::convert::From<std::fs::File> for Program {
-    fn from(file: std::fs::File) -> Self {
+impl std::convert::From<Box<dyn std::io::BufRead>> for Program {
+    fn from(input: Box<dyn std::io::BufRead>) -> Self {
         use std::io::BufRead;
-        std::io::BufReader::new(file)
+        input
             .lines()
             .map(|line| line.expect("expect line to be parseable as a String"))
             .collect()
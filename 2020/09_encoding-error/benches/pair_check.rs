@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use encoding_error::{PairCheck, Window};
+
+/// A full window of the numbers from 1 to `capacity`, out of order.
+fn window(capacity: usize) -> Window {
+    let mut window = Window::new(capacity);
+    for n in 0..capacity {
+        window.push((n * 7919) % capacity + 1);
+    }
+    window
+}
+
+fn pair_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pair_check");
+    group.sample_size(10);
+    for &capacity in &[25, 1_000, 10_000, 100_000] {
+        let window = window(capacity);
+        // No two numbers of the window sum up to it, the worst case
+        let number = 3 * capacity;
+        for &pair_check in &[PairCheck::Nested, PairCheck::Sorted, PairCheck::Parallel] {
+            // Trying every pair of the largest window takes minutes
+            if pair_check == PairCheck::Nested && capacity > 10_000 {
+                continue;
+            }
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", pair_check), capacity),
+                &number,
+                |b, &number| b.iter(|| window.pair(number, pair_check)),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, pair_check);
+criterion_main!(benches);
@@ -0,0 +1,360 @@
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Xmas {
+    Buffer(usize),
+    Valid(usize, usize, usize),
+    NotValid(usize),
+}
+
+/// How to look for two different numbers of the window summing up to a
+/// number. The timings for several sizes of window are in
+/// `benches/pair_check.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairCheck {
+    /// Try every pair of numbers of the window.
+    Nested,
+    /// Move two indexes toward each other in the sorted window.
+    Sorted,
+    /// Look for the complement of every number of the window in parallel.
+    Parallel,
+}
+
+/// Smallest window searched in parallel by `PairCheck::for_window`, below it
+/// spreading the search over the threads costs more than walking the sorted
+/// window.
+pub const PARALLEL_WINDOW: usize = 100_000;
+
+impl PairCheck {
+    /// The check suited to a window of `capacity` numbers: trying every pair
+    /// is quicker for small windows, searching in parallel only pays off for
+    /// the largest ones when rayon has more than one thread.
+    pub fn for_window(capacity: usize) -> Self {
+        if capacity <= 64 {
+            PairCheck::Nested
+        } else if capacity >= PARALLEL_WINDOW && rayon::current_num_threads() > 1 {
+            PairCheck::Parallel
+        } else {
+            PairCheck::Sorted
+        }
+    }
+}
+
+impl std::str::FromStr for PairCheck {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nested" => Ok(PairCheck::Nested),
+            "sorted" => Ok(PairCheck::Sorted),
+            "parallel" => Ok(PairCheck::Parallel),
+            _ => Err(format!(
+                "expect the check to be 'nested', 'sorted' or 'parallel', got '{}'",
+                s
+            )),
+        }
+    }
+}
+
+/// The last numbers of the stream, both in the order they came in and sorted.
+#[derive(Debug)]
+pub struct Window {
+    capacity: usize,
+    numbers: VecDeque<usize>,
+    sorted: Vec<usize>,
+}
+
+impl Window {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            numbers: VecDeque::with_capacity(capacity + 1),
+            sorted: Vec::with_capacity(capacity + 1),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.numbers.len() >= self.capacity
+    }
+
+    /// Add a number to the window, dropping the oldest one if the window was
+    /// full.
+    pub fn push(&mut self, number: usize) {
+        self.numbers.push_back(number);
+        let index = self.sorted.partition_point(|&n| n < number);
+        self.sorted.insert(index, number);
+        if self.numbers.len() > self.capacity {
+            if let Some(oldest) = self.numbers.pop_front() {
+                let index = self.sorted.partition_point(|&n| n < oldest);
+                self.sorted.remove(index);
+            }
+        }
+    }
+
+    /// Two different numbers of the window summing up to `number`, if any.
+    pub fn pair(&self, number: usize, pair_check: PairCheck) -> Option<(usize, usize)> {
+        match pair_check {
+            PairCheck::Nested => self.nested_pair(number),
+            PairCheck::Sorted => self.sorted_pair(number),
+            PairCheck::Parallel => self.parallel_pair(number),
+        }
+    }
+
+    fn nested_pair(&self, number: usize) -> Option<(usize, usize)> {
+        for operand1 in &self.numbers {
+            for operand2 in &self.numbers {
+                if operand1 != operand2 && operand1 + operand2 == number {
+                    return Some((*operand1, *operand2));
+                }
+            }
+        }
+        None
+    }
+
+    fn sorted_pair(&self, number: usize) -> Option<(usize, usize)> {
+        if self.sorted.is_empty() {
+            return None;
+        }
+        let (mut low, mut high) = (0, self.sorted.len() - 1);
+        while low < high {
+            let (operand1, operand2) = (self.sorted[low], self.sorted[high]);
+            match (operand1 + operand2).cmp(&number) {
+                std::cmp::Ordering::Less => low += 1,
+                std::cmp::Ordering::Greater => high -= 1,
+                // Every number between the two is the same one
+                std::cmp::Ordering::Equal if operand1 == operand2 => return None,
+                std::cmp::Ordering::Equal => return Some((operand1, operand2)),
+            }
+        }
+        None
+    }
+
+    fn parallel_pair(&self, number: usize) -> Option<(usize, usize)> {
+        self.numbers.par_iter().find_map_first(|&operand1| {
+            let operand2 = number.checked_sub(operand1)?;
+            if operand1 != operand2 && self.sorted.binary_search(&operand2).is_ok() {
+                Some((operand1, operand2))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct XmasIterator<I>
+where
+    I: Iterator<Item = String>,
+{
+    stream: I,
+    window: Window,
+    pair_check: PairCheck,
+}
+
+impl<I> XmasIterator<I>
+where
+    I: Iterator<Item = String>,
+{
+    pub fn new(stream: I, capacity: usize) -> Self {
+        Self {
+            stream,
+            window: Window::new(capacity),
+            pair_check: PairCheck::for_window(capacity),
+        }
+    }
+
+    /// Check the numbers with `pair_check` instead of the one suited to the
+    /// size of the window.
+    pub fn with_pair_check(mut self, pair_check: PairCheck) -> Self {
+        self.pair_check = pair_check;
+        self
+    }
+
+    pub fn xmas_number(self) -> (usize, Vec<usize>) {
+        let mut valid_numbers = Vec::new();
+        let invalid_number = self
+            .filter_map(|number| match number {
+                Xmas::NotValid(n) => Some(n),
+                Xmas::Valid(n, _, _) | Xmas::Buffer(n) => {
+                    valid_numbers.push(n);
+                    None
+                }
+            })
+            .next()
+            .expect("expect to find at least one invalid number");
+        for low_bound in 0..valid_numbers.len() {
+            let mut sum = 0;
+            for (up_bound, n) in valid_numbers[low_bound..].iter().enumerate() {
+                sum += n;
+                if sum == invalid_number {
+                    return (
+                        invalid_number,
+                        valid_numbers[low_bound..=(low_bound + up_bound)].to_vec(),
+                    );
+                }
+            }
+        }
+        panic!("expect to find a valid set of numbers that sums up to the first invalid number");
+    }
+}
+
+impl<I> std::iter::Iterator for XmasIterator<I>
+where
+    I: Iterator<Item = String>,
+{
+    type Item = Xmas;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(next) = self.stream.next() {
+            let number: usize = next
+                .parse()
+                .expect("expect the String to be parseable as a usize");
+            if !self.window.is_full() {
+                self.window.push(number);
+                // Buffer not filled up yet, just yielding the numbers
+                Some(Xmas::Buffer(number))
+            } else {
+                if let Some((operand1, operand2)) = self.window.pair(number, self.pair_check) {
+                    self.window.push(number);
+                    // New valid number to yield
+                    Some(Xmas::Valid(number, operand1, operand2))
+                } else {
+                    // No more valid number, ending the iterator
+                    Some(Xmas::NotValid(number))
+                }
+            }
+        } else {
+            // No more number in the file, ending the Iterator
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn xmas_simple() {
+        use Xmas::*;
+        let numbers = || (1usize..=25usize).map(|n| n.to_string());
+        // 26
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(26usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(Valid(26, 1, 25), next);
+        // 49
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(49usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(Valid(49, 24, 25), next);
+        // 100
+        let mut xmas =
+            XmasIterator::new(numbers().chain(std::iter::once(100usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(NotValid(100), next);
+        // 50
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(50usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(NotValid(50), next);
+    }
+
+    #[test]
+    fn xmas_missing_20() {
+        use Xmas::*;
+        let numbers = || {
+            (1usize..20usize)
+                .chain(21usize..=25usize)
+                .chain(std::iter::once(45))
+                .map(|n| n.to_string())
+        };
+        // 26
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(26usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(Valid(26, 1, 25), next);
+        // 65
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(65usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(NotValid(65), next);
+        // 64
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(64usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(Valid(64, 19, 45), next);
+        // 66
+        let mut xmas = XmasIterator::new(numbers().chain(std::iter::once(66usize.to_string())), 25);
+        let next = xmas.nth(25).unwrap();
+        assert_eq!(Valid(66, 21, 45), next);
+    }
+
+    #[test]
+    fn xmas() {
+        let numbers = vec![
+            35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309,
+            576,
+        ]
+        .into_iter()
+        .map(|n| n.to_string());
+        let numbers: Vec<String> = numbers.collect();
+        for pair_check in [PairCheck::Nested, PairCheck::Sorted, PairCheck::Parallel] {
+            let xmas =
+                XmasIterator::new(numbers.clone().into_iter(), 5).with_pair_check(pair_check);
+            let (invalid_number, range) = xmas.xmas_number();
+            assert_eq!(127, invalid_number);
+            assert_eq!(15, range.iter().copied().min().unwrap());
+            assert_eq!(47, range.iter().copied().max().unwrap());
+        }
+    }
+
+    #[test]
+    fn check_for_window() {
+        assert_eq!(PairCheck::Nested, PairCheck::for_window(25));
+        assert_eq!(PairCheck::Sorted, PairCheck::for_window(1_000));
+        let largest = if rayon::current_num_threads() > 1 {
+            PairCheck::Parallel
+        } else {
+            PairCheck::Sorted
+        };
+        assert_eq!(largest, PairCheck::for_window(PARALLEL_WINDOW));
+    }
+
+    #[test]
+    fn window_stays_sorted() {
+        let mut window = Window::new(3);
+        for number in [5, 1, 4, 1, 3] {
+            window.push(number);
+        }
+        assert_eq!(
+            vec![4, 1, 3],
+            window.numbers.iter().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![1, 3, 4], window.sorted);
+    }
+
+    #[test]
+    fn pair_checks_agree() {
+        let mut window = Window::new(100);
+        // Numbers from 1 to 100, out of order
+        for n in 0..100 {
+            window.push((n * 37) % 100 + 1);
+        }
+        for number in 0..250 {
+            let nested = window.pair(number, PairCheck::Nested);
+            assert_eq!(nested, window.pair(number, PairCheck::Parallel));
+            let sorted = window.pair(number, PairCheck::Sorted);
+            assert_eq!(nested.is_some(), sorted.is_some());
+            if let Some((operand1, operand2)) = sorted {
+                assert_ne!(operand1, operand2);
+                assert_eq!(number, operand1 + operand2);
+            }
+        }
+    }
+
+    #[test]
+    fn no_pair_of_the_same_number() {
+        let mut window = Window::new(4);
+        for number in [3, 3, 3, 10] {
+            window.push(number);
+        }
+        for pair_check in [PairCheck::Nested, PairCheck::Sorted, PairCheck::Parallel] {
+            assert_eq!(None, window.pair(6, pair_check));
+            assert_eq!(Some((3, 10)), window.pair(13, pair_check));
+        }
+    }
+}
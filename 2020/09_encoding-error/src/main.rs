@@ -1,117 +1,56 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use common::cli::{value_parser, Arg, Args};
+use encoding_error::{PairCheck, XmasIterator};
+use std::io::BufRead;
 
 #[derive(Debug, PartialEq, Eq)]
-enum Xmas {
-    Buffer(usize),
-    Valid(usize, usize, usize),
-    NotValid(usize),
+struct Options {
+    window: usize,
+    pair_check: Option<PairCheck>,
 }
 
-#[derive(Debug)]
-struct XmasIterator<I>
-where
-    I: Iterator<Item = String>,
-{
-    stream: I,
-    capacity: usize,
-    buffer: std::collections::VecDeque<usize>,
-}
-
-impl<I> XmasIterator<I>
-where
-    I: Iterator<Item = String>,
-{
-    fn new(stream: I, capacity: usize) -> Self {
-        Self {
-            stream,
-            capacity,
-            buffer: std::collections::VecDeque::new(),
-        }
-    }
-
-    fn check(&self, number: usize) -> Option<(usize, usize)> {
-        for operand1 in &self.buffer {
-            for operand2 in &self.buffer {
-                if operand1 != operand2 && operand1 + operand2 == number {
-                    return Some((*operand1, *operand2));
-                }
-            }
-        }
-        None
-    }
-
-    fn xmas_number(self) -> (usize, Vec<usize>) {
-        let mut valid_numbers = Vec::new();
-        let invalid_number = self
-            .filter_map(|number| match number {
-                Xmas::NotValid(n) => Some(n),
-                Xmas::Valid(n, _, _) | Xmas::Buffer(n) => {
-                    valid_numbers.push(n);
-                    None
-                }
-            })
-            .next()
-            .expect("expect to find at least one invalid number");
-        for low_bound in 0..valid_numbers.len() {
-            let mut sum = 0;
-            for (up_bound, n) in valid_numbers[low_bound..].iter().enumerate() {
-                sum += n;
-                if sum == invalid_number {
-                    return (
-                        invalid_number,
-                        valid_numbers[low_bound..=(low_bound + up_bound)].to_vec(),
-                    );
-                }
-            }
-        }
-        panic!("expect to find a valid set of numbers that sums up to the first invalid number");
+impl Options {
+    /// Arguments `--window <SIZE>` and `--check <nested|sorted|parallel>`,
+    /// to be given to `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("window")
+                .long("window")
+                .value_name("SIZE")
+                .value_parser(value_parser!(u64).range(2..))
+                .default_value("25")
+                .help("Numbers a number must be the sum of two of"),
+            Arg::new("check")
+                .long("check")
+                .value_name("CHECK")
+                .value_parser(value_parser!(PairCheck))
+                .help("Search of the pairs: nested, sorted or parallel"),
+        ]
     }
-}
 
-impl<I> std::iter::Iterator for XmasIterator<I>
-where
-    I: Iterator<Item = String>,
-{
-    type Item = Xmas;
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(next) = self.stream.next() {
-            let number: usize = next
-                .parse()
-                .expect("expect the String to be parseable as a usize");
-            if self.buffer.len() < self.capacity {
-                self.buffer.push_back(number);
-                // Buffer not filled up yet, just yielding the numbers
-                Some(Xmas::Buffer(number))
-            } else {
-                if let Some((operand1, operand2)) = self.check(number) {
-                    self.buffer.push_back(number);
-                    self.buffer.pop_front();
-                    // New valid number to yield
-                    Some(Xmas::Valid(number, operand1, operand2))
-                } else {
-                    // No more valid number, ending the iterator
-                    Some(Xmas::NotValid(number))
-                }
-            }
-        } else {
-            // No more number in the file, ending the Iterator
-            None
+    /// Options read from `args`.
+    fn from_args(args: &Args) -> Self {
+        let window: u64 = args.get("window").expect("expect a default window");
+        Options {
+            window: window as usize,
+            pair_check: args.get("check"),
         }
     }
 }
 
 fn main() {
-    let file = File::open("xmas.txt").expect("expect file 'xmas.txt' to exist");
-    let reader = BufReader::new(file);
-    let xmas = XmasIterator::new(
+    let args = Args::parse_with(env!("CARGO_PKG_NAME"), &[1, 2], Options::arguments());
+    let options = Options::from_args(&args);
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let mut xmas = XmasIterator::new(
         reader
             .lines()
             .map(|line| line.expect("expect line to be parseable as a String")),
-        25,
+        options.window,
     );
+    if let Some(pair_check) = options.pair_check {
+        xmas = xmas.with_pair_check(pair_check);
+    }
     let (first_invalid, range) = xmas.xmas_number();
     println!("First invalid number is {}", first_invalid);
     println!(
@@ -120,74 +59,43 @@ fn main() {
         range.iter().copied().max().unwrap(),
         range.iter().copied().min().unwrap() + range.iter().copied().max().unwrap(),
     );
+    args.report_time();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse(args: &[&str]) -> Result<Options, common::cli::Error> {
+        let args = Args::try_parse_from(
+            "encoding-error",
+            &[1, 2],
+            Options::arguments(),
+            std::iter::once("encoding-error").chain(args.iter().copied()),
+        )?;
+        Ok(Options::from_args(&args))
+    }
+
     #[test]
-    fn xmas_simple() {
-        use Xmas::*;
-        let numbers = || (1usize..=25usize).into_iter().map(|n| n.to_string());
-        // 26
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(26usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(Valid(26, 1, 25), next);
-        // 49
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(49usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(Valid(49, 24, 25), next);
-        // 100
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(100usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(NotValid(100), next);
-        // 50
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(50usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(NotValid(50), next);
+    fn default_options() {
+        let options = parse(&[]).unwrap();
+        assert_eq!(25, options.window);
+        assert_eq!(None, options.pair_check);
     }
 
     #[test]
-    fn xmas_missing_20() {
-        use Xmas::*;
-        let numbers = || {
-            (1usize..20usize)
-                .into_iter()
-                .chain(21usize..=25usize)
-                .chain(std::iter::once(45))
-                .map(|n| n.to_string())
-        };
-        // 26
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(26usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(Valid(26, 1, 25), next);
-        // 65
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(65usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(NotValid(65), next);
-        // 64
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(64usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(Valid(64, 19, 45), next);
-        // 66
-        let xmas = XmasIterator::new(numbers().chain(std::iter::once(66usize.to_string())), 25);
-        let next = xmas.skip(25).next().unwrap();
-        assert_eq!(Valid(66, 21, 45), next);
+    fn window_and_check() {
+        let options = parse(&["--window", "1000", "input.txt", "--check", "parallel"]).unwrap();
+        assert_eq!(1000, options.window);
+        assert_eq!(Some(PairCheck::Parallel), options.pair_check);
     }
 
     #[test]
-    fn xmas() {
-        let numbers = vec![
-            35, 20, 15, 25, 47, 40, 62, 55, 65, 95, 102, 117, 150, 182, 127, 219, 299, 277, 309,
-            576,
-        ]
-        .into_iter()
-        .map(|n| n.to_string());
-        let xmas = XmasIterator::new(numbers, 5);
-        let (invalid_number, range) = xmas.xmas_number();
-        assert_eq!(127, invalid_number);
-        assert_eq!(15, range.iter().copied().min().unwrap());
-        assert_eq!(47, range.iter().copied().max().unwrap());
+    fn invalid_options() {
+        assert!(parse(&["--window", "1"]).is_err());
+        assert!(parse(&["--window"]).is_err());
+        assert!(parse(&["--check", "fastest"]).is_err());
+        assert!(parse(&["--parallel"]).is_err());
+        assert!(parse(&["a.txt", "b.txt"]).is_err());
     }
 }
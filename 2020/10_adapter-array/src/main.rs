@@ -73,7 +73,10 @@ impl Adapters {
 }
 
 fn main() {
-    let adapters = Adapters::from(include_str!("../adapters.txt").trim().split('\n'));
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let adapters = Adapters::from(input.trim().split('\n'));
     let (one, two, three) = adapters.differences();
     println!(
         "There is respectively {}, {} and {} 1-2-3 differences (1 * 3 -> {})",
@@ -86,6 +89,7 @@ fn main() {
         "There is {} possible adapters arrangements",
         adapters.arrangements()
     );
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -1,9 +1,12 @@
-#[cfg(not(feature = "sight"))]
-const OCCUPIED_LIMIT: usize = 4;
-#[cfg(feature = "sight")]
-const OCCUPIED_LIMIT: usize = 5;
+use common::{
+    automaton::{Automaton, Stabilization},
+    cli::{self, value_parser, Arg, Args},
+    genvec::{Request, Rng},
+    render::Render,
+};
+use std::{io, ops::Range};
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
 enum Emplacement {
     Floor,
     Empty,
@@ -21,6 +24,18 @@ impl std::fmt::Debug for Emplacement {
     }
 }
 
+#[cfg(feature = "animation")]
+impl Emplacement {
+    fn color(_: common::render::Position, emplacement: Option<&Emplacement>) -> viz::Rgb {
+        use Emplacement::*;
+        match emplacement {
+            None | Some(Floor) => [0, 0, 0],
+            Some(Empty) => [32, 160, 32],
+            Some(Occupied) => [224, 32, 32],
+        }
+    }
+}
+
 impl std::str::FromStr for Emplacement {
     type Err = std::convert::Infallible;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
@@ -37,27 +52,33 @@ impl std::str::FromStr for Emplacement {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Hash)]
 struct Boat {
     emplacements: Vec<Vec<Emplacement>>,
 }
 
+impl std::fmt::Display for Boat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let render = Render::new(
+            &self.emplacements,
+            |_, emplacement: Option<&Emplacement>| {
+                format!("{:?}", emplacement.expect("expect a complete boat"))
+            },
+        );
+        write!(f, "{}", render)
+    }
+}
+
 impl std::fmt::Debug for Boat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
         writeln!(f)?;
-        for emplacements_line in &self.emplacements {
-            for emplacement in emplacements_line {
-                write!(f, "{:?}", emplacement)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self)
     }
 }
 
-impl<I> std::convert::From<I> for Boat
+impl<'a, I> std::convert::From<I> for Boat
 where
-    I: Iterator<Item = &'static str>,
+    I: Iterator<Item = &'a str>,
 {
     fn from(stream: I) -> Self {
         let emplacements = stream.fold(Vec::<Vec<Emplacement>>::new(), |mut emplacements, line| {
@@ -69,7 +90,7 @@ where
                         .expect("expect line to be parseable as Cell")
                 })
                 .collect::<Vec<Emplacement>>();
-            if emplacements.len() != 0 && emplacements_line.len() != emplacements[0].len() {
+            if !emplacements.is_empty() && emplacements_line.len() != emplacements[0].len() {
                 panic!("expect every emplacement's line in the boat to be of the same length");
             }
             emplacements.push(emplacements_line);
@@ -79,118 +100,272 @@ where
     }
 }
 
-enum Direction {
-    Up,
-    Down,
-    None,
+/// Rule deciding which emplacements are the neighbors of a seat.
+trait NeighborStrategy {
+    /// Number of occupied neighbors from which an occupied seat is left.
+    fn occupied_limit(&self) -> usize;
+    /// Positions of the neighbors of the emplacement at position `(x, y)` in
+    /// the `boat`.
+    fn neighbors(&self, boat: &Boat, position: (usize, usize)) -> Vec<(usize, usize)>;
 }
-impl Direction {
-    fn from(&self, value: usize) -> usize {
-        match self {
-            Direction::Up => value + 1,
-            Direction::Down => value - 1,
-            Direction::None => value,
-        }
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+
+/// The 8 emplacements around the seat.
+#[cfg(any(test, not(feature = "sight")))]
+struct Adjacent;
+
+#[cfg(any(test, not(feature = "sight")))]
+impl NeighborStrategy for Adjacent {
+    fn occupied_limit(&self) -> usize {
+        4
+    }
+    fn neighbors(&self, boat: &Boat, position: (usize, usize)) -> Vec<(usize, usize)> {
+        DIRECTIONS
+            .iter()
+            .filter_map(|&direction| boat.neighbor(position, direction, 1))
+            .collect()
+    }
+}
+
+/// The first seat seen in each of the 8 directions, looking over the floor.
+#[cfg(any(test, feature = "sight"))]
+struct LineOfSight;
+
+#[cfg(any(test, feature = "sight"))]
+impl NeighborStrategy for LineOfSight {
+    fn occupied_limit(&self) -> usize {
+        5
+    }
+    fn neighbors(&self, boat: &Boat, position: (usize, usize)) -> Vec<(usize, usize)> {
+        DIRECTIONS
+            .iter()
+            .filter_map(|&direction| {
+                (1..)
+                    .map(|distance| boat.neighbor(position, direction, distance))
+                    .find(|&neighbor| {
+                        neighbor.map(|neighbor| boat.emplacement(neighbor))
+                            != Some(Emplacement::Floor)
+                    })
+                    .flatten()
+            })
+            .collect()
+    }
+}
+
+/// Every emplacement in the square of `radius` emplacements around the seat.
+struct Radius {
+    radius: usize,
+    occupied_limit: usize,
+}
+
+impl NeighborStrategy for Radius {
+    fn occupied_limit(&self) -> usize {
+        self.occupied_limit
+    }
+    fn neighbors(&self, boat: &Boat, position: (usize, usize)) -> Vec<(usize, usize)> {
+        let radius = self.radius as isize;
+        (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(|&direction| direction != (0, 0))
+            .filter_map(|direction| boat.neighbor(position, direction, 1))
+            .collect()
     }
 }
+
 impl Boat {
-    fn direction_seat(
+    /// Position `distance` times `direction` away from `position`, if still in the boat.
+    fn neighbor(
         &self,
         position: (usize, usize),
-        direction: (Direction, Direction),
-    ) -> Option<Emplacement> {
-        let height = self.emplacements.len();
-        let width = self.emplacements[0].len();
-        let x = direction.0.from(position.0);
-        let y = direction.1.from(position.1);
-        if x == 0 || x > width || y == 0 || y > height {
-            None
-        } else {
-            let emplacement = self.emplacements[y - 1][x - 1];
-            #[cfg(not(feature = "sight"))]
-            {
-                Some(emplacement)
-            }
-            #[cfg(feature = "sight")]
-            if emplacement == Emplacement::Floor {
-                {
-                    self.direction_seat((x, y), direction)
-                }
-            } else {
-                Some(emplacement)
-            }
+        direction: (isize, isize),
+        distance: isize,
+    ) -> Option<(usize, usize)> {
+        let x = position.0 as isize + direction.0 * distance;
+        let y = position.1 as isize + direction.1 * distance;
+        if x < 0 || y < 0 {
+            return None;
         }
+        let (x, y) = (x as usize, y as usize);
+        self.emplacements
+            .get(y)
+            .and_then(|emplacements_line| emplacements_line.get(x))
+            .map(|_| (x, y))
     }
-    fn in_sight(&self, x: usize, y: usize) -> impl Iterator<Item = Emplacement> + '_ {
-        use Direction::*;
-        vec![
-            (Down, Down),
-            (Down, None),
-            (Down, Up),
-            (None, Up),
-            (Up, Up),
-            (Up, None),
-            (Up, Down),
-            (None, Down),
-        ]
-        .into_iter()
-        .filter_map(move |direction| self.direction_seat((x, y), direction))
-    }
-    fn round(&mut self) {
-        let height = self.emplacements.len();
-        let width = self.emplacements[0].len();
-        let mut emplacements = Vec::new();
-        for y in 1..=height {
-            let mut emplacements_line = Vec::new();
-            for x in 1..=width {
-                let occupied = self
-                    .in_sight(x, y)
-                    .filter(|&e| e == Emplacement::Occupied)
-                    .count();
-                match (self.emplacements[y - 1][x - 1], occupied) {
-                    (Emplacement::Floor, _) => emplacements_line.push(Emplacement::Floor),
-                    (Emplacement::Empty, 0) => emplacements_line.push(Emplacement::Occupied),
-                    (Emplacement::Occupied, o) if o >= OCCUPIED_LIMIT => {
-                        emplacements_line.push(Emplacement::Empty)
-                    }
-                    (e, _) => emplacements_line.push(e),
-                }
-            }
-            emplacements.push(emplacements_line);
-        }
-        std::mem::swap(&mut self.emplacements, &mut emplacements);
-    }
-    fn stabilize(&mut self) {
-        loop {
-            let previous_boat = self.clone();
-            self.round();
-            if self == &previous_boat {
-                return;
-            }
-        }
+    fn emplacement(&self, (x, y): (usize, usize)) -> Emplacement {
+        self.emplacements[y][x]
     }
     fn occupied(&self) -> usize {
+        self.row_counts().iter().sum()
+    }
+    /// Occupied seats whose column is in `x_range` and whose line is in
+    /// `y_range`, the parts of the ranges out of the boat being ignored.
+    fn occupied_in(&self, x_range: Range<usize>, y_range: Range<usize>) -> usize {
         self.emplacements
             .iter()
-            .flatten()
+            .take(y_range.end)
+            .skip(y_range.start)
+            .flat_map(|line| line.iter().take(x_range.end).skip(x_range.start))
             .filter(|&&emplacement| emplacement == Emplacement::Occupied)
             .count()
     }
+    /// Occupied seats of each line, from the first one.
+    fn row_counts(&self) -> Vec<usize> {
+        (0..self.emplacements.len())
+            .map(|y| self.occupied_in(0..usize::MAX, y..y + 1))
+            .collect()
+    }
+}
+
+/// Each round, an empty seat without occupied neighbors is taken, and an
+/// occupied seat with too many occupied neighbors is left.
+impl<S: NeighborStrategy> Automaton<S> for Boat {
+    type Position = (usize, usize);
+    type State = Emplacement;
+
+    fn positions(&self, _: &S) -> Vec<(usize, usize)> {
+        self.emplacements
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| (0..line.len()).map(move |x| (x, y)))
+            .collect()
+    }
+    fn state(&self, position: (usize, usize)) -> Emplacement {
+        self.emplacement(position)
+    }
+    fn neighbors(&self, strategy: &S, position: (usize, usize)) -> Vec<(usize, usize)> {
+        strategy.neighbors(self, position)
+    }
+    fn transition(
+        strategy: &S,
+        emplacement: Emplacement,
+        neighbors: &[Emplacement],
+    ) -> Emplacement {
+        let occupied = neighbors
+            .iter()
+            .filter(|&&e| e == Emplacement::Occupied)
+            .count();
+        match (emplacement, occupied) {
+            (Emplacement::Empty, 0) => Emplacement::Occupied,
+            (Emplacement::Occupied, o) if o >= strategy.occupied_limit() => Emplacement::Empty,
+            (e, _) => e,
+        }
+    }
+    fn with_states(&self, emplacements: Vec<((usize, usize), Emplacement)>) -> Self {
+        let mut boat = self.clone();
+        for ((x, y), emplacement) in emplacements {
+            boat.emplacements[y][x] = emplacement;
+        }
+        boat
+    }
+}
+
+/// A boat of `size` by `size` where about one emplacement out of six is
+/// floor, like the puzzle inputs, every seat being empty.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    common::genvec::grid(rng, size, size, 83, 'L', '.')
+}
+
+/// Arguments `--radius <RADIUS>` and `--occupied-limit <COUNT>`, next to the
+/// ones of `genvec::Request`, to be given to `cli::Args::parse_with`.
+fn arguments() -> Vec<Arg> {
+    let mut arguments = vec![
+        Arg::new("radius")
+            .long("radius")
+            .value_name("RADIUS")
+            .value_parser(value_parser!(u64).range(1..))
+            .help("Take every emplacement at most RADIUS away as a neighbor"),
+        Arg::new("occupied-limit")
+            .long("occupied-limit")
+            .value_name("COUNT")
+            .value_parser(value_parser!(u64))
+            .help("Occupied neighbors from which a seat is left with --radius, 4 by default"),
+    ];
+    arguments.extend(Request::arguments());
+    arguments
+}
+
+/// Neighbors in the radius given with `--radius`, if any.
+fn radius_from_args(args: &Args) -> Result<Option<Radius>, cli::Error> {
+    let occupied_limit = args.get::<u64>("occupied-limit");
+    match args.get::<u64>("radius") {
+        Some(radius) => Ok(Some(Radius {
+            radius: radius as usize,
+            occupied_limit: occupied_limit.unwrap_or(4) as usize,
+        })),
+        None if occupied_limit.is_some() => {
+            Err(args.error("'--occupied-limit' only applies with '--radius'"))
+        }
+        None => Ok(None),
+    }
 }
 
-fn main() {
-    let mut boat = Boat::from(include_str!("../boat.txt").trim().split('\n'));
-    boat.stabilize();
-    println!("There is {} occupied seats", boat.occupied());
+/// Rounds of `boat` with the neighbors of `strategy` until it stabilizes or
+/// oscillates, drawn as an animation with the `animation` feature.
+fn stabilize<S: NeighborStrategy>(boat: &mut Boat, strategy: &S) -> io::Result<Stabilization> {
+    #[cfg(not(feature = "animation"))]
+    let stabilization = boat.stabilize(strategy);
+    #[cfg(feature = "animation")]
+    let stabilization = {
+        let mut animation = viz::Animation::default();
+        animation.capture(&boat.emplacements, Emplacement::color);
+        let stabilization = boat.stabilize_with(strategy, |boat| {
+            animation.capture(&boat.emplacements, Emplacement::color)
+        });
+        animation.save(viz::path())?;
+        stabilization
+    };
+    Ok(stabilization)
+}
+
+fn main() -> io::Result<()> {
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "sight") { 2 } else { 1 }],
+        arguments(),
+    );
+    if let Some(request) = Request::from_args(&args) {
+        println!("{}", generate(&mut request.rng(), request.size));
+        return Ok(());
+    }
+    let radius = radius_from_args(&args).unwrap_or_else(|error| error.exit());
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let mut boat = Boat::from(input.trim().split('\n'));
+    #[cfg(not(feature = "sight"))]
+    let strategy = Adjacent;
+    #[cfg(feature = "sight")]
+    let strategy = LineOfSight;
+    let stabilization = match radius {
+        Some(radius) => stabilize(&mut boat, &radius)?,
+        None => stabilize(&mut boat, &strategy)?,
+    };
+    match stabilization {
+        Stabilization::Stabilized(_) => println!("There is {} occupied seats", boat.occupied()),
+        Stabilization::Cycle { start, period } => println!(
+            "The seats never stabilize, they come back every {} rounds from round {}",
+            period, start
+        ),
+    }
+    args.report_time();
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[cfg(not(feature = "sight"))]
     #[test]
-    fn boat() {
+    fn adjacent() {
         let boat0 = Boat::from(
             r#"L.LL.LL.LL
 LLLLLLL.LL
@@ -270,25 +445,25 @@ L.#.L..#..
                 .split('\n'),
         );
         let mut boat = boat0.clone();
-        boat.round();
-        assert_eq!(boat1, boat, "different after 1 iteration");
-        boat.round();
-        assert_eq!(boat2, boat, "different after 2 iterations");
-        boat.round();
-        assert_eq!(boat3, boat, "different after 3 iterations");
-        boat.round();
-        assert_eq!(boat4, boat, "different after 4 iterations");
-        boat.round();
-        assert_eq!(boat5, boat, "different after 5 iterations");
+        boat = boat.step(&Adjacent);
+        common::assert_grid_eq!(boat1, boat, "different after 1 iteration");
+        boat = boat.step(&Adjacent);
+        common::assert_grid_eq!(boat2, boat, "different after 2 iterations");
+        boat = boat.step(&Adjacent);
+        common::assert_grid_eq!(boat3, boat, "different after 3 iterations");
+        boat = boat.step(&Adjacent);
+        common::assert_grid_eq!(boat4, boat, "different after 4 iterations");
+        boat = boat.step(&Adjacent);
+        common::assert_grid_eq!(boat5, boat, "different after 5 iterations");
         assert_eq!(37, boat.occupied());
         let mut boat = boat0.clone();
-        boat.stabilize();
+        boat.stabilize(&Adjacent);
         assert_eq!(37, boat.occupied());
+        common::assert_display_snapshot!(boat, "adjacent.txt");
     }
 
-    #[cfg(feature = "sight")]
     #[test]
-    fn boat() {
+    fn line_of_sight() {
         let boat0 = Boat::from(
             r#"L.LL.LL.LL
 LLLLLLL.LL
@@ -381,21 +556,194 @@ LLL###LLL#
                 .split('\n'),
         );
         let mut boat = boat0.clone();
-        boat.round();
-        assert_eq!(boat1, boat, "different after 1 iteration");
-        boat.round();
-        assert_eq!(boat2, boat, "different after 2 iterations");
-        boat.round();
-        assert_eq!(boat3, boat, "different after 3 iterations");
-        boat.round();
-        assert_eq!(boat4, boat, "different after 4 iterations");
-        boat.round();
-        assert_eq!(boat5, boat, "different after 5 iterations");
-        boat.round();
-        assert_eq!(boat6, boat, "different after 6 iterations");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat1, boat, "different after 1 iteration");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat2, boat, "different after 2 iterations");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat3, boat, "different after 3 iterations");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat4, boat, "different after 4 iterations");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat5, boat, "different after 5 iterations");
+        boat = boat.step(&LineOfSight);
+        common::assert_grid_eq!(boat6, boat, "different after 6 iterations");
         assert_eq!(26, boat.occupied());
         let mut boat = boat0.clone();
-        boat.stabilize();
+        boat.stabilize(&LineOfSight);
         assert_eq!(26, boat.occupied());
+        common::assert_display_snapshot!(boat, "line-of-sight.txt");
+    }
+
+    #[test]
+    fn radius_one_is_adjacent() {
+        let boat0 = Boat::from(
+            r#"L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL"#
+                .split('\n'),
+        );
+        let radius = Radius {
+            radius: 1,
+            occupied_limit: 4,
+        };
+        let mut adjacent_boat = boat0.clone();
+        let mut radius_boat = boat0;
+        for iteration in 1..=5 {
+            adjacent_boat = adjacent_boat.step(&Adjacent);
+            radius_boat = radius_boat.step(&radius);
+            common::assert_grid_eq!(
+                adjacent_boat,
+                radius_boat,
+                "different after {} iterations",
+                iteration
+            );
+        }
+        radius_boat.stabilize(&radius);
+        assert_eq!(37, radius_boat.occupied());
+    }
+
+    #[test]
+    fn stabilized_or_cycle() {
+        let boat0 = Boat::from(
+            r#"L.LL.LL.LL
+LLLLLLL.LL
+L.L.L..L..
+LLLL.LL.LL
+L.LL.LL.LL
+L.LLLLL.LL
+..L.L.....
+LLLLLLLLLL
+L.LLLLLL.L
+L.LLLLL.LL"#
+                .split('\n'),
+        );
+        let mut boat = boat0.clone();
+        assert_eq!(Stabilization::Stabilized(5), boat.stabilize(&Adjacent));
+        let mut boat = boat0;
+        assert_eq!(Stabilization::Stabilized(6), boat.stabilize(&LineOfSight));
+        // Every occupied seat is left, so a lone seat is taken and left again
+        let never_occupied = Radius {
+            radius: 1,
+            occupied_limit: 0,
+        };
+        let mut boat = Boat::from("L.L".split('\n'));
+        assert_eq!(
+            Stabilization::Cycle {
+                start: 0,
+                period: 2
+            },
+            boat.stabilize(&never_occupied)
+        );
+        // Both seats are left first, then taken and left together
+        let mut boat = Boat::from("#L".split('\n'));
+        let mut rounds = 0;
+        let stabilization = boat.stabilize_with(&never_occupied, |_| rounds += 1);
+        assert_eq!(
+            Stabilization::Cycle {
+                start: 1,
+                period: 2
+            },
+            stabilization
+        );
+        assert_eq!(3, rounds);
+    }
+
+    #[test]
+    fn radius_options() {
+        let radius = |args: &[&str]| {
+            let args = Args::try_parse_from(
+                "seating-system",
+                &[1],
+                arguments(),
+                std::iter::once("seating-system").chain(args.iter().copied()),
+            )?;
+            radius_from_args(&args)
+        };
+        assert!(radius(&[]).unwrap().is_none());
+        let selected = radius(&["--radius", "2"]).unwrap().unwrap();
+        assert_eq!((2, 4), (selected.radius, selected.occupied_limit));
+        let selected = radius(&["--radius", "2", "--occupied-limit", "12"])
+            .unwrap()
+            .unwrap();
+        assert_eq!((2, 12), (selected.radius, selected.occupied_limit));
+        assert!(radius(&["--radius", "0"]).is_err());
+        assert!(radius(&["--occupied-limit", "12"]).is_err());
+    }
+
+    #[test]
+    fn radius_two() {
+        let radius = Radius {
+            radius: 2,
+            occupied_limit: 12,
+        };
+        let mut boat = Boat::from(
+            r#"#####
+#####
+#####
+#####
+#####"#
+                .split('\n'),
+        );
+        assert_eq!(8, radius.neighbors(&boat, (0, 0)).len());
+        assert_eq!(11, radius.neighbors(&boat, (1, 0)).len());
+        assert_eq!(24, radius.neighbors(&boat, (2, 2)).len());
+        boat = boat.step(&radius);
+        let expected = Boat::from(
+            r#"##L##
+#LLL#
+LLLLL
+#LLL#
+##L##"#
+                .split('\n'),
+        );
+        common::assert_grid_eq!(expected, boat);
+    }
+
+    #[test]
+    fn generated_boat() {
+        let text = generate(&mut Rng::new(common::genvec::SEED), 30);
+        let mut boat = Boat::from(text.lines());
+        assert_eq!(30, boat.emplacements.len());
+        assert_eq!(0, boat.occupied());
+        let mut sight = boat.clone();
+        assert!(matches!(
+            boat.stabilize(&Adjacent),
+            Stabilization::Stabilized(_)
+        ));
+        assert!(matches!(
+            sight.stabilize(&LineOfSight),
+            Stabilization::Stabilized(_)
+        ));
+        assert!(boat.occupied() > 0);
+    }
+
+    #[test]
+    fn occupied_by_region() {
+        let boat = Boat::from(
+            r#"#.#L
+L##.
+#..#"#
+                .split('\n'),
+        );
+        assert_eq!(vec![2, 2, 2], boat.row_counts());
+        assert_eq!(1, boat.occupied_in(1..3, 0..1));
+        assert_eq!(2, boat.occupied_in(2..10, 1..5));
+        assert_eq!(0, boat.occupied_in(0..0, 0..3));
+        let quarters = [
+            boat.occupied_in(0..2, 0..2),
+            boat.occupied_in(2..4, 0..2),
+            boat.occupied_in(0..2, 2..3),
+            boat.occupied_in(2..4, 2..3),
+        ];
+        assert_eq!([2, 2, 1, 1], quarters);
+        assert_eq!(boat.occupied(), quarters.iter().sum());
     }
 }
@@ -1,12 +1,11 @@
-#[cfg(not(feature = "waypoint"))]
+use common::cli::{Arg, ArgAction, Args};
+
 #[derive(Debug, Clone, Copy)]
 enum Direction {
-    Forward,
     Left,
     Right,
 }
 
-#[cfg(not(feature = "waypoint"))]
 #[derive(Debug, Clone, Copy)]
 enum Cardinal {
     North,
@@ -14,21 +13,20 @@ enum Cardinal {
     South,
     West,
 }
-#[cfg(not(feature = "waypoint"))]
 impl Cardinal {
     fn turn(&self, direction: Direction) -> Self {
         use Cardinal::*;
         use Direction::*;
         match (*self, direction) {
-            (North, Left) | (South, Right) | (West, Forward) => West,
-            (North, Right) | (South, Left) | (East, Forward) => East,
-            (North, Forward) | (West, Right) | (East, Left) => North,
-            (South, Forward) | (West, Left) | (East, Right) => South,
+            (North, Left) | (South, Right) => West,
+            (North, Right) | (South, Left) => East,
+            (West, Right) | (East, Left) => North,
+            (West, Left) | (East, Right) => South,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Action {
     North(isize),
     East(isize),
@@ -62,30 +60,71 @@ impl std::str::FromStr for Action {
     }
 }
 
+impl std::fmt::Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use Action::*;
+        match self {
+            North(n) => write!(f, "N{}", n),
+            East(n) => write!(f, "E{}", n),
+            South(n) => write!(f, "S{}", n),
+            West(n) => write!(f, "W{}", n),
+            Left(n) => write!(f, "L{}", n * 90),
+            Right(n) => write!(f, "R{}", n * 90),
+            Forward(n) => write!(f, "F{}", n),
+        }
+    }
+}
+
+/// How the actions move the boat: `Facing` moves the boat itself, while
+/// `Waypoint` (see feature `waypoint`) moves a waypoint the boat follows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Navigation {
+    Facing,
+    Waypoint,
+}
+
+impl std::default::Default for Navigation {
+    fn default() -> Self {
+        if cfg!(feature = "waypoint") {
+            Navigation::Waypoint
+        } else {
+            Navigation::Facing
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Boat {
+    navigation: Navigation,
     position: (isize, isize),
-    #[cfg(not(feature = "waypoint"))]
     facing: Cardinal,
-    #[cfg(feature = "waypoint")]
     waypoint: (isize, isize),
 }
 
-impl std::default::Default for Boat {
-    fn default() -> Self {
+impl Boat {
+    fn new(navigation: Navigation) -> Self {
         Self {
+            navigation,
             position: (0, 0),
-            #[cfg(not(feature = "waypoint"))]
             facing: Cardinal::East,
-            #[cfg(feature = "waypoint")]
             waypoint: (10, 1),
         }
     }
-}
-impl Boat {
+
     fn step(mut self, action: Action) -> Self {
+        self.apply(action);
+        self
+    }
+
+    fn apply(&mut self, action: Action) {
+        match self.navigation {
+            Navigation::Facing => self.step_facing(action),
+            Navigation::Waypoint => self.step_waypoint(action),
+        }
+    }
+
+    fn step_facing(&mut self, action: Action) {
         use Action::*;
-        #[cfg(not(feature = "waypoint"))]
         match action {
             North(n) => self.position.1 += n,
             East(n) => self.position.0 += n,
@@ -94,13 +133,16 @@ impl Boat {
             Left(n) => (0..n).for_each(|_| self.facing = self.facing.turn(Direction::Left)),
             Right(n) => (0..n).for_each(|_| self.facing = self.facing.turn(Direction::Right)),
             Forward(n) => match self.facing {
-                Cardinal::North => self = self.step(Action::North(n)),
-                Cardinal::East => self = self.step(Action::East(n)),
-                Cardinal::South => self = self.step(Action::South(n)),
-                Cardinal::West => self = self.step(Action::West(n)),
+                Cardinal::North => self.step_facing(Action::North(n)),
+                Cardinal::East => self.step_facing(Action::East(n)),
+                Cardinal::South => self.step_facing(Action::South(n)),
+                Cardinal::West => self.step_facing(Action::West(n)),
             },
         }
-        #[cfg(feature = "waypoint")]
+    }
+
+    fn step_waypoint(&mut self, action: Action) {
+        use Action::*;
         match action {
             North(n) => self.waypoint.1 += n,
             East(n) => self.waypoint.0 += n,
@@ -127,8 +169,8 @@ impl Boat {
                 self.position.1 += y * n;
             }
         }
-        self
     }
+
     fn manhattan(&self) -> isize {
         self.position.0.abs() + self.position.1.abs()
     }
@@ -167,17 +209,90 @@ impl<I> Actions<I>
 where
     I: Iterator<Item = &'static str>,
 {
-    fn execute(self) -> Boat {
-        self.fold(Boat::default(), |boat, action| boat.step(action))
+    fn execute(self, navigation: Navigation) -> Boat {
+        self.fold(Boat::new(navigation), |boat, action| boat.step(action))
+    }
+}
+
+/// Position of the boat with each navigation, after one action.
+#[derive(Debug)]
+struct TraceStep {
+    action: Action,
+    facing: (isize, isize),
+    waypoint: (isize, isize),
+}
+
+/// Run the actions with both navigations at once, keeping where each one
+/// took the boat after every action.
+fn trace(actions: impl Iterator<Item = Action>) -> Vec<TraceStep> {
+    let mut facing = Boat::new(Navigation::Facing);
+    let mut waypoint = Boat::new(Navigation::Waypoint);
+    actions
+        .map(|action| {
+            facing.apply(action);
+            waypoint.apply(action);
+            TraceStep {
+                action,
+                facing: facing.position,
+                waypoint: waypoint.position,
+            }
+        })
+        .collect()
+}
+
+/// Index of the first action after which the navigations put the boat in
+/// different positions.
+fn divergence(trace: &[TraceStep]) -> Option<usize> {
+    trace.iter().position(|step| step.facing != step.waypoint)
+}
+
+fn print_trace(trace: &[TraceStep]) {
+    let position = |(east, north): (isize, isize)| format!("({}, {})", east, north);
+    println!("    # action facing           waypoint");
+    for (index, step) in trace.iter().enumerate() {
+        println!(
+            "{:>5} {:<6} {:<16} {}",
+            index + 1,
+            step.action.to_string(),
+            position(step.facing),
+            position(step.waypoint)
+        );
+    }
+    match divergence(trace) {
+        Some(index) => println!(
+            "The navigations diverge at action {} ({})",
+            index + 1,
+            trace[index].action
+        ),
+        None => println!("The navigations never diverge"),
     }
 }
 
+/// Argument `--trace`, to be given to `cli::Args::parse_with`.
+fn arguments() -> Vec<Arg> {
+    vec![Arg::new("trace")
+        .long("trace")
+        .action(ArgAction::SetTrue)
+        .help("Print the position of both navigations after each action")]
+}
+
 fn main() {
-    let boat = Actions::from(include_str!("../actions.txt").trim().split('\n')).execute();
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "waypoint") { 2 } else { 1 }],
+        arguments(),
+    );
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    if args.flag("trace") {
+        print_trace(&trace(Actions::from(input.trim().split('\n'))));
+    }
+    let boat = Actions::from(input.trim().split('\n')).execute(Navigation::default());
     println!(
         "The boat moved {} units (Manhattan distance)",
         boat.manhattan()
     );
+    args.report_time();
 }
 
 #[cfg(test)]
@@ -187,14 +302,56 @@ mod tests {
     #[cfg(not(feature = "waypoint"))]
     #[test]
     fn boat() {
-        let boat = Actions::from("F10 N3 F7 R90 F11".split_whitespace()).execute();
+        let boat =
+            Actions::from("F10 N3 F7 R90 F11".split_whitespace()).execute(Navigation::default());
         assert_eq!(25, boat.manhattan());
     }
 
     #[cfg(feature = "waypoint")]
     #[test]
     fn boat() {
-        let boat = Actions::from("F10 N3 F7 R90 F11".split_whitespace()).execute();
+        let boat =
+            Actions::from("F10 N3 F7 R90 F11".split_whitespace()).execute(Navigation::default());
         assert_eq!(286, boat.manhattan());
     }
+
+    #[test]
+    fn trace_both_navigations() {
+        let trace = trace(Actions::from("N3 F10 R90 F11".split_whitespace()));
+        let positions: Vec<_> = trace
+            .iter()
+            .map(|step| (step.action.to_string(), step.facing, step.waypoint))
+            .collect();
+        assert_eq!(
+            vec![
+                ("N3".to_string(), (0, 3), (0, 0)),
+                ("F10".to_string(), (10, 3), (100, 40)),
+                ("R90".to_string(), (10, 3), (100, 40)),
+                ("F11".to_string(), (10, -8), (144, -70)),
+            ],
+            positions
+        );
+        assert_eq!(Some(0), divergence(&trace));
+        // Turning moves neither the boat nor the waypoint around it
+        let trace = super::trace(Actions::from("R90 L180".split_whitespace()));
+        assert_eq!(None, divergence(&trace));
+    }
+
+    #[test]
+    fn options() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                "rain-risk",
+                &[1],
+                arguments(),
+                std::iter::once("rain-risk").chain(args.iter().copied()),
+            )
+        };
+        let args = parse(&["--trace", "actions.txt"]).unwrap();
+        assert!(args.flag("trace"));
+        assert_eq!(Some("actions.txt"), args.input());
+        assert!(!parse(&[]).unwrap().flag("trace"));
+        assert!(parse(&["--table"]).is_err());
+        assert!(parse(&["a.txt", "b.txt"]).is_err());
+    }
 }
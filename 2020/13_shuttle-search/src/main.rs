@@ -1,72 +1,186 @@
+use common::cli::{self, value_parser, Arg, Args};
+use std::ops::Range;
+
 type BusId = usize;
 #[derive(Debug)]
-struct ShuttleSearch<I>
-where
-    I: Iterator<Item = &'static str>,
-{
+struct ShuttleSearch {
     #[cfg(not(feature = "contest"))]
     arrival_time: usize,
-    buses: I,
+    /// Buses in the order of the schedule, `None` being a bus out of service
+    /// (`x`).
+    buses: Vec<Option<BusId>>,
 }
 
-impl<I> std::convert::From<I> for ShuttleSearch<std::str::Split<'static, char>>
-where
-    I: Iterator<Item = &'static str>,
-{
-    fn from(mut iter: I) -> Self {
-        #[cfg(not(feature = "contest"))]
-        let arrival_time = iter
-            .next()
-            .expect("expect at least one line for the arrival time")
-            .parse()
-            .expect("expect arrival time to be an integer");
-        #[cfg(feature = "contest")]
-        let _ = iter.next();
-        let buses = iter
-            .next()
-            .expect("expect at least a list of buses")
-            .split::<'static, char>(',');
+/// What is wrong in a schedule, and where: the line, from 1, and the span of
+/// the offending token in this line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ScheduleError {
+    message: String,
+    line_number: usize,
+    line: String,
+    span: Range<usize>,
+}
+
+impl ScheduleError {
+    fn new(message: String, (line_number, line): (usize, &str), span: Range<usize>) -> Self {
         Self {
+            message,
+            line_number,
+            line: line.to_string(),
+            span,
+        }
+    }
+}
+
+impl std::fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: {}\n{}",
+            self.line_number,
+            self.message,
+            common::token::underline(&self.line, &self.span)
+        )
+    }
+}
+
+impl std::str::FromStr for ShuttleSearch {
+    type Err = ScheduleError;
+    /// The arrival time on a first line, then the buses separated by commas,
+    /// the blank lines, the whitespaces around each token and the trailing
+    /// commas being ignored.
+    fn from_str(schedule: &str) -> Result<Self, Self::Err> {
+        let mut lines = (1..)
+            .zip(schedule.lines())
+            .filter(|(_, line)| !line.trim().is_empty());
+        let arrival_line = lines.next().ok_or_else(|| {
+            let message = "expect a line with the arrival time".to_string();
+            ScheduleError::new(message, (1, ""), 0..0)
+        })?;
+        let arrival = common::token::within(arrival_line.1, arrival_line.1.trim());
+        let arrival_time: usize = arrival.text.parse().map_err(|_| {
+            let message = format!(
+                "expect the arrival time to be an integer, found '{}'",
+                arrival.text
+            );
+            ScheduleError::new(message, arrival_line, arrival.span.clone())
+        })?;
+        // Checked even though the contest does not need it
+        #[cfg(feature = "contest")]
+        let _ = arrival_time;
+        let buses_line = lines.next().ok_or_else(|| {
+            let message = "expect a line with the buses".to_string();
+            ScheduleError::new(message, (arrival_line.0 + 1, ""), 0..0)
+        })?;
+        let mut tokens: Vec<_> = common::token::split(buses_line.1, ',').collect();
+        while tokens.len() > 1 && tokens.last().map(|token| token.text.is_empty()) == Some(true) {
+            tokens.pop();
+        }
+        let buses = tokens
+            .into_iter()
+            .map(|token| match token.text {
+                "x" => Ok(None),
+                text => match text.parse() {
+                    Ok(bus_id) if bus_id > 0 => Ok(Some(bus_id)),
+                    _ => {
+                        let message = format!("expect a bus id or 'x', found '{}'", text);
+                        Err(ScheduleError::new(message, buses_line, token.span))
+                    }
+                },
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if buses.iter().all(Option::is_none) {
+            let message = "expect at least one bus in service".to_string();
+            return Err(ScheduleError::new(
+                message,
+                buses_line,
+                0..buses_line.1.len(),
+            ));
+        }
+        if let Some(extra_line) = lines.next() {
+            let message = "expect nothing after the buses".to_string();
+            return Err(ScheduleError::new(
+                message,
+                extra_line,
+                0..extra_line.1.len(),
+            ));
+        }
+        Ok(Self {
             #[cfg(not(feature = "contest"))]
             arrival_time,
             buses,
-        }
+        })
     }
 }
 
-impl<I> std::iter::Iterator for ShuttleSearch<I>
-where
-    I: Iterator<Item = &'static str>,
-{
-    type Item = BusId;
+impl ShuttleSearch {
+    /// Buses in service, in the order of the schedule.
+    #[cfg(any(test, not(feature = "contest")))]
+    fn in_service(&self) -> impl Iterator<Item = BusId> + '_ {
+        self.buses.iter().flatten().copied()
+    }
+}
+
+/// Departures of a set of buses from a starting timestamp: each item is a
+/// timestamp with all the buses leaving at that time. Timestamps without any
+/// departure are skipped.
+#[cfg(any(test, not(feature = "contest")))]
+#[derive(Debug)]
+struct Departures {
+    buses: Vec<BusId>,
+    timestamp: usize,
+}
+
+#[cfg(any(test, not(feature = "contest")))]
+impl Departures {
+    fn new(mut buses: Vec<BusId>, timestamp: usize) -> Self {
+        buses.sort_unstable();
+        Self { buses, timestamp }
+    }
+}
+
+#[cfg(any(test, not(feature = "contest")))]
+impl std::iter::Iterator for Departures {
+    type Item = (usize, Vec<BusId>);
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(bus_id) = self.buses.next() {
-            #[cfg(not(feature = "contest"))]
-            if let Ok(bus_id) = bus_id.parse() {
-                return Some(bus_id);
+        if self.buses.is_empty() {
+            return None;
+        }
+        loop {
+            let timestamp = self.timestamp;
+            self.timestamp += 1;
+            let buses: Vec<BusId> = self
+                .buses
+                .iter()
+                .copied()
+                .filter(|&bus_id| timestamp.is_multiple_of(bus_id))
+                .collect();
+            if !buses.is_empty() {
+                return Some((timestamp, buses));
             }
-            #[cfg(feature = "contest")]
-            return bus_id.parse().ok().or(Some(1));
         }
-        None
     }
 }
 
-impl<I> ShuttleSearch<I>
-where
-    I: Iterator<Item = &'static str>,
-{
+impl ShuttleSearch {
+    /// Departures of all the buses in service, starting at `timestamp`.
+    #[cfg(any(test, not(feature = "contest")))]
+    fn departures(&self, timestamp: usize) -> Departures {
+        Departures::new(self.in_service().collect(), timestamp)
+    }
+
     #[cfg(not(feature = "contest"))]
-    fn next_bus(self) -> (usize, usize) {
+    fn next_bus(&self) -> (usize, usize) {
         let arrival_time = self.arrival_time;
-        self.map(|bus_id| {
-            // Integer division will get the passage before arrival_time
-            let previous_passage = (arrival_time / bus_id) * bus_id;
-            let next_passage = previous_passage + bus_id - arrival_time;
-            (bus_id, next_passage)
-        })
-        .min_by_key(|passage| passage.1)
-        .expect("expect at least one bus to be the next")
+        self.in_service()
+            .map(|bus_id| {
+                // Integer division will get the passage before arrival_time
+                let previous_passage = (arrival_time / bus_id) * bus_id;
+                let next_passage = previous_passage + bus_id - arrival_time;
+                (bus_id, next_passage)
+            })
+            .min_by_key(|passage| passage.1)
+            .expect("expect at least one bus to be the next")
     }
     /// This algorithm is inspired by the Chinese Remainder Theorem
     /// Important: The theorem assumes all pairs of 'bus_id` should be coprime.
@@ -74,10 +188,11 @@ where
     /// The algorithm could probably be adapted to avoid this coprime
     /// assumption by modifying how the increment is modified.
     #[cfg(feature = "contest")]
-    fn golden_timestamp(self) -> usize {
+    fn golden_timestamp(&self) -> usize {
         let mut solution = 0;
         let mut increment = 1;
-        for (index, bus_id) in self.enumerate() {
+        let buses = self.buses.iter().enumerate();
+        for (index, bus_id) in buses.filter_map(|(index, bus_id)| Some((index, (*bus_id)?))) {
             for partial_solution in (0..).map(|v| solution + v * increment) {
                 if (partial_solution + index) % bus_id == 0 {
                     solution = partial_solution;
@@ -93,10 +208,73 @@ where
     }
 }
 
+#[derive(Debug, PartialEq, Eq)]
+struct Options {
+    next: Option<usize>,
+    schedule: Option<String>,
+}
+
+impl Options {
+    /// Arguments `--next <MINUTES>` and `--schedule <SCHEDULE>`, to be given
+    /// to `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("next")
+                .long("next")
+                .value_name("MINUTES")
+                .value_parser(value_parser!(u64))
+                .help("Print the departures in the next MINUTES"),
+            Arg::new("schedule")
+                .long("schedule")
+                .value_name("SCHEDULE")
+                .help("Schedule to use instead of an input, its lines separated by '\\n'"),
+        ]
+    }
+
+    /// Options read from `args`. The schedule holds both lines of an input,
+    /// separated by a newline or by a literal `\n`, and replaces the input.
+    fn from_args(args: &Args) -> Result<Self, cli::Error> {
+        let schedule = args
+            .get::<String>("schedule")
+            .map(|schedule| schedule.replace("\\n", "\n"));
+        if schedule.is_some() && args.input().is_some() {
+            return Err(args.error("expect either a schedule or an input, not both"));
+        }
+        Ok(Options {
+            next: args.get::<u64>("next").map(|minutes| minutes as usize),
+            schedule,
+        })
+    }
+}
+
 fn main() {
-    let shuttle_search = ShuttleSearch::from(include_str!("../buses.txt").trim().split('\n'));
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "contest") { 2 } else { 1 }],
+        Options::arguments(),
+    );
+    let options = Options::from_args(&args).unwrap_or_else(|error| error.exit());
+    let input = match &options.schedule {
+        Some(schedule) => schedule.as_str(),
+        None => common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+            .expect("expect the input to exist"),
+    };
+    let shuttle_search: ShuttleSearch = input
+        .parse()
+        .unwrap_or_else(|error| panic!("Invalid schedule, {}", error));
     #[cfg(not(feature = "contest"))]
     {
+        if let Some(minutes) = options.next {
+            let arrival_time = shuttle_search.arrival_time;
+            for (timestamp, bus_ids) in shuttle_search
+                .departures(arrival_time)
+                .take_while(|(timestamp, _)| *timestamp < arrival_time + minutes)
+            {
+                println!("At {}, departure of buses {:?}", timestamp, bus_ids);
+            }
+            args.report_time();
+            return;
+        }
         let (bus_id, minutes) = shuttle_search.next_bus();
         println!(
             "The next bus is {} in {} minutes (bus_id * minutes = {})",
@@ -110,38 +288,177 @@ fn main() {
         let timestamp = shuttle_search.golden_timestamp();
         println!("The golden timestamp is {}", timestamp);
     }
+    args.report_time();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parsed(schedule: &str) -> ShuttleSearch {
+        schedule.parse().unwrap()
+    }
+
+    fn parse(args: &[&str]) -> Result<Options, cli::Error> {
+        let args = Args::try_parse_from(
+            "shuttle-search",
+            &[1],
+            Options::arguments(),
+            std::iter::once("shuttle-search").chain(args.iter().copied()),
+        )?;
+        Options::from_args(&args)
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(
+            Options {
+                next: Some(10),
+                schedule: None,
+            },
+            parse(&["--next", "10", "input.txt"]).unwrap()
+        );
+        let schedule = Some("939\n7,13,x,x,59,x,31,19".to_string());
+        assert_eq!(
+            schedule,
+            parse(&["--schedule", "939\\n7,13,x,x,59,x,31,19"])
+                .unwrap()
+                .schedule
+        );
+        assert_eq!(
+            schedule,
+            parse(&["--schedule", "939\n7,13,x,x,59,x,31,19"])
+                .unwrap()
+                .schedule
+        );
+        assert!(parse(&["--schedule", "939\n7", "input.txt"]).is_err());
+        assert!(parse(&["--next", "soon"]).is_err());
+        assert!(parse(&["--schedule"]).is_err());
+    }
+
+    #[test]
+    fn tolerant_schedule() {
+        let shuttle_search = parsed("\n 939 \n\n7, 13 ,x,x,59,x,31,19,,\n\n");
+        let buses = vec![
+            Some(7),
+            Some(13),
+            None,
+            None,
+            Some(59),
+            None,
+            Some(31),
+            Some(19),
+        ];
+        assert_eq!(buses, shuttle_search.buses);
+        #[cfg(not(feature = "contest"))]
+        assert_eq!(939, shuttle_search.arrival_time);
+    }
+
+    #[test]
+    fn schedule_errors() {
+        let error = |schedule: &str| schedule.parse::<ShuttleSearch>().unwrap_err();
+        // A typo is not taken for a bus out of service
+        let typo = error("939\n7,13,xx,59");
+        assert_eq!((2, 5..7), (typo.line_number, typo.span.clone()));
+        assert_eq!(
+            "line 2: expect a bus id or 'x', found 'xx'\n7,13,xx,59\n     ^^",
+            typo.to_string()
+        );
+        let empty = error("939\n\n7,,13");
+        assert_eq!((3, 2..2), (empty.line_number, empty.span));
+        assert_eq!(2..6, error("  93a9\n7").span);
+        assert_eq!(2..3, error("939\n7,0").span);
+        assert_eq!(1, error("").line_number);
+        assert_eq!(2, error("939\n").line_number);
+        assert_eq!(2, error("939\nx,x,").line_number);
+        assert_eq!(3, error("939\n7\n13").line_number);
+    }
+
     #[cfg(not(feature = "contest"))]
     #[test]
     fn shuttle_search() {
-        let (bus_id, next_passage) =
-            ShuttleSearch::from("939\n7,13,x,x,59,x,31,19".split('\n')).next_bus();
+        let (bus_id, next_passage) = parsed("939\n7,13,x,x,59,x,31,19").next_bus();
         assert_eq!(59, bus_id);
         assert_eq!(5, next_passage);
     }
 
+    #[test]
+    fn departures() {
+        let departures: Vec<(usize, Vec<BusId>)> = parsed("939\n7,13,x,x,59,x,31,19")
+            .departures(939)
+            .take(4)
+            .collect();
+        assert_eq!(
+            vec![
+                (944, vec![59]),
+                (945, vec![7]),
+                (949, vec![13]),
+                (950, vec![19])
+            ],
+            departures
+        );
+        let departures: Vec<(usize, Vec<BusId>)> = Departures::new(vec![3, 2], 5).take(3).collect();
+        assert_eq!(
+            vec![(6, vec![2, 3]), (8, vec![2]), (9, vec![3])],
+            departures
+        );
+        assert_eq!(None, Departures::new(Vec::new(), 0).next());
+    }
+
+    #[cfg(feature = "contest")]
+    fn brute_force_golden_timestamp(schedule: &'static str) -> usize {
+        let bus_ids = parsed(schedule).buses;
+        let departures = parsed(schedule).departures(0);
+        let in_service = departures.buses.clone();
+        departures
+            .filter(|(_, departing_bus_ids)| departing_bus_ids.contains(&bus_ids[0].unwrap()))
+            .map(|(timestamp, _)| timestamp)
+            .find(|&timestamp| {
+                let departures: std::collections::HashMap<usize, Vec<BusId>> =
+                    Departures::new(in_service.clone(), timestamp)
+                        .take_while(|(departure, _)| *departure < timestamp + bus_ids.len())
+                        .collect();
+                bus_ids
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, bus_id)| Some((index, bus_id.as_ref()?)))
+                    .all(|(index, bus_id)| {
+                        departures
+                            .get(&(timestamp + index))
+                            .map(|departing_bus_ids| departing_bus_ids.contains(bus_id))
+                            .unwrap_or(false)
+                    })
+            })
+            .expect("expect a golden timestamp to exist")
+    }
+
     #[cfg(feature = "contest")]
     #[test]
     fn shuttle_search() {
-        let timestamp = ShuttleSearch::from("0\n3,4,7".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n3,4,7").golden_timestamp();
         assert_eq!(75, timestamp);
-        let timestamp =
-            ShuttleSearch::from("0\n7,13,x,x,59,x,31,19".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n7,13,x,x,59,x,31,19").golden_timestamp();
         assert_eq!(1068781, timestamp);
-        let timestamp = ShuttleSearch::from("0\n17,x,13,19".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n17,x,13,19").golden_timestamp();
         assert_eq!(3417, timestamp);
-        let timestamp = ShuttleSearch::from("0\n67,7,59,61".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n67,7,59,61").golden_timestamp();
         assert_eq!(754018, timestamp);
-        let timestamp = ShuttleSearch::from("0\n67,x,7,59,61".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n67,x,7,59,61").golden_timestamp();
         assert_eq!(779210, timestamp);
-        let timestamp = ShuttleSearch::from("0\n67,7,x,59,61".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n67,7,x,59,61").golden_timestamp();
         assert_eq!(1261476, timestamp);
-        let timestamp = ShuttleSearch::from("0\n1789,37,47,1889".split('\n')).golden_timestamp();
+        let timestamp = parsed("0\n1789,37,47,1889").golden_timestamp();
         assert_eq!(1202161486, timestamp);
     }
+
+    #[cfg(feature = "contest")]
+    #[test]
+    fn golden_timestamp_by_brute_force() {
+        for schedule in &["0\n3,4,7", "0\n17,x,13,19", "0\n67,7,59,61"] {
+            assert_eq!(
+                parsed(schedule).golden_timestamp(),
+                brute_force_golden_timestamp(schedule)
+            );
+        }
+    }
 }
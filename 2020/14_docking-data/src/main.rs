@@ -1,3 +1,5 @@
+use common::cli::{value_parser, Arg, ArgAction, Args};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MaskBit {
     False,
@@ -52,15 +54,15 @@ impl std::convert::From<usize> for Bits {
         let mut bits_36 = Bits::default();
         for i in (0..36).rev() {
             bits_36[i] = value % 2 == 1;
-            value = value / 2;
+            value /= 2;
         }
         bits_36
     }
 }
-impl std::convert::Into<usize> for Bits {
-    fn into(self) -> usize {
+impl std::convert::From<Bits> for usize {
+    fn from(bits: Bits) -> Self {
         let mut result = 0;
-        for &bit in self.into_iter() {
+        for &bit in bits.iter() {
             result *= 2;
             if bit {
                 result += 1;
@@ -84,7 +86,7 @@ impl std::str::FromStr for MaskPatch {
             if mask.len() > 36 {
                 panic!("expect the mask to be 36 bits long");
             }
-            let bits = mask.chars().map(|c| MaskBit::from(c)).enumerate().collect();
+            let bits = mask.chars().map(MaskBit::from).enumerate().collect();
             let mask = Mask(bits);
             return Ok(mask);
         } else if let Some(index) = s.find(']') {
@@ -130,86 +132,203 @@ where
     }
 }
 
-impl<I> MaskPatches<I>
-where
-    I: Iterator<Item = &'static str>,
-{
-    fn apply(self) -> usize {
-        use std::collections::BTreeMap;
-        let mut memory: BTreeMap<usize, usize> = BTreeMap::new();
-        let mut mask = BTreeMap::new();
-        for patch in self {
-            match patch {
-                MaskPatch::Mask(mask_bits) => mask.extend(mask_bits),
-                #[cfg(not(feature = "v2"))]
-                MaskPatch::Mem { offset, value } => {
-                    let mut bits = Bits::from(value);
-                    for i in 0..36 {
-                        if let Some(mask_bit) = mask.get(&i) {
-                            match mask_bit {
-                                MaskBit::True => bits[i] = true,
-                                MaskBit::False => bits[i] = false,
-                                MaskBit::Neutral => (),
-                            }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Version {
+    V1,
+    V2,
+}
+
+#[cfg(not(feature = "v2"))]
+const VERSION: Version = Version::V1;
+#[cfg(feature = "v2")]
+const VERSION: Version = Version::V2;
+
+type Memory = std::collections::BTreeMap<usize, usize>;
+
+/// Number of addresses a single write of the version 2 may reach by default,
+/// i.e. a mask of 16 floating bits, the masks of the puzzle having at most 9.
+const MAX_ADDRESSES: u64 = 1 << 16;
+
+/// Run the patches, each write of the version 2 reaching at most
+/// `max_addresses` addresses: a mask of `k` floating bits reaches `2^k` of
+/// them, so that a few more floating bits would take all the memory.
+fn run(patches: &[MaskPatch], version: Version, max_addresses: u64) -> Result<Memory, String> {
+    use std::collections::BTreeMap;
+    let mut memory = Memory::new();
+    let mut mask = BTreeMap::new();
+    for patch in patches {
+        match (patch, version) {
+            (MaskPatch::Mask(mask_bits), _) => mask.extend(mask_bits.clone()),
+            (&MaskPatch::Mem { offset, value }, Version::V1) => {
+                let mut bits = Bits::from(value);
+                for i in 0..36 {
+                    if let Some(mask_bit) = mask.get(&i) {
+                        match mask_bit {
+                            MaskBit::True => bits[i] = true,
+                            MaskBit::False => bits[i] = false,
+                            MaskBit::Neutral => (),
                         }
                     }
-                    memory.insert(offset, bits.into());
                 }
-                #[cfg(feature = "v2")]
-                MaskPatch::Mem { offset, value } => {
-                    let mut addresses = std::collections::HashSet::new();
-                    addresses.insert(Bits::from(offset));
-                    for i in 0..36 {
-                        addresses = {
-                            let mut new_addresses = std::collections::HashSet::new();
-                            for mut address in addresses.into_iter() {
-                                match mask[&i] {
-                                    MaskBit::False => {
-                                        new_addresses.insert(address);
-                                    }
-                                    MaskBit::True => {
-                                        address[i] = true;
-                                        new_addresses.insert(address);
-                                    }
-                                    MaskBit::Neutral => {
-                                        let mut toggle_address = address.clone();
-                                        address[i] = true;
-                                        new_addresses.insert(address);
-                                        toggle_address[i] = false;
-                                        new_addresses.insert(toggle_address);
-                                    }
+                memory.insert(offset, bits.into());
+            }
+            (&MaskPatch::Mem { offset, value }, Version::V2) => {
+                let floating = mask
+                    .values()
+                    .filter(|&&mask_bit| mask_bit == MaskBit::Neutral)
+                    .count();
+                if 1u64 << floating > max_addresses {
+                    return Err(format!(
+                        "expect mem[{}] to reach at most {} addresses, but its mask has {} floating bits reaching {} (see --max-addresses)",
+                        offset,
+                        max_addresses,
+                        floating,
+                        1u64 << floating
+                    ));
+                }
+                let mut addresses = std::collections::HashSet::new();
+                addresses.insert(Bits::from(offset));
+                for i in 0..36 {
+                    addresses = {
+                        let mut new_addresses = std::collections::HashSet::new();
+                        for mut address in addresses.into_iter() {
+                            match mask[&i] {
+                                MaskBit::False => {
+                                    new_addresses.insert(address);
+                                }
+                                MaskBit::True => {
+                                    address[i] = true;
+                                    new_addresses.insert(address);
+                                }
+                                MaskBit::Neutral => {
+                                    let mut toggle_address = address.clone();
+                                    address[i] = true;
+                                    new_addresses.insert(address);
+                                    toggle_address[i] = false;
+                                    new_addresses.insert(toggle_address);
                                 }
                             }
-                            new_addresses
-                        };
-                    }
-                    for address in addresses {
-                        memory.insert(address.into(), value);
-                    }
+                        }
+                        new_addresses
+                    };
+                }
+                for address in addresses {
+                    memory.insert(address.into(), value);
                 }
             }
         }
-        memory.values().sum()
     }
+    Ok(memory)
+}
+
+/// Addresses where the final values differ between both versions, with the
+/// value of each version (`None` if the address was never written).
+fn differences(
+    memory_v1: &Memory,
+    memory_v2: &Memory,
+) -> Vec<(usize, Option<usize>, Option<usize>)> {
+    let addresses: std::collections::BTreeSet<usize> =
+        memory_v1.keys().chain(memory_v2.keys()).copied().collect();
+    addresses
+        .into_iter()
+        .map(|address| {
+            (
+                address,
+                memory_v1.get(&address).copied(),
+                memory_v2.get(&address).copied(),
+            )
+        })
+        .filter(|(_, value_v1, value_v2)| value_v1 != value_v2)
+        .collect()
+}
+
+impl<I> MaskPatches<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    fn apply(self, max_addresses: u64) -> Result<usize, String> {
+        let patches: Vec<MaskPatch> = self.collect();
+        Ok(run(&patches, VERSION, max_addresses)?.values().sum())
+    }
+}
+
+fn print_differences(patches: &[MaskPatch], max_addresses: u64) -> Result<(), String> {
+    let memory_v1 = run(patches, Version::V1, max_addresses)?;
+    let memory_v2 = run(patches, Version::V2, max_addresses)?;
+    let format_value = |value: Option<usize>| {
+        value
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| String::from("-"))
+    };
+    println!("{:>12} | {:>16} | {:>16}", "address", "v1", "v2");
+    for (address, value_v1, value_v2) in differences(&memory_v1, &memory_v2) {
+        println!(
+            "{:>12} | {:>16} | {:>16}",
+            address,
+            format_value(value_v1),
+            format_value(value_v2)
+        );
+    }
+    println!(
+        "{:>12} | {:>16} | {:>16}",
+        "sum",
+        memory_v1.values().sum::<usize>(),
+        memory_v2.values().sum::<usize>()
+    );
+    Ok(())
+}
+
+/// Arguments `--diff` and `--max-addresses <COUNT>`, to be given to
+/// `cli::Args::parse_with`.
+fn arguments() -> Vec<Arg> {
+    vec![
+        Arg::new("diff")
+            .long("diff")
+            .action(ArgAction::SetTrue)
+            .help("Print the memory written by both versions of the decoder"),
+        Arg::new("max-addresses")
+            .long("max-addresses")
+            .value_name("COUNT")
+            .value_parser(value_parser!(u64).range(1..))
+            .help("Addresses a single write may reach, 65536 by default"),
+    ]
 }
 
 fn main() {
-    let sum = MaskPatches::from(include_str!("../masks.txt").trim().split('\n')).apply();
-    println!("Sum of all in-memory values is {}", sum);
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "v2") { 2 } else { 1 }],
+        arguments(),
+    );
+    let max_addresses = args.get::<u64>("max-addresses").unwrap_or(MAX_ADDRESSES);
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let masks = input.trim().split('\n');
+    if args.flag("diff") {
+        let patches: Vec<MaskPatch> = MaskPatches::from(masks).collect();
+        print_differences(&patches, max_addresses).unwrap_or_else(|error| panic!("{}", error));
+    } else {
+        let sum = MaskPatches::from(masks)
+            .apply(max_addresses)
+            .unwrap_or_else(|error| panic!("{}", error));
+        println!("Sum of all in-memory values is {}", sum);
+    }
+    args.report_time();
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::cli::ErrorKind;
 
     #[test]
     fn test_usize_to_bits() {
         let bits = Bits::from(6);
-        assert_eq!(false, bits[35]);
-        assert_eq!(true, bits[34]);
-        assert_eq!(true, bits[33]);
+        assert!(!bits[35]);
+        assert!(bits[34]);
+        assert!(bits[33]);
         for i in 0..32 {
-            assert_eq!(false, bits[i]);
+            assert!(!bits[i]);
         }
         let value: usize = bits.into();
         assert_eq!(6, value);
@@ -225,7 +344,9 @@ mod tests {
 mem[8] = 11
 mem[7] = 101
 mem[8] = 0"#;
-        let sum = MaskPatches::from(masks.split('\n')).apply();
+        let sum = MaskPatches::from(masks.split('\n'))
+            .apply(MAX_ADDRESSES)
+            .unwrap();
         assert_eq!(165, sum);
     }
 
@@ -236,7 +357,114 @@ mem[8] = 0"#;
 mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1"#;
-        let sum = MaskPatches::from(masks.split('\n')).apply();
+        let sum = MaskPatches::from(masks.split('\n'))
+            .apply(MAX_ADDRESSES)
+            .unwrap();
         assert_eq!(208, sum);
     }
+
+    #[test]
+    fn versions_differences() {
+        let masks = r#"mask = 000000000000000000000000000000X1001X
+mem[42] = 100
+mask = 00000000000000000000000000000000X0XX
+mem[26] = 1"#;
+        let patches: Vec<MaskPatch> = MaskPatches::from(masks.split('\n')).collect();
+        let memory_v1 = run(&patches, Version::V1, MAX_ADDRESSES).unwrap();
+        let memory_v2 = run(&patches, Version::V2, MAX_ADDRESSES).unwrap();
+        assert_eq!(
+            vec![
+                (16, None, Some(1)),
+                (17, None, Some(1)),
+                (18, None, Some(1)),
+                (19, None, Some(1)),
+                (24, None, Some(1)),
+                (25, None, Some(1)),
+                (27, None, Some(1)),
+                (42, Some(50), None),
+                (58, None, Some(100)),
+                (59, None, Some(100)),
+            ],
+            differences(&memory_v1, &memory_v2)
+        );
+        assert!(differences(&memory_v1, &memory_v1).is_empty());
+    }
+
+    #[test]
+    fn floating_addresses_limit() {
+        let masks = r#"mask = 0000000000000000000000000000000XXXXX
+mem[8] = 3"#;
+        let patches: Vec<MaskPatch> = MaskPatches::from(masks.split('\n')).collect();
+        let memory = run(&patches, Version::V2, 32).unwrap();
+        assert_eq!(32, memory.len());
+        assert_eq!(
+            Err(String::from(
+                "expect mem[8] to reach at most 16 addresses, but its mask has 5 floating bits reaching 32 (see --max-addresses)"
+            )),
+            run(&patches, Version::V2, 16)
+        );
+        // Only the version 2 writes at floating addresses
+        assert_eq!(1, run(&patches, Version::V1, 16).unwrap().len());
+    }
+
+    #[test]
+    fn options() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                "docking-data",
+                &[1],
+                arguments(),
+                std::iter::once("docking-data").chain(args.iter().copied()),
+            )
+        };
+        let args = parse(&[]).unwrap();
+        assert!(!args.flag("diff"));
+        assert_eq!(None, args.get::<u64>("max-addresses"));
+        let args = parse(&["--diff", "masks.txt", "--max-addresses", "1048576"]).unwrap();
+        assert!(args.flag("diff"));
+        assert_eq!(Some(1 << 20), args.get::<u64>("max-addresses"));
+        assert_eq!(Some("masks.txt"), args.input());
+        assert!(parse(&["--max-addresses"]).is_err());
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            parse(&["--max-addresses", "many"]).unwrap_err().kind()
+        );
+        assert!(parse(&["--max-addresses", "0"]).is_err());
+        assert!(parse(&["a.txt", "b.txt"]).is_err());
+    }
+
+    mod mask_patch_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        proptest! {
+            #[test]
+            fn parse_mask(mask in "[01X]{36}") {
+                let patch: MaskPatch = format!("mask = {}", mask).parse().unwrap();
+                let expected: Vec<MaskBit> = mask.chars().map(MaskBit::from).collect();
+                match patch {
+                    MaskPatch::Mask(bits) => {
+                        let bits: Vec<MaskBit> = bits.into_values().collect();
+                        prop_assert_eq!(expected, bits);
+                    }
+                    patch => prop_assert!(false, "expect a mask, got {:?}", patch),
+                }
+            }
+
+            #[test]
+            fn parse_mem(offset in 0usize..1 << 36, value in 0usize..1 << 36) {
+                let patch: MaskPatch = format!("mem[{}] = {}", offset, value).parse().unwrap();
+                match patch {
+                    MaskPatch::Mem {
+                        offset: patch_offset,
+                        value: patch_value,
+                    } => {
+                        prop_assert_eq!(offset, patch_offset);
+                        prop_assert_eq!(value, patch_value);
+                    }
+                    patch => prop_assert!(false, "expect a mem, got {:?}", patch),
+                }
+            }
+        }
+    }
 }
@@ -1,9 +1,18 @@
+use common::interval::{Interval, IntervalSet};
+
+/// Environment variable naming the CSV file the compatibility of the rules
+/// and the fields is written to.
+const COMPATIBILITY: &str = "AOC_COMPATIBILITY";
+
 type RuleName = &'static str;
-type RuleConstraint = std::ops::RangeInclusive<usize>;
-type RulesInner = std::collections::BTreeMap<RuleName, Vec<RuleConstraint>>;
+type RuleConstraint = IntervalSet<usize>;
+type RulesInner = std::collections::BTreeMap<RuleName, RuleConstraint>;
 #[derive(Debug)]
 struct Rules {
     inner: RulesInner,
+    /// Union of the ranges of every rule, the values valid for at least one
+    /// rule.
+    valid: RuleConstraint,
 }
 impl std::ops::Deref for Rules {
     type Target = RulesInner;
@@ -17,45 +26,45 @@ where
     I: Iterator<Item = &'static str>,
 {
     fn from(iter: I) -> Self {
-        let inner = iter.fold(RulesInner::new(), |mut acc, line| {
-            let mut split = line.split(": ");
-            let name = split.next().expect("expect a name for the rule");
-            let ranges = split
-                .next()
-                .expect("expect ranges for the rule")
-                .split(" or ")
-                .map(|range| {
-                    let mut split = range.split('-');
-                    let min = split
-                        .next()
-                        .expect("expect a minimum in the range")
-                        .parse()
-                        .expect("expect minimum bound to be an integer");
-                    let max = split
-                        .next()
-                        .expect("expect a maximum in the range")
-                        .parse()
-                        .expect("expect maximum bound to be an integer");
-                    std::ops::RangeInclusive::new(min, max)
-                })
-                .collect();
-            acc.insert(name, ranges);
-            acc
-        });
-        Self { inner }
+        let inner: RulesInner = iter
+            .map(|line| {
+                let mut split = line.split(": ");
+                let name = split.next().expect("expect a name for the rule");
+                let ranges = split
+                    .next()
+                    .expect("expect ranges for the rule")
+                    .split(" or ")
+                    .map(|range| {
+                        let mut split = range.split('-');
+                        let min = split
+                            .next()
+                            .expect("expect a minimum in the range")
+                            .parse()
+                            .expect("expect minimum bound to be an integer");
+                        let max = split
+                            .next()
+                            .expect("expect a maximum in the range")
+                            .parse()
+                            .expect("expect maximum bound to be an integer");
+                        Interval::new(min, max)
+                            .expect("expect the minimum to be before the maximum")
+                    })
+                    .collect();
+                (name, ranges)
+            })
+            .collect();
+        let valid = inner
+            .values()
+            .flat_map(IntervalSet::intervals)
+            .copied()
+            .collect();
+        Self { inner, valid }
     }
 }
 
 impl Rules {
     fn is_valid_field(&self, field: &usize) -> bool {
-        for ranges in self.inner.values() {
-            for range in ranges {
-                if range.contains(&field) {
-                    return true;
-                }
-            }
-        }
-        false
+        self.valid.contains(field)
     }
 }
 
@@ -90,6 +99,54 @@ impl Ticket {
     }
 }
 
+/// `text` as a CSV field, between double quotes with the quotes inside doubled
+/// if it holds a comma, a quote or a line break.
+fn csv_field(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.contains(&[',', '"', '\r', '\n'][..]) {
+        std::borrow::Cow::Owned(format!("\"{}\"", text.replace('"', "\"\"")))
+    } else {
+        std::borrow::Cow::Borrowed(text)
+    }
+}
+
+/// Whether each field index of the valid tickets is compatible with each rule.
+#[derive(Debug, PartialEq, Eq)]
+struct Compatibility {
+    rules: Vec<RuleName>,
+    /// One line per rule, one column per field index.
+    matrix: Vec<Vec<bool>>,
+}
+
+impl Compatibility {
+    /// Field indexes compatible with the rule of the line `rule_index`.
+    fn indexes(&self, rule_index: usize) -> Vec<usize> {
+        self.matrix[rule_index]
+            .iter()
+            .enumerate()
+            .filter(|(_, &compatible)| compatible)
+            .map(|(field_index, _)| field_index)
+            .collect()
+    }
+    /// One line per rule, starting with its name, then `1` for each
+    /// compatible field index and `0` otherwise, as RFC 4180 writes CSV.
+    fn write_csv<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let fields_len = self.matrix.first().map(Vec::len).unwrap_or(0);
+        write!(writer, "rule")?;
+        for field_index in 0..fields_len {
+            write!(writer, ",{}", field_index)?;
+        }
+        write!(writer, "\r\n")?;
+        for (rule_name, line) in self.rules.iter().zip(&self.matrix) {
+            write!(writer, "{}", csv_field(rule_name))?;
+            for &compatible in line {
+                write!(writer, ",{}", compatible as u8)?;
+            }
+            write!(writer, "\r\n")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 struct Notes {
     rules: Rules,
@@ -113,8 +170,7 @@ impl std::convert::From<&'static str> for Notes {
                 .expect("expect at least my ticket information in the notes")
                 .trim()
                 .lines()
-                .skip(1)
-                .next()
+                .nth(1)
                 .expect("expect at least one line for my ticket information")
                 .split(','),
         );
@@ -142,33 +198,41 @@ impl Notes {
             .flat_map(move |ticket| ticket.invalid_fields(&self.rules))
             .copied()
     }
-    fn identify_fields(&self) -> Vec<RuleName> {
+    fn compatibility(&self) -> Compatibility {
         let fields_len = self.rules.len();
-        let mut fields_order = std::collections::HashMap::new();
         let valid_tickets: Vec<&Ticket> = self
             .tickets
             .iter()
             .filter(|ticket| ticket.is_valid(&self.rules))
             .collect();
-        for (&rule_name, rule_ranges) in self.rules.inner.iter() {
-            for field_index in 0..fields_len {
-                if valid_tickets
-                    .iter()
-                    .map(|ticket| ticket.fields[field_index])
-                    .all(|field| {
-                        let contains = rule_ranges.iter().any(|range| range.contains(&field));
-                        contains
+        let rules = self.rules.keys().copied().collect();
+        let matrix = self
+            .rules
+            .values()
+            .map(|rule_ranges| {
+                (0..fields_len)
+                    .map(|field_index| {
+                        valid_tickets
+                            .iter()
+                            .map(|ticket| ticket.fields[field_index])
+                            .all(|field| rule_ranges.contains(&field))
                     })
-                {
-                    fields_order
-                        .entry(rule_name)
-                        .or_insert_with(Vec::new)
-                        .push(field_index);
-                }
+                    .collect()
+            })
+            .collect();
+        Compatibility { rules, matrix }
+    }
+    fn identify_fields(&self) -> Vec<RuleName> {
+        let compatibility = self.compatibility();
+        let mut fields_order = std::collections::HashMap::new();
+        for (rule_index, &rule_name) in compatibility.rules.iter().enumerate() {
+            let indexes = compatibility.indexes(rule_index);
+            if !indexes.is_empty() {
+                fields_order.insert(rule_name, indexes);
             }
         }
         let mut final_order = Vec::new();
-        while fields_order.len() > 0 {
+        while !fields_order.is_empty() {
             // Extract rules with only one solution
             for (&rule_name, indexes) in &fields_order {
                 if indexes.len() == 1 {
@@ -177,7 +241,7 @@ impl Notes {
             }
             for (index, rule_name) in &final_order {
                 fields_order.remove(rule_name);
-                for (_, indexes) in &mut fields_order {
+                for indexes in fields_order.values_mut() {
                     indexes.retain(|i| i != index);
                 }
             }
@@ -198,8 +262,17 @@ impl Notes {
     }
 }
 
-fn main() {
-    let notes = Notes::from(include_str!("../notes.txt"));
+fn main() -> std::io::Result<()> {
+    let args = common::cli::Args::parse(env!("CARGO_PKG_NAME"), &[1, 2]);
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let notes = Notes::from(input);
+    if let Some(path) = std::env::var_os(COMPATIBILITY) {
+        let file = std::fs::File::create(path)?;
+        notes
+            .compatibility()
+            .write_csv(std::io::BufWriter::new(file))?;
+    }
     println!(
         "Sum of all invalid fields is {}",
         notes.invalid_fields().sum::<usize>()
@@ -208,6 +281,8 @@ fn main() {
         "Product of my 'departure' fields is {}",
         notes.my_departure_fields().product::<usize>()
     );
+    args.report_time();
+    Ok(())
 }
 
 #[cfg(test)]
@@ -232,6 +307,29 @@ nearby tickets:
         assert_eq!(71, notes.invalid_fields().sum::<usize>());
     }
 
+    #[test]
+    fn valid_fields() {
+        let rules = Rules::from("class: 1-3 or 5-7\nrow: 6-11 or 33-44".lines());
+        assert_eq!(
+            &[
+                Interval::new(1, 3).unwrap(),
+                Interval::new(5, 11).unwrap(),
+                Interval::new(33, 44).unwrap()
+            ],
+            rules.valid.intervals()
+        );
+        for (field, expected) in [
+            (0, false),
+            (3, true),
+            (4, false),
+            (8, true),
+            (12, false),
+            (44, true),
+        ] {
+            assert_eq!(expected, rules.is_valid_field(&field));
+        }
+    }
+
     #[test]
     fn identify_fields() {
         let notes = r#"class: 0-1 or 4-19
@@ -252,4 +350,137 @@ nearby tickets:
         assert_eq!("departure_seat", identified_fields[2]);
         assert_eq!(11 * 13, notes.my_departure_fields().product::<usize>());
     }
+
+    #[test]
+    fn compatibility() {
+        let notes = r#"class: 0-1 or 4-19
+row: 0-5 or 8-19
+seat: 0-13 or 16-19
+
+your ticket:
+11,12,13
+
+nearby tickets:
+3,9,18
+15,1,5
+5,14,9"#;
+        let notes = Notes::from(notes);
+        let compatibility = notes.compatibility();
+        assert_eq!(vec!["class", "row", "seat"], compatibility.rules);
+        assert_eq!(
+            vec![
+                vec![false, true, true],
+                vec![true, true, true],
+                vec![false, false, true],
+            ],
+            compatibility.matrix
+        );
+        let mut csv = Vec::new();
+        compatibility.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            "rule,0,1,2\r\nclass,0,1,1\r\nrow,1,1,1\r\nseat,0,0,1\r\n",
+            String::from_utf8(csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn csv_fields() {
+        assert_eq!("arrival station", csv_field("arrival station"));
+        assert_eq!("\"row, seat\"", csv_field("row, seat"));
+        assert_eq!("\"the \"\"class\"\"\"", csv_field("the \"class\""));
+    }
+
+    mod notes_properties {
+        use super::*;
+        use proptest::{collection::vec, prelude::*};
+
+        /// Width of the values of each field, the values of the field of the
+        /// rule `k` being from `k * BAND`.
+        const BAND: usize = 100;
+
+        /// Name of the rule `k`, the first three rules being departure ones.
+        fn name(k: usize) -> String {
+            if k < 3 {
+                format!("departure {}", k)
+            } else {
+                format!("class {}", k)
+            }
+        }
+
+        /// Values of a ticket, each field of the rule `k` being `k * BAND`
+        /// plus an offset below 80.
+        fn fields(order: &[usize], offsets: &[usize]) -> Vec<usize> {
+            order
+                .iter()
+                .zip(offsets)
+                .map(|(k, offset)| k * BAND + offset)
+                .collect()
+        }
+
+        /// Notes whose rules each accept the values of their own field and of
+        /// the fields of the following rules, so that the fields are
+        /// identified from the last rule back to the first one, with the
+        /// rule of each field index, and the sum of the invalid fields.
+        fn notes() -> impl Strategy<Value = (String, Vec<usize>, usize)> {
+            (1usize..10).prop_flat_map(|len| {
+                (
+                    Just((0..len).collect::<Vec<_>>()).prop_shuffle(),
+                    vec(0usize..80, len),
+                    vec(vec(0usize..80, len), 1..10),
+                    vec((vec(0usize..80, len), 0..len, 0usize..1000), 0..5),
+                )
+                    .prop_map(move |(order, mine, valid, invalid)| {
+                        let mut text = String::new();
+                        for k in 0..len {
+                            text += &format!(
+                                "{}: {}-{} or {}-{}\n",
+                                name(k),
+                                k * BAND,
+                                k * BAND + 79,
+                                k * BAND + 90,
+                                len * BAND - 1
+                            );
+                        }
+                        let line = |fields: Vec<usize>| {
+                            let fields: Vec<String> = fields.iter().map(usize::to_string).collect();
+                            fields.join(",") + "\n"
+                        };
+                        text += "\nyour ticket:\n";
+                        text += &line(fields(&order, &mine));
+                        text += "\nnearby tickets:\n";
+                        for offsets in &valid {
+                            text += &line(fields(&order, offsets));
+                        }
+                        let mut error_rate = 0;
+                        for (offsets, index, invalid) in &invalid {
+                            let mut fields = fields(&order, offsets);
+                            // Above the ranges of every rule
+                            fields[*index] = len * BAND + invalid;
+                            error_rate += fields[*index];
+                            text += &line(fields);
+                        }
+                        (text, order.clone(), error_rate)
+                    })
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn generated_notes((text, order, error_rate) in notes()) {
+                // The rules borrow their names from the notes for the whole
+                // program, as with the puzzle input
+                let notes = Notes::from(&*Box::leak(text.into_boxed_str()));
+                prop_assert_eq!(error_rate, notes.invalid_fields().sum::<usize>());
+                let expected: Vec<String> = order.iter().map(|&k| name(k)).collect();
+                prop_assert_eq!(expected, notes.identify_fields());
+                let departures: usize = order
+                    .iter()
+                    .zip(&notes.ticket.fields)
+                    .filter(|(&k, _)| name(k).starts_with("departure"))
+                    .map(|(_, field)| field)
+                    .product();
+                prop_assert_eq!(departures, notes.my_departure_fields().product::<usize>());
+            }
+        }
+    }
 }
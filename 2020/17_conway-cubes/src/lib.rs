@@ -0,0 +1,50 @@
+/// Initial plane of `size` by `size` cubes, made of copies of the `seed`
+/// pattern side by side (the last copies being cut at the border), unless the
+/// seed has no line.
+pub fn inflate(seed: &str, size: usize) -> Option<String> {
+    let seed: Vec<Vec<char>> = seed
+        .lines()
+        .map(|line| line.trim().chars().collect())
+        .filter(|line: &Vec<char>| !line.is_empty())
+        .collect();
+    if seed.is_empty() {
+        return None;
+    }
+    let plane = (0..size)
+        .map(|y| {
+            let line = &seed[y % seed.len()];
+            (0..size).map(|x| line[x % line.len()]).collect::<String>()
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    Some(plane)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflate_seed() {
+        let seed = r#".#.
+..#
+###"#;
+        assert_eq!(Some(String::from(".#\n..")), inflate(seed, 2));
+        assert_eq!(
+            Some(String::from(".#..#\n..#..\n#####\n.#..#\n..#..")),
+            inflate(seed, 5)
+        );
+        let plane = inflate(seed, 100).unwrap();
+        assert_eq!(100, plane.lines().count());
+        assert!(plane.lines().all(|line| line.len() == 100));
+        // 5 active cubes in each of the 33x33 full copies of the seed, plus
+        // the first line and the first column of the copies cut by the border
+        assert_eq!(33 * 33 * 5 + 33 + 33, plane.matches('#').count());
+    }
+
+    #[test]
+    fn inflate_empty_seed() {
+        assert_eq!(None, inflate("", 10));
+        assert_eq!(None, inflate("\n  \n", 10));
+    }
+}
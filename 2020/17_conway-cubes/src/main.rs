@@ -1,3 +1,9 @@
+use common::{
+    automaton::Automaton,
+    cli::{value_parser, Arg, Args},
+};
+use conway_cubes::inflate;
+
 #[cfg(not(feature = "hypercube"))]
 type Coordinates = (isize, isize, isize);
 #[cfg(feature = "hypercube")]
@@ -20,8 +26,8 @@ impl std::ops::DerefMut for ConwayCubes {
     }
 }
 
-impl std::convert::From<&'static str> for ConwayCubes {
-    fn from(map: &'static str) -> Self {
+impl std::convert::From<&str> for ConwayCubes {
+    fn from(map: &str) -> Self {
         let mut conway_cubes = Self::default();
         for (line_index, line) in map.lines().enumerate() {
             for (row_index, c) in line.chars().enumerate() {
@@ -95,63 +101,264 @@ impl std::fmt::Debug for ConwayCubes {
     }
 }
 
-impl ConwayCubes {
-    fn actives(&self) -> usize {
-        self.inner.len()
+/// Numbers of active neighbors for which an inactive cube becomes active
+/// (birth) and an active cube stays active (survival), written as the
+/// rulestring of a Life-like automaton, `B3/S23` by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self {
+            birth: vec![3],
+            survival: vec![2, 3],
+        }
     }
-    fn cycle(self) -> Self {
-        fn neighbors(c: Coordinates) -> Vec<Coordinates> {
-            let mut neighbors = Vec::new();
-            for dx in -1..=1 {
-                for dy in -1..=1 {
-                    for dz in -1..=1 {
-                        #[cfg(not(feature = "hypercube"))]
-                        if dx != 0 || dy != 0 || dz != 0 {
-                            neighbors.push((c.0 + dx, c.1 + dy, c.2 + dz));
-                        }
-                        #[cfg(feature = "hypercube")]
-                        for dd in -1..=1 {
-                            if dx != 0 || dy != 0 || dz != 0 || dd != 0 {
-                                neighbors.push((c.0 + dx, c.1 + dy, c.2 + dz, c.3 + dd));
-                            }
-                        }
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+    /// Read a rulestring like `B36/S23`, each digit being a number of active
+    /// neighbors, so that numbers above 9 cannot be written. A birth without
+    /// any active neighbor is refused, since it would activate the infinity
+    /// of cubes around.
+    fn from_str(rulestring: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("expect a rulestring like 'B3/S23', got '{}'", rulestring);
+        let counts = |counts: &str| -> Result<Vec<usize>, String> {
+            let mut counts: Vec<usize> = counts
+                .chars()
+                .map(|c| {
+                    c.to_digit(10)
+                        .map(|count| count as usize)
+                        .ok_or_else(invalid)
+                })
+                .collect::<Result<_, _>>()?;
+            counts.sort_unstable();
+            counts.dedup();
+            Ok(counts)
+        };
+        let (birth, survival) = rulestring.split_once('/').ok_or_else(invalid)?;
+        let rule = match (birth.chars().next(), survival.chars().next()) {
+            (Some('B'), Some('S')) | (Some('b'), Some('s')) => Self {
+                birth: counts(&birth[1..])?,
+                survival: counts(&survival[1..])?,
+            },
+            _ => return Err(invalid()),
+        };
+        if rule.birth.contains(&0) {
+            return Err(format!(
+                "expect no birth with 0 active neighbors, got '{}'",
+                rulestring
+            ));
+        }
+        Ok(rule)
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = |counts: &[usize]| {
+            counts
+                .iter()
+                .map(|count| count.to_string())
+                .collect::<String>()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+impl Rule {
+    /// Whether a cube is active after a cycle, from whether it is active and
+    /// its number of active neighbors.
+    fn next(&self, active: bool, active_neighbors: usize) -> bool {
+        if active {
+            self.survival.contains(&active_neighbors)
+        } else {
+            self.birth.contains(&active_neighbors)
+        }
+    }
+}
+
+/// The cubes at most one unit away on each axis.
+fn neighbors(c: Coordinates) -> Vec<Coordinates> {
+    let mut neighbors = Vec::new();
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                #[cfg(not(feature = "hypercube"))]
+                if dx != 0 || dy != 0 || dz != 0 {
+                    neighbors.push((c.0 + dx, c.1 + dy, c.2 + dz));
+                }
+                #[cfg(feature = "hypercube")]
+                for dd in -1..=1 {
+                    if dx != 0 || dy != 0 || dz != 0 || dd != 0 {
+                        neighbors.push((c.0 + dx, c.1 + dy, c.2 + dz, c.3 + dd));
                     }
                 }
             }
-            neighbors
         }
-        let next_cubes: ConwayCubes = self
+    }
+    neighbors
+}
+
+impl ConwayCubes {
+    fn actives(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+/// Each cycle, only the active cubes and their neighbors may change, every
+/// other cube staying inactive.
+impl Automaton<Rule> for ConwayCubes {
+    type Position = Coordinates;
+    type State = bool;
+
+    fn positions(&self, _: &Rule) -> Vec<Coordinates> {
+        let positions: ConwayCubesInner = self
             .iter()
             .copied()
             .chain(self.iter().flat_map(|&coordinates| neighbors(coordinates)))
             .collect();
-        let mut output = Self::default();
-        for cube in next_cubes {
-            let active_neighbors = neighbors(cube)
-                .into_iter()
-                .filter(|c| self.contains(c))
-                .count();
-            if (self.contains(&cube) && (active_neighbors == 2 || active_neighbors == 3))
-                || (!self.contains(&cube) && active_neighbors == 3)
-            {
-                output.insert(cube);
-            }
+        positions.into_iter().collect()
+    }
+    fn state(&self, cube: Coordinates) -> bool {
+        self.contains(&cube)
+    }
+    fn neighbors(&self, _: &Rule, cube: Coordinates) -> Vec<Coordinates> {
+        neighbors(cube)
+    }
+    fn transition(rule: &Rule, active: bool, neighbors: &[bool]) -> bool {
+        let active_neighbors = neighbors.iter().filter(|&&active| active).count();
+        rule.next(active, active_neighbors)
+    }
+    fn with_states(&self, cubes: Vec<(Coordinates, bool)>) -> Self {
+        cubes
+            .into_iter()
+            .filter(|&(_, active)| active)
+            .map(|(cube, _)| cube)
+            .collect()
+    }
+}
+
+#[cfg(feature = "image")]
+impl ConwayCubes {
+    /// Slices of the cubes along `z` (and `w`), named after their
+    /// coordinates, with whether each position is active, between the
+    /// bounds of all the cubes so that every slice has the same size.
+    fn slices(
+        &self,
+    ) -> std::collections::BTreeMap<String, std::collections::HashMap<(i64, i64), bool>> {
+        let mut slices = std::collections::BTreeMap::new();
+        let xs = self.iter().map(|cube| cube.0 as i64);
+        let ys = self.iter().map(|cube| cube.1 as i64);
+        let (x_min, x_max) = (xs.clone().min(), xs.max());
+        let (y_min, y_max) = (ys.clone().min(), ys.max());
+        let inactive: std::collections::HashMap<(i64, i64), bool> =
+            match (x_min, x_max, y_min, y_max) {
+                (Some(x_min), Some(x_max), Some(y_min), Some(y_max)) => (y_min..=y_max)
+                    .flat_map(|y| (x_min..=x_max).map(move |x| ((x, y), false)))
+                    .collect(),
+                _ => return slices,
+            };
+        for cube in self.iter() {
+            #[cfg(not(feature = "hypercube"))]
+            let name = format!("z{}", cube.2);
+            #[cfg(feature = "hypercube")]
+            let name = format!("z{}-w{}", cube.2, cube.3);
+            slices
+                .entry(name)
+                .or_insert_with(|| inactive.clone())
+                .insert((cube.0 as i64, cube.1 as i64), true);
         }
-        output
+        slices
     }
 }
 
-fn main() {
-    let mut conway_cubes = ConwayCubes::from(include_str!("../initial.txt"));
-    for _ in 0..6 {
-        conway_cubes = conway_cubes.cycle();
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    inflate: Option<usize>,
+    rule: Rule,
+}
+
+impl Options {
+    /// Arguments `--inflate <SIZE>` and `--rule <RULESTRING>`, to be given to
+    /// `cli::Args::parse_with`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("inflate")
+                .long("inflate")
+                .value_name("SIZE")
+                .value_parser(value_parser!(u64).range(1..))
+                .help("Replicate the input into a SIZExSIZE plane and time the cycles"),
+            Arg::new("rule")
+                .long("rule")
+                .value_name("RULESTRING")
+                .value_parser(value_parser!(Rule))
+                .help("Life-like rule of the cubes, B3/S23 by default"),
+        ]
     }
+
+    /// Options read from `args`, any missing option keeping its default
+    /// value.
+    fn from_args(args: &Args) -> Self {
+        Options {
+            inflate: args.get::<u64>("inflate").map(|size| size as usize),
+            rule: args.get::<Rule>("rule").unwrap_or_default(),
+        }
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let args = Args::parse_with(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "hypercube") { 2 } else { 1 }],
+        Options::arguments(),
+    );
+    let options = Options::from_args(&args);
+    let inflate_size = options.inflate;
+    let input = common::input::load_str(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let mut conway_cubes = match inflate_size {
+        Some(size) => match inflate(input, size) {
+            Some(plane) => ConwayCubes::from(plane.as_str()),
+            None => args
+                .error("expect a seed pattern with at least one line to inflate")
+                .exit(),
+        },
+        None => ConwayCubes::from(input),
+    };
+    let start = std::time::Instant::now();
+    conway_cubes = conway_cubes.step_n(&options.rule, 6);
     println!("{} active cubes after 6 cycles", conway_cubes.actives());
+    #[cfg(feature = "image")]
+    for (name, slice) in conway_cubes.slices() {
+        let image = viz::Image::capture(&slice, |_, active| match active {
+            Some(true) => [255, 255, 255],
+            _ => [0, 0, 0],
+        });
+        if let Some(image) = image {
+            image.save(viz::image_path_of(&name))?;
+        }
+    }
+    if let Some(size) = inflate_size {
+        println!(
+            "Plane of {}x{} cubes cycled in {:?}",
+            size,
+            size,
+            start.elapsed()
+        );
+    }
+    args.report_time();
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::cli::{self, ErrorKind};
 
     #[cfg(not(feature = "hypercube"))]
     #[test]
@@ -169,7 +376,7 @@ mod tests {
         assert!(conway_cubes.contains(&(2, 2, 0)));
 
         // Cycle 1
-        let conway_cubes = conway_cubes.cycle();
+        let conway_cubes = conway_cubes.step(&Rule::default());
         assert_eq!(11, conway_cubes.actives());
         assert!(conway_cubes.contains(&(0, 1, -1)));
         assert!(conway_cubes.contains(&(2, 2, -1)));
@@ -184,14 +391,31 @@ mod tests {
         assert!(conway_cubes.contains(&(1, 3, 1)));
 
         // Cycle 2
-        let conway_cubes = conway_cubes.cycle();
+        let conway_cubes = conway_cubes.step(&Rule::default());
         assert_eq!(21, conway_cubes.actives());
 
         // Cycle 3
-        let conway_cubes = conway_cubes.cycle();
+        let conway_cubes = conway_cubes.step(&Rule::default());
         assert_eq!(38, conway_cubes.actives());
     }
 
+    #[cfg(all(feature = "image", not(feature = "hypercube")))]
+    #[test]
+    fn slices() {
+        let slices = ConwayCubes::from(".#.\n..#\n###")
+            .step(&Rule::default())
+            .slices();
+        let names: Vec<&str> = slices.keys().map(String::as_str).collect();
+        assert_eq!(vec!["z-1", "z0", "z1"], names);
+        // Every slice covers x from 0 to 2 and y from 1 to 3
+        assert!(slices.values().all(|slice| slice.len() == 9));
+        let z0 = &slices["z0"];
+        let actives = z0.values().filter(|&&active| active).count();
+        assert_eq!(5, actives);
+        assert_eq!(Some(&true), z0.get(&(1, 3)));
+        assert_eq!(Some(&false), z0.get(&(0, 3)));
+    }
+
     #[cfg(feature = "hypercube")]
     #[test]
     fn conway_cubes() {
@@ -200,9 +424,76 @@ mod tests {
 ###"#;
         let mut conway_cubes = ConwayCubes::from(initial);
         assert_eq!(5, conway_cubes.actives());
-        for _ in 0..6 {
-            conway_cubes = conway_cubes.cycle();
-        }
+        conway_cubes = conway_cubes.step_n(&Rule::default(), 6);
         assert_eq!(848, conway_cubes.actives());
     }
+
+    #[test]
+    fn rulestring() {
+        let rule: Rule = "B36/S23".parse().unwrap();
+        assert_eq!(vec![3, 6], rule.birth);
+        assert_eq!(vec![2, 3], rule.survival);
+        assert_eq!("B36/S23", rule.to_string());
+        assert_eq!(Rule::default(), "b3/s32".parse().unwrap());
+        assert_eq!("B/S", "B/S".parse::<Rule>().unwrap().to_string());
+        assert!("B3".parse::<Rule>().is_err());
+        assert!("S23/B3".parse::<Rule>().is_err());
+        assert!("B3/S2x".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn other_rules() {
+        let initial = ".#.\n..#\n###";
+        let cycle = |rule: &str| ConwayCubes::from(initial).step(&rule.parse::<Rule>().unwrap());
+        // Nothing is ever born nor survives
+        assert_eq!(0, cycle("B/S").actives());
+        // Every cube survives with at most 8 active neighbors
+        let survivors = cycle("B3/S012345678");
+        assert!(ConwayCubes::from(initial)
+            .iter()
+            .all(|cube| survivors.contains(cube)));
+        #[cfg(not(feature = "hypercube"))]
+        assert_eq!((13, 11), (survivors.actives(), cycle("B36/S23").actives()));
+        #[cfg(feature = "hypercube")]
+        assert_eq!((31, 29), (survivors.actives(), cycle("B36/S23").actives()));
+    }
+
+    #[test]
+    fn options() {
+        let parse = |args: &[&str]| {
+            let args = Args::try_parse_from(
+                "conway-cubes",
+                &[1],
+                Options::arguments(),
+                std::iter::once("conway-cubes").chain(args.iter().copied()),
+            )?;
+            Ok::<_, cli::Error>(Options::from_args(&args))
+        };
+        assert_eq!(Options::default(), parse(&[]).unwrap());
+        assert_eq!(
+            Options {
+                inflate: Some(100),
+                rule: "B36/S23".parse().unwrap(),
+            },
+            parse(&["--inflate", "100", "--rule", "B36/S23", "cubes.txt"]).unwrap()
+        );
+        assert!(parse(&["--rule"]).is_err());
+        assert_eq!(
+            ErrorKind::ValueValidation,
+            parse(&["--rule", "23/3"]).unwrap_err().kind()
+        );
+        assert!(parse(&["--rule", "B03/S23"]).is_err());
+        assert!(parse(&["--inflate", "big"]).is_err());
+        assert!(parse(&["--inflate", "0"]).is_err());
+    }
+
+    #[test]
+    fn birth_without_neighbors() {
+        assert!("B0/S23".parse::<Rule>().is_err());
+        assert!("b036/s23".parse::<Rule>().is_err());
+        assert_eq!(
+            Ok(vec![0, 2, 3]),
+            "B3/S023".parse::<Rule>().map(|rule| rule.survival)
+        );
+    }
 }
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use operation_order::Operation;
+
+fuzz_target!(|line: &str| {
+    let _ = Operation::parse(line);
+});
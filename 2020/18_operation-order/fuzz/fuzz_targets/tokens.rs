@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use operation_order::Tokens;
+
+fuzz_target!(|line: &str| {
+    for _ in Tokens::from(line) {}
+});
@@ -0,0 +1,413 @@
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Mul,
+}
+
+impl std::fmt::Debug for Operator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        use Operator::*;
+        match self {
+            Add => write!(f, "+")?,
+            Mul => write!(f, "*")?,
+        }
+        Ok(())
+    }
+}
+
+impl std::cmp::PartialOrd for Operator {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        use std::cmp::Ordering;
+        // Without the 'add-first' feature, all operators have the same precedence
+        #[cfg(not(feature = "add-first"))]
+        #[allow(clippy::match_single_binding)]
+        match (self, other) {
+            _ => Some(Ordering::Equal),
+        }
+        #[cfg(feature = "add-first")]
+        match (self, other) {
+            (Operator::Add, Operator::Add) | (Operator::Mul, Operator::Mul) => {
+                Some(Ordering::Equal)
+            }
+            (Operator::Add, Operator::Mul) => Some(Ordering::Greater),
+            (Operator::Mul, Operator::Add) => Some(Ordering::Less),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Group {
+    Opening,
+    Closing,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Token {
+    Scalar(isize),
+    Operator(Operator),
+    Group(Group),
+}
+
+/// Token with the byte span it covers in its line.
+#[derive(Debug, Clone)]
+pub struct Spanned {
+    token: Token,
+    span: std::ops::Range<usize>,
+}
+
+/// What is wrong in an expression, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    message: String,
+    span: std::ops::Range<usize>,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ParseError {
+    /// The line of the expression, with the offending span underlined.
+    pub fn underline(&self, line: &str) -> String {
+        common::token::underline(line, &self.span)
+    }
+}
+
+/// Tokens of a line, from its start to its end.
+#[derive(Debug)]
+pub struct Tokens<'a> {
+    line: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl<'a> std::convert::From<&'a str> for Tokens<'a> {
+    fn from(line: &'a str) -> Self {
+        Self {
+            line,
+            chars: line.char_indices().peekable(),
+        }
+    }
+}
+
+impl std::iter::Iterator for Tokens<'_> {
+    type Item = Result<Spanned, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (start, c) = loop {
+            match self.chars.next()? {
+                (_, ' ') => continue,
+                found => break found,
+            }
+        };
+        let token = match c {
+            '(' => Token::Group(Group::Opening),
+            ')' => Token::Group(Group::Closing),
+            '+' => Token::Operator(Operator::Add),
+            '*' => Token::Operator(Operator::Mul),
+            '0'..='9' => {
+                let mut end = start + 1;
+                while let Some(&(index, '0'..='9')) = self.chars.peek() {
+                    end = index + 1;
+                    self.chars.next();
+                }
+                let span = start..end;
+                return Some(match self.line[span.clone()].parse() {
+                    Ok(scalar) => Ok(Spanned {
+                        token: Token::Scalar(scalar),
+                        span,
+                    }),
+                    Err(_) => Err(ParseError {
+                        message: String::from("expect a smaller integer"),
+                        span,
+                    }),
+                });
+            }
+            c => {
+                return Some(Err(ParseError {
+                    message: format!(
+                        "expect an integer, an operator or a parenthesis, found '{}'",
+                        c
+                    ),
+                    span: start..start + c.len_utf8(),
+                }))
+            }
+        };
+        Some(Ok(Spanned {
+            token,
+            span: start..start + c.len_utf8(),
+        }))
+    }
+}
+
+pub enum Operation {
+    Scalar(isize),
+    Expression {
+        operator: Operator,
+        op1: Box<Operation>,
+        op2: Box<Operation>,
+    },
+    Group(Box<Operation>),
+}
+
+impl std::fmt::Debug for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        use Operation::*;
+        match self {
+            Scalar(v) => write!(f, "{}", v)?,
+            Expression { operator, op1, op2 } => write!(f, "{:?}{:?}{:?}", op1, operator, op2)?,
+            Group(operation) => write!(f, "({:?})", operation)?,
+        }
+        Ok(())
+    }
+}
+impl Operation {
+    /// Every expression between parentheses, showing how precedence applied.
+    pub fn parenthesized(&self) -> String {
+        use Operation::*;
+        match self {
+            Scalar(v) => v.to_string(),
+            Expression { operator, op1, op2 } => format!(
+                "({} {:?} {})",
+                op1.parenthesized(),
+                operator,
+                op2.parenthesized()
+            ),
+            Group(operation) => operation.parenthesized(),
+        }
+    }
+    pub fn evaluate(self) -> isize {
+        use Operation::*;
+        match self {
+            Scalar(v) => v,
+            Expression { operator, op1, op2 } => match operator {
+                Operator::Add => op1.evaluate() + op2.evaluate(),
+                Operator::Mul => op1.evaluate() * op2.evaluate(),
+            },
+            Group(operation) => operation.evaluate(),
+        }
+    }
+}
+
+/// Parentheses an expression nests one into the other at most, far more than
+/// the inputs need, so that the parser never overflows the stack.
+const MAX_NESTING: usize = 100;
+
+/// Precedence climbing over the tokens of a line: each operator takes as its
+/// right operand the longest expression whose operators all have a higher
+/// precedence, so that operators of the same precedence apply from left to
+/// right.
+struct Parser<'a> {
+    tokens: std::iter::Peekable<Tokens<'a>>,
+    /// Empty span at the end of the line, for what is missing there.
+    end: std::ops::Range<usize>,
+    /// Parentheses opened and not closed yet.
+    nesting: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Result<Option<Spanned>, ParseError> {
+        self.tokens.peek().cloned().transpose()
+    }
+    fn next(&mut self) -> Result<Option<Spanned>, ParseError> {
+        self.tokens.next().transpose()
+    }
+    /// An integer or an expression between parentheses.
+    fn operand(&mut self) -> Result<Operation, ParseError> {
+        match self.next()? {
+            Some(Spanned {
+                token: Token::Scalar(scalar),
+                ..
+            }) => Ok(Operation::Scalar(scalar)),
+            Some(Spanned {
+                token: Token::Group(Group::Opening),
+                span,
+            }) => {
+                if self.nesting == MAX_NESTING {
+                    return Err(ParseError {
+                        message: format!("expect at most {} nested parentheses", MAX_NESTING),
+                        span,
+                    });
+                }
+                self.nesting += 1;
+                let operation = self.expression(None)?;
+                self.nesting -= 1;
+                match self.next()? {
+                    Some(Spanned {
+                        token: Token::Group(Group::Closing),
+                        ..
+                    }) => Ok(Operation::Group(Box::new(operation))),
+                    _ => Err(ParseError {
+                        message: String::from("expect this parenthesis to be closed"),
+                        span,
+                    }),
+                }
+            }
+            Some(Spanned { span, .. }) => Err(ParseError {
+                message: String::from("expect an integer or an opening parenthesis"),
+                span,
+            }),
+            None => Err(ParseError {
+                message: String::from("expect an integer or an opening parenthesis"),
+                span: self.end.clone(),
+            }),
+        }
+    }
+    /// Expression whose operators all have a higher precedence than
+    /// `operator`, or any expression without it.
+    fn expression(&mut self, operator: Option<Operator>) -> Result<Operation, ParseError> {
+        let mut operation = self.operand()?;
+        while let Some(Spanned {
+            token: Token::Operator(next),
+            ..
+        }) = self.peek()?
+        {
+            if matches!(operator, Some(operator) if next <= operator) {
+                break;
+            }
+            self.next()?;
+            operation = Operation::Expression {
+                operator: next,
+                op1: Box::new(operation),
+                op2: Box::new(self.expression(Some(next))?),
+            };
+        }
+        Ok(operation)
+    }
+}
+
+impl Operation {
+    pub fn parse(line: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            tokens: Tokens::from(line).peekable(),
+            end: line.len()..line.len(),
+            nesting: 0,
+        };
+        let operation = parser.expression(None)?;
+        match parser.next()? {
+            None => Ok(operation),
+            Some(Spanned {
+                token: Token::Group(Group::Closing),
+                span,
+            }) => Err(ParseError {
+                message: String::from("expect no closing parenthesis without an opening one"),
+                span,
+            }),
+            Some(Spanned { span, .. }) => Err(ParseError {
+                message: String::from("expect an operator"),
+                span,
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Operations<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    iter: I,
+}
+
+impl<I> std::convert::From<I> for Operations<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    fn from(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<I> std::iter::Iterator for Operations<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    type Item = Operation;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|line| {
+            Operation::parse(line)
+                .unwrap_or_else(|error| panic!("{}\n{}", error, error.underline(line)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "add-first"))]
+    fn operation_order() {
+        let mut operations = Operations::from(
+            r#"1 + 2 * 3 + 4 * 5 + 6
+1 + (2 * 3) + (4 * (5 + 6))
+2 * 3 + (4 * 5)
+5 + (8 * 3 + 9 + 3 * 4 * 3)
+5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
+((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"#
+                .lines(),
+        );
+        assert_eq!(71, operations.next().unwrap().evaluate());
+        assert_eq!(51, operations.next().unwrap().evaluate());
+        assert_eq!(26, operations.next().unwrap().evaluate());
+        assert_eq!(437, operations.next().unwrap().evaluate());
+        assert_eq!(12240, operations.next().unwrap().evaluate());
+        assert_eq!(13632, operations.next().unwrap().evaluate());
+    }
+
+    #[test]
+    #[cfg(not(feature = "add-first"))]
+    fn parenthesized() {
+        let operation = Operation::parse("1 * 2 + (3 + 4 * 5)").unwrap();
+        assert_eq!("((1 * 2) + ((3 + 4) * 5))", operation.parenthesized());
+    }
+
+    #[test]
+    #[cfg(feature = "add-first")]
+    fn operation_order() {
+        let mut operations = Operations::from(
+            r#"1 + 2 * 3 + 4 * 5 + 6
+1 + (2 * 3) + (4 * (5 + 6))
+2 * 3 + (4 * 5)
+5 + (8 * 3 + 9 + 3 * 4 * 3)
+5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))
+((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"#
+                .lines(),
+        );
+        assert_eq!(231, operations.next().unwrap().evaluate());
+        assert_eq!(51, operations.next().unwrap().evaluate());
+        assert_eq!(46, operations.next().unwrap().evaluate());
+        assert_eq!(1445, operations.next().unwrap().evaluate());
+        assert_eq!(669060, operations.next().unwrap().evaluate());
+        assert_eq!(23340, operations.next().unwrap().evaluate());
+    }
+
+    #[test]
+    #[cfg(feature = "add-first")]
+    fn parenthesized() {
+        let operation = Operation::parse("1 * 2 + (3 + 4 * 5)").unwrap();
+        assert_eq!("(1 * (2 + ((3 + 4) * 5)))", operation.parenthesized());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |line| Operation::parse(line).unwrap_err();
+        assert_eq!(
+            ParseError {
+                message: String::from("expect an integer or an opening parenthesis"),
+                span: 3..3
+            },
+            error("1 +")
+        );
+        assert_eq!(0..1, error("(1 + 2").span);
+        assert_eq!(5..6, error("1 + 2)").span);
+        assert_eq!("expect an operator", error("1 2").message);
+        assert_eq!("1 - 2\n  ^", error("1 - 2").underline("1 - 2"));
+        assert_eq!(576, Operation::parse("12 * (3 + 45)").unwrap().evaluate());
+        let nested = format!("{}1{}", "(".repeat(MAX_NESTING), ")".repeat(MAX_NESTING));
+        assert_eq!(1, Operation::parse(&nested).unwrap().evaluate());
+        let error = error(&format!("({}", nested));
+        assert_eq!("expect at most 100 nested parentheses", error.message);
+        assert_eq!(100..101, error.span);
+    }
+}
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use monster_messages::Rule;
+
+fuzz_target!(|rule: &str| {
+    let _ = rule.parse::<Rule>();
+});
@@ -0,0 +1,608 @@
+use common::{dot::Builder, memo::Memo};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    Char(char),
+    Sequences(HashSet<Vec<usize>>),
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rule = if s.chars().count() == 3 && s.starts_with('"') && s.ends_with('"') {
+            Rule::Char(
+                s.chars()
+                    .nth(1)
+                    .expect("expect at least 1 char surrounded by quotes so this should not fail"),
+            )
+        } else {
+            Rule::Sequences(
+                s.split('|')
+                    .map(|sequence| {
+                        sequence
+                            .split_whitespace()
+                            .map(|index| {
+                                index.parse().map_err(|_| {
+                                    format!("expect a rule index to be an integer, got '{}'", index)
+                                })
+                            })
+                            .collect()
+                    })
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+        Ok(rule)
+    }
+}
+
+#[derive(Debug)]
+pub struct Rules {
+    rules_map: RulesMap,
+    /// Number of rules the matcher follows one into the other, see
+    /// [`Rules::with_max_depth`].
+    max_depth: usize,
+}
+
+/// Rules followed one into the other by default, far more than the inputs
+/// need, yet few enough for the stack of any thread.
+pub const MAX_DEPTH: usize = 1000;
+
+/// A message needs more nested rules than allowed, as with a rule referring
+/// to itself, directly or not, before matching any character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrammarTooDeep {
+    pub rule: usize,
+    pub position: usize,
+    pub max_depth: usize,
+}
+
+impl std::fmt::Display for GrammarTooDeep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "rule {} at position {} is nested deeper than {} rules",
+            self.rule, self.position, self.max_depth
+        )
+    }
+}
+
+macro_rules! hash_set {
+    ($($e:expr),*) => {{
+        let mut hash_set = HashSet::new();
+        $(hash_set.insert($e);)*
+        hash_set
+    }}
+}
+
+pub type RulesMap = HashMap<usize, Rule>;
+common::memoized! {
+    /// Positions in `message` where the rule can end, for the rule and the
+    /// position it starts at. Each rule is matched once at each position,
+    /// however many rules refer to it. The rule is the `depth`-th nested
+    /// one, and no more than `max_depth` are followed.
+    fn ends(
+        memo,
+        rule_start: (usize, usize),
+        rules_map: &RulesMap,
+        message: &[char],
+        depth: usize,
+        max_depth: usize,
+    ) -> Result<Vec<usize>, GrammarTooDeep> {
+        let (rule_index, start) = rule_start;
+        if depth > max_depth {
+            return Err(GrammarTooDeep {
+                rule: rule_index,
+                position: start,
+                max_depth,
+            });
+        }
+        let ends = match rules_map.get(&rule_index).expect("expect the rule to exist") {
+            Rule::Char(c) if message.get(start) == Some(c) => vec![start + 1],
+            Rule::Char(_) => Vec::new(),
+            Rule::Sequences(sequences) => {
+                let mut sequences_ends = Vec::new();
+                for sequence in sequences {
+                    let mut positions = vec![start];
+                    for &rule_index in sequence {
+                        let mut next = Vec::new();
+                        for position in positions {
+                            next.extend(ends(
+                                memo,
+                                (rule_index, position),
+                                rules_map,
+                                message,
+                                depth + 1,
+                                max_depth,
+                            )?);
+                        }
+                        next.sort_unstable();
+                        next.dedup();
+                        positions = next;
+                    }
+                    sequences_ends.extend(positions);
+                }
+                sequences_ends.sort_unstable();
+                sequences_ends.dedup();
+                sequences_ends
+            }
+        };
+        Ok(ends)
+    }
+}
+
+impl<I> std::convert::From<I> for Rules
+where
+    I: Iterator<Item = &'static str>,
+{
+    fn from(iter: I) -> Self {
+        let mut rules_map: RulesMap = iter
+            .map(|rule| {
+                let mut split = rule.split(':');
+                let index = split
+                    .next()
+                    .expect("expect at least one element for the index of the rule")
+                    .parse()
+                    .expect("expect the index of the rule to be an integer");
+                let rule = split
+                    .next()
+                    .expect("expect at least a second element to describe the rule")
+                    .trim()
+                    .parse()
+                    .expect("expect each part of the rule to be an integer");
+                (index, rule)
+            })
+            .collect();
+        if cfg!(feature = "looping") {
+            rules_map.insert(8, Rule::Sequences(hash_set![vec![42], vec![42, 8]]));
+            rules_map.insert(
+                11,
+                Rule::Sequences(hash_set![vec![42, 31], vec![42, 11, 31]]),
+            );
+        }
+        Self {
+            rules_map,
+            max_depth: MAX_DEPTH,
+        }
+    }
+}
+
+fn regex_escape(c: char) -> String {
+    if "\\.+*?()|[]{}^$#&-~".contains(c) {
+        format!("\\{}", c)
+    } else {
+        c.to_string()
+    }
+}
+
+/// Why rules can't be written as a regex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The rules are recursive, the rule referring to itself, directly or
+    /// not (see feature `looping`).
+    Recursive { rule: usize },
+    /// A rule refers to a rule which doesn't exist.
+    MissingRule(usize),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Recursive { rule } => write!(
+                f,
+                "rules are recursive from rule {}, which can't be a regex",
+                rule
+            ),
+            Error::MissingRule(rule) => write!(f, "rule {} doesn't exist", rule),
+        }
+    }
+}
+
+/// Regex matching the same messages as the rule `index`, `path` holding the
+/// rules being written, which refer to it.
+pub fn rule_to_regex(
+    rules_map: &RulesMap,
+    index: usize,
+    path: &mut Vec<usize>,
+) -> Result<String, Error> {
+    if path.contains(&index) {
+        return Err(Error::Recursive { rule: index });
+    }
+    path.push(index);
+    let regex = match rules_map.get(&index).ok_or(Error::MissingRule(index))? {
+        Rule::Char(c) => regex_escape(*c),
+        Rule::Sequences(sequences) => {
+            let mut alternatives: Vec<String> = sequences
+                .iter()
+                .map(|sequence| {
+                    sequence
+                        .iter()
+                        .map(|rule_index| rule_to_regex(rules_map, *rule_index, path))
+                        .collect()
+                })
+                .collect::<Result<_, _>>()?;
+            // `HashSet` has no order, sort to always produce the same regex
+            alternatives.sort();
+            if alternatives.len() == 1 {
+                alternatives.remove(0)
+            } else {
+                format!("(?:{})", alternatives.join("|"))
+            }
+        }
+    };
+    path.pop();
+    Ok(regex)
+}
+
+impl Rules {
+    /// Anchored regex matching the same messages as the rule 0, unless the
+    /// rules are recursive (see feature `looping`).
+    pub fn to_regex(&self) -> Result<String, Error> {
+        let regex = rule_to_regex(&self.rules_map, 0, &mut Vec::new())?;
+        Ok(format!("^{}$", regex))
+    }
+
+    /// Indices of the rules referred to by the rule `index`.
+    fn references(&self, index: usize) -> BTreeSet<usize> {
+        match self.rules_map.get(&index) {
+            Some(Rule::Sequences(sequences)) => sequences.iter().flatten().copied().collect(),
+            _ => BTreeSet::new(),
+        }
+    }
+
+    /// Whether the rule `to` can be reached from the rule `from` by following
+    /// at least one reference.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut pending: Vec<usize> = self.references(from).into_iter().collect();
+        while let Some(index) = pending.pop() {
+            if index == to {
+                return true;
+            }
+            if visited.insert(index) {
+                pending.extend(self.references(index));
+            }
+        }
+        false
+    }
+
+    /// Graph of the references between the rules, in the DOT language. The
+    /// references within a loop (see feature `looping`) are drawn in red.
+    pub fn to_dot(&self) -> Builder {
+        let mut graph = Builder::new("rules");
+        let mut indices: Vec<usize> = self.rules_map.keys().copied().collect();
+        indices.sort_unstable();
+        for &index in &indices {
+            if let Some(Rule::Char(c)) = self.rules_map.get(&index) {
+                graph.node(
+                    index,
+                    &[("label", &format!("{}: {}", index, c)), ("shape", "box")],
+                );
+            } else {
+                graph.node(index, &[]);
+            }
+        }
+        for &index in &indices {
+            for reference in self.references(index) {
+                if self.reaches(reference, index) {
+                    graph.edge(index, reference, &[("color", "red")]);
+                } else {
+                    graph.edge(index, reference, &[]);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Follow at most `max_depth` rules one into the other while matching a
+    /// message, instead of [`MAX_DEPTH`].
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn is_valid(&self, message: &Message) -> Result<bool, GrammarTooDeep> {
+        let message: Vec<char> = message.chars().collect();
+        let ends = ends(
+            &mut Memo::default(),
+            (0, 0),
+            &self.rules_map,
+            &message,
+            0,
+            self.max_depth,
+        )?;
+        Ok(ends.contains(&message.len()))
+    }
+}
+
+#[derive(Debug)]
+pub struct Message {
+    inner: &'static str,
+}
+
+impl std::ops::Deref for Message {
+    type Target = &'static str;
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl std::convert::From<&'static str> for Message {
+    fn from(inner: &'static str) -> Self {
+        Self { inner }
+    }
+}
+
+#[derive(Debug)]
+pub struct Messages<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    iter: I,
+}
+impl<I> std::convert::From<I> for Messages<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    fn from(iter: I) -> Self {
+        Self { iter }
+    }
+}
+
+impl<I> std::iter::Iterator for Messages<I>
+where
+    I: Iterator<Item = &'static str>,
+{
+    type Item = Message;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(Message::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rules() {
+        assert_eq!(Ok(Rule::Char('a')), "\"a\"".parse());
+        assert_eq!(
+            Ok(Rule::Sequences(
+                vec![vec![1, 2], vec![3]].into_iter().collect()
+            )),
+            "1 2 | 3".parse()
+        );
+        assert_eq!(
+            Err(String::from(
+                "expect a rule index to be an integer, got 'a'"
+            )),
+            "1 a".parse::<Rule>()
+        );
+    }
+
+    #[test]
+    fn monster_messages1() {
+        let rules = r#"0: 1 2
+1: "a"
+2: 1 3 | 3 1
+3: "b""#;
+        let rules = Rules::from(rules.lines());
+        assert!(rules.is_valid(&Message::from("aab")).unwrap());
+        assert!(rules.is_valid(&Message::from("aba")).unwrap());
+        assert!(!rules.is_valid(&Message::from("aaa")).unwrap());
+        assert!(!rules.is_valid(&Message::from("abb")).unwrap());
+        assert!(!rules.is_valid(&Message::from("bab")).unwrap());
+        assert!(!rules.is_valid(&Message::from("bba")).unwrap());
+        assert!(!rules.is_valid(&Message::from("baa")).unwrap());
+        assert!(!rules.is_valid(&Message::from("bbb")).unwrap());
+        assert_eq!(Ok(String::from("^a(?:ab|ba)$")), rules.to_regex());
+    }
+
+    #[test]
+    fn monster_messages2() {
+        let rules = r#"0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: "a"
+5: "b""#;
+        let rules = Rules::from(rules.lines());
+        assert!(rules.is_valid(&Message::from("aaaabb")).unwrap());
+        assert!(rules.is_valid(&Message::from("aaabab")).unwrap());
+        assert!(rules.is_valid(&Message::from("abbabb")).unwrap());
+        assert!(rules.is_valid(&Message::from("abbbab")).unwrap());
+        assert!(rules.is_valid(&Message::from("aabaab")).unwrap());
+        assert!(rules.is_valid(&Message::from("aabbbb")).unwrap());
+        assert!(rules.is_valid(&Message::from("abaaab")).unwrap());
+        assert!(rules.is_valid(&Message::from("ababbb")).unwrap());
+        assert!(!rules.is_valid(&Message::from("bababa")).unwrap());
+        assert!(!rules.is_valid(&Message::from("aaabbb")).unwrap());
+        assert!(!rules.is_valid(&Message::from("aaaabbb")).unwrap());
+    }
+
+    #[test]
+    fn looping_rules() {
+        let rules = r#"42: 9 14 | 10 1
+9: 14 27 | 1 26
+10: 23 14 | 28 1
+1: "a"
+11: 42 31
+5: 1 14 | 15 1
+19: 14 1 | 14 14
+12: 24 14 | 19 1
+16: 15 1 | 14 14
+31: 14 17 | 1 13
+6: 14 14 | 1 14
+2: 1 24 | 14 4
+0: 8 11
+13: 14 3 | 1 12
+15: 1 | 14
+17: 14 2 | 1 7
+23: 25 1 | 22 14
+28: 16 1
+4: 1 1
+20: 14 14 | 1 15
+3: 5 14 | 16 1
+27: 1 6 | 14 18
+14: "b"
+21: 14 1 | 1 14
+25: 1 1 | 1 14
+22: 14 14
+8: 42
+26: 14 22 | 1 20
+18: 15 15
+7: 14 5 | 1 21
+24: 14 1"#;
+        let messages = [
+            "abbbbbabbbaaaababbaabbbbabababbbabbbbbbabaaaa",
+            "bbabbbbaabaabba",
+            "babbbbaabbbbbabbbbbbaabaaabaaa",
+            "aaabbbbbbaaaabaababaabababbabaaabbababababaaa",
+            "bbbbbbbaaaabbbbaaabbabaaa",
+            "bbbababbbbaaaaaaaabbababaaababaabab",
+            "ababaaaaaabaaab",
+            "ababaaaaabbbaba",
+            "baabbaaaabbaaaababbaababb",
+            "abbbbabbbbaaaababbbbbbaaaababb",
+            "aaaaabbaabaaaaababaa",
+            "aaaabbaaaabbaaa",
+            "aaaabbaabbaaaaaaabbbabbbaaabbaabaaa",
+            "babaaabbbaaabaababbaabababaaab",
+            "aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba",
+        ];
+        let mut rules = Rules::from(rules.lines());
+        let valid = |rules: &Rules| {
+            messages
+                .iter()
+                .filter(|message| rules.is_valid(&Message::from(**message)).unwrap())
+                .count()
+        };
+        if !cfg!(feature = "looping") {
+            assert_eq!(3, valid(&rules));
+        }
+        rules
+            .rules_map
+            .insert(8, Rule::Sequences(hash_set![vec![42], vec![42, 8]]));
+        rules.rules_map.insert(
+            11,
+            Rule::Sequences(hash_set![vec![42, 31], vec![42, 11, 31]]),
+        );
+        assert_eq!(12, valid(&rules));
+    }
+
+    #[test]
+    fn regex_matches_same_messages() {
+        let rules = r#"0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: "a"
+5: "b""#;
+        let rules = Rules::from(rules.lines());
+        let regex = regex::Regex::new(&rules.to_regex().unwrap()).unwrap();
+        for message in &[
+            "aaaabb", "aaabab", "abbabb", "abbbab", "aabaab", "aabbbb", "abaaab", "ababbb",
+            "bababa", "aaabbb", "aaaabbb", "",
+        ] {
+            assert_eq!(
+                rules.is_valid(&Message::from(*message)).unwrap(),
+                regex.is_match(message),
+                "message '{}'",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn dot_of_looping_rules() {
+        let rules = Rules {
+            rules_map: vec![
+                (0, "1 2".parse().unwrap()),
+                (1, "3 | 3 1".parse().unwrap()),
+                (2, "3 1 | 4".parse().unwrap()),
+                (3, "\"a\"".parse().unwrap()),
+                (4, "\"b\"".parse().unwrap()),
+            ]
+            .into_iter()
+            .collect(),
+            max_depth: MAX_DEPTH,
+        };
+        assert_eq!(
+            r#"digraph "rules" {
+    "0";
+    "1";
+    "2";
+    "3" [label="3: a", shape="box"];
+    "4" [label="4: b", shape="box"];
+    "0" -> "1";
+    "0" -> "2";
+    "1" -> "1" [color="red"];
+    "1" -> "3";
+    "2" -> "1";
+    "2" -> "3";
+    "2" -> "4";
+}
+"#,
+            rules.to_dot().to_string()
+        );
+    }
+
+    #[test]
+    fn grammar_too_deep() {
+        // Rules referring to each other without any character to match
+        let rules = Rules::from(vec!["0: 1", "1: 0"].into_iter());
+        let error = rules.is_valid(&Message::from("a")).unwrap_err();
+        assert_eq!(
+            GrammarTooDeep {
+                rule: 1,
+                position: 0,
+                max_depth: MAX_DEPTH
+            },
+            error
+        );
+        assert_eq!(
+            "rule 1 at position 0 is nested deeper than 1000 rules",
+            error.to_string()
+        );
+        // A rule referring first to itself, with a character to match
+        let rules = Rules::from(vec!["0: 0 1 | 1", "1: \"a\""].into_iter());
+        assert!(rules.is_valid(&Message::from("aaa")).is_err());
+    }
+
+    #[test]
+    fn max_depth() {
+        let rules = r#"0: 4 1 5
+1: 2 3 | 3 2
+2: 4 4 | 5 5
+3: 4 5 | 5 4
+4: "a"
+5: "b""#;
+        let rules = Rules::from(rules.lines()).with_max_depth(3);
+        assert!(rules.is_valid(&Message::from("aaaabb")).unwrap());
+        let rules = rules.with_max_depth(2);
+        assert!(matches!(
+            rules.is_valid(&Message::from("aaaabb")),
+            Err(GrammarTooDeep {
+                position: 1,
+                max_depth: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn regex_of_looping_rules() {
+        let rules = Rules {
+            rules_map: vec![
+                (0, "1 | 1 0".parse().unwrap()),
+                (1, "\"a\"".parse().unwrap()),
+            ]
+            .into_iter()
+            .collect(),
+            max_depth: MAX_DEPTH,
+        };
+        assert_eq!(Err(Error::Recursive { rule: 0 }), rules.to_regex());
+        let rules = Rules::from(vec!["0: 1 2", "1: \"a\""].into_iter());
+        assert_eq!(Err(Error::MissingRule(2)), rules.to_regex());
+    }
+}
@@ -0,0 +1,65 @@
+use std::{env, io::BufRead};
+
+/// Number of sums of `window` consecutive depths which are bigger than the
+/// previous sum.
+fn increases(depths: &[u32], window: usize) -> usize {
+    // Two consecutive sums only differ by the first depth of the previous one
+    // and the last depth of the next one
+    depths
+        .windows(window + 1)
+        .filter(|depths| depths[window] > depths[0])
+        .count()
+}
+
+fn depths(reader: impl BufRead) -> Vec<u32> {
+    reader
+        .lines()
+        .map(|line| line.expect("expect line to be parseable as a String"))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| common::parse::integer(&line).unwrap_or_else(|error| panic!("{}", error)))
+        .collect()
+}
+
+fn main() {
+    let args = common::cli::Args::parse(
+        env!("CARGO_PKG_NAME"),
+        &[if cfg!(feature = "window") { 2 } else { 1 }],
+    );
+    let reader = common::input::load(args.input(), env!("CARGO_MANIFEST_DIR"))
+        .expect("expect the input to exist");
+    let depths = depths(reader);
+    #[cfg(not(feature = "window"))]
+    let window = 1;
+    #[cfg(feature = "window")]
+    let window = 3;
+    println!(
+        "{} measurements are larger than the previous one",
+        increases(&depths, window)
+    );
+    args.report_time();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEPTHS: [u32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+
+    fn solve(input: &str) -> Vec<(u8, usize)> {
+        let depths = depths(input.as_bytes());
+        vec![(1, increases(&depths, 1)), (2, increases(&depths, 3))]
+    }
+
+    common::test_examples!(solve);
+
+    #[test]
+    fn single_measurements() {
+        assert_eq!(7, increases(&DEPTHS, 1));
+    }
+
+    #[test]
+    fn sliding_window() {
+        assert_eq!(5, increases(&DEPTHS, 3));
+        assert_eq!(0, increases(&DEPTHS[..3], 3));
+    }
+}
@@ -1,4 +1,4 @@
-let file = "calories.txt"
+let file = "../../inputs/2022/01.txt"
 
 exception MissingElves of int
 
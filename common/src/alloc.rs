@@ -0,0 +1,206 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Allocator of the system, counting the allocations and the bytes in use.
+///
+/// A day installs it behind its own `alloc-stats` feature.
+/// ```ignore
+/// #[cfg(feature = "alloc-stats")]
+/// #[global_allocator]
+/// static ALLOCATOR: common::alloc::Counting = common::alloc::Counting;
+/// ```
+pub struct Counting;
+
+fn allocated(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let in_use = IN_USE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(in_use, Ordering::Relaxed);
+}
+
+fn deallocated(size: usize) {
+    IN_USE.fetch_sub(size, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        deallocated(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            deallocated(layout.size());
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// What was allocated with `Counting` since the start of the program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of allocations, a growing `Vec` counting once per growth.
+    pub allocations: usize,
+    /// Most bytes in use at once.
+    pub peak: usize,
+}
+
+pub fn stats() -> Stats {
+    Stats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        peak: PEAK.load(Ordering::Relaxed),
+    }
+}
+
+/// A number of bytes, displayed in the largest unit keeping it above 1, e.g.
+/// `1.5 MiB`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bytes(pub usize);
+
+const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut size = self.0 as f64;
+        for unit in &UNITS[..UNITS.len() - 1] {
+            if size < 1024.0 {
+                return write!(f, "{:.1} {}", size, unit);
+            }
+            size /= 1024.0;
+        }
+        write!(f, "{:.1} {}", size, UNITS[UNITS.len() - 1])
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, peak memory {}",
+            self.allocations,
+            Bytes(self.peak)
+        )
+    }
+}
+
+/// Stats as displayed, e.g. read back from what a day printed, the peak being
+/// only as precise as its display.
+impl FromStr for Stats {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "expect '<COUNT> allocations, peak memory <SIZE> <UNIT>', found '{}'",
+                s
+            )
+        };
+        let (allocations, peak) = s
+            .trim()
+            .split_once(" allocations, peak memory ")
+            .ok_or_else(invalid)?;
+        let allocations = allocations.parse().map_err(|_| invalid())?;
+        let (size, unit) = peak.split_once(' ').ok_or_else(invalid)?;
+        let size: f64 = size.parse().map_err(|_| invalid())?;
+        let power = UNITS
+            .iter()
+            .position(|known| *known == unit)
+            .ok_or_else(invalid)?;
+        Ok(Stats {
+            allocations,
+            peak: (size * 1024f64.powi(power as i32)).round() as usize,
+        })
+    }
+}
+
+/// Print the time elapsed since `start` with the stats of the allocations
+/// on the standard error, keeping the answer alone on the standard output.
+pub fn report(start: Instant) {
+    eprintln!("Ran in {:.2?}, {}", start.elapsed(), stats());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_allocations() {
+        // `Counting` is not the allocator of the tests, only this test uses it
+        let before = stats();
+        let layout = Layout::from_size_align(1 << 20, 8).unwrap();
+        unsafe {
+            let ptr = Counting.alloc(layout);
+            assert!(!ptr.is_null());
+            let ptr = Counting.realloc(ptr, layout, 1 << 21);
+            assert!(!ptr.is_null());
+            Counting.dealloc(ptr, Layout::from_size_align(1 << 21, 8).unwrap());
+        }
+        let after = stats();
+        assert_eq!(before.allocations + 2, after.allocations);
+        assert!(after.peak >= 1 << 21);
+        assert_eq!(0, IN_USE.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn display_stats() {
+        let stats = |peak| Stats {
+            allocations: 3,
+            peak,
+        };
+        assert_eq!("3 allocations, peak memory 512.0 B", stats(512).to_string());
+        assert_eq!(
+            "3 allocations, peak memory 1.5 MiB",
+            stats(3 << 19).to_string()
+        );
+        assert_eq!(
+            "3 allocations, peak memory 2.0 GiB",
+            stats(1 << 31).to_string()
+        );
+    }
+
+    #[test]
+    fn parse_stats() {
+        let stats = |peak| Stats {
+            allocations: 3,
+            peak,
+        };
+        assert_eq!(Ok(stats(512)), "3 allocations, peak memory 512.0 B".parse());
+        assert_eq!(
+            Ok(stats(3 << 19)),
+            "3 allocations, peak memory 1.5 MiB".parse()
+        );
+        assert_eq!(Ok(stats(1 << 31)), stats(1 << 31).to_string().parse());
+        assert!("3 allocations".parse::<Stats>().is_err());
+        assert!("3 allocations, peak memory 1.5 TiB"
+            .parse::<Stats>()
+            .is_err());
+        assert!("many allocations, peak memory 1.5 MiB"
+            .parse::<Stats>()
+            .is_err());
+    }
+}
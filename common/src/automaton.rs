@@ -0,0 +1,152 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// How the steps of an automaton end, if they do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stabilization {
+    /// The automaton doesn't change anymore after this number of steps.
+    Stabilized(usize),
+    /// The automaton after `start` steps comes back every `period` steps.
+    Cycle { start: usize, period: usize },
+}
+
+/// Cells which all change at once at each step, the next state of each cell
+/// depending on its state and on the states of its neighbors, as given by a
+/// rule `R`.
+pub trait Automaton<R: ?Sized>: Sized {
+    type Position: Copy;
+    type State: Copy;
+
+    /// Positions of the cells which may change at the next step.
+    fn positions(&self, rule: &R) -> Vec<Self::Position>;
+    fn state(&self, position: Self::Position) -> Self::State;
+    fn neighbors(&self, rule: &R, position: Self::Position) -> Vec<Self::Position>;
+    /// Next state of a cell, from its state and the states of its neighbors.
+    fn transition(rule: &R, state: Self::State, neighbors: &[Self::State]) -> Self::State;
+    /// Automaton with the new state of each cell in `states`, the other
+    /// cells being left as they are.
+    fn with_states(&self, states: Vec<(Self::Position, Self::State)>) -> Self;
+
+    fn step(&self, rule: &R) -> Self {
+        let states = self
+            .positions(rule)
+            .into_iter()
+            .map(|position| {
+                let neighbors: Vec<Self::State> = self
+                    .neighbors(rule, position)
+                    .into_iter()
+                    .map(|neighbor| self.state(neighbor))
+                    .collect();
+                let state = Self::transition(rule, self.state(position), &neighbors);
+                (position, state)
+            })
+            .collect();
+        self.with_states(states)
+    }
+
+    fn step_n(self, rule: &R, steps: usize) -> Self {
+        (0..steps).fold(self, |automaton, _| automaton.step(rule))
+    }
+
+    /// Run steps until the automaton comes back to a previous state, which
+    /// is the previous step if the automaton stabilizes.
+    fn stabilize(&mut self, rule: &R) -> Stabilization
+    where
+        Self: Clone + Eq + Hash,
+    {
+        self.stabilize_with(rule, |_| {})
+    }
+
+    /// Same as `stabilize`, calling `on_step` with the automaton after each
+    /// step.
+    fn stabilize_with<F>(&mut self, rule: &R, mut on_step: F) -> Stabilization
+    where
+        Self: Clone + Eq + Hash,
+        F: FnMut(&Self),
+    {
+        let mut steps = HashMap::new();
+        steps.insert(self.clone(), 0);
+        for step in 1.. {
+            *self = self.step(rule);
+            on_step(self);
+            if let Some(&start) = steps.get(self) {
+                let period = step - start;
+                return if period == 1 {
+                    Stabilization::Stabilized(start)
+                } else {
+                    Stabilization::Cycle { start, period }
+                };
+            }
+            steps.insert(self.clone(), step);
+        }
+        unreachable!("expect the steps to go on until the automaton repeats itself")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Elementary automaton on a ring of cells, the rule being its Wolfram
+    /// code.
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    struct Ring(Vec<bool>);
+
+    impl Automaton<u8> for Ring {
+        type Position = usize;
+        type State = bool;
+
+        fn positions(&self, _: &u8) -> Vec<usize> {
+            (0..self.0.len()).collect()
+        }
+        fn state(&self, position: usize) -> bool {
+            self.0[position]
+        }
+        fn neighbors(&self, _: &u8, position: usize) -> Vec<usize> {
+            let len = self.0.len();
+            vec![(position + len - 1) % len, (position + 1) % len]
+        }
+        fn transition(rule: &u8, state: bool, neighbors: &[bool]) -> bool {
+            let pattern = (neighbors[0] as u8) << 2 | (state as u8) << 1 | neighbors[1] as u8;
+            rule >> pattern & 1 == 1
+        }
+        fn with_states(&self, states: Vec<(usize, bool)>) -> Self {
+            let mut ring = self.clone();
+            for (position, state) in states {
+                ring.0[position] = state;
+            }
+            ring
+        }
+    }
+
+    fn ring(cells: &str) -> Ring {
+        Ring(cells.chars().map(|cell| cell == '#').collect())
+    }
+
+    #[test]
+    fn steps() {
+        // Rule 90: each cell becomes the exclusive or of its neighbors
+        assert_eq!(ring("..#.#.."), ring("...#...").step(&90));
+        assert_eq!(ring(".#...#."), ring("...#...").step_n(&90, 2));
+        assert_eq!(ring("...#..."), ring("...#...").step_n(&90, 0));
+    }
+
+    #[test]
+    fn stabilized_or_cycle() {
+        // Rule 4: only the lone cells stay
+        let mut automaton = ring("##.#..##.");
+        assert_eq!(Stabilization::Stabilized(1), automaton.stabilize(&4));
+        assert_eq!(ring("...#....."), automaton);
+        // Rule 2: every cell moves one cell to the left
+        let mut automaton = ring("#...");
+        let mut steps = 0;
+        let stabilization = automaton.stabilize_with(&2, |_| steps += 1);
+        assert_eq!(
+            Stabilization::Cycle {
+                start: 0,
+                period: 4
+            },
+            stabilization
+        );
+        assert_eq!(4, steps);
+    }
+}
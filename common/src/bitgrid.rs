@@ -0,0 +1,251 @@
+use crate::automaton::Automaton;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Grid of `width` by `height` cells, alive or dead, packed 64 to a word.
+/// Each line is surrounded by a border of dead cells, so that the
+/// neighbors of any cell are counted without checking the bounds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    /// Words of each line, the border included.
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    /// Grid of dead cells.
+    pub fn new(width: usize, height: usize) -> Self {
+        let words = (width + 2).div_ceil(BITS);
+        BitGrid {
+            width,
+            height,
+            words,
+            bits: vec![0; words * (height + 2)],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Word and bit of the cell at `(x, y)`, past the border.
+    fn index(&self, x: usize, y: usize) -> (usize, usize) {
+        assert!(
+            x < self.width && y < self.height,
+            "expect ({}, {}) to be in a grid of {}x{}",
+            x,
+            y,
+            self.width,
+            self.height
+        );
+        let column = x + 1;
+        ((y + 1) * self.words + column / BITS, column % BITS)
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (word, bit) = self.index(x, y);
+        self.bits[word] >> bit & 1 == 1
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, alive: bool) {
+        let (word, bit) = self.index(x, y);
+        if alive {
+            self.bits[word] |= 1 << bit;
+        } else {
+            self.bits[word] &= !(1 << bit);
+        }
+    }
+
+    /// Make the cell at `(x, y)` alive, returning whether it was dead, as
+    /// inserting into a set of visited positions.
+    pub fn insert(&mut self, x: usize, y: usize) -> bool {
+        let dead = !self.get(x, y);
+        self.set(x, y, true);
+        dead
+    }
+
+    /// Number of alive cells.
+    pub fn count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The 3 cells of the line `row` from the column `column`, both counted
+    /// with the border, as the lowest bits.
+    fn three(&self, row: usize, column: usize) -> u64 {
+        let word = row * self.words + column / BITS;
+        let bit = column % BITS;
+        let mut three = self.bits[word] >> bit;
+        if bit > BITS - 3 {
+            three |= self.bits[word + 1] << (BITS - bit);
+        }
+        three & 0b111
+    }
+
+    /// Number of alive cells among the 8 around `(x, y)`.
+    pub fn neighbors(&self, x: usize, y: usize) -> usize {
+        let around: u32 = (y..y + 3).map(|row| self.three(row, x).count_ones()).sum();
+        around as usize - self.get(x, y) as usize
+    }
+}
+
+/// Cells of a Life-like automaton, the rule giving whether a cell is alive
+/// at the next step from whether it is alive and its number of alive
+/// neighbors. The cells past the border stay dead.
+impl<R> Automaton<R> for BitGrid
+where
+    R: Fn(bool, usize) -> bool,
+{
+    type Position = (usize, usize);
+    type State = bool;
+
+    fn positions(&self, _: &R) -> Vec<(usize, usize)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .collect()
+    }
+    fn state(&self, (x, y): (usize, usize)) -> bool {
+        self.get(x, y)
+    }
+    fn neighbors(&self, _: &R, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let (x, y) = (x as isize, y as isize);
+        (y - 1..=y + 1)
+            .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+            .filter(|&neighbor| neighbor != (x, y))
+            .filter(|&(nx, ny)| {
+                0 <= nx && nx < self.width as isize && 0 <= ny && ny < self.height as isize
+            })
+            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .collect()
+    }
+    fn transition(rule: &R, alive: bool, neighbors: &[bool]) -> bool {
+        rule(alive, neighbors.iter().filter(|&&alive| alive).count())
+    }
+    fn with_states(&self, states: Vec<((usize, usize), bool)>) -> Self {
+        let mut grid = self.clone();
+        for ((x, y), alive) in states {
+            grid.set(x, y, alive);
+        }
+        grid
+    }
+
+    /// Same as the step of any automaton, counting the neighbors of each
+    /// cell from three words instead of eight cells.
+    fn step(&self, rule: &R) -> Self {
+        let mut grid = BitGrid::new(self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if rule(self.get(x, y), self.neighbors(x, y)) {
+                    grid.set(x, y, true);
+                }
+            }
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::automaton::Stabilization;
+
+    fn life(alive: bool, neighbors: usize) -> bool {
+        neighbors == 3 || (alive && neighbors == 2)
+    }
+
+    fn grid(lines: &str) -> BitGrid {
+        let lines: Vec<&str> = lines.lines().collect();
+        let mut grid = BitGrid::new(lines[0].len(), lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, cell) in line.chars().enumerate() {
+                grid.set(x, y, cell == '#');
+            }
+        }
+        grid
+    }
+
+    #[test]
+    fn cells() {
+        let mut grid = BitGrid::new(70, 3);
+        assert_eq!((70, 3, 0), (grid.width(), grid.height(), grid.count()));
+        assert!(grid.insert(63, 1));
+        assert!(!grid.insert(63, 1));
+        grid.set(0, 0, true);
+        grid.set(69, 2, true);
+        assert!(grid.get(63, 1) && grid.get(0, 0) && grid.get(69, 2));
+        assert!(!grid.get(62, 1) && !grid.get(64, 1));
+        assert_eq!(3, grid.count());
+        grid.set(63, 1, false);
+        assert_eq!(2, grid.count());
+    }
+
+    #[test]
+    fn neighbors_across_words() {
+        let mut grid = BitGrid::new(130, 3);
+        for x in 0..130 {
+            for y in 0..3 {
+                grid.set(x, y, true);
+            }
+        }
+        assert_eq!(3, grid.neighbors(0, 0));
+        assert_eq!(5, grid.neighbors(0, 1));
+        // Around the bounds of the words, the border being counted too
+        for x in [61, 62, 63, 64, 125, 126, 127, 128] {
+            assert_eq!(8, grid.neighbors(x, 1), "at x = {}", x);
+        }
+        assert_eq!(3, grid.neighbors(129, 2));
+    }
+
+    #[test]
+    fn popcount_step_is_a_step() {
+        let grid = grid(
+            "\
+.#..#####..#.....#..........#......#...........#.......#...........#.
+#.##...#..###...#.#....#..#.#.#..#.##.#..#...#..#.#...####..#..#.....
+.#....#.#..#.##..##.#.#..#..####.#..#..##.#.#..##.#.#...#.##...##.#.#
+##.#.#..###..##..##.#..#....##...#...#.#..#.##.#..#.##....#.#.##.#..#",
+        );
+        // Each neighbor checked one after the other
+        let naive = |x: usize, y: usize| {
+            let (x, y) = (x as isize, y as isize);
+            (y - 1..=y + 1)
+                .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                .filter(|&neighbor| neighbor != (x, y))
+                .filter(|(nx, ny)| (0..69).contains(nx) && (0..4).contains(ny))
+                .filter(|&(nx, ny)| grid.get(nx as usize, ny as usize))
+                .count()
+        };
+        let mut expected = BitGrid::new(69, 4);
+        for y in 0..4 {
+            for x in 0..69 {
+                assert_eq!(naive(x, y), grid.neighbors(x, y), "at ({}, {})", x, y);
+                expected.set(x, y, life(grid.get(x, y), naive(x, y)));
+            }
+        }
+        assert_eq!(expected, grid.step(&life));
+    }
+
+    #[test]
+    fn blinker() {
+        let mut blinker = grid(".....\n..#..\n..#..\n..#..\n.....");
+        assert_eq!(
+            grid(".....\n.....\n.###.\n.....\n....."),
+            blinker.step(&life)
+        );
+        assert_eq!(
+            Stabilization::Cycle {
+                start: 0,
+                period: 2
+            },
+            blinker.stabilize(&life)
+        );
+    }
+}
@@ -0,0 +1,375 @@
+pub use clap::{error::ErrorKind, value_parser, Arg, ArgAction, Error};
+use clap::{ArgMatches, Command};
+use std::{ffi::OsString, fmt::Display, time::Instant};
+
+/// Arguments shared by the days: the input, the part expected from the day,
+/// and whether to print how long the day took.
+#[derive(Debug)]
+pub struct Args {
+    inputs: Vec<String>,
+    time: bool,
+    start: Instant,
+    matches: ArgMatches,
+    command: Command,
+}
+
+fn command(name: &'static str) -> Command {
+    options(name)
+        .arg(
+            Arg::new("input")
+                .value_name("INPUT")
+                .help("Path of the input, `-` for the standard input"),
+        )
+        .arg(
+            Arg::new("input-path")
+                .long("input")
+                .value_name("INPUT")
+                .conflicts_with("input")
+                .help("Path of the input, like the positional argument"),
+        )
+}
+
+/// Arguments of every day, whether it reads an input or not.
+fn options(name: &'static str) -> Command {
+    Command::new(name)
+        .arg(
+            Arg::new("part")
+                .long("part")
+                .value_name("PART")
+                .value_parser(value_parser!(u8).range(1..=2))
+                .help("Part expected from the day, an error if not answered by this build"),
+        )
+        .arg(
+            Arg::new("time")
+                .long("time")
+                .action(ArgAction::SetTrue)
+                .help("Print how long the day took on the standard error"),
+        )
+}
+
+impl Args {
+    /// Read the arguments of the day `name`, which answers `parts` as built,
+    /// e.g. `&[1, 2]` when a single run prints both answers. Exit with the
+    /// usage of the day on an invalid argument.
+    pub fn parse(name: &'static str, parts: &[u8]) -> Self {
+        Self::parse_with(name, parts, Vec::new())
+    }
+
+    /// Like `parse`, with the arguments `extra` of the day, whose values are
+    /// read back with `value`.
+    pub fn parse_with(name: &'static str, parts: &[u8], extra: Vec<Arg>) -> Self {
+        Self::try_parse_from(name, parts, extra, std::env::args_os())
+            .unwrap_or_else(|error| error.exit())
+    }
+
+    /// Like `parse_with`, for a day reading its values from the command line
+    /// instead of an input: `extra` holds all its arguments, the positional
+    /// ones included.
+    pub fn parse_values(name: &'static str, parts: &[u8], extra: Vec<Arg>) -> Self {
+        Self::try_parse_values_from(name, parts, extra, std::env::args_os())
+            .unwrap_or_else(|error| error.exit())
+    }
+
+    /// Like `parse`, for a day reading several inputs one after the other,
+    /// all given as positional arguments and read back with `inputs`.
+    pub fn parse_inputs(name: &'static str, parts: &[u8]) -> Self {
+        Self::try_parse_inputs_from(name, parts, std::env::args_os())
+            .unwrap_or_else(|error| error.exit())
+    }
+
+    /// Like `parse_with`, from `args` whose first one is the name of the
+    /// program.
+    pub fn try_parse_from<I, T>(
+        name: &'static str,
+        parts: &[u8],
+        extra: Vec<Arg>,
+        args: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Self::try_parse_command(command(name).args(extra), parts, args)
+    }
+
+    /// Like `parse_values`, from `args` whose first one is the name of the
+    /// program.
+    pub fn try_parse_values_from<I, T>(
+        name: &'static str,
+        parts: &[u8],
+        extra: Vec<Arg>,
+        args: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        Self::try_parse_command(options(name).args(extra), parts, args)
+    }
+
+    /// Like `parse_inputs`, from `args` whose first one is the name of the
+    /// program.
+    pub fn try_parse_inputs_from<I, T>(
+        name: &'static str,
+        parts: &[u8],
+        args: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let command = command(name).mut_arg("input", |input| {
+            input
+                .num_args(1..)
+                .help("Paths of the inputs, `-` for the standard input")
+        });
+        Self::try_parse_command(command, parts, args)
+    }
+
+    fn try_parse_command<I, T>(mut command: Command, parts: &[u8], args: I) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = command.try_get_matches_from_mut(args)?;
+        if let Some(part) = matches.get_one::<u8>("part") {
+            if !parts.contains(part) {
+                let message = format!(
+                    "this build does not answer part {}, see the README of the day for its features",
+                    part
+                );
+                return Err(command.error(ErrorKind::InvalidValue, message));
+            }
+        }
+        // Days reading their values instead of an input have no input
+        let given = |id| matches.try_get_many::<String>(id).ok().flatten();
+        let inputs = given("input")
+            .or_else(|| given("input-path"))
+            .map(|inputs| inputs.cloned().collect())
+            .unwrap_or_default();
+        Ok(Args {
+            inputs,
+            time: matches.get_flag("time"),
+            start: Instant::now(),
+            matches,
+            command,
+        })
+    }
+
+    /// Value of the argument `id` of the day, if given.
+    pub fn value(&self, id: &str) -> Option<&str> {
+        self.matches.get_one::<String>(id).map(String::as_str)
+    }
+
+    /// Value of the argument `id` of the day, as read by its `value_parser`,
+    /// if given or defaulted.
+    pub fn get<T>(&self, id: &str) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.matches.get_one::<T>(id).cloned()
+    }
+
+    /// Values of the argument `id` of the day, as read by its `value_parser`,
+    /// given as many times or with as many values as it takes.
+    pub fn get_all<T>(&self, id: &str) -> Vec<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.matches
+            .get_many::<T>(id)
+            .map(|values| values.cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Values of the argument `id` of the day, given as many times as needed
+    /// with `ArgAction::Append`.
+    pub fn values(&self, id: &str) -> Vec<&str> {
+        self.matches
+            .get_many::<String>(id)
+            .map(|values| values.map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Whether the flag `id` of the day, with `ArgAction::SetTrue`, is given.
+    pub fn flag(&self, id: &str) -> bool {
+        self.matches.get_flag(id)
+    }
+
+    /// Error about arguments which were read but can't be used, e.g. a value
+    /// unknown to the input, shown with the usage of the day by `exit`.
+    pub fn error<D: Display>(&self, message: D) -> Error {
+        self.command
+            .clone()
+            .error(ErrorKind::ValueValidation, message)
+    }
+
+    /// Path of the input given on the command line, if any, to be loaded with
+    /// `common::input`.
+    pub fn input(&self) -> Option<&str> {
+        self.inputs.first().map(String::as_str)
+    }
+
+    /// Paths of the inputs given on the command line, read with
+    /// `parse_inputs`, to be loaded one after the other with `common::input`.
+    /// None of them means the default input of the day.
+    pub fn inputs(&self) -> Vec<Option<&str>> {
+        if self.inputs.is_empty() {
+            vec![None]
+        } else {
+            self.inputs
+                .iter()
+                .map(|input| Some(input.as_str()))
+                .collect()
+        }
+    }
+
+    /// With `--time`, print how long the day took since its arguments were
+    /// read, on the standard error so that the answer stays alone on the
+    /// standard output.
+    pub fn report_time(&self) {
+        if self.time {
+            eprintln!("Answered in {:.2?}", self.start.elapsed());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(parts: &[u8], args: &[&str]) -> Result<Args, Error> {
+        Args::try_parse_from(
+            "day",
+            parts,
+            vec![Arg::new("metric").long("metric")],
+            std::iter::once("day").chain(args.iter().copied()),
+        )
+    }
+
+    #[test]
+    fn input() {
+        assert_eq!(None, parse(&[1], &[]).unwrap().input());
+        assert_eq!(Some("in.txt"), parse(&[1], &["in.txt"]).unwrap().input());
+        assert_eq!(
+            Some("in.txt"),
+            parse(&[1], &["--input", "in.txt"]).unwrap().input()
+        );
+        assert_eq!(Some("-"), parse(&[1], &["-"]).unwrap().input());
+    }
+
+    #[test]
+    fn part_and_time() {
+        let args = parse(&[1, 2], &["--part", "2", "--time"]).unwrap();
+        assert!(args.time);
+        assert!(!parse(&[2], &[]).unwrap().time);
+        assert_eq!(
+            ErrorKind::InvalidValue,
+            parse(&[1], &["--part", "2"]).unwrap_err().kind()
+        );
+        assert!(parse(&[1, 2], &["--part", "3"]).is_err());
+    }
+
+    #[test]
+    fn extra_argument() {
+        let args = parse(&[1], &["--metric", "steps", "in.txt"]).unwrap();
+        assert_eq!(Some("steps"), args.value("metric"));
+        assert_eq!(Some("in.txt"), args.input());
+        assert_eq!(None, parse(&[1], &[]).unwrap().value("metric"));
+    }
+
+    #[test]
+    fn several_inputs() {
+        let parse_inputs = |args: &[&str]| {
+            Args::try_parse_inputs_from(
+                "day",
+                &[1],
+                std::iter::once("day").chain(args.iter().copied()),
+            )
+        };
+        assert_eq!(vec![None], parse_inputs(&[]).unwrap().inputs());
+        assert_eq!(
+            vec![Some("a.txt"), Some("-"), Some("b.txt")],
+            parse_inputs(&["a.txt", "-", "b.txt"]).unwrap().inputs()
+        );
+        assert_eq!(
+            vec![Some("a.txt")],
+            parse_inputs(&["--input", "a.txt"]).unwrap().inputs()
+        );
+        assert!(parse_inputs(&["a.txt", "--input", "b.txt"]).is_err());
+        assert_eq!(vec![None], parse(&[1], &[]).unwrap().inputs());
+    }
+
+    #[test]
+    fn typed_arguments() {
+        let extra = || {
+            vec![
+                Arg::new("count").long("count").action(ArgAction::SetTrue),
+                Arg::new("bag").long("bag").action(ArgAction::Append),
+                Arg::new("every")
+                    .long("every")
+                    .value_parser(value_parser!(u64).range(1..)),
+                Arg::new("offsets")
+                    .num_args(0..)
+                    .allow_negative_numbers(true)
+                    .value_parser(value_parser!(i64)),
+            ]
+        };
+        let parse_typed = |args: &[&str]| {
+            Args::try_parse_from(
+                "day",
+                &[1],
+                extra(),
+                std::iter::once("day").chain(args.iter().copied()),
+            )
+        };
+        let args = parse_typed(&["--count", "--bag", "a", "--every", "3", "--bag", "b"]).unwrap();
+        assert!(args.flag("count"));
+        assert_eq!(vec!["a", "b"], args.values("bag"));
+        assert_eq!(Some(3u64), args.get("every"));
+        let args = parse_typed(&["in.txt", "4", "-2"]).unwrap();
+        assert_eq!(vec![4i64, -2], args.get_all("offsets"));
+        let args = parse_typed(&[]).unwrap();
+        assert!(!args.flag("count"));
+        assert!(args.values("bag").is_empty());
+        assert_eq!(None, args.get::<u64>("every"));
+        assert!(args.get_all::<i64>("offsets").is_empty());
+        for every in &["0", "often"] {
+            assert_eq!(
+                ErrorKind::ValueValidation,
+                parse_typed(&["--every", every]).unwrap_err().kind()
+            );
+        }
+        assert_eq!(ErrorKind::ValueValidation, args.error("no such bag").kind());
+    }
+
+    #[test]
+    fn values_instead_of_input() {
+        let parse_values = |args: &[&str]| {
+            Args::try_parse_values_from(
+                "day",
+                &[1],
+                vec![
+                    Arg::new("start").required(true),
+                    Arg::new("end").required(true),
+                ],
+                std::iter::once("day").chain(args.iter().copied()),
+            )
+        };
+        let args = parse_values(&["12", "--time", "34"]).unwrap();
+        assert_eq!(Some("12"), args.value("start"));
+        assert_eq!(Some("34"), args.value("end"));
+        assert_eq!(None, args.input());
+        assert!(args.time);
+        assert!(parse_values(&["12"]).is_err());
+        assert!(parse_values(&["12", "34", "56"]).is_err());
+        assert!(parse_values(&["12", "34", "--input", "in.txt"]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse(&[1], &["a.txt", "b.txt"]).is_err());
+        assert!(parse(&[1], &["a.txt", "--input", "b.txt"]).is_err());
+        assert!(parse(&[1], &["--fast"]).is_err());
+    }
+}
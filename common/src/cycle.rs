@@ -0,0 +1,129 @@
+/// Where a sequence `x, step(x), step(step(x)), ...` starts repeating itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Index of the first element of the sequence which is in the cycle.
+    pub start: u64,
+    /// Number of elements in the cycle.
+    pub length: u64,
+}
+
+/// Cycle of the sequence from `start`, found with the tortoise and the hare
+/// of Floyd: only two elements of the sequence are kept at any time.
+pub fn floyd<T, F>(start: T, mut step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: FnMut(&T) -> T,
+{
+    // The hare goes twice as fast, and meets the tortoise somewhere in the
+    // cycle, at a multiple of the length of the cycle
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+    let mut tortoise = start;
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+    let mut length = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        length += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Cycle of the sequence from `start`, found with the algorithm of Brent,
+/// which computes fewer steps than `floyd`.
+pub fn brent<T, F>(start: T, mut step: F) -> Cycle
+where
+    T: Clone + PartialEq,
+    F: FnMut(&T) -> T,
+{
+    // The tortoise waits at each power of two for the hare, which counts
+    // the length of the cycle once they meet
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut cycle_start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        cycle_start += 1;
+    }
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Number of elements in the cycle of the sequence from `start`.
+pub fn cycle_length<T, F>(start: T, step: F) -> u64
+where
+    T: Clone + PartialEq,
+    F: FnMut(&T) -> T,
+{
+    brent(start, step).length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn both(start: u64, step: fn(&u64) -> u64) -> (Cycle, Cycle) {
+        (floyd(start, step), brent(start, step))
+    }
+
+    #[test]
+    fn cycle_from_start() {
+        let cycle = Cycle {
+            start: 0,
+            length: 5,
+        };
+        assert_eq!((cycle, cycle), both(0, |x| (x + 1) % 5));
+        assert_eq!(5, cycle_length(3, |x| (x + 1) % 5));
+    }
+
+    #[test]
+    fn cycle_after_a_tail() {
+        // 0, 1, 2, then 3 to 10 again and again
+        let cycle = Cycle {
+            start: 3,
+            length: 8,
+        };
+        assert_eq!((cycle, cycle), both(0, |&x| if x < 10 { x + 1 } else { 3 }));
+    }
+
+    #[test]
+    fn fixed_point() {
+        let cycle = Cycle {
+            start: 4,
+            length: 1,
+        };
+        assert_eq!((cycle, cycle), both(0, |&x| (x + 1).min(4)));
+    }
+}
@@ -0,0 +1,192 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Attributes of a graph, a node or an edge, e.g. `[("color", "red")]`.
+type Attributes = Vec<(String, String)>;
+
+/// Directed graph written in the DOT language of Graphviz, to be drawn with
+/// e.g. `dot -Tsvg`. Everything is written in the order it is added.
+///
+/// ```ignore
+/// let mut graph = Builder::new("bags");
+/// graph
+///     .node("shiny gold", &[("color", "gold")])
+///     .edge("shiny gold", "dark red", &[("label", "2")]);
+/// print!("{}", graph);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Builder {
+    name: String,
+    attributes: Attributes,
+    subgraphs: Vec<Builder>,
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+fn owned(attributes: &[(&str, &str)]) -> Attributes {
+    attributes
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// `text` between double quotes, the quotes and the backslashes inside being
+/// escaped, and the line breaks written as `\n`.
+fn quoted(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn write_attributes(f: &mut Formatter, attributes: &[(String, String)]) -> fmt::Result {
+    if attributes.is_empty() {
+        return Ok(());
+    }
+    let attributes: Vec<String> = attributes
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, quoted(value)))
+        .collect();
+    write!(f, " [{}]", attributes.join(", "))
+}
+
+impl Builder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: Attributes::new(),
+            subgraphs: Vec::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Set an attribute of the whole graph, e.g. `("rankdir", "LR")`, or the
+    /// `label` of a subgraph.
+    pub fn attribute(&mut self, key: &str, value: &str) -> &mut Self {
+        self.attributes.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Add a graph within this one. Graphviz draws a box around the nodes of
+    /// a subgraph whose name starts with `cluster`.
+    pub fn subgraph(&mut self, subgraph: Builder) -> &mut Self {
+        self.subgraphs.push(subgraph);
+        self
+    }
+
+    /// Add the node `id`, only needed for a node with attributes, e.g. a
+    /// `label` or a `color`, or without any edge.
+    pub fn node(&mut self, id: impl Display, attributes: &[(&str, &str)]) -> &mut Self {
+        self.nodes.push((id.to_string(), owned(attributes)));
+        self
+    }
+
+    pub fn edge(
+        &mut self,
+        from: impl Display,
+        to: impl Display,
+        attributes: &[(&str, &str)],
+    ) -> &mut Self {
+        self.edges
+            .push((from.to_string(), to.to_string(), owned(attributes)));
+        self
+    }
+
+    fn write_body(&self, f: &mut Formatter, depth: usize) -> fmt::Result {
+        let indent = "    ".repeat(depth);
+        for (key, value) in &self.attributes {
+            writeln!(f, "{}{}={};", indent, key, quoted(value))?;
+        }
+        for subgraph in &self.subgraphs {
+            writeln!(f, "{}subgraph {} {{", indent, quoted(&subgraph.name))?;
+            subgraph.write_body(f, depth + 1)?;
+            writeln!(f, "{}}}", indent)?;
+        }
+        for (id, attributes) in &self.nodes {
+            write!(f, "{}{}", indent, quoted(id))?;
+            write_attributes(f, attributes)?;
+            writeln!(f, ";")?;
+        }
+        for (from, to, attributes) in &self.edges {
+            write!(f, "{}{} -> {}", indent, quoted(from), quoted(to))?;
+            write_attributes(f, attributes)?;
+            writeln!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Builder {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "digraph {} {{", quoted(&self.name))?;
+        self.write_body(f, 1)?;
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_nodes_and_edges() {
+        let mut graph = Builder::new("bags");
+        graph
+            .node("shiny gold", &[("label", "shiny gold"), ("color", "gold")])
+            .node("faded blue", &[])
+            .edge("shiny gold", "dark red", &[("label", "2")])
+            .edge(1, 2, &[("color", "red"), ("style", "bold")]);
+        assert_eq!(
+            r#"digraph "bags" {
+    "shiny gold" [label="shiny gold", color="gold"];
+    "faded blue";
+    "shiny gold" -> "dark red" [label="2"];
+    "1" -> "2" [color="red", style="bold"];
+}
+"#,
+            graph.to_string()
+        );
+    }
+
+    #[test]
+    fn escape_strings() {
+        let mut graph = Builder::new("say \"hi\"");
+        graph.node(r"C:\path", &[("label", "two\nlines")]);
+        assert_eq!(
+            r#"digraph "say \"hi\"" {
+    "C:\\path" [label="two\nlines"];
+}
+"#,
+            graph.to_string()
+        );
+    }
+
+    #[test]
+    fn write_subgraphs() {
+        let mut inner = Builder::new("cluster_inner");
+        inner.attribute("label", "inner").node("a", &[]);
+        let mut outer = Builder::new("cluster_outer");
+        outer.subgraph(inner).edge("a", "b", &[]);
+        let mut graph = Builder::new("nested");
+        graph
+            .attribute("rankdir", "LR")
+            .subgraph(outer)
+            .edge("b", "c", &[]);
+        assert_eq!(
+            r#"digraph "nested" {
+    rankdir="LR";
+    subgraph "cluster_outer" {
+        subgraph "cluster_inner" {
+            label="inner";
+            "a";
+        }
+        "a" -> "b";
+    }
+    "b" -> "c";
+}
+"#,
+            graph.to_string()
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use std::{collections::BTreeMap, ffi::OsStr, fmt::Display, fs, path::Path};
+
+/// Directory of the examples of a day, next to its `Cargo.toml`.
+pub const DIR: &str = "examples";
+
+/// Check every example of the calling crate with `solve`, a function from an
+/// input to the answers of the parts answered by the build, see [`check`].
+///
+/// ```ignore
+/// fn solve(input: &str) -> Vec<(u8, usize)> {
+///     let depths = depths(input.as_bytes());
+///     vec![(1, increases(&depths, 1)), (2, increases(&depths, 3))]
+/// }
+///
+/// common::test_examples!(solve);
+/// ```
+#[macro_export]
+macro_rules! test_examples {
+    ($solve:expr) => {
+        #[test]
+        fn examples() {
+            $crate::example::check(
+                &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($crate::example::DIR),
+                $solve,
+            );
+        }
+    };
+}
+
+/// Answers of each part in an `.expected` file, a line `<part>: <answer>` by
+/// part, e.g. `2: 5`.
+fn expected(text: &str) -> Result<BTreeMap<u8, String>, String> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (part, answer) = line
+                .split_once(':')
+                .ok_or_else(|| format!("expect '<part>: <answer>', found '{}'", line))?;
+            let part = part
+                .trim()
+                .parse()
+                .map_err(|_| format!("expect a part, found '{}'", part))?;
+            Ok((part, answer.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Differences between the `answers` to an example and the `expected` ones.
+/// A part missing from the answers is not a difference, another build of the
+/// day answering it.
+fn differences<A: Display>(
+    name: &str,
+    answers: &[(u8, A)],
+    expected: &BTreeMap<u8, String>,
+) -> Vec<String> {
+    if answers.is_empty() {
+        return vec![format!("{}: no part answered", name)];
+    }
+    answers
+        .iter()
+        .filter_map(|(part, answer)| {
+            let answer = answer.to_string();
+            match expected.get(part) {
+                Some(expected) if *expected == answer => None,
+                Some(expected) => Some(format!(
+                    "{} part {}: expected {}, answered {}",
+                    name, part, expected, answer
+                )),
+                None => Some(format!("{} part {}: no expected answer", name, part)),
+            }
+        })
+        .collect()
+}
+
+/// Give each `<name>.txt` of `directory` to `solve`, and compare its answers
+/// to the ones in `<name>.expected`. Panic with every difference, or if there
+/// is no example at all.
+#[track_caller]
+pub fn check<A, F>(directory: &Path, solve: F)
+where
+    A: Display,
+    F: Fn(&str) -> Vec<(u8, A)>,
+{
+    let entries = fs::read_dir(directory).unwrap_or_else(|error| {
+        panic!("expect the examples in {} ({})", directory.display(), error)
+    });
+    let mut inputs: Vec<_> = entries
+        .map(|entry| entry.expect("expect an entry of the examples").path())
+        .filter(|path| path.extension() == Some(OsStr::new("txt")))
+        .collect();
+    inputs.sort();
+    assert!(
+        !inputs.is_empty(),
+        "expect at least an example in {}",
+        directory.display()
+    );
+    let mut failures = Vec::new();
+    for input in inputs {
+        let name = input.display().to_string();
+        let text = fs::read_to_string(&input)
+            .unwrap_or_else(|error| panic!("expect the example {} ({})", name, error));
+        let expected_path = input.with_extension("expected");
+        let expected = fs::read_to_string(&expected_path)
+            .map_err(|error| format!("{} ({})", expected_path.display(), error))
+            .and_then(|text| expected(&text))
+            .unwrap_or_else(|error| panic!("expect the answers of {}: {}", name, error));
+        failures.extend(differences(&name, &solve(&text), &expected));
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn parse_expected() {
+        let answers = expected("1: 7\n2:  hello world \n\n").unwrap();
+        assert_eq!(Some("7"), answers.get(&1).map(String::as_str));
+        assert_eq!(Some("hello world"), answers.get(&2).map(String::as_str));
+        assert!(expected("7").is_err());
+        assert!(expected("one: 7").is_err());
+    }
+
+    #[test]
+    fn compare_answers() {
+        let expected = expected("1: 7\n2: 5").unwrap();
+        assert!(differences("a", &[(1, 7), (2, 5)], &expected).is_empty());
+        assert!(differences("a", &[(2, 5)], &expected).is_empty());
+        assert_eq!(
+            vec![String::from("a part 1: expected 7, answered 8")],
+            differences("a", &[(1, 8), (2, 5)], &expected)
+        );
+        assert_eq!(1, differences::<u8>("a", &[], &expected).len());
+        assert_eq!(1, differences("a", &[(3, 1)], &expected).len());
+    }
+
+    #[test]
+    fn check_directory() {
+        let directory = env::temp_dir().join("common-examples");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("small.txt"), "1\n2\n").unwrap();
+        fs::write(directory.join("small.expected"), "1: 3\n").unwrap();
+        let sum = |input: &str| {
+            vec![(
+                1,
+                input
+                    .lines()
+                    .map(|n| n.parse::<u32>().unwrap())
+                    .sum::<u32>(),
+            )]
+        };
+        check(&directory, sum);
+        let wrong = std::panic::catch_unwind(|| check(&directory, |_| vec![(1, 4)]));
+        fs::remove_dir_all(directory).unwrap();
+        assert!(wrong.is_err());
+    }
+}
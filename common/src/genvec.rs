@@ -0,0 +1,176 @@
+use crate::cli::{value_parser, Arg, Args};
+use std::ops::Range;
+
+/// Seed of the generated inputs when `--seed` is not given.
+pub const SEED: u64 = 2019;
+
+/// Pseudo-random numbers (xorshift64*), not fit for anything but generating
+/// inputs, the same seed always giving the same input.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // Mixed so that close seeds give unrelated numbers, a state of 0 staying 0
+        let state = (seed ^ 0x9e37_79b9_7f4a_7c15).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        Rng {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "expect a non-empty range");
+        range.start + self.next_u64() % (range.end - range.start)
+    }
+
+    /// `true` `percent` times out of a hundred.
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.range(0..100) < percent
+    }
+
+    /// One of `items`, which must not be empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len() as u64) as usize]
+    }
+}
+
+/// `len` values, each generated by `generate`.
+pub fn vec<T, F>(rng: &mut Rng, len: usize, mut generate: F) -> Vec<T>
+where
+    F: FnMut(&mut Rng) -> T,
+{
+    (0..len).map(|_| generate(rng)).collect()
+}
+
+/// Lines of `width` cells, each cell being `on` `percent` times out of a
+/// hundred, else `off`, like the maps of asteroids or of seats.
+pub fn grid(
+    rng: &mut Rng,
+    width: usize,
+    height: usize,
+    percent: u64,
+    on: char,
+    off: char,
+) -> String {
+    vec(rng, height, |rng| {
+        vec(rng, width, |rng| if rng.chance(percent) { on } else { off })
+            .into_iter()
+            .collect::<String>()
+    })
+    .join("\n")
+}
+
+/// Size and seed of an input to generate instead of solving one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Request {
+    pub size: usize,
+    pub seed: u64,
+}
+
+impl Request {
+    /// Arguments `--generate <SIZE>` and `--seed <SEED>`, to be given to
+    /// `cli::Args::parse_with`.
+    pub fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("generate")
+                .long("generate")
+                .value_name("SIZE")
+                .allow_negative_numbers(true)
+                .value_parser(value_parser!(usize))
+                .help("Print a generated input of SIZE instead of solving one"),
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .allow_negative_numbers(true)
+                .value_parser(value_parser!(u64))
+                .help("Seed of the generated input"),
+        ]
+    }
+
+    /// Request read from `args`, if `--generate` is given.
+    pub fn from_args(args: &Args) -> Option<Self> {
+        let size = args.get::<usize>("generate")?;
+        let seed = args.get::<u64>("seed").unwrap_or(SEED);
+        Some(Request { size, seed })
+    }
+
+    pub fn rng(&self) -> Rng {
+        Rng::new(self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let numbers = |seed| vec(&mut Rng::new(seed), 20, Rng::next_u64);
+        assert_eq!(numbers(0), numbers(0));
+        assert_ne!(numbers(0), numbers(1));
+        assert!(numbers(0).iter().any(|&number| number != 0));
+    }
+
+    #[test]
+    fn within_bounds() {
+        let mut rng = Rng::new(SEED);
+        let numbers = vec(&mut rng, 1000, |rng| rng.range(3..7));
+        assert!(numbers.iter().all(|number| (3..7).contains(number)));
+        assert!((3..7).all(|number| numbers.contains(&number)));
+        assert!(!rng.chance(0));
+        assert!(rng.chance(100));
+        assert_eq!(&'a', rng.pick(&['a']));
+    }
+
+    #[test]
+    fn grid_shape() {
+        let grid = grid(&mut Rng::new(SEED), 7, 3, 50, '#', '.');
+        assert_eq!(3, grid.lines().count());
+        assert!(grid.lines().all(|line| line.len() == 7));
+        assert!(grid.contains('#') && grid.contains('.'));
+    }
+
+    #[test]
+    fn request() {
+        let parse = |args: &[&str]| {
+            Args::try_parse_from(
+                "day",
+                &[1],
+                Request::arguments(),
+                std::iter::once("day").chain(args.iter().copied()),
+            )
+            .map(|args| Request::from_args(&args))
+        };
+        assert_eq!(None, parse(&[]).unwrap());
+        assert_eq!(
+            Some(Request {
+                size: 10,
+                seed: SEED
+            }),
+            parse(&["--generate", "10"]).unwrap()
+        );
+        assert_eq!(
+            Some(Request { size: 10, seed: 7 }),
+            parse(&["--generate", "10", "--seed", "7"]).unwrap()
+        );
+        for args in &[
+            &["--generate", "big"][..],
+            &["--generate", "10", "--seed", "-1"],
+        ] {
+            assert_eq!(
+                crate::cli::ErrorKind::ValueValidation,
+                parse(args).unwrap_err().kind()
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+/// Name of the input meaning the standard input.
+pub const STDIN: &str = "-";
+/// Environment variable naming the input when none is given on the command line.
+pub const ENV: &str = "AOC_INPUT";
+/// Environment variable naming the directory of the inputs of every day.
+pub const DIR_ENV: &str = "AOC_INPUT_DIR";
+/// Directory of the inputs of every day, at the root of the repository.
+pub const DIR: &str = "inputs";
+
+/// Path of the input of a day: the command line argument if any, else the
+/// `AOC_INPUT` environment variable, else the input of the day in [`dir`].
+pub fn path(arg: Option<&str>, crate_dir: &str) -> PathBuf {
+    arg.map(PathBuf::from)
+        .or_else(|| env::var_os(ENV).map(PathBuf::from))
+        .unwrap_or_else(|| in_dir(dir(crate_dir), crate_dir))
+}
+
+/// Directory of the inputs of every day: the `AOC_INPUT_DIR` environment
+/// variable, else `inputs` at the root of the repository holding `crate_dir`.
+pub fn dir(crate_dir: &str) -> PathBuf {
+    env::var_os(DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(crate_dir).join("..").join("..").join(DIR))
+}
+
+/// Path of the input of the day of `crate_dir` in `dir`: `<year>/<day>.txt`
+/// for the crate `<year>/<day>_<title>`, e.g. `2019/12.txt` for
+/// `2019/12_the-n-body-problem`.
+pub fn in_dir<P: AsRef<Path>>(dir: P, crate_dir: &str) -> PathBuf {
+    let crate_dir = Path::new(crate_dir);
+    let name = |path: Option<&Path>| {
+        path.and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let year = name(crate_dir.parent());
+    let title = name(Some(crate_dir));
+    let day = title.split('_').next().unwrap_or_default();
+    dir.as_ref().join(year).join(format!("{}.txt", day))
+}
+
+/// Open the input of a day found by [`path`].
+///
+/// `crate_dir` is expected to be `env!("CARGO_MANIFEST_DIR")`.
+pub fn load(arg: Option<&str>, crate_dir: &str) -> io::Result<Box<dyn BufRead>> {
+    open(path(arg, crate_dir))
+}
+
+/// Read the whole input of a day found by [`path`].
+///
+/// The input is kept until the end of the program, as it would be with
+/// `include_str!`, so that it can be borrowed for `'static`.
+pub fn load_str(arg: Option<&str>, crate_dir: &str) -> io::Result<&'static str> {
+    let mut input = String::new();
+    load(arg, crate_dir)?.read_to_string(&mut input)?;
+    Ok(Box::leak(input.into_boxed_str()))
+}
+
+/// Open the input of a day, either a file or the standard input if `path` is `-`.
+///
+/// The standard input is locked rather than wrapped in a new buffer, so that
+/// whatever is not consumed from it stays available to `io::stdin()` once the
+/// returned reader is dropped.
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    if path == Path::new(STDIN) {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn open_file() {
+        let path = std::env::temp_dir().join("common-input-open-file.txt");
+        File::create(&path)
+            .unwrap()
+            .write_all(b"first\nsecond\n")
+            .unwrap();
+        let lines: Vec<String> = open(path.to_str().unwrap())
+            .unwrap()
+            .lines()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(lines, vec!["first", "second"]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn path_from_argument() {
+        assert_eq!(path(Some("puzzle.txt"), "day"), PathBuf::from("puzzle.txt"));
+    }
+
+    #[test]
+    fn path_default() {
+        if env::var_os(ENV).is_none() && env::var_os(DIR_ENV).is_none() {
+            assert_eq!(
+                path(None, "aoc/2019/12_the-n-body-problem"),
+                PathBuf::from("aoc/2019/12_the-n-body-problem/../../inputs/2019/12.txt")
+            );
+        }
+    }
+
+    #[test]
+    fn in_dir_by_year_and_day() {
+        assert_eq!(
+            in_dir("/inputs", "/aoc/2023/08_haunted-wasteland"),
+            PathBuf::from("/inputs/2023/08.txt")
+        );
+        assert_eq!(
+            in_dir("/inputs", "/aoc/2019/intcode"),
+            PathBuf::from("/inputs/2019/intcode.txt")
+        );
+    }
+
+    #[test]
+    fn open_missing_file() {
+        let error = open("this/file/does/not/exist").err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,281 @@
+use std::ops::{Add, RangeInclusive, Sub};
+
+/// Addition and subtraction failing instead of overflowing, for the integers
+/// bounding an interval.
+pub trait Checked: Sized {
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked {
+    ($($integer:ty),*) => {
+        $(
+            impl Checked for $integer {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$integer>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$integer>::checked_sub(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Integers from `start` to `end`, both included, never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>,
+{
+    /// The interval from `start` to `end`, if `start` is not after `end`.
+    pub fn new(start: T, end: T) -> Option<Self> {
+        if start <= end {
+            Some(Self { start, end })
+        } else {
+            None
+        }
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        self.start <= *value && *value <= self.end
+    }
+
+    /// An interval always holds at least one integer.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The integers in both intervals, if any.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// The interval covering both intervals, if they overlap or follow each
+    /// other without a gap.
+    pub fn merge(&self, other: &Self) -> Option<Self> {
+        let (first, second) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        // `second.start - 1` cannot underflow, being after `first.start`
+        if second.start == first.start || second.start - T::from(1) <= first.end {
+            Self::new(first.start, first.end.max(second.end))
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Interval<T>
+where
+    T: Copy + Checked + From<u8>,
+{
+    /// Number of integers in the interval, if `T` can hold it: the full range
+    /// of `T` holds one more integer than its largest value.
+    pub fn len(&self) -> Option<T> {
+        self.end.checked_sub(self.start)?.checked_add(T::from(1))
+    }
+}
+
+impl<T> From<Interval<T>> for RangeInclusive<T> {
+    fn from(interval: Interval<T>) -> Self {
+        interval.start..=interval.end
+    }
+}
+
+/// Union of intervals, kept sorted and merged so that no two of them overlap
+/// or follow each other without a gap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>,
+{
+    pub fn insert(&mut self, interval: Interval<T>) {
+        let index = self
+            .intervals
+            .partition_point(|other| other.start < interval.start);
+        self.intervals.insert(index, interval);
+        // Only the neighbours of the new interval may now overlap with it
+        let mut index = index.saturating_sub(1);
+        while index + 1 < self.intervals.len() {
+            if let Some(merged) = self.intervals[index].merge(&self.intervals[index + 1]) {
+                self.intervals[index] = merged;
+                self.intervals.remove(index + 1);
+            } else if self.intervals[index].start > interval.end {
+                break;
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.end < *value);
+        self.intervals
+            .get(index)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// The disjoint intervals of the union, in order.
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Checked + From<u8>,
+{
+    /// Number of integers in the union, if `T` can hold it.
+    pub fn len(&self) -> Option<T> {
+        self.intervals
+            .iter()
+            .try_fold(T::from(0), |len, interval| len.checked_add(interval.len()?))
+    }
+}
+
+impl<T> std::iter::FromIterator<Interval<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>,
+{
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(intervals: I) -> Self {
+        let mut set = Self::default();
+        set.extend(intervals);
+        set
+    }
+}
+
+impl<T> Extend<Interval<T>> for IntervalSet<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + From<u8>,
+{
+    fn extend<I: IntoIterator<Item = Interval<T>>>(&mut self, intervals: I) {
+        for interval in intervals {
+            self.insert(interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interval(start: i64, end: i64) -> Interval<i64> {
+        Interval::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn new_interval() {
+        assert_eq!(None, Interval::new(3, 2));
+        assert_eq!(Some(1), interval(2, 2).len());
+        assert_eq!(Some(11), interval(-5, 5).len());
+        assert!(interval(-5, 5).contains(&-5));
+        assert!(!interval(-5, 5).contains(&6));
+        assert_eq!(-5..=5, RangeInclusive::from(interval(-5, 5)));
+    }
+
+    #[test]
+    fn len_of_full_ranges() {
+        assert_eq!(None, Interval::new(0, u8::MAX).unwrap().len());
+        assert_eq!(Some(u8::MAX), Interval::new(1, u8::MAX).unwrap().len());
+        assert_eq!(None, interval(i64::MIN, i64::MAX).len());
+        assert_eq!(Some(i64::MAX), interval(0, i64::MAX - 1).len());
+        let set: IntervalSet<u8> = vec![
+            Interval::new(0, 127).unwrap(),
+            Interval::new(129, u8::MAX).unwrap(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(Some(u8::MAX), set.len());
+        let set: IntervalSet<u8> = vec![
+            Interval::new(0, 127).unwrap(),
+            Interval::new(128, u8::MAX).unwrap(),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(None, set.len());
+    }
+
+    #[test]
+    fn intersect_intervals() {
+        assert_eq!(
+            Some(interval(3, 5)),
+            interval(1, 5).intersect(&interval(3, 7))
+        );
+        assert_eq!(
+            Some(interval(5, 5)),
+            interval(1, 5).intersect(&interval(5, 7))
+        );
+        assert_eq!(None, interval(1, 5).intersect(&interval(6, 7)));
+    }
+
+    #[test]
+    fn merge_intervals() {
+        assert_eq!(Some(interval(1, 7)), interval(3, 7).merge(&interval(1, 5)));
+        assert_eq!(Some(interval(1, 7)), interval(1, 5).merge(&interval(6, 7)));
+        assert_eq!(Some(interval(1, 5)), interval(1, 5).merge(&interval(2, 3)));
+        assert_eq!(None, interval(1, 5).merge(&interval(7, 8)));
+        let min = Interval::new(u8::MIN, u8::MIN).unwrap();
+        assert_eq!(
+            Interval::new(0, 1),
+            min.merge(&Interval::new(1, 1).unwrap())
+        );
+    }
+
+    #[test]
+    fn union_of_intervals() {
+        let set: IntervalSet<i64> = vec![
+            interval(10, 12),
+            interval(1, 3),
+            interval(5, 7),
+            interval(4, 4),
+            interval(20, 30),
+            interval(11, 21),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(&[interval(1, 7), interval(10, 30)], set.intervals());
+        assert_eq!(Some(28), set.len());
+        assert!(set.contains(&4));
+        assert!(set.contains(&30));
+        assert!(!set.contains(&8));
+        assert!(!set.contains(&0));
+        assert!(!set.contains(&31));
+        assert!(IntervalSet::<i64>::default().is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+#[cfg(feature = "alloc-stats")]
+pub mod alloc;
+pub mod automaton;
+pub mod bitgrid;
+pub mod cli;
+pub mod cycle;
+pub mod dot;
+pub mod example;
+pub mod genvec;
+pub mod input;
+pub mod interval;
+pub mod memo;
+pub mod ocr;
+pub mod parallel;
+pub mod parse;
+pub mod progress;
+pub mod render;
+pub mod search;
+pub mod snapshot;
+#[cfg(feature = "proptest")]
+pub mod strategy;
+pub mod token;
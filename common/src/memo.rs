@@ -0,0 +1,163 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+/// Values already computed by a function, by argument, so that a function
+/// called again and again on the same arguments computes each value once.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    values: HashMap<K, V>,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V> Memo<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.get(key)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.contains_key(key)
+    }
+
+    /// Keep `value` for `key`, and give it back.
+    pub fn remember(&mut self, key: K, value: V) -> V {
+        self.values.insert(key, value.clone());
+        value
+    }
+
+    /// The value of `key`, computed the first time only. `compute` is given
+    /// the memo back, to look up the values it depends on.
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        F: FnOnce(&mut Self, &K) -> V,
+    {
+        if let Some(value) = self.values.get(&key) {
+            return value.clone();
+        }
+        let value = compute(self, &key);
+        self.remember(key, value)
+    }
+
+    /// Drop the value of `key`, to be computed again when next needed.
+    pub fn forget<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.values.remove(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// Define a function computing each value once, by its first argument. The
+/// function takes a `&mut Memo` first, given to the recursive calls in its
+/// body.
+///
+/// ```ignore
+/// common::memoized! {
+///     fn fibonacci(memo, n: u64) -> u64 {
+///         if n < 2 { n } else { fibonacci(memo, n - 1) + fibonacci(memo, n - 2) }
+///     }
+/// }
+/// fibonacci(&mut Memo::default(), 90);
+/// ```
+#[macro_export]
+macro_rules! memoized {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($memo:ident, $key:ident: $key_ty:ty $(, $arg:ident: $arg_ty:ty)* $(,)?) -> $value:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $vis fn $name(
+            $memo: &mut $crate::memo::Memo<$key_ty, $value>,
+            $key: $key_ty
+            $(, $arg: $arg_ty)*
+        ) -> $value {
+            if let Some(value) = $memo.get(&$key) {
+                return ::std::clone::Clone::clone(value);
+            }
+            let key = ::std::clone::Clone::clone(&$key);
+            #[allow(clippy::redundant_closure_call)]
+            let value = (|| -> $value { $body })();
+            $memo.remember(key, value)
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    memoized! {
+        fn fibonacci(memo, n: u64, calls: &mut usize) -> u64 {
+            *calls += 1;
+            if n < 2 {
+                n
+            } else {
+                fibonacci(memo, n - 1, calls) + fibonacci(memo, n - 2, calls)
+            }
+        }
+    }
+
+    #[test]
+    fn memoized_recursion() {
+        let mut memo = Memo::default();
+        let mut calls = 0;
+        assert_eq!(2880067194370816120, fibonacci(&mut memo, 90, &mut calls));
+        assert_eq!(91, calls);
+        assert_eq!(91, memo.len());
+        assert_eq!(Some(&55), memo.get(&10));
+        fibonacci(&mut memo, 90, &mut calls);
+        assert_eq!(91, calls);
+    }
+
+    #[test]
+    fn get_or_compute() {
+        let mut memo: Memo<String, usize> = Memo::default();
+        assert_eq!(
+            3,
+            memo.get_or_compute("abc".to_string(), |_, key| key.len())
+        );
+        assert_eq!(
+            3,
+            memo.get_or_compute("abc".to_string(), |_, _| unreachable!())
+        );
+        let value = memo.get_or_compute("abcd".to_string(), |memo, key| {
+            memo.get_or_compute(key[1..].to_string(), |_, key| key.len()) + 1
+        });
+        assert_eq!(4, value);
+        assert!(memo.contains_key("bcd"));
+        assert_eq!(Some(3), memo.forget("abc"));
+        assert!(!memo.contains_key("abc"));
+        memo.clear();
+        assert!(memo.is_empty());
+    }
+}
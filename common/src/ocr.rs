@@ -0,0 +1,107 @@
+/// Height, in cells, of the letters drawn by the puzzles.
+pub const HEIGHT: usize = 6;
+
+/// Letters drawn by the puzzles, row by row, `#` being lit. Each letter is
+/// as wide as its lit cells, e.g. 3 cells for `I` but 5 for `Y`.
+const GLYPHS: &[(char, [&str; HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// The letters drawn by the lit cells of `rows`, from left to right.
+///
+/// Blank rows around the letters are ignored, and the letters are told apart
+/// by the blank columns between them. `None` if the letters are not 6 cells
+/// tall, or if one of them is unknown.
+pub fn read<R: AsRef<[bool]>>(rows: &[R]) -> Option<String> {
+    let is_blank = |row: &R| !row.as_ref().contains(&true);
+    let first = rows.iter().position(|row| !is_blank(row))?;
+    let last = rows.iter().rposition(|row| !is_blank(row))?;
+    let rows = &rows[first..=last];
+    if rows.len() != HEIGHT {
+        return None;
+    }
+    let lit = |x: usize, y: usize| rows[y].as_ref().get(x).copied().unwrap_or(false);
+    let width = rows.iter().map(|row| row.as_ref().len()).max().unwrap_or(0);
+    let lit_columns: Vec<bool> = (0..width).map(|x| (0..HEIGHT).any(|y| lit(x, y))).collect();
+    let mut letters = String::new();
+    let mut x = 0;
+    while x < width {
+        if !lit_columns[x] {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < width && lit_columns[x] {
+            x += 1;
+        }
+        let (letter, _) = GLYPHS.iter().find(|(_, glyph)| {
+            glyph.iter().enumerate().all(|(y, glyph_row)| {
+                glyph_row.len() == x - start
+                    && glyph_row
+                        .chars()
+                        .zip(start..x)
+                        .all(|(cell, x)| (cell == '#') == lit(x, y))
+            })
+        })?;
+        letters.push(*letter);
+    }
+    Some(letters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(drawing: &str) -> Vec<Vec<bool>> {
+        drawing
+            .lines()
+            .map(|line| line.chars().map(|c| c == '#').collect())
+            .collect()
+    }
+
+    #[test]
+    fn every_glyph() {
+        for (letter, glyph) in GLYPHS {
+            assert_eq!(Some(letter.to_string()), read(&grid(&glyph.join("\n"))));
+        }
+    }
+
+    #[test]
+    fn read_letters() {
+        let drawing = "\
+.........................
+.####.#..#.###..#..#.####
+.#....#..#.#..#.#..#.#...
+.###..####.#..#.#..#.###.
+.#....#..#.###..#..#.#...
+.#....#..#.#.#..#..#.#...
+.####.#..#.#..#..##..####
+.........................";
+        assert_eq!(Some("EHRUE".to_string()), read(&grid(drawing)));
+    }
+
+    #[test]
+    fn unreadable_letters() {
+        assert_eq!(None, read::<Vec<bool>>(&[]));
+        assert_eq!(None, read(&grid("####\n#..#\n####")));
+        // Not a letter, although 6 cells tall
+        assert_eq!(None, read(&grid("#\n#\n#\n#\n#\n#")));
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::cmp::Reverse;
+
+/// Map each item, then combine the results with `reduce`, on every core with
+/// the `parallel` feature and one item after the other without it. The
+/// results are combined in any grouping, so `reduce` must be associative, with
+/// possibly several partial results starting from `identity()`, which must
+/// not change a result it is combined with.
+///
+/// ```ignore
+/// let found = common::parallel::map_reduce(candidates, || 0, |c| check(c), |a, b| a + b);
+/// ```
+pub fn map_reduce<T, R, I, M, F>(items: Vec<T>, identity: I, map: M, reduce: F) -> R
+where
+    T: Send,
+    R: Send,
+    I: Fn() -> R + Sync + Send,
+    M: Fn(T) -> R + Sync + Send,
+    F: Fn(R, R) -> R + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        items.into_par_iter().map(map).reduce(identity, reduce)
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.into_iter().map(map).fold(identity(), reduce)
+    }
+}
+
+/// Item with the largest key, the first one among equal keys as with a
+/// sequential search keeping the first maximum.
+pub fn max_by_key<T, K, F>(items: Vec<T>, key: F) -> Option<T>
+where
+    T: Send,
+    K: Ord + Send,
+    F: Fn(&T) -> K + Sync + Send,
+{
+    let items: Vec<(usize, T)> = items.into_iter().enumerate().collect();
+    map_reduce(
+        items,
+        || None,
+        |(index, item)| Some((key(&item), Reverse(index), item)),
+        |first, second| match (first, second) {
+            (Some(first), Some(second)) => {
+                if (&second.0, &second.1) > (&first.0, &first.1) {
+                    Some(second)
+                } else {
+                    Some(first)
+                }
+            }
+            (first, None) => first,
+            (None, second) => second,
+        },
+    )
+    .map(|(_, _, item)| item)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_of_squares() {
+        let squares = map_reduce((1..=100u64).collect(), || 0, |n| n * n, |a, b| a + b);
+        assert_eq!(338_350, squares);
+        assert_eq!(0, map_reduce(Vec::<u64>::new(), || 0, |n| n, |a, b| a + b));
+    }
+
+    #[test]
+    fn errors_are_kept() {
+        let checked = |numbers: Vec<i32>| {
+            map_reduce(
+                numbers,
+                || Ok(0),
+                |n| if n < 0 { Err(n) } else { Ok(n) },
+                |a, b| Ok(a?.max(b?)),
+            )
+        };
+        assert_eq!(Ok(7), checked(vec![3, 7, 1]));
+        assert_eq!(Err(-2), checked(vec![3, -2, 1]));
+    }
+
+    #[test]
+    fn first_maximum() {
+        let words = vec!["a", "bbb", "cc", "ddd", "e"];
+        assert_eq!(Some("bbb"), max_by_key(words, |word| word.len()));
+        assert_eq!(None, max_by_key(Vec::<u8>::new(), |n| *n));
+        let many: Vec<(u32, usize)> = (0..10_000).map(|i| (i % 100, i as usize)).collect();
+        assert_eq!(Some((99, 99)), max_by_key(many, |&(key, _)| key));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::token;
+use std::{fmt, str::FromStr};
+
+/// What a parser expected, with the text it found instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error<'a> {
+    pub expected: &'static str,
+    pub found: &'a str,
+}
+
+impl Error<'_> {
+    /// The line the text found comes from, with this text underlined.
+    pub fn underline(&self, line: &str) -> String {
+        token::within(line, self.found).underline(line)
+    }
+}
+
+impl fmt::Display for Error<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expect {}, found '{}'", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for Error<'_> {}
+
+/// An integer with an optional `+` or `-` sign, e.g. `-12`.
+pub fn integer<T: FromStr>(text: &str) -> Result<T, Error<'_>> {
+    let text = text.trim();
+    let error = Error {
+        expected: "an integer",
+        found: text,
+    };
+    let digits = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if digits.is_empty() || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+        return Err(error);
+    }
+    text.parse().map_err(|_| error)
+}
+
+/// The key and the value around the first `separator`, both trimmed, e.g.
+/// `("byr", "1937")` for `byr:1937` and `:`.
+pub fn key_value<'a>(text: &'a str, separator: &str) -> Result<(&'a str, &'a str), Error<'a>> {
+    text.split_once(separator)
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .ok_or(Error {
+            expected: "a key and a value",
+            found: text,
+        })
+}
+
+/// A quantity of something, e.g. `(7, "ORE")` for `7 ORE` or
+/// `(2, "muted yellow bags")` for `2 muted yellow bags`.
+pub fn quantity_of<T: FromStr>(text: &str) -> Result<(T, &str), Error<'_>> {
+    let text = text.trim();
+    let (quantity, what) = text.split_once(char::is_whitespace).ok_or(Error {
+        expected: "a quantity of something",
+        found: text,
+    })?;
+    Ok((integer(quantity)?, what.trim_start()))
+}
+
+/// Blocks of lines separated by blank lines, the blank lines being dropped.
+pub struct Blocks<I> {
+    lines: I,
+}
+
+/// Group `lines` in blocks separated by blank lines, like the passports of a
+/// batch file.
+pub fn blocks<I, S>(lines: I) -> Blocks<I::IntoIter>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    Blocks {
+        lines: lines.into_iter(),
+    }
+}
+
+impl<I, S> Iterator for Blocks<I>
+where
+    I: Iterator<Item = S>,
+    S: AsRef<str>,
+{
+    type Item = Vec<S>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut block = Vec::new();
+        for line in &mut self.lines {
+            if !line.as_ref().trim().is_empty() {
+                block.push(line);
+            } else if !block.is_empty() {
+                return Some(block);
+            }
+        }
+        if block.is_empty() {
+            None
+        } else {
+            Some(block)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_integers() {
+        assert_eq!(Ok(-12), integer::<i64>("-12"));
+        assert_eq!(Ok(12), integer::<i64>(" +12 "));
+        assert_eq!(Ok(7), integer::<usize>("7"));
+        for text in ["", "-", "1.5", "12a", "- 3"] {
+            assert!(integer::<i64>(text).is_err(), "{}", text);
+        }
+        assert!(integer::<usize>("-7").is_err());
+        assert!(integer::<u8>("256").is_err());
+    }
+
+    #[test]
+    fn key_values() {
+        assert_eq!(Ok(("byr", "1937")), key_value("byr:1937", ":"));
+        assert_eq!(Ok(("1 ORE", "1 FUEL")), key_value("1 ORE => 1 FUEL", "=>"));
+        assert_eq!(Ok(("pid", "")), key_value("pid:", ":"));
+        let error = key_value("byr1937", ":").unwrap_err();
+        assert_eq!(
+            "expect a key and a value, found 'byr1937'",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn quantities_of() {
+        assert_eq!(Ok((7, "ORE")), quantity_of::<usize>("7 ORE"));
+        assert_eq!(
+            Ok((2, "muted yellow bags")),
+            quantity_of::<usize>(" 2  muted yellow bags")
+        );
+        assert!(quantity_of::<usize>("ORE").is_err());
+        assert!(quantity_of::<usize>("many ORE").is_err());
+    }
+
+    #[test]
+    fn underline_error() {
+        let line = "7 A, x B => 1 C";
+        let error = quantity_of::<usize>(&line[5..8]).unwrap_err();
+        assert_eq!("7 A, x B => 1 C\n     ^", error.underline(line));
+    }
+
+    #[test]
+    fn blank_line_blocks() {
+        let lines = ["a", "b", "", "c", "", "", "d", ""];
+        let grouped: Vec<Vec<&str>> = blocks(lines).collect();
+        assert_eq!(vec![vec!["a", "b"], vec!["c"], vec!["d"]], grouped);
+        assert_eq!(None, blocks(Vec::<String>::new()).next());
+    }
+}
@@ -0,0 +1,186 @@
+pub use indicatif::ProgressBar;
+use indicatif::ProgressStyle;
+use std::{
+    io::{self, IsTerminal},
+    time::{Duration, Instant},
+};
+
+/// Projected time from which a search is worth a warning.
+pub const LONG: Duration = Duration::from_secs(60);
+/// Time spent on the first iterations before projecting the total time.
+const SAMPLE: Duration = Duration::from_millis(500);
+
+/// Progress bar over `len` iterations, with the rate and the remaining time.
+///
+/// The bar is hidden when the standard output is not a terminal, so that
+/// piped answers stay clean.
+pub fn bar(len: u64, message: &'static str) -> ProgressBar {
+    if !io::stdout().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let style = ProgressStyle::with_template(
+        "{msg} [{elapsed_precise}] {wide_bar} {pos}/{len} ({per_sec}, ETA {eta})",
+    )
+    .expect("expect a valid progress bar template");
+    ProgressBar::new(len)
+        .with_style(style)
+        .with_message(message)
+}
+
+/// Spinner over an unknown number of iterations, with the rate.
+///
+/// Like [`bar`], the spinner is hidden when the standard output is not a
+/// terminal.
+pub fn spinner(message: &'static str) -> ProgressBar {
+    if !io::stdout().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let style =
+        ProgressStyle::with_template("{spinner} {msg} [{elapsed_precise}] {pos} ({per_sec})")
+            .expect("expect a valid spinner template");
+    ProgressBar::new_spinner()
+        .with_style(style)
+        .with_message(message)
+}
+
+/// Total time of `total` iterations, if `done` of them took `elapsed`.
+pub fn projection(elapsed: Duration, done: u64, total: u64) -> Option<Duration> {
+    if done == 0 {
+        return None;
+    }
+    Some(elapsed.mul_f64(total as f64 / done as f64))
+}
+
+/// `duration` rounded in its largest unit, like `~3h`, `~12min` or `~40s`.
+pub fn roughly(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds >= 3600.0 {
+        format!("~{:.0}h", seconds / 3600.0)
+    } else if seconds >= 60.0 {
+        format!("~{:.0}min", seconds / 60.0)
+    } else {
+        format!("~{:.0}s", seconds)
+    }
+}
+
+/// Progress bar which also projects the total time of the search from its
+/// first iterations, and warns once on the standard error if the search
+/// would last [`LONG`] or more. Unlike the bar, the warning is written even
+/// when the standard output is not a terminal.
+pub struct Eta {
+    bar: ProgressBar,
+    len: u64,
+    message: &'static str,
+    start: Instant,
+    /// Iterations at which the time is checked next, doubled at each check
+    /// so that a fast loop rarely reads the clock.
+    next_check: u64,
+    projected: Option<Duration>,
+    warning: Option<String>,
+}
+
+/// Like [`bar`], with the projection of the total time of an [`Eta`].
+pub fn eta(len: u64, message: &'static str) -> Eta {
+    Eta {
+        bar: bar(len, message),
+        len,
+        message,
+        start: Instant::now(),
+        next_check: 1,
+        projected: None,
+        warning: None,
+    }
+}
+
+impl Eta {
+    pub fn set_position(&mut self, done: u64) {
+        self.bar.set_position(done);
+        if self.projected.is_some() || done < self.next_check {
+            return;
+        }
+        self.next_check = done.saturating_mul(2);
+        let elapsed = self.start.elapsed();
+        if elapsed < SAMPLE {
+            return;
+        }
+        self.projected = projection(elapsed, done, self.len);
+        if let Some(projected) = self.projected.filter(|&projected| projected >= LONG) {
+            let warning = format!(
+                "Warning: {} will take {} ({} of {} in {:.1?})",
+                self.message,
+                roughly(projected),
+                done,
+                self.len,
+                elapsed
+            );
+            self.bar.suspend(|| eprintln!("{}", warning));
+            self.warning = Some(warning);
+        }
+    }
+
+    /// Total time projected once the first iterations took long enough.
+    pub fn projected(&self) -> Option<Duration> {
+        self.projected
+    }
+
+    /// Warning written on the standard error, if the projected time was
+    /// long.
+    pub fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+
+    pub fn finish_and_clear(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projected_time() {
+        let second = Duration::from_secs(1);
+        assert_eq!(Some(second * 100), projection(second, 10, 1000));
+        assert_eq!(Some(second), projection(second, 1000, 1000));
+        assert_eq!(None, projection(second, 0, 1000));
+    }
+
+    #[test]
+    fn rough_durations() {
+        assert_eq!("~3h", roughly(Duration::from_secs(3 * 3600 + 600)));
+        assert_eq!("~12min", roughly(Duration::from_secs(12 * 60 + 10)));
+        assert_eq!("~40s", roughly(Duration::from_millis(40_400)));
+    }
+
+    #[test]
+    fn projected_after_the_sample() {
+        let mut eta = eta(1_000_000, "Test");
+        eta.set_position(1);
+        assert_eq!(None, eta.projected());
+        std::thread::sleep(SAMPLE);
+        // Not checked before twice the iterations of the previous check
+        eta.set_position(1);
+        assert_eq!(None, eta.projected());
+        eta.set_position(2);
+        let projected = eta
+            .projected()
+            .expect("expect a projection after the sample");
+        assert!(projected >= SAMPLE * 500_000, "projected {:?}", projected);
+        let warning = eta.warning().expect("expect a warning for a long search");
+        assert!(
+            warning.starts_with("Warning: Test will take ~"),
+            "warning {:?}",
+            warning
+        );
+    }
+
+    #[test]
+    fn no_warning_for_a_short_search() {
+        let mut eta = eta(4, "Test");
+        std::thread::sleep(SAMPLE);
+        eta.set_position(2);
+        assert!(eta.projected().unwrap() < LONG);
+        assert_eq!(None, eta.warning());
+    }
+}
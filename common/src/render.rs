@@ -0,0 +1,238 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::{self, IsTerminal},
+    sync::OnceLock,
+};
+
+/// Position of a cell, as `(x, y)`.
+pub type Position = (i64, i64);
+
+/// Move the cursor to the top left corner of the terminal, so that the next
+/// frame is drawn over the previous one.
+pub const HOME: &str = "\x1b[H";
+/// Clear the whole terminal.
+pub const CLEAR: &str = "\x1b[2J";
+
+/// Escape sequence moving the cursor to `line` and `column`, both starting
+/// at 1.
+pub fn cursor(line: usize, column: usize) -> String {
+    format!("\x1b[{};{}H", line, column)
+}
+
+/// Whether the standard output is a terminal, i.e. whether escape sequences
+/// for the cursor or the colors should be written at all.
+pub fn is_terminal() -> bool {
+    static IS_TERMINAL: OnceLock<bool> = OnceLock::new();
+    *IS_TERMINAL.get_or_init(|| io::stdout().is_terminal())
+}
+
+/// Cells that can be drawn, indexed by their position.
+pub trait Cells {
+    type Cell;
+    /// Smallest and biggest positions, `None` if there is no cell at all.
+    fn bounds(&self) -> Option<(Position, Position)>;
+    fn cell(&self, position: Position) -> Option<&Self::Cell>;
+}
+
+impl<T> Cells for HashMap<Position, T> {
+    type Cell = T;
+    fn bounds(&self) -> Option<(Position, Position)> {
+        let min_x = self.keys().map(|position| position.0).min()?;
+        let max_x = self.keys().map(|position| position.0).max()?;
+        let min_y = self.keys().map(|position| position.1).min()?;
+        let max_y = self.keys().map(|position| position.1).max()?;
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+    fn cell(&self, position: Position) -> Option<&T> {
+        self.get(&position)
+    }
+}
+
+/// A grid of lines, `x` being the column and `y` the line.
+impl<T> Cells for Vec<Vec<T>> {
+    type Cell = T;
+    fn bounds(&self) -> Option<(Position, Position)> {
+        let width = self.iter().map(Vec::len).max().filter(|&width| width > 0)?;
+        Some(((0, 0), (width as i64 - 1, self.len() as i64 - 1)))
+    }
+    fn cell(&self, position: Position) -> Option<&T> {
+        let (x, y) = position;
+        if x < 0 || y < 0 {
+            return None;
+        }
+        self.get(y as usize)?.get(x as usize)
+    }
+}
+
+/// Draw every position between the bounds of `cells`, line by line, with the
+/// glyph returned by a function of the position and of the cell, if any.
+///
+/// Lines are drawn from the smallest `y` to the biggest, unless
+/// [`Render::y_up`] is used.
+pub struct Render<'c, C, F> {
+    cells: &'c C,
+    glyph: F,
+    y_up: bool,
+}
+
+impl<'c, C, F, G> Render<'c, C, F>
+where
+    C: Cells,
+    F: Fn(Position, Option<&C::Cell>) -> G,
+    G: Display,
+{
+    pub fn new(cells: &'c C, glyph: F) -> Self {
+        Render {
+            cells,
+            glyph,
+            y_up: false,
+        }
+    }
+
+    /// Draw the biggest `y` on the first line instead.
+    pub fn y_up(mut self) -> Self {
+        self.y_up = true;
+        self
+    }
+}
+
+impl<'c, C, F, G> Display for Render<'c, C, F>
+where
+    C: Cells,
+    F: Fn(Position, Option<&C::Cell>) -> G,
+    G: Display,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        let ((min_x, min_y), (max_x, max_y)) = match self.cells.bounds() {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        let lines: Box<dyn Iterator<Item = i64>> = if self.y_up {
+            Box::new((min_y..=max_y).rev())
+        } else {
+            Box::new(min_y..=max_y)
+        };
+        for y in lines {
+            for x in min_x..=max_x {
+                write!(f, "{}", (self.glyph)((x, y), self.cells.cell((x, y))))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Foreground colors of the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    fn code(self) -> u8 {
+        use self::Color::*;
+        match self {
+            Black => 30,
+            Red => 31,
+            Green => 32,
+            Yellow => 33,
+            Blue => 34,
+            Magenta => 35,
+            Cyan => 36,
+            White => 37,
+        }
+    }
+
+    /// Glyph drawn with this color, only if the standard output is a
+    /// terminal.
+    pub fn paint<D: Display>(self, glyph: D) -> Painted<D> {
+        Painted {
+            glyph,
+            color: Some(self).filter(|_| is_terminal()),
+        }
+    }
+}
+
+/// Glyph with an optional color, see [`Color::paint`].
+#[derive(Debug, Clone, Copy)]
+pub struct Painted<D> {
+    glyph: D,
+    color: Option<Color>,
+}
+
+impl<D> Painted<D> {
+    /// Glyph drawn without any color.
+    pub fn plain(glyph: D) -> Self {
+        Painted { glyph, color: None }
+    }
+}
+
+impl<D: Display> Display for Painted<D> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self.color {
+            Some(color) => write!(f, "\x1b[{}m{}\x1b[0m", color.code(), self.glyph),
+            None => write!(f, "{}", self.glyph),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glyph(_: Position, cell: Option<&char>) -> char {
+        cell.cloned().unwrap_or(' ')
+    }
+
+    #[test]
+    fn render_map() {
+        let mut cells = HashMap::new();
+        cells.insert((-1, 0), '#');
+        cells.insert((1, 1), '@');
+        assert_eq!(Render::new(&cells, glyph).to_string(), "#  \n  @\n");
+        assert_eq!(Render::new(&cells, glyph).y_up().to_string(), "  @\n#  \n");
+    }
+
+    #[test]
+    fn render_grid() {
+        let cells = vec![vec!['a', 'b'], vec!['c']];
+        assert_eq!(Render::new(&cells, glyph).to_string(), "ab\nc \n");
+    }
+
+    #[test]
+    fn render_empty() {
+        let cells: HashMap<Position, char> = HashMap::new();
+        assert_eq!(Render::new(&cells, glyph).to_string(), "");
+    }
+
+    #[test]
+    fn render_position() {
+        let cells = vec![vec!['.', '.']];
+        let cursor = |position, cell: Option<&char>| {
+            if position == (1, 0) {
+                'x'
+            } else {
+                *cell.unwrap()
+            }
+        };
+        assert_eq!(Render::new(&cells, cursor).to_string(), ".x\n");
+    }
+
+    #[test]
+    fn painted() {
+        let painted = Painted {
+            glyph: '#',
+            color: Some(Color::Red),
+        };
+        assert_eq!(painted.to_string(), "\x1b[31m#\x1b[0m");
+        assert_eq!(Painted::plain('#').to_string(), "#");
+    }
+}
@@ -0,0 +1,59 @@
+/// Largest `n` for which `satisfies(n)` holds, `satisfies` being true up to
+/// some integer and false after, e.g. whether `n` items are affordable;
+/// `None` if it doesn't hold even for `0`.
+///
+/// The bounds are found by doubling `n`, then narrowed by bisection, so that
+/// `satisfies` is called a number of times logarithmic in the answer.
+pub fn max_satisfying(mut satisfies: impl FnMut(u64) -> bool) -> Option<u64> {
+    if !satisfies(0) {
+        return None;
+    }
+    // `satisfies(low)` holds while `satisfies(high)` doesn't
+    let mut low = 0;
+    let mut high = 1;
+    while satisfies(high) {
+        low = high;
+        match high.checked_mul(2) {
+            Some(double) => high = double,
+            None if satisfies(u64::MAX) => return Some(u64::MAX),
+            None => {
+                high = u64::MAX;
+                break;
+            }
+        }
+    }
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if satisfies(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn largest_satisfying() {
+        assert_eq!(Some(31), max_satisfying(|n| n * n <= 1000));
+        assert_eq!(Some(1 << 40), max_satisfying(|n| n <= 1 << 40));
+        assert_eq!(Some(0), max_satisfying(|n| n == 0));
+        assert_eq!(None, max_satisfying(|_| false));
+        assert_eq!(Some(u64::MAX), max_satisfying(|_| true));
+        assert_eq!(Some(u64::MAX - 1), max_satisfying(|n| n < u64::MAX));
+    }
+
+    #[test]
+    fn logarithmic_calls() {
+        let mut calls = 0;
+        max_satisfying(|n| {
+            calls += 1;
+            n <= 1_000_000_000_000
+        });
+        assert!(calls <= 2 * 41, "{} calls", calls);
+    }
+}
@@ -0,0 +1,263 @@
+use crate::render::Color;
+use std::{env, fs, path::Path};
+
+/// Environment variable which, when set, writes the renderings to their
+/// fixture files instead of comparing them.
+pub const UPDATE: &str = "AOC_UPDATE_SNAPSHOTS";
+
+/// Number of differing cells listed by their position in a report.
+const LISTED: usize = 10;
+
+/// Report of two renderings with the same cells, but not the same lines.
+const ENDS: &str = "the cells are the same, not the ends of the lines\n";
+
+/// Assert that two values have the same `Display`, colors aside. When they
+/// differ, the message shows the differing cells of the grids instead of both
+/// grids, see [`grid_diff`].
+///
+/// ```ignore
+/// common::assert_grid_eq!(expected, boat, "different after {} steps", steps);
+/// ```
+#[macro_export]
+macro_rules! assert_grid_eq {
+    ($expected:expr, $actual:expr $(,)?) => {
+        $crate::snapshot::assert_grid_eq(&$expected.to_string(), &$actual.to_string(), "")
+    };
+    ($expected:expr, $actual:expr, $($message:tt)+) => {
+        $crate::snapshot::assert_grid_eq(
+            &$expected.to_string(),
+            &$actual.to_string(),
+            &format!($($message)+),
+        )
+    };
+}
+
+/// Assert that the `Display` of a value is the content of a fixture file in
+/// the `snapshots` directory of the calling crate, colors aside.
+///
+/// ```ignore
+/// common::assert_display_snapshot!(map, "map.txt");
+/// ```
+#[macro_export]
+macro_rules! assert_display_snapshot {
+    ($value:expr, $file:expr) => {
+        $crate::snapshot::assert_snapshot(
+            &$value.to_string(),
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("snapshots")
+                .join($file),
+        )
+    };
+}
+
+/// `text` without the escape sequences of the colors, which are only written
+/// when the standard output is a terminal.
+pub fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to the end of the sequence, e.g. `\x1b[31m`
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Cell differing between two renderings of a grid, `None` where a grid is
+/// smaller than the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difference {
+    /// Column and line of the cell.
+    pub position: (usize, usize),
+    pub expected: Option<char>,
+    pub actual: Option<char>,
+}
+
+/// Cells which differ between two renderings of a grid, colors aside.
+pub fn differing_cells(expected: &str, actual: &str) -> Vec<Difference> {
+    let lines = |rendering: &str| -> Vec<Vec<char>> {
+        strip_colors(rendering)
+            .lines()
+            .map(|line| line.chars().collect())
+            .collect()
+    };
+    let (expected, actual) = (lines(expected), lines(actual));
+    let cell = |lines: &[Vec<char>], x: usize, y: usize| lines.get(y)?.get(x).copied();
+    let mut differing = Vec::new();
+    for y in 0..expected.len().max(actual.len()) {
+        let width = |lines: &[Vec<char>]| lines.get(y).map(Vec::len).unwrap_or(0);
+        for x in 0..width(&expected).max(width(&actual)) {
+            let (expected, actual) = (cell(&expected, x, y), cell(&actual, x, y));
+            if expected != actual {
+                differing.push(Difference {
+                    position: (x, y),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+    differing
+}
+
+/// Differences between two renderings of a grid, colors aside, `None` if
+/// they are the same: the actual grid with its differing cells painted in
+/// red, then the position of the first differing cells.
+pub fn grid_diff(expected: &str, actual: &str) -> Option<String> {
+    let differing = differing_cells(expected, actual);
+    if differing.is_empty() {
+        return None;
+    }
+    let describe = |cell: Option<char>| match cell {
+        Some(cell) => format!("'{}'", cell),
+        None => "nothing".to_string(),
+    };
+    let mut diff = format!("{} cells differ\n", differing.len());
+    for (y, line) in strip_colors(actual).lines().enumerate() {
+        for (x, cell) in line.chars().enumerate() {
+            if differing
+                .iter()
+                .any(|difference| difference.position == (x, y))
+            {
+                diff += &Color::Red.paint(cell).to_string();
+            } else {
+                diff.push(cell);
+            }
+        }
+        diff.push('\n');
+    }
+    for difference in differing.iter().take(LISTED) {
+        let (x, y) = difference.position;
+        diff += &format!(
+            "({}, {}): expected {}, found {}\n",
+            x,
+            y,
+            describe(difference.expected),
+            describe(difference.actual)
+        );
+    }
+    if differing.len() > LISTED {
+        diff += &format!("and {} more\n", differing.len() - LISTED);
+    }
+    Some(diff)
+}
+
+/// Panic with `message` and the differences of the grids if `actual` is not
+/// the same grid as `expected`, see [`assert_grid_eq!`].
+#[track_caller]
+pub fn assert_grid_eq(expected: &str, actual: &str, message: &str) {
+    let (expected, actual) = (strip_colors(expected), strip_colors(actual));
+    if expected != actual {
+        panic!(
+            "grids differ: {}\n{}",
+            message,
+            grid_diff(&expected, &actual).unwrap_or_else(|| ENDS.to_string())
+        );
+    }
+}
+
+/// Compare `rendering` to the content of the fixture file at `path`, or write
+/// it there if the `AOC_UPDATE_SNAPSHOTS` environment variable is set.
+#[track_caller]
+pub fn assert_snapshot(rendering: &str, path: &Path) {
+    let rendering = strip_colors(rendering);
+    if env::var_os(UPDATE).is_some() {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).expect("expect the snapshots directory to be created");
+        }
+        fs::write(path, &rendering).expect("expect the snapshot to be written");
+        return;
+    }
+    let expected = fs::read_to_string(path).unwrap_or_else(|error| {
+        panic!(
+            "expect the snapshot {} to exist ({}), run the tests with {} set to write it",
+            path.display(),
+            error,
+            UPDATE
+        )
+    });
+    if expected != rendering {
+        panic!(
+            "snapshot {} differs, run the tests with {} set to update it\n{}",
+            path.display(),
+            UPDATE,
+            grid_diff(&expected, &rendering).unwrap_or_else(|| ENDS.to_string())
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_colors_only() {
+        assert_eq!("a#b", strip_colors("a\x1b[31m#\x1b[0mb"));
+        assert_eq!("█ ●\n", strip_colors("█ ●\n"));
+    }
+
+    #[test]
+    fn same_snapshot() {
+        if env::var_os(UPDATE).is_some() {
+            return;
+        }
+        let path = env::temp_dir().join("common-snapshot-same.txt");
+        fs::write(&path, "#.\n.#\n").unwrap();
+        assert_snapshot("#.\n\x1b[31m.#\x1b[0m\n", &path);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn different_snapshot() {
+        if env::var_os(UPDATE).is_some() {
+            return;
+        }
+        let path = env::temp_dir().join("common-snapshot-different.txt");
+        fs::write(&path, "#.\n.#\n").unwrap();
+        let result = std::panic::catch_unwind(|| assert_snapshot("#.\n##\n", &path));
+        fs::remove_file(path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn differing_cells_only() {
+        let difference = |position, expected, actual| Difference {
+            position,
+            expected,
+            actual,
+        };
+        assert!(differing_cells("#.\n.#\n", "#.\n\x1b[31m.#\x1b[0m\n").is_empty());
+        assert_eq!(
+            vec![
+                difference((1, 0), Some('.'), Some('#')),
+                difference((2, 1), None, Some('#'))
+            ],
+            differing_cells("#.\n.#", "##\n.##")
+        );
+        assert_eq!(
+            vec![difference((0, 1), Some('.'), None)],
+            differing_cells("#\n.", "#")
+        );
+    }
+
+    #[test]
+    fn grid_diff_report() {
+        assert_eq!(None, grid_diff("L.L\n", "L.L"));
+        let diff = strip_colors(&grid_diff("L.L\nLLL\n", "#.L\nL#L\n").unwrap());
+        assert_eq!(
+            "2 cells differ\n#.L\nL#L\n(0, 0): expected 'L', found '#'\n(1, 1): expected 'L', found '#'\n",
+            diff
+        );
+        let diff = grid_diff(&".".repeat(12), &"#".repeat(12)).unwrap();
+        assert!(diff.ends_with("(9, 0): expected '.', found '#'\nand 2 more\n"));
+        let result = std::panic::catch_unwind(|| assert_grid_eq("#", ".", "after 1 step"));
+        assert!(result.is_err());
+        // The same cells, but not the same lines
+        let result = std::panic::catch_unwind(|| assert_grid_eq("#\n", "#", ""));
+        assert!(result.is_err());
+        assert_grid_eq("#\n", "\x1b[31m#\x1b[0m\n", "colors aside");
+    }
+}
@@ -0,0 +1,86 @@
+use proptest::{
+    collection::{self, SizeRange},
+    prelude::*,
+};
+use std::ops::Range;
+
+/// A single line, either of any characters or of the ones puzzle inputs are
+/// made of, for parsers which should never panic.
+pub fn line() -> impl Strategy<Value = String> {
+    prop_oneof!["[^\n]{0,40}", "[0-9A-Za-z ,:=>#.()+*\\[\\]-]{0,40}"]
+}
+
+/// Integers separated by commas, like an Intcode program, with their text.
+pub fn integers(len: impl Into<SizeRange>) -> impl Strategy<Value = (Vec<i64>, String)> {
+    collection::vec(any::<i64>(), len).prop_map(|integers| {
+        let text = integers
+            .iter()
+            .map(i64::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        (integers, text)
+    })
+}
+
+/// Lines of `#` and `.`, a cell being `true` for a `#`, with their text.
+pub fn grid(
+    width: Range<usize>,
+    height: Range<usize>,
+) -> impl Strategy<Value = (Vec<Vec<bool>>, String)> {
+    (width, height)
+        .prop_flat_map(|(width, height)| {
+            collection::vec(collection::vec(any::<bool>(), width), height)
+        })
+        .prop_map(|rows| {
+            let text = rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&cell| if cell { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (rows, text)
+        })
+}
+
+/// A quantity followed by a unit, e.g. `10 ORE`, with its text.
+pub fn quantity() -> impl Strategy<Value = (usize, String, String)> {
+    (any::<u32>(), "[A-Z]{1,5}").prop_map(|(quantity, unit)| {
+        let text = format!("{} {}", quantity, unit);
+        (quantity as usize, unit, text)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn integers_text((values, text) in integers(0..10)) {
+            let parsed: Vec<i64> = text
+                .split(',')
+                .filter(|integer| !integer.is_empty())
+                .map(|integer| integer.parse().unwrap())
+                .collect();
+            prop_assert_eq!(values, parsed);
+        }
+
+        #[test]
+        fn grid_text((rows, text) in grid(1..8, 1..8)) {
+            prop_assert_eq!(rows.len(), text.lines().count());
+            for (row, line) in rows.iter().zip(text.lines()) {
+                prop_assert_eq!(row.len(), line.len());
+                let cells: Vec<bool> = line.chars().map(|cell| cell == '#').collect();
+                prop_assert_eq!(row, &cells);
+            }
+        }
+
+        #[test]
+        fn line_is_single(text in line()) {
+            prop_assert!(!text.contains('\n'));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::ops::Range;
+
+/// Piece of a line, with the byte span it covers in this line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub span: Range<usize>,
+}
+
+impl Token<'_> {
+    /// The line the token comes from, with the token underlined below it.
+    pub fn underline(&self, line: &str) -> String {
+        underline(line, &self.span)
+    }
+}
+
+/// Token of `text`, which must be a slice of `line`.
+pub fn within<'a>(line: &str, text: &'a str) -> Token<'a> {
+    // `text` is borrowed from `line`, its offset is its position in memory
+    let start = (text.as_ptr() as usize)
+        .checked_sub(line.as_ptr() as usize)
+        .filter(|start| start + text.len() <= line.len())
+        .expect("expect the text to be a slice of the line");
+    Token {
+        text,
+        span: start..start + text.len(),
+    }
+}
+
+/// Split `line` on `separator`, the whitespaces around each token being
+/// excluded from it.
+pub fn split(line: &str, separator: char) -> impl Iterator<Item = Token<'_>> {
+    let mut start = 0;
+    line.split(separator).map(move |piece| {
+        let leading = piece.len() - piece.trim_start().len();
+        let text = piece.trim();
+        let token = Token {
+            text,
+            span: start + leading..start + leading + text.len(),
+        };
+        start += piece.len() + separator.len_utf8();
+        token
+    })
+}
+
+/// Split `line` on whitespaces, ignoring empty tokens.
+pub fn words(line: &str) -> impl Iterator<Item = Token<'_>> {
+    line.split_whitespace().map(move |text| within(line, text))
+}
+
+/// `line` with `^` below the bytes of `span`, an empty span being pointed at
+/// with a single `^`.
+pub fn underline(line: &str, span: &Range<usize>) -> String {
+    let line = line.trim_end_matches(['\r', '\n']);
+    // A span may point past the end of the line, in the newline trimmed above
+    let start = span.start.min(line.len());
+    let end = span.end.clamp(start, line.len());
+    let offset = line[..start].chars().count();
+    let width = line[start..end].chars().count().max(1);
+    format!("{}\n{}{}", line, " ".repeat(offset), "^".repeat(width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_with_spans() {
+        let tokens: Vec<_> = split("1, 22 ,333", ',').collect();
+        assert_eq!(
+            tokens,
+            vec![
+                Token {
+                    text: "1",
+                    span: 0..1
+                },
+                Token {
+                    text: "22",
+                    span: 3..5
+                },
+                Token {
+                    text: "333",
+                    span: 7..10
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn words_with_spans() {
+        let spans: Vec<_> = words("  10 ORE\t=> 1 A").map(|token| token.span).collect();
+        assert_eq!(spans, vec![2..4, 5..8, 9..11, 12..13, 14..15]);
+    }
+
+    #[test]
+    fn within_line() {
+        let line = "1 A => 2 B";
+        let token = within(line, line.split("=>").nth(1).unwrap().trim());
+        assert_eq!(token.span, 7..10);
+    }
+
+    #[test]
+    fn underline_token() {
+        let line = "R8,U5,X3\n";
+        let token = split(line.trim(), ',').nth(2).unwrap();
+        assert_eq!(token.underline(line), "R8,U5,X3\n      ^^");
+    }
+
+    #[test]
+    fn underline_empty_token() {
+        let token = split("1,,3", ',').nth(1).unwrap();
+        assert_eq!(token.underline("1,,3"), "1,,3\n  ^");
+    }
+
+    #[test]
+    fn underline_past_the_end() {
+        let line = "1 A =>\r\n";
+        let token = within(line, line[6..].trim());
+        assert_eq!(token.underline(line), "1 A =>\n      ^");
+    }
+}
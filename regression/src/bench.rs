@@ -0,0 +1,220 @@
+use crate::Error;
+use common::alloc::{Bytes, Stats};
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path, time::Duration};
+
+/// Time taken by each part of each day, by year and day.
+pub type Timings = BTreeMap<(u16, String), BTreeMap<u8, Duration>>;
+
+/// Allocations of the days with the `alloc-stats` feature, by year and day:
+/// the allocations of all their parts, and the highest peak of them.
+pub type Memory = BTreeMap<(u16, String), Stats>;
+
+/// Add the `stats` of a part of the day to its `memory`.
+pub fn add_stats(memory: &mut Memory, year: u16, day: &str, stats: Stats) {
+    memory
+        .entry((year, day.to_string()))
+        .and_modify(|total| {
+            total.allocations += stats.allocations;
+            total.peak = total.peak.max(stats.peak);
+        })
+        .or_insert(stats);
+}
+
+/// Read timings saved by `write_baseline`, one part of a day per line, e.g.
+/// `2019 01_the-tyranny-of-the-rocket-equation 1 1.234`, the time being in
+/// milliseconds.
+pub fn read_baseline(path: &Path) -> Result<Timings, Error> {
+    parse_baseline(&fs::read_to_string(path)?)
+}
+
+fn parse_baseline(text: &str) -> Result<Timings, Error> {
+    let mut timings = Timings::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let invalid = || Error::Run(format!("Invalid line in the baseline '{}'", line));
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 4 {
+            return Err(invalid());
+        }
+        let year = fields[0].parse().map_err(|_| invalid())?;
+        let part = fields[2].parse().map_err(|_| invalid())?;
+        let milliseconds: f64 = fields[3].parse().map_err(|_| invalid())?;
+        let duration = Duration::try_from_secs_f64(milliseconds / 1000.0).map_err(|_| invalid())?;
+        timings
+            .entry((year, fields[1].to_string()))
+            .or_default()
+            .insert(part, duration);
+    }
+    Ok(timings)
+}
+
+fn baseline(timings: &Timings) -> String {
+    let mut text = String::new();
+    for ((year, day), parts) in timings {
+        for (part, duration) in parts {
+            writeln!(
+                text,
+                "{} {} {} {:.3}",
+                year,
+                day,
+                part,
+                milliseconds(*duration)
+            )
+            .expect("expect to write into a string");
+        }
+    }
+    text
+}
+
+/// Save the timings, to be compared with the next ones.
+pub fn write_baseline(path: &Path, timings: &Timings) -> Result<(), Error> {
+    fs::write(path, baseline(timings))?;
+    Ok(())
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn format_duration(duration: Option<&Duration>) -> String {
+    duration
+        .map(|duration| format!("{:.1} ms", milliseconds(*duration)))
+        .unwrap_or_default()
+}
+
+/// Relative change of the total time of a day, e.g. `-12.5%` when faster.
+fn change(total: Duration, baseline: Option<&BTreeMap<u8, Duration>>) -> String {
+    let baseline: Duration = match baseline {
+        Some(parts) => parts.values().sum(),
+        None => return String::new(),
+    };
+    if baseline.is_zero() {
+        return String::new();
+    }
+    let change = (total.as_secs_f64() / baseline.as_secs_f64() - 1.0) * 100.0;
+    format!("{:+.1}%", change)
+}
+
+/// Table of the timings in Markdown, a line by day with the time of each
+/// part, the total, the allocations and the peak of memory if known, and the
+/// change of the total from the baseline if any.
+pub fn markdown(timings: &Timings, memory: &Memory, baseline: Option<&Timings>) -> String {
+    let mut table = String::new();
+    let mut write = |line: String| table.push_str(&line);
+    write(String::from(
+        "| Day | Part 1 | Part 2 | Total | Allocations | Peak memory |",
+    ));
+    if baseline.is_some() {
+        write(String::from(" Change |"));
+    }
+    write(String::from(
+        "\n|-----|-------:|-------:|------:|------------:|------------:|",
+    ));
+    if baseline.is_some() {
+        write(String::from("-------:|"));
+    }
+    write(String::from("\n"));
+    for ((year, day), parts) in timings {
+        let total: Duration = parts.values().sum();
+        let stats = memory.get(&(*year, day.clone()));
+        write(format!(
+            "| {}/{} | {} | {} | {} | {} | {} |",
+            year,
+            day,
+            format_duration(parts.get(&1)),
+            format_duration(parts.get(&2)),
+            format_duration(Some(&total)),
+            stats
+                .map(|stats| stats.allocations.to_string())
+                .unwrap_or_default(),
+            stats
+                .map(|stats| Bytes(stats.peak).to_string())
+                .unwrap_or_default()
+        ));
+        if let Some(baseline) = baseline {
+            write(format!(
+                " {} |",
+                change(total, baseline.get(&(*year, day.clone())))
+            ));
+        }
+        write(String::from("\n"));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timings(parts: &[(u16, &str, u8, u64)]) -> Timings {
+        let mut timings = Timings::new();
+        for &(year, day, part, milliseconds) in parts {
+            timings
+                .entry((year, day.to_string()))
+                .or_default()
+                .insert(part, Duration::from_millis(milliseconds));
+        }
+        timings
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let timings = timings(&[
+            (2019, "01_a", 1, 2),
+            (2019, "01_a", 2, 3),
+            (2020, "05_b", 1, 40),
+        ]);
+        let text = baseline(&timings);
+        assert_eq!(
+            "2019 01_a 1 2.000\n2019 01_a 2 3.000\n2020 05_b 1 40.000\n",
+            text
+        );
+        assert_eq!(timings, parse_baseline(&text).unwrap());
+        assert!(parse_baseline("2019 01_a 1").is_err());
+        assert!(parse_baseline("2019 01_a 1 -3").is_err());
+    }
+
+    #[test]
+    fn markdown_table() {
+        let current = timings(&[
+            (2019, "01_a", 1, 2),
+            (2019, "01_a", 2, 3),
+            (2020, "05_b", 1, 40),
+        ]);
+        let mut memory = Memory::new();
+        add_stats(
+            &mut memory,
+            2019,
+            "01_a",
+            Stats {
+                allocations: 10,
+                peak: 3 << 19,
+            },
+        );
+        add_stats(
+            &mut memory,
+            2019,
+            "01_a",
+            Stats {
+                allocations: 5,
+                peak: 1 << 10,
+            },
+        );
+        assert_eq!(
+            "| Day | Part 1 | Part 2 | Total | Allocations | Peak memory |
+|-----|-------:|-------:|------:|------------:|------------:|
+| 2019/01_a | 2.0 ms | 3.0 ms | 5.0 ms | 15 | 1.5 MiB |
+| 2020/05_b | 40.0 ms |  | 40.0 ms |  |  |
+",
+            markdown(&current, &memory, None)
+        );
+        let baseline = timings(&[(2019, "01_a", 1, 4), (2019, "01_a", 2, 6)]);
+        assert_eq!(
+            "| Day | Part 1 | Part 2 | Total | Allocations | Peak memory | Change |
+|-----|-------:|-------:|------:|------------:|------------:|-------:|
+| 2019/01_a | 2.0 ms | 3.0 ms | 5.0 ms | 15 | 1.5 MiB | -50.0% |
+| 2020/05_b | 40.0 ms |  | 40.0 ms |  |  |  |
+",
+            markdown(&current, &memory, Some(&baseline))
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use common::cli::{value_parser, Arg, ArgAction, Args};
+use regression::{answers, bench, Error};
+use std::path::PathBuf;
+
+/// Options of the benchmark, see `from_args`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Options {
+    markdown: bool,
+    baseline: Option<PathBuf>,
+    save: Option<PathBuf>,
+    years: Vec<u16>,
+}
+
+impl Options {
+    /// Arguments `--markdown`, `--baseline <PATH>` and `--save <PATH>`, then
+    /// the years to run, to be given to `cli::Args::parse_values`.
+    fn arguments() -> Vec<Arg> {
+        vec![
+            Arg::new("markdown")
+                .long("markdown")
+                .action(ArgAction::SetTrue)
+                .help("Print a table of the timings, to be pasted into notes"),
+            Arg::new("baseline")
+                .long("baseline")
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .help("Timings saved by an earlier run, to show the change of each day"),
+            Arg::new("save")
+                .long("save")
+                .value_name("PATH")
+                .value_parser(value_parser!(PathBuf))
+                .help("Save the timings, to be the baseline of a later run"),
+            Arg::new("years")
+                .value_name("YEAR")
+                .num_args(0..)
+                .value_parser(value_parser!(u16))
+                .help("Years to run, every year with answers by default"),
+        ]
+    }
+
+    /// Options read from `args`.
+    fn from_args(args: &Args) -> Self {
+        let mut years = args.get_all("years");
+        if years.is_empty() {
+            years = vec![2019, 2020, 2021];
+        }
+        Options {
+            markdown: args.flag("markdown"),
+            baseline: args.get("baseline"),
+            save: args.get("save"),
+            years,
+        }
+    }
+}
+
+fn main() -> Result<(), Error> {
+    let args = Args::parse_values("bench", &[], Options::arguments());
+    let options = Options::from_args(&args);
+    let baseline = options
+        .baseline
+        .as_deref()
+        .map(bench::read_baseline)
+        .transpose()?;
+    let mut timings = bench::Timings::new();
+    let mut memory = bench::Memory::new();
+    for &year in &options.years {
+        for answer in answers(year)? {
+            let duration = answer.time(year)?;
+            let stats = answer.alloc_stats(year)?;
+            if !options.markdown {
+                let stats = stats
+                    .map(|stats| format!(", {}", stats))
+                    .unwrap_or_default();
+                println!(
+                    "{}/{} part {} in {:.1} ms{}",
+                    year,
+                    answer.day,
+                    answer.part,
+                    duration.as_secs_f64() * 1000.0,
+                    stats
+                );
+            }
+            if let Some(stats) = stats {
+                bench::add_stats(&mut memory, year, &answer.day, stats);
+            }
+            // A part answered with several arguments counts all of them
+            *timings
+                .entry((year, answer.day.clone()))
+                .or_default()
+                .entry(answer.part)
+                .or_default() += duration;
+        }
+    }
+    if options.markdown {
+        print!("{}", bench::markdown(&timings, &memory, baseline.as_ref()));
+    }
+    if let Some(path) = &options.save {
+        bench::write_baseline(path, &timings)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, common::cli::Error> {
+        Args::try_parse_values_from(
+            "bench",
+            &[],
+            Options::arguments(),
+            std::iter::once("bench").chain(args.iter().copied()),
+        )
+        .map(|args| Options::from_args(&args))
+    }
+
+    #[test]
+    fn options() {
+        assert_eq!(
+            Options {
+                markdown: false,
+                baseline: None,
+                save: None,
+                years: vec![2019, 2020, 2021],
+            },
+            parse(&[]).unwrap()
+        );
+        assert_eq!(
+            Options {
+                markdown: true,
+                baseline: Some(PathBuf::from("before.txt")),
+                save: Some(PathBuf::from("after.txt")),
+                years: vec![2020],
+            },
+            parse(&[
+                "--markdown",
+                "--baseline",
+                "before.txt",
+                "--save",
+                "after.txt",
+                "2020"
+            ])
+            .unwrap()
+        );
+        assert!(parse(&["--save"]).is_err());
+        assert!(parse(&["--json"]).is_err());
+        assert!(parse(&["twenty"]).is_err());
+    }
+}
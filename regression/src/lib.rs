@@ -0,0 +1,319 @@
+use common::alloc::Stats;
+use serde::Deserialize;
+use std::{
+    env,
+    fmt::{self, Display, Formatter},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    time::{Duration, Instant},
+};
+
+pub mod bench;
+
+/// Assert that the answer `found` for the `part` of the puzzle of `day` in
+/// `year` is the `expected` one, the failure naming the puzzle and the part
+/// which regressed.
+///
+/// ```ignore
+/// assert_answer!(2019, "03_crossed-wires", 2, distance, 32132);
+/// ```
+#[macro_export]
+macro_rules! assert_answer {
+    ($year:expr, $day:expr, $part:expr, $found:expr, $expected:expr $(,)?) => {
+        match (&$found, &$expected) {
+            (found, expected) => {
+                if !(*found == *expected) {
+                    panic!(
+                        "{}/{} part {} regressed\nexpected: {:?}\n   found: {:?}",
+                        $year, $day, $part, expected, found
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Run(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Error::Toml(error)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::Toml(error) => write!(f, "{}", error),
+            Error::Run(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// One known answer, i.e. the output expected when running one day with
+/// some features, arguments and standard input.
+#[derive(Debug, Deserialize)]
+pub struct Answer {
+    pub day: String,
+    pub part: u8,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub stdin: Option<String>,
+    pub output: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Answers {
+    /// No answer is known yet for a year just started.
+    #[serde(default)]
+    answer: Vec<Answer>,
+}
+
+pub fn year_directory(year: u16) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("..")
+        .join(year.to_string())
+}
+
+pub fn answers(year: u16) -> Result<Vec<Answer>, Error> {
+    let answers = fs::read_to_string(year_directory(year).join("answers.toml"))?;
+    let answers: Answers = toml::from_str(&answers)?;
+    Ok(answers.answer)
+}
+
+/// Directory where cargo builds the day, `target` in the day unless the
+/// `CARGO_TARGET_DIR` environment variable names another one.
+fn target_directory(day_directory: &Path) -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| day_directory.join("target"))
+}
+
+fn manifest(day_directory: &Path) -> Result<toml::Value, Error> {
+    let manifest = fs::read_to_string(day_directory.join("Cargo.toml"))?;
+    Ok(toml::from_str(&manifest)?)
+}
+
+/// Name of the binary of the day, i.e. the name of its package.
+fn binary_name(day_directory: &Path) -> Result<String, Error> {
+    manifest(day_directory)?
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(toml::Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| {
+            Error::Run(format!(
+                "{} has no package name",
+                day_directory.join("Cargo.toml").display()
+            ))
+        })
+}
+
+fn trimmed_lines(text: &str) -> Vec<&str> {
+    text.lines().map(str::trim_end).collect()
+}
+
+/// Whether the day declares the cargo feature `feature`.
+fn has_feature(day_directory: &Path, feature: &str) -> Result<bool, Error> {
+    Ok(manifest(day_directory)?
+        .get("features")
+        .and_then(|features| features.get(feature))
+        .is_some())
+}
+
+/// Stats of the allocations in what a day printed on the standard error with
+/// `common::alloc::report`, e.g. `Ran in 1.23ms, 12 allocations, peak memory
+/// 3.0 KiB`.
+fn reported_stats(stderr: &str) -> Option<Stats> {
+    stderr.lines().find_map(|line| {
+        let (_, stats) = line.strip_prefix("Ran in ")?.split_once(", ")?;
+        stats.parse().ok()
+    })
+}
+
+impl Answer {
+    /// Build the day in release mode, then run it alone and measure how long
+    /// it takes, so that neither the build nor cargo is part of the time.
+    pub fn time(&self, year: u16) -> Result<Duration, Error> {
+        let (duration, _) = self.run_built(year, &self.features)?;
+        Ok(duration)
+    }
+
+    /// Build the day in release mode with the `alloc-stats` feature, then run
+    /// it and read the allocations it reports, if the day has the feature.
+    /// This run is not timed, counting the allocations slowing it down.
+    pub fn alloc_stats(&self, year: u16) -> Result<Option<Stats>, Error> {
+        const FEATURE: &str = "alloc-stats";
+        if !has_feature(&year_directory(year).join(&self.day), FEATURE)? {
+            return Ok(None);
+        }
+        let mut features = self.features.clone();
+        features.push(String::from(FEATURE));
+        let (_, output) = self.run_built(year, &features)?;
+        reported_stats(&String::from_utf8_lossy(&output.stderr))
+            .map(Some)
+            .ok_or_else(|| {
+                Error::Run(format!(
+                    "{}/{} part {} reports no allocations with '{}'",
+                    year, self.day, self.part, FEATURE
+                ))
+            })
+    }
+
+    /// Build the day in release mode with `features`, then run it alone and
+    /// give how long it took with what it printed on the standard error.
+    fn run_built(&self, year: u16, features: &[String]) -> Result<(Duration, Output), Error> {
+        let day_directory = year_directory(year).join(&self.day);
+        let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+        let mut build = Command::new(cargo);
+        build
+            .current_dir(&day_directory)
+            .args(["build", "--release", "--quiet"]);
+        if !features.is_empty() {
+            build.arg("--features").arg(features.join(","));
+        }
+        let built = build.output()?;
+        if !built.status.success() {
+            return Err(Error::Run(format!(
+                "{}/{} part {} does not build ({})\n{}",
+                year,
+                self.day,
+                self.part,
+                built.status,
+                String::from_utf8_lossy(&built.stderr)
+            )));
+        }
+        let binary = target_directory(&day_directory)
+            .join("release")
+            .join(binary_name(&day_directory)?);
+        let start = Instant::now();
+        let mut child = Command::new(binary)
+            .current_dir(&day_directory)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(input) = &self.stdin {
+                writeln!(stdin, "{}", input)?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        let duration = start.elapsed();
+        if !output.status.success() {
+            return Err(Error::Run(format!(
+                "{}/{} part {} failed ({})\n{}",
+                year,
+                self.day,
+                self.part,
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok((duration, output))
+    }
+
+    /// Expected output, without the spaces at the end of its lines.
+    pub fn expected(&self) -> String {
+        trimmed_lines(&self.output).join("\n")
+    }
+
+    /// Run the day in release mode and give the expected output if it
+    /// appears as consecutive lines in what the day printed, else everything
+    /// the day printed, to be compared with `expected` by `assert_answer!`.
+    pub fn printed(&self, year: u16) -> Result<String, Error> {
+        let stdout = self.run(year)?;
+        let expected = trimmed_lines(&self.output);
+        let found = trimmed_lines(&stdout)
+            .windows(expected.len())
+            .any(|lines| lines == expected.as_slice());
+        if found {
+            Ok(self.expected())
+        } else {
+            Ok(trimmed_lines(&stdout).join("\n"))
+        }
+    }
+
+    /// Run the day in release mode, with its features, arguments and
+    /// standard input, and give what it printed.
+    fn run(&self, year: u16) -> Result<String, Error> {
+        let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+        let mut command = Command::new(cargo);
+        command
+            .current_dir(year_directory(year).join(&self.day))
+            .args(["run", "--release", "--quiet"]);
+        if !self.features.is_empty() {
+            command.arg("--features").arg(self.features.join(","));
+        }
+        let mut child = command
+            .arg("--")
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            if let Some(input) = &self.stdin {
+                writeln!(stdin, "{}", input)?;
+            }
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            return Err(Error::Run(format!(
+                "{}/{} part {} failed ({})\n{}",
+                year,
+                self.day,
+                self.part,
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_of_the_report() {
+        assert_eq!(
+            Some(Stats {
+                allocations: 12,
+                peak: 3 << 10
+            }),
+            reported_stats("warming up\nRan in 1.23ms, 12 allocations, peak memory 3.0 KiB\n")
+        );
+        assert_eq!(None, reported_stats("Answered in 1.23ms\n"));
+    }
+
+    #[test]
+    fn same_answer() {
+        assert_answer!(2019, "03_crossed-wires", 2, 32132, 32132);
+        assert_answer!(2020, "05_binary-boarding", 1, String::from("928"), "928");
+    }
+
+    #[test]
+    #[should_panic(expected = "2019/03_crossed-wires part 2 regressed")]
+    fn regressed_answer() {
+        assert_answer!(2019, "03_crossed-wires", 2, 32133, 32132);
+    }
+}
@@ -0,0 +1,58 @@
+use regression::{answers, assert_answer, Error};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Check every answer of the year, then fail with all the answers which
+/// regressed or which the day failed to print, if any.
+fn check_year(year: u16) -> Result<(), Error> {
+    let mut failures = Vec::new();
+    for answer in answers(year)? {
+        let printed = match answer.printed(year) {
+            Ok(printed) => printed,
+            Err(error) => {
+                failures.push(error.to_string());
+                continue;
+            }
+        };
+        let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+            assert_answer!(year, answer.day, answer.part, printed, answer.expected());
+        }));
+        if let Err(payload) = checked {
+            let message = payload
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| {
+                    payload
+                        .downcast_ref::<&str>()
+                        .map(|message| message.to_string())
+                })
+                .unwrap_or_else(|| {
+                    format!("{}/{} part {} panicked", year, answer.day, answer.part)
+                });
+            failures.push(message);
+        }
+    }
+    if !failures.is_empty() {
+        panic!(
+            "{} answers of {} failed\n\n{}",
+            failures.len(),
+            year,
+            failures.join("\n\n")
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn year_2019() -> Result<(), Error> {
+    check_year(2019)
+}
+
+#[test]
+fn year_2020() -> Result<(), Error> {
+    check_year(2020)
+}
+
+#[test]
+fn year_2021() -> Result<(), Error> {
+    check_year(2021)
+}
@@ -0,0 +1,513 @@
+use common::render::{Cells, Position};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    env,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Environment variable naming the file the animation is written to.
+pub const ENV: &str = "AOC_ANIMATION";
+/// Name of the animation when `AOC_ANIMATION` is not set.
+pub const DEFAULT: &str = "animation.gif";
+
+/// Environment variable naming the file an image is written to.
+pub const IMAGE_ENV: &str = "AOC_IMAGE";
+/// Name of the image when `AOC_IMAGE` is not set.
+pub const IMAGE_DEFAULT: &str = "image.png";
+
+/// Color of a pixel, as red, green and blue.
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to write the animation")]
+    FailedWrite(#[from] io::Error),
+    #[error("Failed to encode the animation as a GIF")]
+    FailedGif(#[from] gif::EncodingError),
+    #[error("Failed to encode the animation as an APNG")]
+    FailedPng(#[from] png::EncodingError),
+    #[error("A GIF has at most 256 colors but the animation has {0}")]
+    TooManyColors(usize),
+    #[error("The animation is too big, {0}x{1} pixels")]
+    TooBig(usize, usize),
+    #[error("The animation doesn't contain any frame")]
+    NoFrame,
+    #[error("Unknown format of animation for '{0}', expect '.gif', '.png' or '.apng'")]
+    UnknownFormat(PathBuf),
+}
+
+/// An animation or an image failing to be written, for the days whose `main`
+/// gives an `io::Error`.
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::other(error)
+    }
+}
+
+/// Path of the animation: the `AOC_ANIMATION` environment variable, else
+/// `animation.gif`.
+pub fn path() -> PathBuf {
+    env::var_os(ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT))
+}
+
+/// Path of an image: the `AOC_IMAGE` environment variable, else `image.png`.
+pub fn image_path() -> PathBuf {
+    env::var_os(IMAGE_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(IMAGE_DEFAULT))
+}
+
+/// Path of one of several images, `image_path` with `-<name>` after its stem,
+/// e.g. `image-z0.png` for the name `z0`.
+pub fn image_path_of(name: &str) -> PathBuf {
+    named(&image_path(), name)
+}
+
+fn named(path: &Path, name: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, name, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, name),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Colors of the cells between the bounds of some cells, at the time of the
+/// capture.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    min: Position,
+    max: Position,
+    pixels: HashMap<Position, Rgb>,
+}
+
+impl Frame {
+    /// Capture every position between the bounds of `cells`, with the color
+    /// returned by a function of the position and of the cell, if any.
+    pub fn capture<C, F>(cells: &C, color: F) -> Option<Self>
+    where
+        C: Cells,
+        F: Fn(Position, Option<&C::Cell>) -> Rgb,
+    {
+        let (min, max) = cells.bounds()?;
+        let pixels = (min.1..=max.1)
+            .flat_map(|y| (min.0..=max.0).map(move |x| (x, y)))
+            .map(|position| (position, color(position, cells.cell(position))))
+            .collect();
+        Some(Frame { min, max, pixels })
+    }
+}
+
+/// Successive frames, each one shown for the same delay.
+///
+/// Frames may have different bounds, the animation covers all of them and the
+/// positions missing from a frame are drawn with the background.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    frames: Vec<Frame>,
+    scale: usize,
+    delay: u16,
+    background: Rgb,
+    y_up: bool,
+}
+
+impl Default for Animation {
+    fn default() -> Self {
+        Animation {
+            frames: Vec::new(),
+            scale: 4,
+            delay: 5,
+            background: [0, 0, 0],
+            y_up: false,
+        }
+    }
+}
+
+impl Animation {
+    /// Size of the square of pixels drawn for each cell.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Delay between two frames, in hundredths of a second.
+    pub fn delay(mut self, delay: u16) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn background(mut self, background: Rgb) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Draw the biggest `y` on the first line, like [`common::render::Render::y_up`].
+    pub fn y_up(mut self) -> Self {
+        self.y_up = true;
+        self
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+
+    /// Capture a new frame of `cells`, see [`Frame::capture`].
+    pub fn capture<C, F>(&mut self, cells: &C, color: F)
+    where
+        C: Cells,
+        F: Fn(Position, Option<&C::Cell>) -> Rgb,
+    {
+        self.frames.extend(Frame::capture(cells, color));
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Smallest and biggest positions over all the frames.
+    fn bounds(&self) -> Result<(Position, Position), Error> {
+        let min_x = self.frames.iter().map(|frame| frame.min.0).min();
+        let min_y = self.frames.iter().map(|frame| frame.min.1).min();
+        let max_x = self.frames.iter().map(|frame| frame.max.0).max();
+        let max_y = self.frames.iter().map(|frame| frame.max.1).max();
+        match (min_x, min_y, max_x, max_y) {
+            (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => {
+                Ok(((min_x, min_y), (max_x, max_y)))
+            }
+            _ => Err(Error::NoFrame),
+        }
+    }
+
+    /// Width and height of the images, in pixels.
+    fn size(&self) -> Result<(usize, usize), Error> {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds()?;
+        let width = (max_x - min_x + 1) as usize * self.scale;
+        let height = (max_y - min_y + 1) as usize * self.scale;
+        if width > u16::MAX as usize || height > u16::MAX as usize {
+            return Err(Error::TooBig(width, height));
+        }
+        Ok((width, height))
+    }
+
+    /// Pixels of every frame, line by line.
+    fn images(&self) -> Result<Vec<Vec<Rgb>>, Error> {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds()?;
+        let lines: Vec<i64> = if self.y_up {
+            (min_y..=max_y).rev().collect()
+        } else {
+            (min_y..=max_y).collect()
+        };
+        let images = self
+            .frames
+            .iter()
+            .map(|frame| {
+                let mut image = Vec::new();
+                for &y in &lines {
+                    let line: Vec<Rgb> = (min_x..=max_x)
+                        .flat_map(|x| {
+                            let pixel = frame
+                                .pixels
+                                .get(&(x, y))
+                                .copied()
+                                .unwrap_or(self.background);
+                            std::iter::repeat_n(pixel, self.scale)
+                        })
+                        .collect();
+                    for _ in 0..self.scale {
+                        image.extend_from_slice(&line);
+                    }
+                }
+                image
+            })
+            .collect();
+        Ok(images)
+    }
+
+    /// Encode the animation as a GIF, looping forever.
+    pub fn write_gif<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let (width, height) = self.size()?;
+        let images = self.images()?;
+        let mut palette: Vec<Rgb> = vec![self.background];
+        let mut indexes: HashMap<Rgb, u8> = HashMap::new();
+        indexes.insert(self.background, 0);
+        let mut frames = Vec::new();
+        for image in images {
+            let mut buffer = Vec::with_capacity(image.len());
+            for pixel in image {
+                let next = palette.len();
+                let index = *indexes.entry(pixel).or_insert_with(|| {
+                    palette.push(pixel);
+                    next as u8
+                });
+                buffer.push(index);
+            }
+            frames.push(buffer);
+        }
+        if palette.len() > 256 {
+            return Err(Error::TooManyColors(palette.len()));
+        }
+        let palette: Vec<u8> = palette.into_iter().flatten().collect();
+        let mut encoder = gif::Encoder::new(writer, width as u16, height as u16, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+        for buffer in frames {
+            let frame = gif::Frame {
+                width: width as u16,
+                height: height as u16,
+                delay: self.delay,
+                buffer: Cow::Owned(buffer),
+                ..gif::Frame::default()
+            };
+            encoder.write_frame(&frame)?;
+        }
+        Ok(())
+    }
+
+    /// Encode the animation as an APNG, looping forever.
+    pub fn write_apng<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let (width, height) = self.size()?;
+        let images = self.images()?;
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(images.len() as u32, 0)?;
+        encoder.set_frame_delay(self.delay, 100)?;
+        let mut writer = encoder.write_header()?;
+        for image in images {
+            let data: Vec<u8> = image.into_iter().flatten().collect();
+            writer.write_image_data(&data)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Write the animation to `path`, as a GIF or an APNG depending on its
+    /// extension.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("gif") => self.write_gif(BufWriter::new(File::create(path)?)),
+            Some("png") | Some("apng") => self.write_apng(BufWriter::new(File::create(path)?)),
+            _ => Err(Error::UnknownFormat(path.to_path_buf())),
+        }
+    }
+}
+
+/// A single capture of some cells, written as a PNG.
+#[derive(Debug, Clone)]
+pub struct Image {
+    animation: Animation,
+}
+
+impl Image {
+    /// Capture every position between the bounds of `cells`, see
+    /// [`Frame::capture`], `None` if there is no cell at all.
+    pub fn capture<C, F>(cells: &C, color: F) -> Option<Self>
+    where
+        C: Cells,
+        F: Fn(Position, Option<&C::Cell>) -> Rgb,
+    {
+        let mut animation = Animation::default();
+        animation.push(Frame::capture(cells, color)?);
+        Some(Image { animation })
+    }
+
+    /// Size of the square of pixels drawn for each cell.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.animation = self.animation.scale(scale);
+        self
+    }
+
+    pub fn background(mut self, background: Rgb) -> Self {
+        self.animation = self.animation.background(background);
+        self
+    }
+
+    /// Draw the biggest `y` on the first line, like [`common::render::Render::y_up`].
+    pub fn y_up(mut self) -> Self {
+        self.animation = self.animation.y_up();
+        self
+    }
+
+    /// Encode the image as a PNG.
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), Error> {
+        let (width, height) = self.animation.size()?;
+        let image = self
+            .animation
+            .images()?
+            .into_iter()
+            .next()
+            .ok_or(Error::NoFrame)?;
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        let data: Vec<u8> = image.into_iter().flatten().collect();
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// Encode the image as a binary PPM, a header followed by the raw pixels.
+    pub fn write_ppm<W: Write>(&self, mut writer: W) -> Result<(), Error> {
+        let (width, height) = self.animation.size()?;
+        let image = self
+            .animation
+            .images()?
+            .into_iter()
+            .next()
+            .ok_or(Error::NoFrame)?;
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+        let data: Vec<u8> = image.into_iter().flatten().collect();
+        writer.write_all(&data)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the image to `path`, as a PPM if its extension is `.ppm`, else
+    /// as a PNG.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let writer = BufWriter::new(File::create(path)?);
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("ppm") => self.write_ppm(writer),
+            _ => self.write_png(writer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgb = [0, 0, 0];
+    const WHITE: Rgb = [255, 255, 255];
+    const RED: Rgb = [255, 0, 0];
+
+    fn color(_: Position, cell: Option<&char>) -> Rgb {
+        match cell {
+            Some('#') => WHITE,
+            Some('@') => RED,
+            _ => BLACK,
+        }
+    }
+
+    fn animation() -> Animation {
+        let mut cells = HashMap::new();
+        cells.insert((0, 0), '#');
+        let mut animation = Animation::default().scale(2);
+        animation.capture(&cells, color);
+        cells.insert((1, 1), '@');
+        animation.capture(&cells, color);
+        animation
+    }
+
+    #[test]
+    fn images_cover_every_frame() {
+        let animation = animation();
+        assert_eq!(animation.size().unwrap(), (4, 4));
+        let images = animation.images().unwrap();
+        assert_eq!(images.len(), 2);
+        assert_eq!(
+            images[0],
+            vec![
+                WHITE, WHITE, BLACK, BLACK, //
+                WHITE, WHITE, BLACK, BLACK, //
+                BLACK, BLACK, BLACK, BLACK, //
+                BLACK, BLACK, BLACK, BLACK, //
+            ]
+        );
+        assert_eq!(images[1][15], RED);
+    }
+
+    #[test]
+    fn images_y_up() {
+        let images = animation().y_up().images().unwrap();
+        assert_eq!(images[1][0], BLACK);
+        assert_eq!(images[1][3], RED);
+        assert_eq!(images[1][12], WHITE);
+    }
+
+    #[test]
+    fn write_gif() {
+        let mut gif = Vec::new();
+        animation().write_gif(&mut gif).unwrap();
+        assert_eq!(&gif[..6], b"GIF89a");
+    }
+
+    #[test]
+    fn write_apng() {
+        let mut apng = Vec::new();
+        animation().write_apng(&mut apng).unwrap();
+        assert_eq!(&apng[1..4], b"PNG");
+        assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+    }
+
+    #[test]
+    fn write_png() {
+        let mut cells = HashMap::new();
+        cells.insert((0, 0), '#');
+        cells.insert((2, 1), '@');
+        let image = Image::capture(&cells, color).unwrap().scale(3);
+        assert_eq!(image.animation.size().unwrap(), (9, 6));
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+        assert!(!png.windows(4).any(|chunk| chunk == b"acTL"));
+        assert!(Image::capture(&HashMap::<Position, char>::new(), color).is_none());
+    }
+
+    #[test]
+    fn write_ppm() {
+        let mut cells = HashMap::new();
+        cells.insert((0, 0), '#');
+        cells.insert((1, 0), '@');
+        let mut ppm = Vec::new();
+        Image::capture(&cells, color)
+            .unwrap()
+            .scale(1)
+            .write_ppm(&mut ppm)
+            .unwrap();
+        assert_eq!(b"P6\n2 1\n255\n\xff\xff\xff\xff\x00\x00", &ppm[..]);
+    }
+
+    #[test]
+    fn named_images() {
+        assert_eq!(
+            PathBuf::from("out/maze-z-1.png"),
+            named(Path::new("out/maze.png"), "z-1")
+        );
+        assert_eq!(PathBuf::from("maze-z0"), named(Path::new("maze"), "z0"));
+    }
+
+    #[test]
+    fn no_frame() {
+        let mut gif = Vec::new();
+        let error = Animation::default().write_gif(&mut gif).unwrap_err();
+        assert!(matches!(error, Error::NoFrame));
+    }
+
+    #[test]
+    fn unknown_format() {
+        let error = animation().save("animation.txt").unwrap_err();
+        assert!(matches!(error, Error::UnknownFormat(_)));
+        let error = io::Error::from(error);
+        assert_eq!(io::ErrorKind::Other, error.kind());
+        assert_eq!(
+            "Unknown format of animation for 'animation.txt', expect '.gif', '.png' or '.apng'",
+            error.to_string()
+        );
+    }
+}